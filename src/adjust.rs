@@ -0,0 +1,144 @@
+//! Brightness/contrast adjustment for `frostwall apply`.
+//!
+//! swww can only show files, so an adjusted wallpaper is written as a PNG in
+//! the cache directory and that copy is applied instead. Copies are keyed by
+//! source path, modification time and the two amounts, so applying the same
+//! adjustment again reuses the file.
+
+use anyhow::{Context, Result};
+use image::{imageops, RgbaImage};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+/// Brightness or contrast change, both in -1.0..=1.0 (0 = unchanged).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Adjustment {
+    /// Added to every channel as a fraction of full scale
+    pub brightness: f32,
+    /// Stretch (positive) or flatten (negative) around mid-gray
+    pub contrast: f32,
+}
+
+impl Adjustment {
+    pub fn new(brightness: f32, contrast: f32) -> Result<Self> {
+        for (name, value) in [("brightness", brightness), ("contrast", contrast)] {
+            if !(-1.0..=1.0).contains(&value) {
+                anyhow::bail!("{} must be between -1.0 and 1.0, got {}", name, value);
+            }
+        }
+        Ok(Self {
+            brightness,
+            contrast,
+        })
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.brightness == 0.0 && self.contrast == 0.0
+    }
+}
+
+/// Apply `adj` to `img`: contrast first, then brightness. Alpha is kept.
+pub fn adjust(img: &RgbaImage, adj: Adjustment) -> RgbaImage {
+    let mut out = img.clone();
+    if adj.contrast != 0.0 {
+        // imageops::contrast takes a percentage (100 doubles the spread)
+        imageops::colorops::contrast_in_place(&mut out, adj.contrast * 100.0);
+    }
+    if adj.brightness != 0.0 {
+        imageops::colorops::brighten_in_place(&mut out, (adj.brightness * 255.0).round() as i32);
+    }
+    out
+}
+
+fn cache_dir() -> PathBuf {
+    directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/frostwall"))
+        .join("adjusted")
+}
+
+/// Cache file for `image_path` with `adj` applied
+pub fn adjusted_path(image_path: &Path, adj: Adjustment) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    image_path.to_string_lossy().hash(&mut hasher);
+    if let Ok(modified) = fs::metadata(image_path).and_then(|m| m.modified()) {
+        modified.hash(&mut hasher);
+    }
+    adj.brightness.to_bits().hash(&mut hasher);
+    adj.contrast.to_bits().hash(&mut hasher);
+
+    cache_dir().join(format!("{:016x}.png", hasher.finish()))
+}
+
+/// Return an adjusted copy of `image_path`, writing it only if not cached yet.
+/// GIFs are adjusted from their first frame.
+pub fn ensure_adjusted(image_path: &Path, adj: Adjustment) -> Result<PathBuf> {
+    let output = adjusted_path(image_path, adj);
+    if output.exists() {
+        return Ok(output);
+    }
+
+    let img = crate::utils::open_still(image_path)
+        .with_context(|| format!("Failed to open image: {}", image_path.display()))?
+        .to_rgba8();
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    adjust(&img, adj)
+        .save(&output)
+        .with_context(|| format!("Failed to write image: {}", output.display()))?;
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- Adjustment ---
+
+    #[test]
+    fn test_adjustment_rejects_out_of_range() {
+        assert!(Adjustment::new(-0.2, 0.1).is_ok());
+        assert!(Adjustment::new(1.5, 0.0).is_err());
+        assert!(Adjustment::new(0.0, -1.01).is_err());
+        assert!(Adjustment::new(0.0, 0.0).unwrap().is_identity());
+    }
+
+    // --- adjust ---
+
+    #[test]
+    fn test_adjust_brightness_and_contrast() {
+        let img = RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([100, 100, 100, 77])
+            } else {
+                image::Rgba([200, 200, 200, 255])
+            }
+        });
+
+        let darker = adjust(&img, Adjustment::new(-0.2, 0.0).unwrap());
+        assert_eq!(darker.get_pixel(0, 0).0, [49, 49, 49, 77], "alpha kept");
+        assert_eq!(darker.get_pixel(1, 0).0[0], 149);
+
+        let flatter = adjust(&img, Adjustment::new(0.0, -0.5).unwrap());
+        let spread = |out: &RgbaImage| out.get_pixel(1, 0).0[0] - out.get_pixel(0, 0).0[0];
+        assert!(spread(&flatter) < spread(&img));
+
+        assert_eq!(adjust(&img, Adjustment::default()), img);
+    }
+
+    // --- adjusted_path ---
+
+    #[test]
+    fn test_adjusted_path_depends_on_amounts() {
+        let path = Path::new("/tmp/frostwall-test/a.png");
+        let a = adjusted_path(path, Adjustment::new(-0.2, 0.1).unwrap());
+        assert_eq!(a, adjusted_path(path, Adjustment::new(-0.2, 0.1).unwrap()));
+        assert_ne!(a, adjusted_path(path, Adjustment::new(-0.2, 0.2).unwrap()));
+        assert_eq!(a.extension().and_then(|e| e.to_str()), Some("png"));
+    }
+}
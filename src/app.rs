@@ -1,28 +1,47 @@
-use crate::pairing::{extract_style_tags, MatchContext, PairingHistory, PairingStyleMode};
+use crate::pairing::{
+    extract_style_tags, optimize_screen_sets, pair_coherence, select_distinct_wallpapers,
+    MatchContext, PairingHistory, PairingStyleMode,
+};
+use crate::profile::Profile;
 use crate::screen::{self, Screen};
 use crate::swww::{self, FillColor, ResizeMode, Transition, TransitionType};
 use crate::thumbnail::ThumbnailCache;
 use crate::ui;
+use crate::ui::halfblock::Halfblocks;
 use crate::utils::ColorHarmony;
-use crate::wallpaper::{MatchMode, SortMode, Wallpaper, WallpaperCache};
+use crate::wallpaper::{
+    MatchMode, RandomMode, ScanConfig, ScanPhase, ScanUpdate, SortMode, Wallpaper, WallpaperCache,
+};
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ratatui::{backend::CrosstermBackend, Terminal};
-use ratatui_image::{picker::Picker, protocol::StatefulProtocol};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
+use ratatui_image::{
+    picker::{Picker, ProtocolType},
+    protocol::StatefulProtocol,
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Write settings changed in the TUI (match mode, resize mode, ...) back to
+    /// config.toml on quit. Turn off to keep a hand-edited file untouched.
+    #[serde(default = "default_persist_runtime_changes")]
+    pub persist_runtime_changes: bool,
     #[serde(default)]
     pub wallpaper: WallpaperConfig,
     #[serde(default)]
@@ -43,6 +62,43 @@ pub struct Config {
     pub time_profiles: crate::timeprofile::TimeProfiles,
     #[serde(default)]
     pub terminal: TerminalConfig,
+    #[serde(default)]
+    pub scan: crate::wallpaper::ScanConfig,
+    #[serde(default)]
+    pub color_filter: ColorFilterConfig,
+    #[serde(default)]
+    pub collections: crate::collections::CollectionsConfig,
+    #[serde(default)]
+    pub schedule: crate::schedule::ScheduleConfig,
+    #[serde(default)]
+    pub import: crate::webimport::ImportConfig,
+}
+
+fn default_persist_runtime_changes() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            persist_runtime_changes: default_persist_runtime_changes(),
+            wallpaper: WallpaperConfig::default(),
+            display: DisplayConfig::default(),
+            transition: TransitionConfig::default(),
+            thumbnails: ThumbnailConfig::default(),
+            theme: ThemeConfig::default(),
+            keybindings: KeybindingsConfig::default(),
+            clip: ClipConfig::default(),
+            pairing: PairingConfig::default(),
+            time_profiles: crate::timeprofile::TimeProfiles::default(),
+            terminal: TerminalConfig::default(),
+            scan: crate::wallpaper::ScanConfig::default(),
+            color_filter: ColorFilterConfig::default(),
+            collections: crate::collections::CollectionsConfig::default(),
+            schedule: crate::schedule::ScheduleConfig::default(),
+            import: crate::webimport::ImportConfig::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +116,57 @@ pub struct DisplayConfig {
     pub resize_mode: ResizeMode,
     #[serde(default)]
     pub fill_color: FillColor,
+    /// How `random` picks: "uniform", "least-recent" or "rated"
+    #[serde(default)]
+    pub random_mode: RandomMode,
+    /// Keep `current-<output>.img` / `current.img` pointing at the applied files
+    #[serde(default = "default_current_symlink")]
+    pub current_symlink: bool,
+    /// Where the current-wallpaper links live (default: the cache directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_link_dir: Option<String>,
+    /// Output whose wallpaper `current.img` follows (default: the last applied)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_output: Option<String>,
+    /// Start swww-daemon when it isn't running; false makes applies fail instead
+    #[serde(default = "default_auto_start_daemon")]
+    pub auto_start_daemon: bool,
+    /// next/prev/random prefer wallpapers at least as large as the output's
+    /// effective pixels (logical size × scale), when any are
+    #[serde(default)]
+    pub prefer_native_resolution: bool,
+    /// Put the same wallpaper on every screen instead of one per screen
+    /// (applying in the TUI, `random` and `surprise`)
+    #[serde(default)]
+    pub mirror_screens: bool,
+    /// Output names in the order commands and the TUI walk them; outputs not
+    /// listed follow in detection order
+    #[serde(default)]
+    pub output_order: Vec<String>,
+    /// Pad `Fit` letterboxing with the wallpaper's dominant color instead of
+    /// `fill_color` (which stays the fallback for images without colors)
+    #[serde(default)]
+    pub fill_from_palette: bool,
+}
+
+impl DisplayConfig {
+    /// Padding color for a wallpaper with palette `colors`
+    pub fn fill_color_for(&self, colors: &[String]) -> FillColor {
+        if self.fill_from_palette {
+            if let Some(fill) = colors.first().and_then(|hex| FillColor::from_hex(hex)) {
+                return fill;
+            }
+        }
+        self.fill_color.clone()
+    }
+}
+
+fn default_auto_start_daemon() -> bool {
+    true
+}
+
+fn default_current_symlink() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -67,6 +174,31 @@ pub struct TransitionConfig {
     pub transition_type: String,
     pub duration: f32,
     pub fps: u32,
+    /// Pick a random transition type from `random_types` on every apply
+    #[serde(default)]
+    pub random: bool,
+    /// Transition types eligible for random selection
+    #[serde(default = "default_random_transition_types")]
+    pub random_types: Vec<String>,
+    /// Bezier easing curve, e.g. ".54,0,.34,.99"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bezier: Option<String>,
+    /// Color step per frame (1-255, lower is smoother)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub step: Option<u8>,
+    /// Angle in degrees for wipe/wave transitions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f32>,
+    /// Origin for grow/outer transitions, e.g. "center" or "0.5,0.5"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pos: Option<String>,
+}
+
+fn default_random_transition_types() -> Vec<String> {
+    ["fade", "wipe", "grow", "center", "outer"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -77,18 +209,107 @@ pub struct ThumbnailConfig {
     pub grid_columns: usize,
     #[serde(default = "default_preload_count")]
     pub preload_count: usize,
+    /// Letterbox each thumbnail to its wallpaper's aspect ratio instead of
+    /// filling the whole cell
+    #[serde(default = "default_preserve_aspect")]
+    pub preserve_aspect: bool,
+    /// Background threads generating thumbnails (0 = one per CPU, at most 4)
+    #[serde(default)]
+    pub workers: usize,
 }
 
+impl ThumbnailConfig {
+    /// Worker threads to spawn: `workers`, or the CPU count capped at 4
+    pub fn worker_count(&self) -> usize {
+        if self.workers > 0 {
+            return self.workers;
+        }
+        thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_AUTO_THUMBNAIL_WORKERS)
+    }
+}
+
+/// Upper bound for the automatic worker count; more threads mostly compete for disk I/O
+const MAX_AUTO_THUMBNAIL_WORKERS: usize = 4;
+
 fn default_preload_count() -> usize {
     3
 }
 
+fn default_preserve_aspect() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThemeConfig {
     pub mode: String, // "auto", "light", "dark"
     pub check_interval_ms: u64,
+    /// Color overrides by name (`accent_primary = "#88c0d0"`), for both variants
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+    /// Applied after `colors` while the terminal is light
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub light_colors: BTreeMap<String, String>,
+    /// Applied after `colors` while the terminal is dark
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dark_colors: BTreeMap<String, String>,
 }
 
+/// Configuration for the TUI color filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColorFilterConfig {
+    /// Minimum perceptual similarity (0.0-1.0) for a palette color to match
+    #[serde(default = "default_color_filter_threshold")]
+    pub threshold: f32,
+    /// How several selected colors combine
+    #[serde(default)]
+    pub mode: ColorFilterMode,
+}
+
+/// How multiple active color filters combine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorFilterMode {
+    /// Wallpaper must contain every selected color
+    #[default]
+    All,
+    /// Wallpaper must contain at least one selected color
+    Any,
+}
+
+impl ColorFilterMode {
+    pub fn next(self) -> Self {
+        match self {
+            ColorFilterMode::All => ColorFilterMode::Any,
+            ColorFilterMode::Any => ColorFilterMode::All,
+        }
+    }
+
+    pub fn display_name(self) -> &'static str {
+        match self {
+            ColorFilterMode::All => "all",
+            ColorFilterMode::Any => "any",
+        }
+    }
+}
+
+fn default_color_filter_threshold() -> f32 {
+    0.7
+}
+
+impl Default for ColorFilterConfig {
+    fn default() -> Self {
+        Self {
+            threshold: default_color_filter_threshold(),
+            mode: ColorFilterMode::default(),
+        }
+    }
+}
+
+/// Step for live color filter threshold adjustment
+const COLOR_THRESHOLD_STEP: f32 = 0.05;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalConfig {
     /// Recommended repaint_delay for kitty.conf (ms)
@@ -100,6 +321,64 @@ pub struct TerminalConfig {
     /// Whether the optimization hint has been shown
     #[serde(default)]
     pub hint_shown: bool,
+    /// Thumbnail graphics protocol: "auto" (detect), "kitty", "sixel", "iterm2" or "halfblocks"
+    #[serde(default)]
+    pub graphics_protocol: GraphicsProtocol,
+}
+
+/// Graphics protocol for rendering thumbnails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphicsProtocol {
+    /// Let ratatui-image guess from the environment and terminal queries
+    #[default]
+    Auto,
+    Kitty,
+    Sixel,
+    Iterm2,
+    Halfblocks,
+}
+
+impl GraphicsProtocol {
+    /// Name as written in config.toml
+    pub fn name(self) -> &'static str {
+        match self {
+            GraphicsProtocol::Auto => "auto",
+            GraphicsProtocol::Kitty => "kitty",
+            GraphicsProtocol::Sixel => "sixel",
+            GraphicsProtocol::Iterm2 => "iterm2",
+            GraphicsProtocol::Halfblocks => "halfblocks",
+        }
+    }
+
+    /// The protocol to force, or `None` to detect
+    pub fn forced(self) -> Option<ProtocolType> {
+        match self {
+            GraphicsProtocol::Auto => None,
+            GraphicsProtocol::Kitty => Some(ProtocolType::Kitty),
+            GraphicsProtocol::Sixel => Some(ProtocolType::Sixel),
+            GraphicsProtocol::Iterm2 => Some(ProtocolType::Iterm2),
+            GraphicsProtocol::Halfblocks => Some(ProtocolType::Halfblocks),
+        }
+    }
+}
+
+/// Image picker for the current terminal, using `protocol` unless it is `Auto`.
+/// `None` when the terminal can't report its font size (not a TTY).
+pub fn detect_picker(protocol: GraphicsProtocol) -> Option<Picker> {
+    let mut picker = Picker::from_termios().ok()?;
+    match protocol.forced() {
+        Some(forced) => {
+            // Detection normally notices tmux; keep passthrough working when forcing
+            picker.protocol_type = forced;
+            picker.is_tmux = std::env::var_os("TMUX").is_some();
+        }
+        None => {
+            // Actively query terminal for graphics protocol support
+            picker.guess_protocol();
+        }
+    }
+    Some(picker)
 }
 
 fn default_repaint_delay() -> u32 {
@@ -116,11 +395,13 @@ impl Default for TerminalConfig {
             recommended_repaint_delay: 5,
             recommended_input_delay: 1,
             hint_shown: false,
+            graphics_protocol: GraphicsProtocol::Auto,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct KeybindingsConfig {
     pub next: String,
     pub prev: String,
@@ -131,6 +412,54 @@ pub struct KeybindingsConfig {
     pub toggle_resize: String,
     pub next_screen: String,
     pub prev_screen: String,
+    /// Enter command mode
+    pub command_mode: String,
+    /// Toggle help popup
+    pub help: String,
+    /// Cycle sort mode
+    pub sort: String,
+    /// Toggle color palette display
+    pub colors: String,
+    /// Open color picker filter
+    pub color_picker: String,
+    /// Toggle pairing preview
+    pub pairing: String,
+    /// Cycle tag filter
+    pub tag_cycle: String,
+    /// Clear tag filter
+    pub tag_clear: String,
+    /// Open tag editor for the selected wallpaper
+    pub tag_editor: String,
+    /// Export pywal colors for the selected wallpaper
+    pub pywal_export: String,
+    /// Toggle automatic pywal export on apply
+    pub pywal_toggle: String,
+    /// Undo last pairing
+    pub undo: String,
+    /// Rescan wallpaper directory
+    pub rescan: String,
+    /// Cycle the selected wallpaper's resize override
+    pub resize_override: String,
+    /// Jump to the next pairing suggestion for the current screen
+    pub next_suggestion: String,
+    /// Jump to the previous pairing suggestion for the current screen
+    pub prev_suggestion: String,
+    /// Tighten the active color filter (raise the similarity threshold)
+    pub color_threshold_up: String,
+    /// Loosen the active color filter (lower the similarity threshold)
+    pub color_threshold_down: String,
+    /// Switch between carousel and grid view
+    pub toggle_view: String,
+    /// Show full metadata for the selected wallpaper
+    pub info: String,
+    /// Cycle pairing style matching (Off/Soft/Strict)
+    pub style_mode: String,
+    /// Browse and re-apply past arrangements
+    pub history: String,
+    /// Toggle putting the applied wallpaper on every screen
+    pub mirror_screens: String,
+    /// Copy the selected wallpaper's path to the clipboard
+    pub copy_path: String,
 }
 
 /// Configuration for CLIP auto-tagging
@@ -157,11 +486,24 @@ pub struct PairingConfig {
     pub undo_window_secs: u64,
     /// Minimum confidence to auto-apply (0.0-1.0)
     pub auto_apply_threshold: f32,
+    /// Show the pairing preview and wait out the undo window before auto-applying,
+    /// instead of switching the other screens right away
+    #[serde(default = "default_pairing_auto_apply_preview")]
+    pub auto_apply_preview: bool,
     /// Maximum history records to keep
     pub max_history_records: usize,
     /// Number of candidate matches shown in pairing preview
     #[serde(default = "default_pairing_preview_match_limit")]
     pub preview_match_limit: usize,
+    /// Allow the same wallpaper on several screens when applying a pairing
+    #[serde(default)]
+    pub allow_duplicates: bool,
+    /// Optimize all screens jointly so the picks also match each other
+    #[serde(default)]
+    pub all_screens: bool,
+    /// Weight for pairwise coherence between screens in all-screens mode
+    #[serde(default = "default_pairing_all_screens_coherence_weight")]
+    pub all_screens_coherence_weight: f32,
     /// Weight for screen-specific co-occurrence history
     #[serde(default = "default_pairing_screen_context_weight")]
     pub screen_context_weight: f32,
@@ -171,6 +513,9 @@ pub struct PairingConfig {
     /// Weight for color harmony bonus
     #[serde(default = "default_pairing_harmony_weight")]
     pub harmony_weight: f32,
+    /// Judge harmonies as seen with a color vision deficiency
+    #[serde(default)]
+    pub harmony_mode: crate::utils::HarmonyMode,
     /// Weight for shared tag bonus
     #[serde(default = "default_pairing_tag_weight")]
     pub tag_weight: f32,
@@ -180,12 +525,26 @@ pub struct PairingConfig {
     /// Multiplier for recent repetition penalty
     #[serde(default = "default_pairing_repetition_penalty_weight")]
     pub repetition_penalty_weight: f32,
+    /// Style matching the TUI starts in: "off", "soft" or "strict" (`y` cycles it)
+    #[serde(default)]
+    pub style_mode: PairingStyleMode,
+    /// Make `frostwall next`/`prev` behave as if `--paired` was given
+    #[serde(default)]
+    pub paired_navigation: bool,
 }
 
 fn default_pairing_preview_match_limit() -> usize {
     10
 }
 
+fn default_pairing_auto_apply_preview() -> bool {
+    true
+}
+
+fn default_pairing_all_screens_coherence_weight() -> f32 {
+    1.0
+}
+
 fn default_pairing_screen_context_weight() -> f32 {
     8.0
 }
@@ -235,6 +594,15 @@ impl Default for DisplayConfig {
             match_mode: MatchMode::Flexible,
             resize_mode: ResizeMode::Fit,
             fill_color: FillColor::black(),
+            random_mode: RandomMode::Uniform,
+            current_symlink: default_current_symlink(),
+            current_link_dir: None,
+            primary_output: None,
+            auto_start_daemon: default_auto_start_daemon(),
+            prefer_native_resolution: false,
+            mirror_screens: false,
+            output_order: Vec::new(),
+            fill_from_palette: false,
         }
     }
 }
@@ -245,6 +613,12 @@ impl Default for TransitionConfig {
             transition_type: "fade".to_string(),
             duration: 1.0,
             fps: 60,
+            random: false,
+            random_types: default_random_transition_types(),
+            bezier: None,
+            step: None,
+            angle: None,
+            pos: None,
         }
     }
 }
@@ -257,6 +631,8 @@ impl Default for ThumbnailConfig {
             quality: 92,
             grid_columns: 3,
             preload_count: 3,
+            preserve_aspect: default_preserve_aspect(),
+            workers: 0,
         }
     }
 }
@@ -266,6 +642,9 @@ impl Default for ThemeConfig {
         Self {
             mode: "auto".to_string(),
             check_interval_ms: 500,
+            colors: BTreeMap::new(),
+            light_colors: BTreeMap::new(),
+            dark_colors: BTreeMap::new(),
         }
     }
 }
@@ -282,6 +661,30 @@ impl Default for KeybindingsConfig {
             toggle_resize: "f".to_string(),
             next_screen: "Tab".to_string(),
             prev_screen: "BackTab".to_string(),
+            command_mode: ":".to_string(),
+            help: "?".to_string(),
+            sort: "s".to_string(),
+            colors: "c".to_string(),
+            color_picker: "C".to_string(),
+            pairing: "p".to_string(),
+            tag_cycle: "t".to_string(),
+            tag_clear: "T".to_string(),
+            tag_editor: "e".to_string(),
+            pywal_export: "w".to_string(),
+            pywal_toggle: "W".to_string(),
+            undo: "u".to_string(),
+            rescan: "R".to_string(),
+            resize_override: "F".to_string(),
+            next_suggestion: "n".to_string(),
+            prev_suggestion: "N".to_string(),
+            color_threshold_up: "+".to_string(),
+            color_threshold_down: "-".to_string(),
+            toggle_view: "g".to_string(),
+            info: "i".to_string(),
+            style_mode: "S".to_string(),
+            history: "H".to_string(),
+            mirror_screens: "M".to_string(),
+            copy_path: "y".to_string(),
         }
     }
 }
@@ -304,14 +707,21 @@ impl Default for PairingConfig {
             auto_apply: false, // Conservative default
             undo_window_secs: 5,
             auto_apply_threshold: 0.7,
+            auto_apply_preview: default_pairing_auto_apply_preview(),
             max_history_records: 1000,
             preview_match_limit: default_pairing_preview_match_limit(),
+            allow_duplicates: false,
+            all_screens: false,
+            all_screens_coherence_weight: default_pairing_all_screens_coherence_weight(),
             screen_context_weight: default_pairing_screen_context_weight(),
             visual_weight: default_pairing_visual_weight(),
             harmony_weight: default_pairing_harmony_weight(),
+            harmony_mode: crate::utils::HarmonyMode::default(),
             tag_weight: default_pairing_tag_weight(),
             semantic_weight: default_pairing_semantic_weight(),
             repetition_penalty_weight: default_pairing_repetition_penalty_weight(),
+            style_mode: PairingStyleMode::default(),
+            paired_navigation: false,
         }
     }
 }
@@ -366,6 +776,80 @@ impl KeybindingsConfig {
     pub fn matches(&self, key: KeyCode, binding: &str) -> bool {
         Self::parse_key(binding) == Some(key)
     }
+
+    /// All (action, binding) pairs, in the order `run_app` checks them
+    fn bindings(&self) -> [(&'static str, &str); 33] {
+        [
+            ("quit", &self.quit),
+            ("next", &self.next),
+            ("prev", &self.prev),
+            ("next_screen", &self.next_screen),
+            ("prev_screen", &self.prev_screen),
+            ("apply", &self.apply),
+            ("random", &self.random),
+            ("toggle_match", &self.toggle_match),
+            ("toggle_resize", &self.toggle_resize),
+            ("resize_override", &self.resize_override),
+            ("command_mode", &self.command_mode),
+            ("help", &self.help),
+            ("tag_editor", &self.tag_editor),
+            ("sort", &self.sort),
+            ("toggle_view", &self.toggle_view),
+            ("info", &self.info),
+            ("history", &self.history),
+            ("style_mode", &self.style_mode),
+            ("colors", &self.colors),
+            ("color_picker", &self.color_picker),
+            ("pairing", &self.pairing),
+            ("tag_cycle", &self.tag_cycle),
+            ("tag_clear", &self.tag_clear),
+            ("pywal_export", &self.pywal_export),
+            ("pywal_toggle", &self.pywal_toggle),
+            ("mirror_screens", &self.mirror_screens),
+            ("copy_path", &self.copy_path),
+            ("undo", &self.undo),
+            ("next_suggestion", &self.next_suggestion),
+            ("prev_suggestion", &self.prev_suggestion),
+            ("color_threshold_up", &self.color_threshold_up),
+            ("color_threshold_down", &self.color_threshold_down),
+            ("rescan", &self.rescan),
+        ]
+    }
+
+    /// Find actions that share a key. Returns (key, first action, second action).
+    pub fn collisions(&self) -> Vec<(String, &'static str, &'static str)> {
+        let bindings = self.bindings();
+        let mut collisions = Vec::new();
+        for (i, (action_a, binding_a)) in bindings.iter().enumerate() {
+            let Some(key_a) = Self::parse_key(binding_a) else {
+                continue;
+            };
+            for (action_b, binding_b) in &bindings[i + 1..] {
+                if Self::parse_key(binding_b) == Some(key_a) {
+                    collisions.push((binding_a.to_string(), *action_a, *action_b));
+                }
+            }
+        }
+        collisions
+    }
+
+    /// Print a warning for every colliding or unparseable binding.
+    fn warn_conflicts(&self) {
+        for (action, binding) in self.bindings() {
+            if Self::parse_key(binding).is_none() {
+                eprintln!(
+                    "Warning: Unknown key '{}' for keybinding '{}'",
+                    binding, action
+                );
+            }
+        }
+        for (key, a, b) in self.collisions() {
+            eprintln!(
+                "Warning: Key '{}' is bound to both '{}' and '{}'; '{}' wins",
+                key, a, b, a
+            );
+        }
+    }
 }
 
 impl Config {
@@ -384,7 +868,10 @@ impl Config {
         if path.exists() {
             let data = fs::read_to_string(&path)?;
             match toml::from_str::<Config>(&data) {
-                Ok(config) => Ok(config),
+                Ok(config) => {
+                    config.keybindings.warn_conflicts();
+                    Ok(config)
+                }
                 Err(e) => {
                     eprintln!(
                         "Warning: Failed to parse config at {}: {}",
@@ -407,6 +894,9 @@ impl Config {
 
     /// Save config to file.
     pub fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
         let path = Self::config_path();
 
         if let Some(parent) = path.parent() {
@@ -419,6 +909,14 @@ impl Config {
         Ok(())
     }
 
+    /// Marker recording the terminal hint when `persist_runtime_changes` is off
+    fn hint_marker_path() -> PathBuf {
+        directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+            .map(|dirs| dirs.cache_dir().to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("/tmp/frostwall"))
+            .join("terminal_hint_shown")
+    }
+
     /// Check if running in Kitty terminal
     pub fn is_kitty_terminal() -> bool {
         std::env::var("TERM")
@@ -430,12 +928,21 @@ impl Config {
     /// Show terminal optimization hint if not shown before
     /// Returns the hint message if it should be shown
     pub fn check_terminal_hint(&mut self) -> Option<String> {
-        if self.terminal.hint_shown || !Self::is_kitty_terminal() {
+        let marker = Self::hint_marker_path();
+        if self.terminal.hint_shown || marker.exists() || !Self::is_kitty_terminal() {
             return None;
         }
 
         self.terminal.hint_shown = true;
-        let _ = self.save(); // Save that hint was shown
+        // Remember that the hint was shown; the caller saves the config, and
+        // one that must not be rewritten gets a marker file in the cache
+        // directory instead
+        if !self.persist_runtime_changes && crate::utils::saves_enabled() {
+            if let Some(parent) = marker.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            let _ = fs::write(&marker, b"");
+        }
 
         Some(format!(
             "Tip: För optimal prestanda i Kitty, lägg till i ~/.config/kitty/kitty.conf:\n\n\
@@ -447,9 +954,20 @@ impl Config {
         ))
     }
 
-    /// Build a Transition struct from config settings.
+    /// Build the swww transition for a single apply.
+    ///
+    /// With `transition.random` enabled a new type is drawn from
+    /// `transition.random_types` on every call.
     pub fn transition(&self) -> Transition {
-        let transition_type = match self.transition.transition_type.as_str() {
+        let name = if self.transition.random {
+            use rand::seq::SliceRandom;
+            crate::utils::with_rng(|rng| self.transition.random_types.choose(rng))
+                .unwrap_or(&self.transition.transition_type)
+        } else {
+            &self.transition.transition_type
+        };
+
+        let transition_type = match name.trim().to_lowercase().as_str() {
             "fade" => TransitionType::Fade,
             "wipe" => TransitionType::Wipe,
             "grow" => TransitionType::Grow,
@@ -463,6 +981,10 @@ impl Config {
             transition_type,
             duration: self.transition.duration,
             fps: self.transition.fps,
+            bezier: self.transition.bezier.clone(),
+            step: self.transition.step,
+            angle: self.transition.angle,
+            pos: self.transition.pos.clone(),
         }
     }
 
@@ -491,39 +1013,128 @@ impl Config {
 pub struct ThumbnailRequest {
     pub cache_idx: usize,
     pub source_path: PathBuf,
+    /// Also encode `▀` cells (the picker has no graphics protocol)
+    pub halfblocks: bool,
 }
 
 /// Response from thumbnail loading
 pub struct ThumbnailResponse {
     pub cache_idx: usize,
     pub image: image::DynamicImage,
+    pub halfblocks: Option<Halfblocks>,
+}
+
+/// A loaded thumbnail, ready to draw
+pub enum Thumbnail {
+    /// Rendered through the terminal graphics protocol (kitty, sixel, iterm2)
+    Graphics(Box<dyn StatefulProtocol>),
+    /// Drawn as colored `▀` cells
+    Halfblocks(Halfblocks),
 }
 
 /// Events from background threads
 pub enum AppEvent {
     Key(event::KeyEvent),
+    Mouse(MouseEvent),
     ThumbnailReady(ThumbnailResponse),
+    ScanProgress {
+        phase: ScanPhase,
+        done: usize,
+        total: usize,
+    },
+    /// `None` when the scan was cancelled; `failed` files were skipped
+    ScanFinished {
+        result: Result<Option<WallpaperCache>>,
+        failed: usize,
+    },
     Resize,
     Tick,
+    /// Outputs were plugged in, removed or resized
+    ScreensChanged(Vec<Screen>),
+}
+
+/// How often the TUI re-detects outputs for hotplug
+const SCREEN_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Send a scan progress event every this many files (plus phase start and end)
+const SCAN_PROGRESS_STEP: usize = 25;
+
+/// State of the background library scan started when there is no usable cache
+pub struct ScanProgress {
+    pub phase: ScanPhase,
+    pub done: usize,
+    pub total: usize,
+    cancel: Arc<AtomicBool>,
+}
+
+impl ScanProgress {
+    fn new() -> Self {
+        Self {
+            phase: ScanPhase::Dimensions,
+            done: 0,
+            total: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Completed fraction of the current phase
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.done as f64 / self.total as f64).min(1.0)
+        }
+    }
 }
 
-/// Thumbnail cache size multiplier over visible grid columns.
+/// Two clicks on the same thumbnail within this window count as a double-click
+const DOUBLE_CLICK_MS: u128 = 400;
+/// Records listed in the history popup
+pub const HISTORY_POPUP_LIMIT: usize = 50;
+
+/// Thumbnail cache size multiplier over visible thumbnails.
 /// Keeps enough thumbnails for smooth scrolling without overwhelming
 /// the terminal graphics protocol.
 const THUMBNAIL_CACHE_MULTIPLIER: usize = 4;
 
+/// How the main browser lays out thumbnails
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    /// Single row centered on the selection
+    #[default]
+    Carousel,
+    /// `grid_columns` wide, as many rows as fit
+    Grid,
+}
+
 /// UI-related transient state (popups, command mode, errors).
 pub struct UiState {
     pub should_quit: bool,
     pub show_help: bool,
+    pub show_info: bool,
     pub show_colors: bool,
     pub show_color_picker: bool,
     pub command_mode: bool,
     pub command_buffer: String,
+    pub show_tag_editor: bool,
+    pub tag_editor_buffer: String,
+    pub tag_editor_idx: usize,
     pub status_message: Option<String>,
     pub pywal_export: bool,
+    pub view_mode: ViewMode,
+    /// Rows that fit in the last drawn grid (used to size the thumbnail cache)
+    pub grid_rows: usize,
     /// Cached theme (updated on theme-change detection, not every frame)
     pub theme: crate::ui::theme::FrostTheme,
+    /// Background scan in progress (first launch or stale cache)
+    pub scan: Option<ScanProgress>,
+    /// Screen area of each thumbnail in the last frame, with its filtered index
+    pub thumb_hits: Vec<(Rect, usize)>,
+    /// Last thumbnail click, for double-click detection
+    last_click: Option<(usize, std::time::Instant)>,
+    /// Pairing history popup, with the highlighted record (0 = newest)
+    pub show_history: bool,
+    pub history_idx: usize,
 }
 
 impl Default for UiState {
@@ -531,13 +1142,24 @@ impl Default for UiState {
         Self {
             should_quit: false,
             show_help: false,
+            show_info: false,
+            show_history: false,
+            history_idx: 0,
             show_colors: false,
             show_color_picker: false,
             command_mode: false,
             command_buffer: String::new(),
+            show_tag_editor: false,
+            tag_editor_buffer: String::new(),
+            tag_editor_idx: 0,
             status_message: None,
             pywal_export: false,
-            theme: crate::ui::theme::frost_theme(),
+            view_mode: ViewMode::Carousel,
+            grid_rows: 1,
+            theme: crate::ui::theme::FrostTheme::default(),
+            scan: None,
+            thumb_hits: Vec::new(),
+            last_click: None,
         }
     }
 }
@@ -546,9 +1168,14 @@ impl Default for UiState {
 pub struct FilterState {
     pub sort_mode: SortMode,
     pub active_tag: Option<String>,
-    pub active_color: Option<String>,
+    /// Selected filter colors, in the order they were picked
+    pub active_colors: Vec<String>,
     pub available_colors: Vec<String>,
     pub color_picker_idx: usize,
+    /// Only show wallpapers modified at or after this unix timestamp
+    pub recent_filter: Option<u64>,
+    /// Only show wallpapers whose rating is in this range
+    pub rating_filter: Option<std::ops::RangeInclusive<u8>>,
 }
 
 impl Default for FilterState {
@@ -556,10 +1183,53 @@ impl Default for FilterState {
         Self {
             sort_mode: SortMode::Name,
             active_tag: None,
-            active_color: None,
+            active_colors: Vec::new(),
             available_colors: Vec::new(),
             color_picker_idx: 0,
+            recent_filter: None,
+            rating_filter: None,
+        }
+    }
+}
+
+/// TUI selection persisted between sessions (`session.json` in the cache dir).
+/// Positions are stored as wallpaper paths so they survive library changes.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionState {
+    /// Name of the screen that was selected on quit
+    #[serde(default)]
+    selected_screen: Option<String>,
+    /// Selected wallpaper per screen name
+    #[serde(default)]
+    positions: HashMap<String, PathBuf>,
+}
+
+impl SessionState {
+    fn path() -> PathBuf {
+        directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+            .map(|dirs| dirs.cache_dir().to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join("session.json")
+    }
+
+    /// Load the previous session; a missing or corrupt file gives an empty one.
+    fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
         }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
     }
 }
 
@@ -576,22 +1246,43 @@ pub struct SelectionState {
 pub struct PairingState {
     pub history: PairingHistory,
     pub suggestions: Vec<PathBuf>,
+    /// Suggested wallpapers per target screen. A screen's entry is kept while
+    /// it is the selected screen, so its suggestions can be cycled through.
+    pub screen_suggestions: HashMap<String, Vec<PathBuf>>,
     pub current_wallpapers: HashMap<String, PathBuf>,
     pub show_preview: bool,
     pub preview_matches: HashMap<String, Vec<(PathBuf, f32, ColorHarmony)>>,
     pub preview_idx: usize,
     pub style_mode: PairingStyleMode,
+    /// Wallpapers to restore on undo once a previewed auto-apply goes through.
+    /// Set while the preview popup counts down.
+    pub auto_apply_pending: Option<HashMap<String, PathBuf>>,
+    /// When the active pairing's duration was last flushed to disk
+    pub last_flush: std::time::Instant,
 }
 
+/// How often the active pairing's running duration is saved
+const PAIRING_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
 /// Thumbnail rendering state.
 pub struct ThumbnailState {
     pub image_picker: Option<Picker>,
-    pub cache: HashMap<usize, Box<dyn StatefulProtocol>>,
+    pub cache: HashMap<usize, Thumbnail>,
     cache_order: Vec<usize>,
     pub loading: std::collections::HashSet<usize>,
     request_tx: Option<Sender<ThumbnailRequest>>,
 }
 
+impl ThumbnailState {
+    /// Whether thumbnails are drawn as `▀` cells instead of through a
+    /// graphics protocol (e.g. over SSH)
+    fn uses_halfblocks(&self) -> bool {
+        self.image_picker
+            .as_ref()
+            .is_none_or(|picker| picker.protocol_type == ProtocolType::Halfblocks)
+    }
+}
+
 pub struct App {
     pub screens: Vec<Screen>,
     pub cache: WallpaperCache,
@@ -601,37 +1292,53 @@ pub struct App {
     pub filters: FilterState,
     pub thumbnails: ThumbnailState,
     pub pairing: PairingState,
+    /// False while `cache` is only the placeholder for a pending first scan
+    pub cache_loaded: bool,
+    /// Profile overlaid on `config`, kept out of config.toml when saving
+    pub profile: Option<Profile>,
 }
 
 impl App {
-    /// Create a new App instance with the given wallpaper directory.
-    pub fn new(wallpaper_dir: PathBuf) -> Result<Self> {
-        let config = Config::load()?;
-        let cache =
-            WallpaperCache::load_or_scan_recursive(&wallpaper_dir, config.wallpaper.recursive)?;
+    /// Create a new App instance with the given wallpaper directory and the
+    /// config as resolved by the CLI, with `profile` already applied.
+    pub fn new(wallpaper_dir: PathBuf, config: Config, profile: Option<Profile>) -> Result<Self> {
+        // Without a usable cache, start empty and let `start_scan` fill the
+        // library in the background so the UI is up right away.
+        let cached = WallpaperCache::load_valid(&wallpaper_dir, &config.scan)?;
+        let scan = cached.is_none().then(ScanProgress::new);
+        let cache = cached
+            .unwrap_or_else(|| WallpaperCache::empty(&wallpaper_dir, config.wallpaper.recursive));
 
         // Try to create image picker for thumbnail rendering
-        // from_termios() queries terminal for font size
-        // guess_protocol() then detects the best graphics protocol (Kitty, Sixel, etc.)
-        let image_picker = Picker::from_termios()
-            .ok()
-            .map(|mut p| {
-                // Actively query terminal for graphics protocol support
-                p.guess_protocol();
-                p
-            })
-            .or_else(|| Some(Picker::new((8, 16))));
+        // from_termios() queries terminal for font size, then the protocol is
+        // detected (Kitty, Sixel, etc.) or taken from terminal.graphics_protocol
+        let protocol = config.terminal.graphics_protocol;
+        let image_picker = detect_picker(protocol).or_else(|| {
+            let mut picker = Picker::new((8, 16));
+            if let Some(forced) = protocol.forced() {
+                picker.protocol_type = forced;
+            }
+            Some(picker)
+        });
 
         // Load pairing history and rebuild affinity scores with corrected formula
         let mut pairing_history = PairingHistory::load(config.pairing.max_history_records)
             .unwrap_or_else(|_| PairingHistory::new(config.pairing.max_history_records));
         pairing_history.rebuild_affinity();
+        let style_mode = config.pairing.style_mode;
+        let theme = crate::ui::theme::frost_theme(&config.theme);
 
         Ok(Self {
             screens: Vec::new(),
             cache,
             config,
-            ui: UiState::default(),
+            cache_loaded: scan.is_none(),
+            profile,
+            ui: UiState {
+                scan,
+                theme,
+                ..UiState::default()
+            },
             selection: SelectionState::default(),
             filters: FilterState::default(),
             thumbnails: ThumbnailState {
@@ -644,27 +1351,120 @@ impl App {
             pairing: PairingState {
                 history: pairing_history,
                 suggestions: Vec::new(),
+                screen_suggestions: HashMap::new(),
                 current_wallpapers: HashMap::new(),
                 show_preview: false,
                 preview_matches: HashMap::new(),
                 preview_idx: 0,
-                style_mode: PairingStyleMode::default(),
+                style_mode,
+                auto_apply_pending: None,
+                last_flush: std::time::Instant::now(),
             },
         })
     }
 
     /// Detect connected screens and refresh the wallpaper filter.
     pub async fn init_screens(&mut self) -> Result<()> {
-        self.screens = screen::detect_screens().await?;
+        self.screens = screen::detect_screens_ordered(&self.config.display.output_order).await?;
         self.update_filtered_wallpapers();
         Ok(())
     }
 
+    /// Restore per-screen positions from the previous session.
+    /// Wallpapers that are no longer in the library are dropped.
+    pub fn restore_session(&mut self) {
+        let session = SessionState::load();
+        if session.positions.is_empty() {
+            return;
+        }
+
+        let target_screen = session
+            .selected_screen
+            .as_deref()
+            .and_then(|name| self.screens.iter().position(|s| s.name == name))
+            .unwrap_or(self.selection.screen_idx);
+
+        for screen_idx in 0..self.screens.len() {
+            let Some(path) = session.positions.get(&self.screens[screen_idx].name) else {
+                continue;
+            };
+            self.selection.screen_idx = screen_idx;
+            self.update_filtered_wallpapers();
+            if let Some(pos) = self
+                .selection
+                .filtered_wallpapers
+                .iter()
+                .position(|&i| self.cache.wallpapers[i].path == *path)
+            {
+                self.selection.screen_positions.insert(screen_idx, pos);
+            }
+        }
+
+        self.selection.screen_idx = target_screen;
+        self.update_filtered_wallpapers();
+        if let Some(&pos) = self.selection.screen_positions.get(&target_screen) {
+            if pos < self.selection.filtered_wallpapers.len() {
+                self.selection.wallpaper_idx = pos;
+            }
+        }
+        self.update_pairing_suggestions();
+    }
+
+    /// Write the config back to config.toml, minus the active profile's overrides.
+    pub fn save_config(&self) -> Result<()> {
+        let Some(profile) = &self.profile else {
+            return self.config.save();
+        };
+        let mut config = self.config.clone();
+        profile.restore_from(&mut config, &Config::load()?);
+        config.save()
+    }
+
+    /// Save per-screen positions for the next session.
+    pub fn save_session(&mut self) -> Result<()> {
+        let current_screen = self.selection.screen_idx;
+        self.selection
+            .screen_positions
+            .insert(current_screen, self.selection.wallpaper_idx);
+
+        let mut session = SessionState {
+            selected_screen: self.screens.get(current_screen).map(|s| s.name.clone()),
+            positions: HashMap::new(),
+        };
+
+        let positions: Vec<(usize, usize)> = self
+            .selection
+            .screen_positions
+            .iter()
+            .map(|(&screen, &pos)| (screen, pos))
+            .collect();
+        for (screen_idx, pos) in positions {
+            let Some(name) = self.screens.get(screen_idx).map(|s| s.name.clone()) else {
+                continue;
+            };
+            // Positions index the filtered list, so rebuild it for that screen
+            self.selection.screen_idx = screen_idx;
+            self.update_filtered_wallpapers();
+            if let Some(&wp_idx) = self.selection.filtered_wallpapers.get(pos) {
+                session
+                    .positions
+                    .insert(name, self.cache.wallpapers[wp_idx].path.clone());
+            }
+        }
+        self.selection.screen_idx = current_screen;
+
+        session.save()
+    }
+
     /// Recompute the filtered wallpaper list based on screen, tag, and color filters.
     pub fn update_filtered_wallpapers(&mut self) {
         let match_mode = self.config.display.match_mode;
         let tag_filter = self.filters.active_tag.as_deref();
-        let color_filter = self.filters.active_color.as_deref();
+        let recent_filter = self.filters.recent_filter;
+        let rating_filter = self.filters.rating_filter.as_ref();
+        let color_filter = &self.filters.active_colors;
+        let color_threshold = self.config.color_filter.threshold;
+        let require_all = self.config.color_filter.mode == ColorFilterMode::All;
 
         if let Some(screen) = self.screens.get(self.selection.screen_idx) {
             self.selection.filtered_wallpapers = self
@@ -683,17 +1483,21 @@ impl App {
                             return false;
                         }
                     }
-                    // Color filtering with perceptual matching
-                    if let Some(color) = color_filter {
-                        // Include if any color is perceptually similar (>0.7 similarity)
-                        let has_similar = wp
-                            .colors
-                            .iter()
-                            .any(|c| crate::utils::color_similarity(c, color) > 0.7);
-                        if !has_similar {
+                    // Recently added/modified
+                    if let Some(cutoff) = recent_filter {
+                        if wp.modified_at < cutoff {
                             return false;
                         }
                     }
+                    if let Some(range) = rating_filter {
+                        if !range.contains(&wp.rating) {
+                            return false;
+                        }
+                    }
+                    // Color filtering with perceptual matching
+                    if !wp.matches_colors(color_filter, color_threshold, require_all) {
+                        return false;
+                    }
                     true
                 })
                 .map(|(i, _)| i)
@@ -728,6 +1532,62 @@ impl App {
         self.config.display.resize_mode = self.config.display.resize_mode.next();
     }
 
+    /// Cycle the selected wallpaper's resize override: none → Crop → Fit → No → Stretch → none
+    pub fn cycle_resize_override(&mut self) {
+        let Some(&idx) = self
+            .selection
+            .filtered_wallpapers
+            .get(self.selection.wallpaper_idx)
+        else {
+            return;
+        };
+        let Some(wp) = self.cache.wallpapers.get_mut(idx) else {
+            return;
+        };
+
+        wp.resize_override = match wp.resize_override {
+            None => Some(ResizeMode::Crop),
+            Some(ResizeMode::Stretch) => None,
+            Some(mode) => Some(mode.next()),
+        };
+        let label = wp
+            .resize_override
+            .map_or("global default", |mode| mode.display_name());
+        self.ui.status_message = Some(format!("Resize override: {}", label));
+
+        if let Err(e) = self.cache.save() {
+            self.ui.status_message = Some(format!("Failed to save resize override: {}", e));
+        }
+    }
+
+    /// Rate the selected wallpaper (0 clears the rating)
+    pub fn rate_selected(&mut self, rating: u8) {
+        let Some(&idx) = self
+            .selection
+            .filtered_wallpapers
+            .get(self.selection.wallpaper_idx)
+        else {
+            return;
+        };
+        let Some(wp) = self.cache.wallpapers.get_mut(idx) else {
+            return;
+        };
+
+        wp.rating = rating.min(crate::wallpaper::MAX_RATING);
+        self.ui.status_message = Some(format!(
+            "Rating: {}",
+            crate::wallpaper::rating_stars(wp.rating)
+        ));
+
+        if let Err(e) = self.cache.save() {
+            self.ui.status_message = Some(format!("Failed to save rating: {}", e));
+        }
+        // A rating filter may no longer match the selection
+        if self.filters.rating_filter.is_some() {
+            self.update_filtered_wallpapers();
+        }
+    }
+
     /// Return the currently selected wallpaper, if any.
     pub fn selected_wallpaper(&self) -> Option<&Wallpaper> {
         self.selection
@@ -762,6 +1622,56 @@ impl App {
         }
     }
 
+    /// Thumbnails visible at once: one row in the carousel, the whole grid otherwise
+    fn page_size(&self) -> usize {
+        let cols = self.config.thumbnails.grid_columns.max(1);
+        match self.ui.view_mode {
+            ViewMode::Carousel => cols,
+            ViewMode::Grid => cols * self.ui.grid_rows.max(1),
+        }
+    }
+
+    /// Select `idx`, clamped to the filtered list
+    fn jump_to(&mut self, idx: usize) {
+        let total = self.selection.filtered_wallpapers.len();
+        if total == 0 {
+            return;
+        }
+        let idx = idx.min(total - 1);
+        if idx != self.selection.wallpaper_idx {
+            self.selection.wallpaper_idx = idx;
+            self.update_pairing_suggestions();
+        }
+    }
+
+    /// Jump forward one page (PageDown / `}`), stopping at the last wallpaper.
+    pub fn page_next(&mut self) {
+        self.jump_to(
+            self.selection
+                .wallpaper_idx
+                .saturating_add(self.page_size()),
+        );
+    }
+
+    /// Jump back one page (PageUp / `{`), stopping at the first wallpaper.
+    pub fn page_prev(&mut self) {
+        self.jump_to(
+            self.selection
+                .wallpaper_idx
+                .saturating_sub(self.page_size()),
+        );
+    }
+
+    /// Jump to the first wallpaper (Home).
+    pub fn jump_first(&mut self) {
+        self.jump_to(0);
+    }
+
+    /// Jump to the last wallpaper (End).
+    pub fn jump_last(&mut self) {
+        self.jump_to(usize::MAX);
+    }
+
     /// Switch to the next screen, preserving cursor position per screen.
     pub fn next_screen(&mut self) {
         if !self.screens.is_empty() {
@@ -818,25 +1728,59 @@ impl App {
         }
     }
 
-    /// Apply the selected wallpaper to the current screen via swww.
+    /// Apply the selected wallpaper to the current screen via swww, or to
+    /// every screen with `display.mirror_screens`.
     pub fn apply_wallpaper(&mut self) -> Result<()> {
+        self.apply_selected(self.config.display.mirror_screens)
+    }
+
+    fn apply_selected(&mut self, mirror: bool) -> Result<()> {
         if let (Some(screen), Some(wp)) = (self.selected_screen(), self.selected_wallpaper()) {
-            let screen_name = screen.name.clone();
+            let targets: Vec<String> = if mirror {
+                self.screens.iter().map(|s| s.name.clone()).collect()
+            } else {
+                vec![screen.name.clone()]
+            };
             let wp_path = wp.path.clone();
             let wp_colors = wp.colors.clone();
+            let resize_mode = wp.resize_mode(self.config.display.resize_mode);
+
+            // Every screen changes at once, so keep what they all showed for undo
+            let previous = (mirror && targets.len() > 1).then(|| {
+                let mut shown = swww::query_current();
+                shown.extend(self.pairing.current_wallpapers.clone());
+                shown.retain(|name, _| targets.contains(name));
+                shown
+            });
+
+            self.cache.mark_shown(&wp_path);
+            for screen_name in &targets {
+                // Update current wallpaper for this screen
+                self.pairing
+                    .current_wallpapers
+                    .insert(screen_name.clone(), wp_path.clone());
 
-            // Update current wallpaper for this screen
-            self.pairing
-                .current_wallpapers
-                .insert(screen_name.clone(), wp_path.clone());
+                swww::apply_wallpaper(
+                    screen_name,
+                    &wp_path,
+                    &self.config.transition(),
+                    resize_mode,
+                    &self.config.display.fill_color_for(&wp_colors),
+                    self.config.scan.gif_mode,
+                )?;
+                if let Err(e) = crate::current::update(&self.config.display, screen_name, &wp_path)
+                {
+                    self.ui.status_message = Some(format!("Current link: {}", e));
+                }
+            }
 
-            swww::set_wallpaper_with_resize(
-                &screen_name,
-                &wp_path,
-                &self.config.transition(),
-                self.config.display.resize_mode,
-                &self.config.display.fill_color,
-            )?;
+            if let Some(previous) = previous {
+                self.pairing.history.begin_undo(
+                    previous,
+                    format!("Mirrored to {} screens", targets.len()),
+                    self.config.pairing.undo_window_secs,
+                );
+            }
 
             // Export pywal colors if enabled
             if self.ui.pywal_export {
@@ -850,15 +1794,27 @@ impl App {
 
     /// Handle undo action (restore previous wallpapers)
     pub fn do_undo(&mut self) -> Result<()> {
-        if let Some(previous) = self.pairing.history.do_undo() {
+        if let Some(mut previous) = self.pairing.history.do_undo() {
+            if let Some((missing, message)) = self.disconnected_outputs(previous.keys()) {
+                previous.retain(|name, _| !missing.contains(name));
+                self.ui.status_message = Some(format!("Undo: {}", message));
+            }
             for (screen_name, wp_path) in &previous {
-                swww::set_wallpaper_with_resize(
+                swww::apply_wallpaper(
                     screen_name,
                     wp_path,
                     &self.config.transition(),
-                    self.config.display.resize_mode,
-                    &self.config.display.fill_color,
+                    self.cache
+                        .resize_mode_for(wp_path, self.config.display.resize_mode),
+                    &self
+                        .config
+                        .display
+                        .fill_color_for(self.cache.colors_for(wp_path)),
+                    self.config.scan.gif_mode,
                 )?;
+                if let Err(e) = crate::current::update(&self.config.display, screen_name, wp_path) {
+                    self.ui.status_message = Some(format!("Current link: {}", e));
+                }
             }
             // Restore current_wallpapers tracking
             self.pairing.current_wallpapers = previous;
@@ -866,18 +1822,146 @@ impl App {
         Ok(())
     }
 
+    /// Outputs among `names` that are no longer connected (monitor unplugged
+    /// since startup), plus a status message naming them and what is connected.
+    fn disconnected_outputs<'a>(
+        &self,
+        names: impl IntoIterator<Item = &'a String>,
+    ) -> Option<(Vec<String>, String)> {
+        // Detection failing says nothing about the outputs; let swww decide
+        let connected = screen::detect_screens_blocking().ok()?;
+        let mut missing: Vec<String> = names
+            .into_iter()
+            .filter(|name| screen::check_output(&connected, name).is_err())
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return None;
+        }
+        missing.sort();
+        let current: Vec<&str> = connected.iter().map(|s| s.name.as_str()).collect();
+        let message = format!(
+            "skipped {} (not connected; current outputs: {})",
+            missing.join(", "),
+            current.join(", ")
+        );
+        Some((missing, message))
+    }
+
     /// Check and clear expired undo window
     pub fn tick_undo(&mut self) {
         self.pairing.history.clear_expired_undo();
+
+        // A previewed auto-apply that wasn't cancelled goes through now
+        if self.pairing.auto_apply_pending.is_some() && !self.pairing.history.can_undo() {
+            if let Err(e) = self.confirm_auto_apply() {
+                self.ui.status_message = Some(format!("Auto-pairing: {}", e));
+            }
+        }
+    }
+
+    /// After applying a wallpaper, pair the other screens if the best match is
+    /// confident enough (`pairing.auto_apply`). Depending on
+    /// `pairing.auto_apply_preview` this applies silently or opens the preview
+    /// and applies when the undo window runs out.
+    pub fn auto_apply_pairing(&mut self) -> Result<()> {
+        let pairing = &self.config.pairing;
+        // Mirroring already filled the other screens
+        if !pairing.enabled
+            || !pairing.auto_apply
+            || self.screens.len() <= 1
+            || self.config.display.mirror_screens
+        {
+            return Ok(());
+        }
+
+        self.update_pairing_preview_matches();
+        let best = self
+            .pairing
+            .preview_matches
+            .values()
+            .filter_map(|matches| matches.first())
+            .map(|(_, score, _)| *score)
+            .fold(None, |best: Option<f32>, score| {
+                Some(best.map_or(score, |b| b.max(score)))
+            });
+        if best.is_none_or(|score| score < self.config.pairing.auto_apply_threshold) {
+            return Ok(());
+        }
+
+        // What the other screens show now, for undo
+        let selected_screen = self.selected_screen().map(|s| s.name.clone());
+        let previous: HashMap<String, PathBuf> = self
+            .pairing
+            .current_wallpapers
+            .iter()
+            .filter(|(name, _)| Some(*name) != selected_screen.as_ref())
+            .map(|(name, path)| (name.clone(), path.clone()))
+            .collect();
+
+        self.pairing.show_preview = true;
+        self.pairing.preview_idx = 0;
+        let undo_secs = self.config.pairing.undo_window_secs;
+
+        if self.config.pairing.auto_apply_preview {
+            self.pairing.history.begin_undo(
+                previous.clone(),
+                "Auto-pairing (Enter applies now)".to_string(),
+                undo_secs,
+            );
+            self.pairing.auto_apply_pending = Some(previous);
+            return Ok(());
+        }
+
+        self.apply_pairing_preview()?;
+        self.pairing.history.begin_undo(
+            previous,
+            "Auto-paired other screens".to_string(),
+            undo_secs,
+        );
+        Ok(())
+    }
+
+    /// Apply a previewed auto-pairing now and open a fresh undo window for it
+    pub fn confirm_auto_apply(&mut self) -> Result<()> {
+        let Some(previous) = self.pairing.auto_apply_pending.take() else {
+            return Ok(());
+        };
+        self.apply_pairing_preview()?;
+        self.pairing.history.begin_undo(
+            previous,
+            "Auto-paired other screens".to_string(),
+            self.config.pairing.undo_window_secs,
+        );
+        Ok(())
+    }
+
+    /// Drop a previewed auto-pairing before it is applied
+    pub fn cancel_auto_apply(&mut self) {
+        if self.pairing.auto_apply_pending.take().is_some() {
+            // The countdown reused the undo window; nothing was changed yet
+            let _ = self.pairing.history.do_undo();
+            self.ui.status_message = Some("Auto-pairing cancelled".to_string());
+        }
+    }
+
+    /// Periodically persist the running duration of the active pairing
+    pub fn tick_pairing_flush(&mut self) {
+        if self.pairing.last_flush.elapsed() < PAIRING_FLUSH_INTERVAL {
+            return;
+        }
+        self.pairing.last_flush = std::time::Instant::now();
+        if let Err(e) = self.pairing.history.flush_current_pairing() {
+            self.ui.status_message = Some(format!("Failed to save pairing history: {}", e));
+        }
     }
 
     /// Pick a random wallpaper from the filtered list and apply it.
     pub fn random_wallpaper(&mut self) -> Result<()> {
         if !self.selection.filtered_wallpapers.is_empty() {
             use rand::Rng;
-            let mut rng = rand::thread_rng();
-            self.selection.wallpaper_idx =
-                rng.gen_range(0..self.selection.filtered_wallpapers.len());
+            let len = self.selection.filtered_wallpapers.len();
+            self.selection.wallpaper_idx = crate::utils::with_rng(|rng| rng.gen_range(0..len));
 
             // Apply immediately
             self.apply_wallpaper()?;
@@ -904,6 +1988,7 @@ impl App {
                 let request = ThumbnailRequest {
                     cache_idx,
                     source_path: wp.path.clone(),
+                    halfblocks: self.thumbnails.uses_halfblocks(),
                 };
                 if tx.send(request).is_ok() {
                     self.thumbnails.loading.insert(cache_idx);
@@ -912,10 +1997,14 @@ impl App {
         }
     }
 
-    /// Dynamic max thumbnail cache size based on grid columns.
+    /// Dynamic max thumbnail cache size based on how many thumbnails are on screen.
     fn max_thumbnail_cache(&self) -> usize {
         let cols = self.config.thumbnails.grid_columns.max(1);
-        (cols * THUMBNAIL_CACHE_MULTIPLIER).max(12)
+        let visible = match self.ui.view_mode {
+            ViewMode::Carousel => cols,
+            ViewMode::Grid => cols * self.ui.grid_rows.max(1),
+        };
+        (visible * THUMBNAIL_CACHE_MULTIPLIER).max(12)
     }
 
     /// Handle a loaded thumbnail from background thread
@@ -927,26 +2016,31 @@ impl App {
             return;
         }
 
+        let thumbnail = match response.halfblocks {
+            Some(halfblocks) => Thumbnail::Halfblocks(halfblocks),
+            None => match &mut self.thumbnails.image_picker {
+                Some(picker) => Thumbnail::Graphics(picker.new_resize_protocol(response.image)),
+                None => return,
+            },
+        };
+
+        // Evict oldest entries if cache is full
         let max_cache = self.max_thumbnail_cache();
-        if let Some(picker) = &mut self.thumbnails.image_picker {
-            // Evict oldest entries if cache is full
-            while self.thumbnails.cache.len() >= max_cache {
-                if let Some(oldest_idx) = self.thumbnails.cache_order.first().copied() {
-                    self.thumbnails.cache.remove(&oldest_idx);
-                    self.thumbnails.cache_order.remove(0);
-                } else {
-                    break;
-                }
+        while self.thumbnails.cache.len() >= max_cache {
+            if let Some(oldest_idx) = self.thumbnails.cache_order.first().copied() {
+                self.thumbnails.cache.remove(&oldest_idx);
+                self.thumbnails.cache_order.remove(0);
+            } else {
+                break;
             }
-
-            let protocol = picker.new_resize_protocol(response.image);
-            self.thumbnails.cache.insert(response.cache_idx, protocol);
-            self.thumbnails.cache_order.push(response.cache_idx);
         }
+
+        self.thumbnails.cache.insert(response.cache_idx, thumbnail);
+        self.thumbnails.cache_order.push(response.cache_idx);
     }
 
     /// Check if a thumbnail is ready (also updates LRU order)
-    pub fn get_thumbnail(&mut self, cache_idx: usize) -> Option<&mut Box<dyn StatefulProtocol>> {
+    pub fn get_thumbnail(&mut self, cache_idx: usize) -> Option<&mut Thumbnail> {
         if self.thumbnails.cache.contains_key(&cache_idx) {
             // Move to end of LRU order (most recently used)
             if let Some(pos) = self
@@ -972,6 +2066,65 @@ impl App {
         self.thumbnails.request_tx = Some(tx);
     }
 
+    /// Switch to a new set of connected outputs. The selected screen and the
+    /// per-screen positions follow their output by name; if the selected
+    /// output is gone, the index is clamped to the remaining ones.
+    pub fn handle_screens_changed(&mut self, mut screens: Vec<Screen>) {
+        screen::sort_by_output_order(&mut screens, &self.config.display.output_order);
+        let changes = screen::diff_screens(&self.screens, &screens);
+        if changes.is_empty() {
+            return;
+        }
+
+        self.selection
+            .screen_positions
+            .insert(self.selection.screen_idx, self.selection.wallpaper_idx);
+        let selected = self
+            .screens
+            .get(self.selection.screen_idx)
+            .map(|s| s.name.clone());
+        let positions: HashMap<String, usize> = self
+            .selection
+            .screen_positions
+            .iter()
+            .filter_map(|(&idx, &pos)| self.screens.get(idx).map(|s| (s.name.clone(), pos)))
+            .collect();
+
+        self.screens = screens;
+        self.selection.screen_positions = self
+            .screens
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, s)| positions.get(&s.name).map(|&pos| (idx, pos)))
+            .collect();
+        self.selection.screen_idx = selected
+            .and_then(|name| self.screens.iter().position(|s| s.name == name))
+            .unwrap_or(self.selection.screen_idx)
+            .min(self.screens.len().saturating_sub(1));
+
+        self.update_filtered_wallpapers();
+        if let Some(&pos) = self
+            .selection
+            .screen_positions
+            .get(&self.selection.screen_idx)
+        {
+            if pos < self.selection.filtered_wallpapers.len() {
+                self.selection.wallpaper_idx = pos;
+            }
+        }
+        self.update_pairing_suggestions();
+
+        let summary: Vec<String> = changes
+            .iter()
+            .map(|change| match change {
+                screen::ScreenChange::Added(s) => format!("+{}", s.name),
+                screen::ScreenChange::Removed(s) => format!("-{}", s.name),
+                screen::ScreenChange::Resized { after, .. } => format!("~{}", after.name),
+            })
+            .collect();
+        self.ui.status_message = Some(format!("Screens changed: {}", summary.join(" ")));
+    }
+
     /// Handle terminal resize: clear thumbnail cache and re-init picker.
     /// StatefulProtocol objects are sized for the old terminal dimensions
     /// and will render garbled if reused after resize.
@@ -981,8 +2134,7 @@ impl App {
         self.thumbnails.loading.clear();
 
         // Re-detect font metrics for the new terminal size
-        if let Ok(mut picker) = Picker::from_termios() {
-            picker.guess_protocol();
+        if let Some(picker) = detect_picker(self.config.terminal.graphics_protocol) {
             self.thumbnails.image_picker = Some(picker);
         }
     }
@@ -991,8 +2143,14 @@ impl App {
     /// existing tags, auto-tags, CLIP embeddings and color data intact.
     /// Returns a human-readable status message.
     pub fn rescan(&mut self) -> Result<String> {
+        if self.ui.scan.is_some() {
+            anyhow::bail!("scan already in progress");
+        }
         let recursive = self.config.wallpaper.recursive;
-        let (added, removed) = self.cache.incremental_rescan(recursive)?;
+        let (added, removed) = self
+            .cache
+            .incremental_rescan(recursive, &self.config.scan)?;
+        self.cache_loaded = true;
         self.update_filtered_wallpapers();
 
         let total = self.cache.wallpapers.len();
@@ -1020,11 +2178,221 @@ impl App {
         Ok(parts.join(", "))
     }
 
+    /// Run the pending background scan, reporting through `tx`.
+    pub fn start_scan(&self, tx: Sender<AppEvent>) {
+        let Some(progress) = &self.ui.scan else {
+            return;
+        };
+        let cancel = Arc::clone(&progress.cancel);
+        let source_dir = self.cache.source_dir.clone();
+        let recursive = self.config.wallpaper.recursive;
+        let scan = self.config.scan.clone();
+        thread::spawn(move || {
+            scan_worker(source_dir, recursive, scan, cancel, tx);
+        });
+    }
+
+    /// Ask the background scan to stop; it reports back with `ScanFinished`.
+    pub fn cancel_scan(&mut self) {
+        if let Some(progress) = &self.ui.scan {
+            progress.cancel.store(true, Ordering::Relaxed);
+            self.ui.status_message = Some("Cancelling scan...".to_string());
+        }
+    }
+
+    fn handle_scan_progress(&mut self, phase: ScanPhase, done: usize, total: usize) {
+        if let Some(progress) = &mut self.ui.scan {
+            progress.phase = phase;
+            progress.done = done;
+            progress.total = total;
+        }
+    }
+
+    fn handle_scan_finished(&mut self, result: Result<Option<WallpaperCache>>, failed: usize) {
+        self.ui.scan = None;
+        let cache = match result {
+            Ok(Some(cache)) => cache,
+            Ok(None) => {
+                self.ui.status_message = Some("Scan cancelled (R rescans the library)".to_string());
+                return;
+            }
+            Err(e) => {
+                self.ui.status_message = Some(format!("Scan failed: {}", e));
+                return;
+            }
+        };
+
+        self.cache = cache;
+        self.cache_loaded = true;
+        self.update_filtered_wallpapers();
+        self.restore_session();
+
+        let mut message = format!("Scanned {} wallpapers", self.cache.wallpapers.len());
+        if failed > 0 {
+            message.push_str(&format!(", {} unreadable", failed));
+        }
+        if let Err(e) = self.cache.save() {
+            message = format!("Scan done, but saving the cache failed: {}", e);
+        }
+        self.ui.status_message = Some(message);
+    }
+
+    /// Handle a mouse event: click selects a thumbnail, double-click applies it,
+    /// and the wheel moves through the list. Ignored while a popup is open.
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Result<()> {
+        if self.ui.show_help
+            || self.ui.show_info
+            || self.ui.show_history
+            || self.ui.scan.is_some()
+            || self.ui.show_color_picker
+            || self.ui.command_mode
+            || self.ui.show_tag_editor
+            || self.pairing.show_preview
+            || self.pairing.history.can_undo()
+        {
+            return Ok(());
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown => self.next_wallpaper(),
+            MouseEventKind::ScrollUp => self.prev_wallpaper(),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let Some(idx) = self
+                    .ui
+                    .thumb_hits
+                    .iter()
+                    .find(|(rect, _)| rect.contains((mouse.column, mouse.row).into()))
+                    .map(|&(_, idx)| idx)
+                else {
+                    self.ui.last_click = None;
+                    return Ok(());
+                };
+
+                let now = std::time::Instant::now();
+                let double = matches!(
+                    self.ui.last_click,
+                    Some((last, at)) if last == idx && now.duration_since(at).as_millis() <= DOUBLE_CLICK_MS
+                );
+
+                if idx != self.selection.wallpaper_idx {
+                    self.selection.wallpaper_idx = idx;
+                    self.update_pairing_suggestions();
+                }
+
+                if double {
+                    self.ui.last_click = None;
+                    return self
+                        .apply_wallpaper()
+                        .and_then(|_| self.auto_apply_pairing());
+                }
+                self.ui.last_click = Some((idx, now));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     /// Toggle help popup
     pub fn toggle_help(&mut self) {
         self.ui.show_help = !self.ui.show_help;
     }
 
+    /// Toggle the metadata popup for the selected wallpaper
+    pub fn toggle_info(&mut self) {
+        self.ui.show_info = !self.ui.show_info && self.selected_wallpaper().is_some();
+    }
+
+    /// Toggle the pairing history popup
+    pub fn toggle_history(&mut self) {
+        if self.ui.show_history {
+            self.ui.show_history = false;
+        } else if self.pairing.history.record_count() == 0 {
+            self.ui.status_message = Some("No pairing history yet".to_string());
+        } else {
+            self.ui.show_history = true;
+            self.ui.history_idx = 0;
+        }
+    }
+
+    /// Move the history highlight; `older` goes back in time
+    pub fn history_move(&mut self, older: bool) {
+        let count = self
+            .pairing
+            .history
+            .recent_records(HISTORY_POPUP_LIMIT)
+            .len();
+        if count == 0 {
+            return;
+        }
+        self.ui.history_idx = if older {
+            (self.ui.history_idx + 1).min(count - 1)
+        } else {
+            self.ui.history_idx.saturating_sub(1)
+        };
+    }
+
+    /// Re-apply the highlighted history record and record it as a new pairing
+    pub fn apply_history_selection(&mut self) -> Result<()> {
+        let Some(record) = self
+            .pairing
+            .history
+            .recent_records(HISTORY_POPUP_LIMIT)
+            .get(self.ui.history_idx)
+            .map(|record| record.wallpapers.clone())
+        else {
+            return Ok(());
+        };
+        self.ui.show_history = false;
+
+        let mut picks: Vec<(String, PathBuf)> = record.into_iter().collect();
+        picks.sort();
+        if let Some((missing, message)) =
+            self.disconnected_outputs(picks.iter().map(|(name, _)| name))
+        {
+            picks.retain(|(name, _)| !missing.contains(name));
+            self.ui.status_message = Some(format!("History: {}", message));
+        }
+
+        for (screen_name, wp_path) in picks {
+            if !wp_path.exists() {
+                self.ui.status_message =
+                    Some(format!("History: {} no longer exists", wp_path.display()));
+                continue;
+            }
+            swww::apply_wallpaper(
+                &screen_name,
+                &wp_path,
+                &self.config.transition(),
+                self.cache
+                    .resize_mode_for(&wp_path, self.config.display.resize_mode),
+                &self
+                    .config
+                    .display
+                    .fill_color_for(self.cache.colors_for(&wp_path)),
+                self.config.scan.gif_mode,
+            )?;
+            if let Err(e) = crate::current::update(&self.config.display, &screen_name, &wp_path) {
+                self.ui.status_message = Some(format!("Current link: {}", e));
+            }
+            self.cache.mark_shown(&wp_path);
+            self.pairing.current_wallpapers.insert(screen_name, wp_path);
+        }
+
+        if self.pairing.current_wallpapers.len() > 1 {
+            self.pairing
+                .history
+                .record_pairing(self.pairing.current_wallpapers.clone(), true);
+            if let Err(e) = crate::collections::capture_recent(
+                &self.config.collections,
+                &self.pairing.current_wallpapers,
+            ) {
+                self.ui.status_message = Some(format!("Recent collection: {}", e));
+            }
+            self.update_pairing_suggestions();
+        }
+        Ok(())
+    }
+
     /// Cycle through sort modes
     pub fn toggle_sort_mode(&mut self) {
         self.filters.sort_mode = self.filters.sort_mode.next();
@@ -1053,8 +2421,43 @@ impl App {
             }
         });
 
-        // Reset selection after sort
-        self.selection.wallpaper_idx = 0;
+        // Reset selection after sort
+        self.selection.wallpaper_idx = 0;
+    }
+
+    /// Switch between the carousel and the grid view
+    pub fn toggle_view_mode(&mut self) {
+        self.ui.view_mode = match self.ui.view_mode {
+            ViewMode::Carousel => ViewMode::Grid,
+            ViewMode::Grid => ViewMode::Carousel,
+        };
+        // Protocols are sized for the old cell size
+        self.thumbnails.cache.clear();
+        self.thumbnails.cache_order.clear();
+    }
+
+    /// Move the grid selection one row down (`forward`) or up.
+    /// Moving down from a row above a short last row lands on its last item.
+    pub fn move_grid_row(&mut self, forward: bool) {
+        let total = self.selection.filtered_wallpapers.len();
+        if total == 0 {
+            return;
+        }
+        let cols = self.config.thumbnails.grid_columns.max(1);
+        let idx = self.selection.wallpaper_idx.min(total - 1);
+        let target = if forward {
+            if idx / cols == (total - 1) / cols {
+                return;
+            }
+            (idx + cols).min(total - 1)
+        } else {
+            match idx.checked_sub(cols) {
+                Some(target) => target,
+                None => return,
+            }
+        };
+        self.selection.wallpaper_idx = target;
+        self.update_pairing_suggestions();
     }
 
     /// Toggle color display for selected wallpaper
@@ -1107,6 +2510,94 @@ impl App {
         self.cache.all_tags()
     }
 
+    // ===== Tag Editor =====
+
+    /// Open the tag editor for the selected wallpaper
+    pub fn open_tag_editor(&mut self) {
+        if self.selected_wallpaper().is_none() {
+            return;
+        }
+        self.ui.show_tag_editor = true;
+        self.ui.tag_editor_buffer.clear();
+        self.ui.tag_editor_idx = 0;
+    }
+
+    /// Close the tag editor and persist any tag changes
+    pub fn close_tag_editor(&mut self) {
+        self.ui.show_tag_editor = false;
+        self.ui.tag_editor_buffer.clear();
+        if let Err(e) = self.cache.save() {
+            self.ui.status_message = Some(format!("Failed to save tags: {}", e));
+        }
+        // Tag changes can move wallpapers in or out of an active tag filter
+        if self.filters.active_tag.is_some() {
+            self.update_filtered_wallpapers();
+        }
+    }
+
+    /// Manual tags of the selected wallpaper
+    pub fn tag_editor_tags(&self) -> &[String] {
+        self.selected_wallpaper()
+            .map(|wp| wp.tags.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn tag_editor_input(&mut self, c: char) {
+        self.ui.tag_editor_buffer.push(c);
+    }
+
+    pub fn tag_editor_backspace(&mut self) {
+        self.ui.tag_editor_buffer.pop();
+    }
+
+    /// Select the next existing tag
+    pub fn tag_editor_next(&mut self) {
+        let count = self.tag_editor_tags().len();
+        if count > 0 {
+            self.ui.tag_editor_idx = (self.ui.tag_editor_idx + 1) % count;
+        }
+    }
+
+    /// Select the previous existing tag
+    pub fn tag_editor_prev(&mut self) {
+        let count = self.tag_editor_tags().len();
+        if count > 0 {
+            self.ui.tag_editor_idx = if self.ui.tag_editor_idx == 0 {
+                count - 1
+            } else {
+                self.ui.tag_editor_idx - 1
+            };
+        }
+    }
+
+    /// Add the typed tag to the selected wallpaper
+    pub fn tag_editor_submit(&mut self) {
+        let tag = self.ui.tag_editor_buffer.trim().to_string();
+        self.ui.tag_editor_buffer.clear();
+        if tag.is_empty() {
+            return;
+        }
+        if let Some(path) = self.selected_wallpaper().map(|wp| wp.path.clone()) {
+            if self.cache.add_tag(&path, &tag) {
+                self.ui.status_message = Some(format!("Added tag: {}", tag.to_lowercase()));
+            }
+        }
+    }
+
+    /// Remove the highlighted tag from the selected wallpaper
+    pub fn tag_editor_remove_selected(&mut self) {
+        let Some(tag) = self.tag_editor_tags().get(self.ui.tag_editor_idx).cloned() else {
+            return;
+        };
+        if let Some(path) = self.selected_wallpaper().map(|wp| wp.path.clone()) {
+            if self.cache.remove_tag(&path, &tag) {
+                self.ui.status_message = Some(format!("Removed tag: {}", tag));
+            }
+        }
+        let count = self.tag_editor_tags().len();
+        self.ui.tag_editor_idx = self.ui.tag_editor_idx.min(count.saturating_sub(1));
+    }
+
     // ===== Command Mode (vim-style :) =====
 
     /// Enter command mode
@@ -1182,10 +2673,53 @@ impl App {
             // Clear filters
             "c" | "clear" => {
                 self.filters.active_tag = None;
-                self.filters.active_color = None;
+                self.filters.active_colors.clear();
+                self.filters.recent_filter = None;
+                self.filters.rating_filter = None;
                 self.update_filtered_wallpapers();
             }
 
+            // Star rating filter
+            "rating" | "stars" => {
+                if args.is_empty() || args == "off" {
+                    self.filters.rating_filter = None;
+                    self.update_filtered_wallpapers();
+                    self.ui.status_message = Some("Rating filter cleared".to_string());
+                } else if let Some(range) = crate::wallpaper::parse_rating_filter(args) {
+                    self.filters.rating_filter = Some(range);
+                    self.update_filtered_wallpapers();
+                    self.ui.status_message = Some(format!(
+                        "Rating {}: {} wallpaper(s)",
+                        args,
+                        self.selection.filtered_wallpapers.len()
+                    ));
+                } else {
+                    self.ui.status_message =
+                        Some(format!("Invalid rating filter: {} (e.g. >=4, <2, 5)", args));
+                }
+            }
+
+            // Recently added wallpapers
+            "recent" | "new" => {
+                if args.is_empty() || args == "off" {
+                    self.filters.recent_filter = None;
+                    self.update_filtered_wallpapers();
+                    self.ui.status_message = Some("Recent filter cleared".to_string());
+                } else if let Some(window) = crate::watch::parse_interval(args) {
+                    self.filters.recent_filter = Some(crate::wallpaper::recent_cutoff(window));
+                    self.filters.sort_mode = SortMode::Date;
+                    self.update_filtered_wallpapers();
+                    self.ui.status_message = Some(format!(
+                        "Recent {}: {} wallpaper(s)",
+                        args,
+                        self.selection.filtered_wallpapers.len()
+                    ));
+                } else {
+                    self.ui.status_message =
+                        Some(format!("Invalid duration: {} (e.g. 7d, 12h, 30m)", args));
+                }
+            }
+
             // Random wallpaper
             "r" | "random" => {
                 let _ = self.random_wallpaper();
@@ -1359,23 +2893,66 @@ impl App {
         }
     }
 
-    /// Apply selected color filter
-    pub fn apply_color_filter(&mut self) {
+    /// Toggle the color under the picker cursor in the active color set
+    pub fn toggle_picker_color(&mut self) {
         if let Some(color) = self
             .filters
             .available_colors
             .get(self.filters.color_picker_idx)
         {
-            self.filters.active_color = Some(color.clone());
-            self.ui.show_color_picker = false;
+            if let Some(pos) = self.filters.active_colors.iter().position(|c| c == color) {
+                self.filters.active_colors.remove(pos);
+            } else {
+                self.filters.active_colors.push(color.clone());
+            }
             self.update_filtered_wallpapers();
         }
     }
 
+    /// Apply selected color filter. With no colors toggled, filters by the
+    /// color under the cursor alone.
+    pub fn apply_color_filter(&mut self) {
+        if self.filters.active_colors.is_empty() {
+            if let Some(color) = self
+                .filters
+                .available_colors
+                .get(self.filters.color_picker_idx)
+            {
+                self.filters.active_colors.push(color.clone());
+            }
+        }
+        self.ui.show_color_picker = false;
+        self.update_filtered_wallpapers();
+    }
+
+    /// Switch between requiring all or any of the selected colors
+    pub fn toggle_color_filter_mode(&mut self) {
+        self.config.color_filter.mode = self.config.color_filter.mode.next();
+        self.update_filtered_wallpapers();
+    }
+
     /// Clear color filter
     pub fn clear_color_filter(&mut self) {
-        self.filters.active_color = None;
+        self.filters.active_colors.clear();
+        self.update_filtered_wallpapers();
+    }
+
+    /// Nudge the color filter threshold while a color filter is active
+    pub fn adjust_color_threshold(&mut self, delta: f32) {
+        if self.filters.active_colors.is_empty() {
+            self.ui.status_message = Some("Color threshold: no color filter active".to_string());
+            return;
+        }
+        let threshold = (self.config.color_filter.threshold + delta).clamp(0.0, 1.0);
+        // Snap to the step grid so repeated presses stay on round values
+        self.config.color_filter.threshold =
+            (threshold / COLOR_THRESHOLD_STEP).round() * COLOR_THRESHOLD_STEP;
         self.update_filtered_wallpapers();
+        self.ui.status_message = Some(format!(
+            "Color threshold: {:.2} ({} matches)",
+            self.config.color_filter.threshold,
+            self.selection.filtered_wallpapers.len()
+        ));
     }
 
     /// Export pywal colors for current wallpaper
@@ -1391,11 +2968,38 @@ impl App {
         self.ui.pywal_export = !self.ui.pywal_export;
     }
 
+    /// Toggle applying the selected wallpaper to every screen
+    pub fn toggle_mirror_screens(&mut self) {
+        let display = &mut self.config.display;
+        display.mirror_screens = !display.mirror_screens;
+        self.ui.status_message = Some(if display.mirror_screens {
+            "Mirror: apply sets every screen".to_string()
+        } else {
+            "Mirror off: apply sets the selected screen".to_string()
+        });
+    }
+
+    /// Copy the selected wallpaper's path to the clipboard. Without a
+    /// clipboard tool the path is shown in the status bar instead.
+    pub fn copy_selected_path(&mut self) {
+        let Some(path) = self
+            .selected_wallpaper()
+            .map(|wp| wp.path.display().to_string())
+        else {
+            return;
+        };
+        self.ui.status_message = Some(match crate::utils::copy_to_clipboard(&path) {
+            Ok(_) => format!("Copied: {}", path),
+            Err(_) => format!("No clipboard (wl-copy/xclip/xsel): {}", path),
+        });
+    }
+
     /// Update pairing suggestions based on currently selected wallpaper
     pub fn update_pairing_suggestions(&mut self) {
         self.pairing.suggestions.clear();
 
         if !self.config.pairing.enabled {
+            self.pairing.screen_suggestions.clear();
             return;
         }
 
@@ -1415,6 +3019,7 @@ impl App {
 
         // Get suggestions from pairing history
         let match_mode = self.config.display.match_mode;
+        let suggestion_limit = self.config.pairing.preview_match_limit.clamp(1, 50);
 
         // For each other screen, find suggested wallpapers
         for (screen_idx, screen) in self.screens.iter().enumerate() {
@@ -1441,27 +3046,94 @@ impl App {
                 screen_context_weight: self.config.pairing.screen_context_weight,
                 visual_weight: self.config.pairing.visual_weight,
                 harmony_weight: self.config.pairing.harmony_weight,
+                harmony_mode: self.config.pairing.harmony_mode,
                 tag_weight: self.config.pairing.tag_weight,
                 semantic_weight: self.config.pairing.semantic_weight,
                 repetition_penalty_weight: self.config.pairing.repetition_penalty_weight,
                 style_mode: PairingStyleMode::Off,
                 selected_style_tags: &selected_style_tags,
             };
-            if let Some(suggested_path) = self
+            let top_matches: Vec<PathBuf> = self
                 .pairing
                 .history
-                .get_best_match(&match_context, &matching)
-            {
-                if !self.pairing.suggestions.contains(&suggested_path) {
-                    self.pairing.suggestions.push(suggested_path);
+                .get_top_matches(&match_context, &matching, suggestion_limit)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            if let Some(suggested_path) = top_matches.first() {
+                if !self.pairing.suggestions.contains(suggested_path) {
+                    self.pairing.suggestions.push(suggested_path.clone());
                 }
             }
+            self.pairing
+                .screen_suggestions
+                .insert(screen.name.clone(), top_matches);
         }
     }
 
     /// Check if a wallpaper is in the pairing suggestions
     pub fn is_pairing_suggestion(&self, path: &std::path::Path) -> bool {
         self.pairing.suggestions.iter().any(|p| p == path)
+            || self
+                .selected_screen()
+                .and_then(|screen| self.pairing.screen_suggestions.get(&screen.name))
+                .is_some_and(|paths| paths.iter().any(|p| p == path))
+    }
+
+    /// Positions in `filtered_wallpapers` of the selected screen's own
+    /// suggestions. `pairing.suggestions` is left out: those are picks for the
+    /// other screens and change with every selection.
+    fn suggested_positions(&self) -> Vec<usize> {
+        let Some(suggested) = self
+            .selected_screen()
+            .and_then(|screen| self.pairing.screen_suggestions.get(&screen.name))
+        else {
+            return Vec::new();
+        };
+        self.selection
+            .filtered_wallpapers
+            .iter()
+            .enumerate()
+            .filter(|(_, &idx)| {
+                self.cache
+                    .wallpapers
+                    .get(idx)
+                    .is_some_and(|wp| suggested.contains(&wp.path))
+            })
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    /// Move the selection to the next (or previous) suggested wallpaper,
+    /// wrapping around the filtered list.
+    pub fn jump_to_suggestion(&mut self, forward: bool) {
+        let positions = self.suggested_positions();
+        let current = self.selection.wallpaper_idx;
+        let target = if forward {
+            positions
+                .iter()
+                .find(|&&pos| pos > current)
+                .or(positions.first())
+        } else {
+            positions
+                .iter()
+                .rev()
+                .find(|&&pos| pos < current)
+                .or(positions.last())
+        };
+
+        match target {
+            Some(&pos) if pos != current => {
+                self.selection.wallpaper_idx = pos;
+                // Refreshes the other screens only; the selected screen's
+                // entry is skipped, so repeated jumps cycle a fixed set
+                self.update_pairing_suggestions();
+            }
+            Some(_) => {}
+            None => {
+                self.ui.status_message = Some("No pairing suggestions for this screen".to_string());
+            }
+        }
     }
 
     /// Toggle pairing preview popup
@@ -1512,6 +3184,7 @@ impl App {
 
         let match_mode = self.config.display.match_mode;
         let preview_limit = self.config.pairing.preview_match_limit.clamp(1, 50);
+        let harmony_mode = self.config.pairing.harmony_mode;
         let wallpaper_by_path: HashMap<&std::path::Path, &Wallpaper> = self
             .cache
             .wallpapers
@@ -1543,6 +3216,7 @@ impl App {
                 screen_context_weight: self.config.pairing.screen_context_weight,
                 visual_weight: self.config.pairing.visual_weight,
                 harmony_weight: self.config.pairing.harmony_weight,
+                harmony_mode: self.config.pairing.harmony_mode,
                 tag_weight: self.config.pairing.tag_weight,
                 semantic_weight: self.config.pairing.semantic_weight,
                 repetition_penalty_weight: self.config.pairing.repetition_penalty_weight,
@@ -1567,11 +3241,12 @@ impl App {
                             } else {
                                 wp.color_weights.clone()
                             };
-                            let (harmony, _strength) = crate::utils::detect_harmony(
+                            let (harmony, _strength) = crate::utils::detect_harmony_for(
                                 &selected_colors,
                                 &selected_weights,
                                 &wp.colors,
                                 &wp_weights,
+                                harmony_mode,
                             );
                             harmony
                         })
@@ -1586,6 +3261,66 @@ impl App {
                     .insert(screen.name.clone(), matches_with_harmony);
             }
         }
+
+        if self.config.pairing.all_screens && self.pairing.preview_matches.len() > 1 {
+            Self::optimize_preview_sets(
+                &mut self.pairing.preview_matches,
+                &wallpaper_by_path,
+                self.config.pairing.all_screens_coherence_weight,
+                harmony_mode,
+                preview_limit,
+            );
+        }
+    }
+
+    /// Reorder preview alternatives so that alternative `i` on every screen
+    /// forms the i-th best jointly scored set, instead of each screen being
+    /// ranked only against the selected wallpaper.
+    fn optimize_preview_sets(
+        preview_matches: &mut HashMap<String, Vec<(PathBuf, f32, ColorHarmony)>>,
+        wallpaper_by_path: &HashMap<&std::path::Path, &Wallpaper>,
+        coherence_weight: f32,
+        harmony_mode: crate::utils::HarmonyMode,
+        limit: usize,
+    ) {
+        const BEAM_WIDTH: usize = 16;
+
+        let mut screens: Vec<String> = preview_matches.keys().cloned().collect();
+        screens.sort();
+
+        let per_screen: Vec<Vec<(PathBuf, f32)>> = screens
+            .iter()
+            .map(|name| {
+                preview_matches[name]
+                    .iter()
+                    .map(|(path, score, _)| (path.clone(), *score))
+                    .collect()
+            })
+            .collect();
+
+        let coherence = |a: &std::path::Path, b: &std::path::Path| match (
+            wallpaper_by_path.get(a),
+            wallpaper_by_path.get(b),
+        ) {
+            (Some(wa), Some(wb)) => pair_coherence(wa, wb, harmony_mode),
+            _ => 0.0,
+        };
+
+        let sets =
+            optimize_screen_sets(&per_screen, coherence, coherence_weight, BEAM_WIDTH, limit);
+        if sets.is_empty() {
+            return;
+        }
+
+        for (screen_pos, name) in screens.iter().enumerate() {
+            if let Some(matches) = preview_matches.get_mut(name) {
+                let reordered: Vec<_> = sets
+                    .iter()
+                    .map(|set| matches[set[screen_pos]].clone())
+                    .collect();
+                *matches = reordered;
+            }
+        }
     }
 
     /// Cycle through pairing preview alternatives
@@ -1628,28 +3363,81 @@ impl App {
         }
 
         // First apply the selected wallpaper to current screen
-        self.apply_wallpaper()?;
+        self.apply_selected(false)?;
 
         // Then apply the preview selections to other screens
-        for (screen_name, matches) in &self.pairing.preview_matches {
-            let idx = self
-                .pairing
-                .preview_idx
-                .min(matches.len().saturating_sub(1));
-            if let Some((wp_path, _, _)) = matches.get(idx) {
-                if let Err(e) = swww::set_wallpaper_with_resize(
-                    screen_name,
-                    wp_path,
-                    &self.config.transition(),
-                    self.config.display.resize_mode,
-                    &self.config.display.fill_color,
-                ) {
-                    self.ui.status_message = Some(format!("Pairing {}: {}", screen_name, e));
-                } else {
-                    self.pairing
-                        .current_wallpapers
-                        .insert(screen_name.clone(), wp_path.clone());
+        let mut candidates: Vec<(String, Vec<PathBuf>)> = self
+            .pairing
+            .preview_matches
+            .iter()
+            .map(|(screen, matches)| {
+                (
+                    screen.clone(),
+                    matches.iter().map(|(path, _, _)| path.clone()).collect(),
+                )
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let taken: HashSet<PathBuf> = self
+            .selected_wallpaper()
+            .map(|wp| wp.path.clone())
+            .into_iter()
+            .collect();
+        let selection = select_distinct_wallpapers(
+            &candidates,
+            self.pairing.preview_idx,
+            &taken,
+            self.config.pairing.allow_duplicates,
+        );
+
+        if !selection.replaced.is_empty() || !selection.skipped.is_empty() {
+            let notes: Vec<String> = selection
+                .replaced
+                .iter()
+                .map(|screen| format!("{} got next match", screen))
+                .chain(
+                    selection
+                        .skipped
+                        .iter()
+                        .map(|screen| format!("{} skipped", screen)),
+                )
+                .collect();
+            self.ui.status_message = Some(format!(
+                "Pairing: avoided duplicate wallpaper ({})",
+                notes.join(", ")
+            ));
+        }
+
+        let mut picks = selection.picks;
+        if let Some((missing, message)) =
+            self.disconnected_outputs(picks.iter().map(|(name, _)| name))
+        {
+            picks.retain(|(name, _)| !missing.contains(name));
+            self.ui.status_message = Some(format!("Pairing: {}", message));
+        }
+
+        for (screen_name, wp_path) in picks {
+            if let Err(e) = swww::apply_wallpaper(
+                &screen_name,
+                &wp_path,
+                &self.config.transition(),
+                self.cache
+                    .resize_mode_for(&wp_path, self.config.display.resize_mode),
+                &self
+                    .config
+                    .display
+                    .fill_color_for(self.cache.colors_for(&wp_path)),
+                self.config.scan.gif_mode,
+            ) {
+                self.ui.status_message = Some(format!("Pairing {}: {}", screen_name, e));
+            } else {
+                if let Err(e) = crate::current::update(&self.config.display, &screen_name, &wp_path)
+                {
+                    self.ui.status_message = Some(format!("Current link: {}", e));
                 }
+                self.cache.mark_shown(&wp_path);
+                self.pairing.current_wallpapers.insert(screen_name, wp_path);
             }
         }
 
@@ -1658,6 +3446,14 @@ impl App {
             self.pairing
                 .history
                 .record_pairing(self.pairing.current_wallpapers.clone(), true);
+            if let Err(e) = crate::collections::capture_recent(
+                &self.config.collections,
+                &self.pairing.current_wallpapers,
+            ) {
+                self.ui.status_message = Some(format!("Recent collection: {}", e));
+            }
+            // Affinity is updated on record, so refresh the highlights now
+            self.update_pairing_suggestions();
         }
 
         self.pairing.show_preview = false;
@@ -1675,11 +3471,18 @@ impl App {
     }
 }
 
-pub async fn run_tui(wallpaper_dir: PathBuf) -> Result<()> {
-    let mut app = App::new(wallpaper_dir)?;
+pub async fn run_tui(
+    wallpaper_dir: PathBuf,
+    config: Config,
+    profile: Option<Profile>,
+) -> Result<()> {
+    let mut app = App::new(wallpaper_dir, config, profile)?;
 
     // Show terminal optimization hint if first run in Kitty
     if let Some(hint) = app.config.check_terminal_hint() {
+        if app.config.persist_runtime_changes {
+            let _ = app.save_config();
+        }
         println!("\n{}\n", hint);
         // Wait for keypress
         enable_raw_mode()?;
@@ -1688,25 +3491,36 @@ pub async fn run_tui(wallpaper_dir: PathBuf) -> Result<()> {
     }
 
     enable_raw_mode()?;
+    crate::logging::set_tui_active(true);
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     app.init_screens().await?;
+    app.restore_session();
 
     // Set up channels for background thumbnail loading
     let (thumb_tx, thumb_rx) = mpsc::channel::<ThumbnailRequest>();
     let (event_tx, event_rx) = mpsc::channel::<AppEvent>();
 
     app.set_thumb_channel(thumb_tx);
-
-    // Spawn thumbnail worker thread
-    let event_tx_thumb = event_tx.clone();
-    let disk_cache = ThumbnailCache::new();
-    thread::spawn(move || {
-        thumbnail_worker(thumb_rx, event_tx_thumb, disk_cache);
-    });
+    app.start_scan(event_tx.clone());
+
+    // Spawn the thumbnail worker pool; all workers drain the same request queue
+    let thumb_rx = Arc::new(Mutex::new(thumb_rx));
+    let thumbs = &app.config.thumbnails;
+    let disk_cache = ThumbnailCache::new()
+        .with_size(thumbs.width, thumbs.height, thumbs.quality)
+        .with_max_decode_dimension(app.config.scan.max_decode_dimension);
+    for _ in 0..app.config.thumbnails.worker_count() {
+        let rx = Arc::clone(&thumb_rx);
+        let event_tx_thumb = event_tx.clone();
+        let disk_cache = disk_cache.clone();
+        thread::spawn(move || {
+            thumbnail_worker(rx, event_tx_thumb, disk_cache);
+        });
+    }
 
     // Spawn event polling thread
     let event_tx_input = event_tx.clone();
@@ -1714,8 +3528,16 @@ pub async fn run_tui(wallpaper_dir: PathBuf) -> Result<()> {
         input_worker(event_tx_input);
     });
 
+    // Spawn output hotplug polling thread
+    let event_tx_screens = event_tx.clone();
+    let known_screens = app.screens.clone();
+    thread::spawn(move || {
+        screen_worker(event_tx_screens, known_screens);
+    });
+
     let res = run_app(&mut terminal, &mut app, event_rx);
 
+    crate::logging::set_tui_active(false);
     disable_raw_mode()?;
     execute!(
         terminal.backend_mut(),
@@ -1724,32 +3546,89 @@ pub async fn run_tui(wallpaper_dir: PathBuf) -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    app.cache.save()?;
-    app.config.save()?;
+    if let Some(progress) = &app.ui.scan {
+        progress.cancel.store(true, Ordering::Relaxed);
+    }
+    // An unfinished first scan leaves a placeholder that must not replace the old cache
+    if app.cache_loaded {
+        app.cache.save()?;
+    }
+    if app.config.persist_runtime_changes {
+        app.save_config()?;
+    }
+    if let Err(e) = app.pairing.history.flush_current_pairing() {
+        eprintln!("Warning: Failed to save pairing history: {}", e);
+    }
+    if let Err(e) = app.save_session() {
+        eprintln!("Warning: Failed to save session: {}", e);
+    }
 
     res
 }
 
+/// Background thread that builds the library when there is no usable cache
+fn scan_worker(
+    source_dir: PathBuf,
+    recursive: bool,
+    scan: ScanConfig,
+    cancel: Arc<AtomicBool>,
+    tx: Sender<AppEvent>,
+) {
+    let progress_tx = Mutex::new(tx.clone());
+    let failed = AtomicUsize::new(0);
+    // Called from rayon threads; per-file warnings are only counted because
+    // stderr would draw over the alternate screen.
+    let report = |update: ScanUpdate| match update {
+        ScanUpdate::Progress { phase, done, total } => {
+            if done == 0 || done == total || done.is_multiple_of(SCAN_PROGRESS_STEP) {
+                if let Ok(tx) = progress_tx.lock() {
+                    let _ = tx.send(AppEvent::ScanProgress { phase, done, total });
+                }
+            }
+        }
+        ScanUpdate::Failed { .. } => {
+            failed.fetch_add(1, Ordering::Relaxed);
+        }
+    };
+
+    let result =
+        WallpaperCache::scan_with_progress(&source_dir, recursive, &scan, &report, &cancel);
+    let _ = tx.send(AppEvent::ScanFinished {
+        result,
+        failed: failed.load(Ordering::Relaxed),
+    });
+}
+
 /// Background thread that loads thumbnails using fast_image_resize
 fn thumbnail_worker(
-    rx: Receiver<ThumbnailRequest>,
+    rx: Arc<Mutex<Receiver<ThumbnailRequest>>>,
     tx: Sender<AppEvent>,
     disk_cache: ThumbnailCache,
 ) {
-    while let Ok(request) = rx.recv() {
+    loop {
+        // Hold the lock only while waiting, so other workers can decode meanwhile
+        let request = match rx.lock() {
+            Ok(rx) => rx.recv(),
+            Err(_) => break,
+        };
+        let Ok(request) = request else {
+            break;
+        };
         // Load thumbnail (uses fast_image_resize with disk caching)
         match disk_cache.load(&request.source_path) {
             Ok(image) => {
+                let halfblocks = request.halfblocks.then(|| Halfblocks::encode(&image));
                 let response = ThumbnailResponse {
                     cache_idx: request.cache_idx,
                     image,
+                    halfblocks,
                 };
                 if tx.send(AppEvent::ThumbnailReady(response)).is_err() {
                     break;
                 }
             }
             Err(e) => {
-                eprintln!(
+                tracing::debug!(
                     "Thumbnail failed for {}: {}",
                     request.source_path.display(),
                     e
@@ -1764,16 +3643,9 @@ fn input_worker(tx: Sender<AppEvent>) {
     loop {
         if event::poll(std::time::Duration::from_millis(50)).unwrap_or(false) {
             match event::read() {
-                Ok(Event::Key(key)) => {
-                    if tx.send(AppEvent::Key(key)).is_err() {
-                        break;
-                    }
-                }
-                Ok(Event::Resize(_, _)) => {
-                    if tx.send(AppEvent::Resize).is_err() {
-                        break;
-                    }
-                }
+                Ok(Event::Key(key)) if tx.send(AppEvent::Key(key)).is_err() => break,
+                Ok(Event::Mouse(mouse)) if tx.send(AppEvent::Mouse(mouse)).is_err() => break,
+                Ok(Event::Resize(_, _)) if tx.send(AppEvent::Resize).is_err() => break,
                 _ => {}
             }
         } else if tx.send(AppEvent::Tick).is_err() {
@@ -1782,6 +3654,24 @@ fn input_worker(tx: Sender<AppEvent>) {
     }
 }
 
+/// Background thread that re-detects outputs and reports when they change
+fn screen_worker(tx: Sender<AppEvent>, mut known: Vec<Screen>) {
+    loop {
+        thread::sleep(SCREEN_POLL_INTERVAL);
+        // Detection fails briefly while outputs reconfigure; try again next round
+        let Ok(screens) = screen::detect_screens_blocking() else {
+            continue;
+        };
+        if screen::diff_screens(&known, &screens).is_empty() {
+            continue;
+        }
+        known = screens.clone();
+        if tx.send(AppEvent::ScreensChanged(screens)).is_err() {
+            break;
+        }
+    }
+}
+
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
     app: &mut App,
@@ -1797,7 +3687,7 @@ fn run_app<B: ratatui::backend::Backend>(
             let new_is_light = crate::ui::theme::is_light_theme();
             if new_is_light != current_theme_is_light {
                 current_theme_is_light = new_is_light;
-                app.ui.theme = crate::ui::theme::frost_theme();
+                app.ui.theme = crate::ui::theme::frost_theme(&app.config.theme);
                 terminal.clear()?; // Force full terminal redraw
                 needs_redraw = true;
             }
@@ -1831,6 +3721,12 @@ fn run_app<B: ratatui::backend::Backend>(
                         continue;
                     }
 
+                    // Esc stops a running scan instead of quitting
+                    if app.ui.scan.is_some() && key.code == KeyCode::Esc {
+                        app.cancel_scan();
+                        continue;
+                    }
+
                     // Handle help popup first (blocks other input)
                     if app.ui.show_help {
                         match key.code {
@@ -1842,6 +3738,36 @@ fn run_app<B: ratatui::backend::Backend>(
                         continue;
                     }
 
+                    // Info popup closes on its own key, Esc or Enter
+                    if app.ui.show_info {
+                        let kb = &app.config.keybindings;
+                        if kb.matches(key.code, &kb.info)
+                            || matches!(key.code, KeyCode::Esc | KeyCode::Enter)
+                        {
+                            app.ui.show_info = false;
+                        }
+                        continue;
+                    }
+
+                    // History popup: j/k or arrows browse, Enter re-applies
+                    if app.ui.show_history {
+                        let kb = &app.config.keybindings;
+                        match key.code {
+                            code if kb.matches(code, &kb.history) || code == KeyCode::Esc => {
+                                app.ui.show_history = false;
+                            }
+                            KeyCode::Char('j') | KeyCode::Down => app.history_move(true),
+                            KeyCode::Char('k') | KeyCode::Up => app.history_move(false),
+                            KeyCode::Enter => {
+                                if let Err(e) = app.apply_history_selection() {
+                                    app.ui.status_message = Some(format!("History: {}", e));
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     // Handle color picker popup
                     if app.ui.show_color_picker {
                         match key.code {
@@ -1854,6 +3780,12 @@ fn run_app<B: ratatui::backend::Backend>(
                             KeyCode::Char('h') | KeyCode::Left => {
                                 app.color_picker_prev();
                             }
+                            KeyCode::Char(' ') => {
+                                app.toggle_picker_color();
+                            }
+                            KeyCode::Char('a') => {
+                                app.toggle_color_filter_mode();
+                            }
                             KeyCode::Enter => {
                                 app.apply_color_filter();
                             }
@@ -1870,6 +3802,7 @@ fn run_app<B: ratatui::backend::Backend>(
                     if app.pairing.show_preview {
                         match key.code {
                             KeyCode::Esc | KeyCode::Char('p') => {
+                                app.cancel_auto_apply();
                                 app.pairing.show_preview = false;
                             }
                             KeyCode::Char('l') | KeyCode::Right | KeyCode::Char('n') => {
@@ -1879,13 +3812,25 @@ fn run_app<B: ratatui::backend::Backend>(
                                 app.pairing_preview_prev();
                             }
                             KeyCode::Enter => {
-                                if let Err(e) = app.apply_pairing_preview() {
+                                let result = if app.pairing.auto_apply_pending.is_some() {
+                                    app.confirm_auto_apply()
+                                } else {
+                                    app.apply_pairing_preview()
+                                };
+                                if let Err(e) = result {
                                     app.ui.status_message = Some(format!("{}", e));
                                 }
                             }
                             KeyCode::Char('y') => {
                                 app.toggle_pairing_style_mode();
                             }
+                            code if app
+                                .config
+                                .keybindings
+                                .matches(code, &app.config.keybindings.style_mode) =>
+                            {
+                                app.toggle_pairing_style_mode();
+                            }
                             KeyCode::Char(c) if c.is_ascii_digit() => {
                                 let idx = if c == '0' {
                                     9
@@ -1902,6 +3847,21 @@ fn run_app<B: ratatui::backend::Backend>(
                         continue;
                     }
 
+                    // Handle tag editor popup (captures text input like command mode)
+                    if app.ui.show_tag_editor {
+                        match key.code {
+                            KeyCode::Esc => app.close_tag_editor(),
+                            KeyCode::Enter => app.tag_editor_submit(),
+                            KeyCode::Backspace => app.tag_editor_backspace(),
+                            KeyCode::Down | KeyCode::Tab => app.tag_editor_next(),
+                            KeyCode::Up | KeyCode::BackTab => app.tag_editor_prev(),
+                            KeyCode::Delete => app.tag_editor_remove_selected(),
+                            KeyCode::Char(c) => app.tag_editor_input(c),
+                            _ => {}
+                        }
+                        continue;
+                    }
+
                     // Handle command mode (vim-style :)
                     if app.ui.command_mode {
                         match key.code {
@@ -1935,6 +3895,10 @@ fn run_app<B: ratatui::backend::Backend>(
                         app.next_wallpaper();
                     } else if kb.matches(code, &kb.prev) || code == KeyCode::Left {
                         app.prev_wallpaper();
+                    } else if app.ui.view_mode == ViewMode::Grid && code == KeyCode::Down {
+                        app.move_grid_row(true);
+                    } else if app.ui.view_mode == ViewMode::Grid && code == KeyCode::Up {
+                        app.move_grid_row(false);
                     }
                     // Screen navigation (configurable)
                     else if kb.matches(code, &kb.next_screen) {
@@ -1944,13 +3908,17 @@ fn run_app<B: ratatui::backend::Backend>(
                     }
                     // Apply wallpaper (configurable)
                     else if kb.matches(code, &kb.apply) {
-                        if let Err(e) = app.apply_wallpaper() {
+                        if let Err(e) = app.apply_wallpaper().and_then(|_| app.auto_apply_pairing())
+                        {
                             app.ui.status_message = Some(format!("{}", e));
                         }
                     }
                     // Random wallpaper (configurable)
                     else if kb.matches(code, &kb.random) {
-                        if let Err(e) = app.random_wallpaper() {
+                        if let Err(e) = app
+                            .random_wallpaper()
+                            .and_then(|_| app.auto_apply_pairing())
+                        {
                             app.ui.status_message = Some(format!("{}", e));
                         }
                     }
@@ -1961,55 +3929,110 @@ fn run_app<B: ratatui::backend::Backend>(
                     // Toggle resize mode (configurable)
                     else if kb.matches(code, &kb.toggle_resize) {
                         app.toggle_resize_mode();
-                    }
-                    // Non-configurable keys
-                    else {
-                        match code {
-                            KeyCode::Char(':') => app.enter_command_mode(),
-                            KeyCode::Char('?') => app.toggle_help(),
-                            KeyCode::Char('s') => app.toggle_sort_mode(),
-                            KeyCode::Char('c') => app.toggle_colors(),
-                            KeyCode::Char('C') => app.toggle_color_picker(),
-                            KeyCode::Char('p') => app.toggle_pairing_preview(),
-                            KeyCode::Char('t') => app.cycle_tag_filter(),
-                            KeyCode::Char('T') => app.clear_tag_filter(),
-                            KeyCode::Char('w') => {
-                                if let Err(e) = app.export_pywal() {
-                                    app.ui.status_message = Some(format!("pywal: {}", e));
-                                }
-                            }
-                            KeyCode::Char('W') => app.toggle_pywal_export(),
-                            KeyCode::Char('u') => {
-                                // Undo pairing
-                                if let Err(e) = app.do_undo() {
-                                    app.ui.status_message = Some(format!("Undo: {}", e));
-                                }
+                    } else if kb.matches(code, &kb.resize_override) {
+                        app.cycle_resize_override();
+                    } else if kb.matches(code, &kb.command_mode) {
+                        app.enter_command_mode();
+                    } else if kb.matches(code, &kb.help) {
+                        app.toggle_help();
+                    } else if kb.matches(code, &kb.tag_editor) {
+                        app.open_tag_editor();
+                    } else if kb.matches(code, &kb.sort) {
+                        app.toggle_sort_mode();
+                    } else if kb.matches(code, &kb.toggle_view) {
+                        app.toggle_view_mode();
+                    } else if kb.matches(code, &kb.info) {
+                        app.toggle_info();
+                    } else if kb.matches(code, &kb.history) {
+                        app.toggle_history();
+                    } else if kb.matches(code, &kb.style_mode) {
+                        app.toggle_pairing_style_mode();
+                        app.ui.status_message = Some(format!(
+                            "Pairing style: {}",
+                            app.pairing.style_mode.display_name()
+                        ));
+                    } else if kb.matches(code, &kb.colors) {
+                        app.toggle_colors();
+                    } else if kb.matches(code, &kb.color_picker) {
+                        app.toggle_color_picker();
+                    } else if kb.matches(code, &kb.pairing) {
+                        app.toggle_pairing_preview();
+                    } else if kb.matches(code, &kb.tag_cycle) {
+                        app.cycle_tag_filter();
+                    } else if kb.matches(code, &kb.tag_clear) {
+                        app.clear_tag_filter();
+                    } else if kb.matches(code, &kb.pywal_export) {
+                        if let Err(e) = app.export_pywal() {
+                            app.ui.status_message = Some(format!("pywal: {}", e));
+                        }
+                    } else if kb.matches(code, &kb.pywal_toggle) {
+                        app.toggle_pywal_export();
+                    } else if kb.matches(code, &kb.mirror_screens) {
+                        app.toggle_mirror_screens();
+                    } else if kb.matches(code, &kb.copy_path) {
+                        app.copy_selected_path();
+                    } else if kb.matches(code, &kb.undo) {
+                        // Undo pairing
+                        if let Err(e) = app.do_undo() {
+                            app.ui.status_message = Some(format!("Undo: {}", e));
+                        }
+                    } else if kb.matches(code, &kb.next_suggestion) {
+                        app.jump_to_suggestion(true);
+                    } else if kb.matches(code, &kb.prev_suggestion) {
+                        app.jump_to_suggestion(false);
+                    } else if kb.matches(code, &kb.color_threshold_up) {
+                        app.adjust_color_threshold(COLOR_THRESHOLD_STEP);
+                    } else if kb.matches(code, &kb.color_threshold_down) {
+                        app.adjust_color_threshold(-COLOR_THRESHOLD_STEP);
+                    } else if kb.matches(code, &kb.rescan) {
+                        // Rescan wallpaper directory
+                        match app.rescan() {
+                            Ok(msg) => {
+                                app.ui.status_message = Some(format!("Rescan: {}", msg));
                             }
-                            KeyCode::Char('R') => {
-                                // Rescan wallpaper directory
-                                match app.rescan() {
-                                    Ok(msg) => {
-                                        app.ui.status_message = Some(format!("Rescan: {}", msg));
-                                    }
-                                    Err(e) => {
-                                        app.ui.status_message = Some(format!("Rescan: {}", e));
-                                    }
-                                }
+                            Err(e) => {
+                                app.ui.status_message = Some(format!("Rescan: {}", e));
                             }
-                            _ => {}
                         }
                     }
+                    // Fixed paging keys; checked last so remapped actions win
+                    else if matches!(code, KeyCode::PageDown | KeyCode::Char('}')) {
+                        app.page_next();
+                    } else if matches!(code, KeyCode::PageUp | KeyCode::Char('{')) {
+                        app.page_prev();
+                    } else if code == KeyCode::Home {
+                        app.jump_first();
+                    } else if code == KeyCode::End {
+                        app.jump_last();
+                    } else if let KeyCode::Char(c @ '0'..='5') = code {
+                        app.rate_selected(c as u8 - b'0');
+                    }
+                }
+                AppEvent::Mouse(mouse) => {
+                    if let Err(e) = app.handle_mouse(mouse) {
+                        app.ui.status_message = Some(format!("{}", e));
+                    }
                 }
                 AppEvent::ThumbnailReady(response) => {
                     app.handle_thumbnail_ready(response);
                 }
+                AppEvent::ScanProgress { phase, done, total } => {
+                    app.handle_scan_progress(phase, done, total);
+                }
+                AppEvent::ScanFinished { result, failed } => {
+                    app.handle_scan_finished(result, failed);
+                }
                 AppEvent::Resize => {
                     app.handle_resize();
                     terminal.clear()?;
                 }
+                AppEvent::ScreensChanged(screens) => {
+                    app.handle_screens_changed(screens);
+                }
                 AppEvent::Tick => {
                     // Check for expired undo window
                     app.tick_undo();
+                    app.tick_pairing_flush();
                 }
             }
         }
@@ -2019,3 +4042,73 @@ fn run_app<B: ratatui::backend::Backend>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rgba(fill: FillColor) -> (u8, u8, u8, u8) {
+        (fill.r, fill.g, fill.b, fill.a)
+    }
+
+    // --- KeybindingsConfig::collisions ---
+
+    #[test]
+    fn test_default_keybindings_do_not_collide() {
+        assert!(KeybindingsConfig::default().collisions().is_empty());
+    }
+
+    #[test]
+    fn test_collisions_name_the_action_run_app_checks_first() {
+        // `run_app` tests toggle_view before colors and undo before rescan
+        let kb = KeybindingsConfig {
+            colors: "v".to_string(),
+            toggle_view: "v".to_string(),
+            rescan: "u".to_string(),
+            undo: "u".to_string(),
+            ..KeybindingsConfig::default()
+        };
+        assert_eq!(
+            kb.collisions(),
+            [
+                ("v".to_string(), "toggle_view", "colors"),
+                ("u".to_string(), "undo", "rescan"),
+            ]
+        );
+    }
+
+    // --- DisplayConfig::fill_color_for ---
+
+    #[test]
+    fn test_fill_color_for() {
+        let mut display = DisplayConfig {
+            fill_color: FillColor::from_hex("#102030").unwrap(),
+            ..DisplayConfig::default()
+        };
+        let palette = vec!["#ff8800".to_string(), "#000000".to_string()];
+
+        display.fill_from_palette = false;
+        assert_eq!(
+            rgba(display.fill_color_for(&palette)),
+            (0x10, 0x20, 0x30, 255),
+            "disabled: configured color"
+        );
+
+        display.fill_from_palette = true;
+        assert_eq!(
+            rgba(display.fill_color_for(&palette)),
+            (0xff, 0x88, 0x00, 255),
+            "dominant palette color"
+        );
+        assert_eq!(
+            rgba(display.fill_color_for(&[])),
+            (0x10, 0x20, 0x30, 255),
+            "no palette yet"
+        );
+        assert_eq!(
+            rgba(display.fill_color_for(&["not a color".to_string()])),
+            (0x10, 0x20, 0x30, 255),
+            "invalid hex"
+        );
+    }
+}
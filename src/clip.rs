@@ -176,7 +176,7 @@ impl ModelManager {
 
         if visual_path.exists() {
             if !Self::verify_checksum(&visual_path, VISUAL_MODEL_SHA256)? {
-                eprintln!("WARNING: Model checksum mismatch — re-downloading...");
+                tracing::warn!("Model checksum mismatch — re-downloading...");
                 std::fs::remove_file(&visual_path)?;
                 self.download_model(VISUAL_MODEL_URL, &visual_path, "visual encoder")
                     .await?;
@@ -196,6 +196,16 @@ impl ModelManager {
         Ok(visual_path)
     }
 
+    /// Where the visual model lives, and whether its checksum is right
+    /// (`None` when it hasn't been downloaded). Doesn't download anything.
+    pub fn visual_model_status(&self) -> (PathBuf, Option<bool>) {
+        let path = self.visual_model_path();
+        let valid = path
+            .exists()
+            .then(|| Self::verify_checksum(&path, VISUAL_MODEL_SHA256).unwrap_or(false));
+        (path, valid)
+    }
+
     fn verify_checksum(path: &Path, expected_hex: &str) -> Result<bool> {
         let mut file = std::fs::File::open(path)?;
         let mut hasher = Sha256::new();
@@ -205,7 +215,7 @@ impl ModelManager {
     }
 
     async fn download_model(&self, url: &str, dest: &Path, name: &str) -> Result<()> {
-        eprintln!("Downloading CLIP {} model...", name);
+        tracing::info!("Downloading CLIP {} model...", name);
 
         let client = reqwest::Client::new();
         let response = client
@@ -236,7 +246,7 @@ impl ModelManager {
         }
 
         pb.finish_with_message("Download complete");
-        eprintln!("Saved to {}", dest.display());
+        tracing::info!("Saved to {}", dest.display());
 
         Ok(())
     }
@@ -262,7 +272,7 @@ impl ClipTagger {
         let model_manager = ModelManager::new();
         let visual_path = model_manager.ensure_models().await?;
 
-        eprintln!("Loading CLIP visual model...");
+        tracing::info!("Loading CLIP visual model...");
 
         // Try CUDA first, fall back to CPU
         #[cfg(feature = "clip-cuda")]
@@ -272,13 +282,13 @@ impl ClipTagger {
             let cuda_available = CUDAExecutionProvider::default().is_available()?;
 
             if cuda_available {
-                eprintln!("Using CUDA GPU acceleration");
+                tracing::info!("Using CUDA GPU acceleration");
                 Session::builder()?
                     .with_execution_providers([CUDAExecutionProvider::default().build()])?
                     .commit_from_file(&visual_path)
                     .context("Failed to load visual model with CUDA")?
             } else {
-                eprintln!("CUDA not available, using CPU");
+                tracing::info!("CUDA not available, using CPU");
                 Session::builder()?
                     .with_intra_threads(4)?
                     .commit_from_file(&visual_path)
@@ -292,7 +302,7 @@ impl ClipTagger {
             .commit_from_file(&visual_path)
             .context("Failed to load visual model")?;
 
-        eprintln!("CLIP model loaded successfully");
+        tracing::info!("CLIP model loaded successfully");
 
         Ok(Self {
             visual_session,
@@ -335,22 +345,134 @@ impl ClipTagger {
         // 1. Preprocess image to CLIP format
         let input = preprocess_image(image_path)?;
 
-        // 2. Create input tensor from ndarray
-        let (input_data, _offset) = input.into_raw_vec_and_offset();
+        // 2. Run visual encoder on a batch of one
+        let embedding = self
+            .embed_batch(vec![input], verbose)?
+            .pop()
+            .context("No embedding returned")?;
+
+        Ok(self.classify_embedding(embedding, threshold, verbose))
+    }
+
+    /// Analyze many images at once.
+    ///
+    /// Preprocessing runs in parallel on the rayon pool, then the inputs are
+    /// fed to the visual encoder `batch_size` images per `session.run`.
+    /// Results are returned in the same order as `image_paths`.
+    pub fn analyze_batch(
+        &mut self,
+        image_paths: &[PathBuf],
+        threshold: f32,
+        batch_size: usize,
+        verbose: bool,
+    ) -> Vec<Result<ClipAnalysis>> {
+        use rayon::prelude::*;
+
+        let mut results = Vec::with_capacity(image_paths.len());
+
+        for (chunk_idx, chunk) in image_paths.chunks(batch_size.max(1)).enumerate() {
+            let inputs: Vec<Result<Array4<f32>>> =
+                chunk.par_iter().map(|p| preprocess_image(p)).collect();
+
+            // Only successfully preprocessed images go into the batch
+            let mut slots: Vec<Option<Result<ClipAnalysis>>> = Vec::with_capacity(chunk.len());
+            let mut batch = Vec::with_capacity(chunk.len());
+            for input in inputs {
+                match input {
+                    Ok(array) => {
+                        batch.push(array);
+                        slots.push(None);
+                    }
+                    Err(e) => slots.push(Some(Err(e))),
+                }
+            }
+
+            let show_debug = verbose && chunk_idx == 0;
+            let embeddings = if batch.is_empty() {
+                Ok(Vec::new())
+            } else {
+                self.embed_batch(batch, show_debug)
+            };
+
+            match embeddings {
+                Ok(embeddings) => {
+                    let mut embeddings = embeddings.into_iter();
+                    for (i, slot) in slots.iter_mut().enumerate() {
+                        if slot.is_none() {
+                            *slot = Some(
+                                embeddings
+                                    .next()
+                                    .context("Batch returned too few embeddings")
+                                    .map(|emb| {
+                                        self.classify_embedding(
+                                            emb,
+                                            threshold,
+                                            show_debug && i == 0,
+                                        )
+                                    }),
+                            );
+                        }
+                    }
+                }
+                Err(e) => {
+                    let msg = e.to_string();
+                    for slot in slots.iter_mut().filter(|s| s.is_none()) {
+                        *slot = Some(Err(anyhow::anyhow!("Batch inference failed: {}", msg)));
+                    }
+                }
+            }
+
+            results.extend(slots.into_iter().flatten());
+        }
+
+        results
+    }
+
+    /// Run the visual encoder on preprocessed `[1,3,224,224]` inputs as one
+    /// `[N,3,224,224]` batch and split the output back into per-image rows.
+    ///
+    /// Some ONNX exports have a fixed batch dimension of 1; if a batched run
+    /// fails, the inputs are retried one at a time.
+    fn embed_batch(&mut self, inputs: Vec<Array4<f32>>, verbose: bool) -> Result<Vec<Vec<f32>>> {
+        let n = inputs.len();
+        let image_len = 3 * CLIP_IMAGE_SIZE as usize * CLIP_IMAGE_SIZE as usize;
+
+        let mut input_data = Vec::with_capacity(n * image_len);
+        for input in &inputs {
+            input_data.extend(input.iter().copied());
+        }
+
+        match self.run_visual(n, input_data, verbose) {
+            Ok(rows) => Ok(rows),
+            Err(e) if n > 1 => {
+                if verbose {
+                    tracing::debug!("Batched inference failed ({}), retrying per image", e);
+                }
+                let mut rows = Vec::with_capacity(n);
+                for input in inputs {
+                    let data: Vec<f32> = input.iter().copied().collect();
+                    rows.extend(self.run_visual(1, data, false)?);
+                }
+                Ok(rows)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Single `session.run` over `n` images laid out contiguously in CHW order.
+    fn run_visual(
+        &mut self,
+        n: usize,
+        input_data: Vec<f32>,
+        verbose: bool,
+    ) -> Result<Vec<Vec<f32>>> {
         let input_tensor = ort::value::Tensor::<f32>::from_array((
-            [
-                1usize,
-                3,
-                CLIP_IMAGE_SIZE as usize,
-                CLIP_IMAGE_SIZE as usize,
-            ],
+            [n, 3, CLIP_IMAGE_SIZE as usize, CLIP_IMAGE_SIZE as usize],
             input_data,
         ))?;
 
-        // 3. Run visual encoder inference
         let outputs = self.visual_session.run(ort::inputs![input_tensor])?;
 
-        // 4. Extract image embedding from output
         // Get first output tensor
         let (_, output_value) = outputs.iter().next().context("No output tensor found")?;
 
@@ -362,50 +484,67 @@ impl ClipTagger {
         let embedding_data: &[f32] = tensor_ref.1;
 
         if verbose {
-            eprintln!("  Output shape: {:?}", shape);
-            eprintln!("  Output data length: {}", embedding_data.len());
+            tracing::debug!("Output shape: {:?}", shape);
+            tracing::debug!("Output data length: {}", embedding_data.len());
         }
 
+        if embedding_data.is_empty() || !embedding_data.len().is_multiple_of(n) {
+            anyhow::bail!(
+                "Output length {} does not split into {} images",
+                embedding_data.len(),
+                n
+            );
+        }
+        let row_len = embedding_data.len() / n;
+
         // Get the [CLS] token embedding (first token) or pooled output
-        let embedding: Vec<f32> = if shape.len() == 3 {
+        let take = if shape.len() == 3 {
             // Shape: [batch, seq_len, hidden_dim] - take first token (CLS)
-            let hidden_dim = shape[2];
             if verbose {
-                eprintln!(
-                    "  3D tensor, taking first {} values (CLS token)",
-                    hidden_dim
-                );
+                tracing::debug!("3D tensor, taking first {} values (CLS token)", shape[2]);
             }
-            embedding_data[..hidden_dim].to_vec()
+            shape[2]
         } else if shape.len() == 2 {
             // Shape: [batch, hidden_dim]
-            let hidden_dim = shape[1];
             if verbose {
-                eprintln!("  2D tensor, taking {} values", hidden_dim);
+                tracing::debug!("2D tensor, taking {} values", shape[1]);
             }
-            embedding_data[..hidden_dim].to_vec()
+            shape[1]
         } else {
             if verbose {
-                eprintln!("  Using all {} values", embedding_data.len());
+                tracing::debug!("Using all {} values per image", row_len);
             }
-            embedding_data.to_vec()
+            row_len
         };
 
+        Ok(embedding_data
+            .chunks(row_len)
+            .map(|row| row[..take.min(row.len())].to_vec())
+            .collect())
+    }
+
+    /// Normalize a raw image embedding and score it against every category.
+    fn classify_embedding(
+        &self,
+        embedding: Vec<f32>,
+        threshold: f32,
+        verbose: bool,
+    ) -> ClipAnalysis {
         if verbose {
-            eprintln!("  Embedding dimension: {}", embedding.len());
-            eprintln!("  Expected dimension: {}", EMBEDDING_DIM);
-            eprintln!(
+            tracing::debug!("Embedding dimension: {}", embedding.len());
+            tracing::debug!("Expected dimension: {}", EMBEDDING_DIM);
+            tracing::debug!(
                 "  First 5 values: {:?}",
                 &embedding[..5.min(embedding.len())]
             );
         }
 
-        // 4. Project to CLIP embedding space if needed (512 dim)
+        // 1. Project to CLIP embedding space if needed (512 dim)
         let projected = if embedding.len() != EMBEDDING_DIM {
             // The raw hidden state is 768 dim, but we compare against 512-dim text embeddings
             // For now, truncate or warn - ideally we'd have the projection layer
-            eprintln!(
-                "WARNING: embedding dim {} != expected {}! Model may be incompatible.",
+            tracing::warn!(
+                "Embedding dim {} != expected {}! Model may be incompatible.",
                 embedding.len(),
                 EMBEDDING_DIM
             );
@@ -414,7 +553,7 @@ impl ClipTagger {
             embedding
         };
 
-        // 5. Normalize embedding
+        // 2. Normalize embedding
         let norm: f32 = projected.iter().map(|x| x * x).sum::<f32>().sqrt();
         let normalized: Vec<f32> = if norm > 0.0 {
             projected.iter().map(|x| x / norm).collect()
@@ -422,7 +561,7 @@ impl ClipTagger {
             projected
         };
 
-        // 6. Compute cosine similarity with each category embedding
+        // 3. Compute cosine similarity with each category embedding
         let mut tags = Vec::new();
         let mut all_scores: Vec<(&str, f32, f32)> = Vec::new();
 
@@ -452,14 +591,14 @@ impl ClipTagger {
         }
 
         if verbose {
-            eprintln!("  Raw similarities (top 5):");
+            tracing::debug!("Raw similarities (top 5):");
             let mut sorted_scores = all_scores.clone();
             sorted_scores
                 .sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
             for (name, sim, conf) in sorted_scores.iter().take(5) {
-                eprintln!("    {}: raw={:.4}, conf={:.4}", name, sim, conf);
+                tracing::debug!("{}: raw={:.4}, conf={:.4}", name, sim, conf);
             }
-            eprintln!("  Tags above threshold {}: {}", threshold, tags.len());
+            tracing::debug!("Tags above threshold {}: {}", threshold, tags.len());
         }
 
         // Sort by confidence descending
@@ -469,10 +608,10 @@ impl ClipTagger {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
-        Ok(ClipAnalysis {
+        ClipAnalysis {
             tags,
             embedding: normalized,
-        })
+        }
     }
 
     /// Get list of available tag categories
@@ -559,8 +698,8 @@ fn get_cached_thumbnail(source_path: &Path) -> Option<PathBuf> {
     use std::hash::{Hash, Hasher};
 
     let cache_dir = directories::ProjectDirs::from("com", "mrmattias", "frostwall")
-        .map(|dirs| dirs.cache_dir().join("thumbs_v2"))
-        .unwrap_or_else(|| PathBuf::from("/tmp/frostwall/thumbs_v2"));
+        .map(|dirs| dirs.cache_dir().join("thumbs_v3"))
+        .unwrap_or_else(|| PathBuf::from("/tmp/frostwall/thumbs_v3"));
 
     let mut hasher = DefaultHasher::new();
     source_path.to_string_lossy().hash(&mut hasher);
@@ -585,10 +724,10 @@ fn preprocess_image(path: &Path) -> Result<Array4<f32>> {
     // Try to use cached thumbnail first (800x600 vs 4K original = much faster)
     let img = if let Some(thumb_path) = get_cached_thumbnail(path) {
         image::open(&thumb_path)
-            .or_else(|_| image::open(path))
+            .or_else(|_| crate::utils::open_still(path))
             .context("Failed to open image")?
     } else {
-        image::open(path).context("Failed to open image")?
+        crate::utils::open_still(path).context("Failed to open image")?
     };
 
     // Resize to CLIP input size (Triangle is fast and good enough for 224x224)
@@ -7,6 +7,60 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+/// Tag on collections captured automatically by `collections.auto_capture`
+pub const RECENT_TAG: &str = "recent";
+
+/// Name that `collection apply`/`show` resolve to the newest recent capture
+pub const LATEST: &str = "latest";
+
+/// Automatic capture settings (`[collections]` in config.toml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionsConfig {
+    /// Save every multi-screen apply as a recent collection
+    #[serde(default)]
+    pub auto_capture: bool,
+    /// How many recent captures to keep (oldest are dropped first)
+    #[serde(default = "default_recent_limit")]
+    pub recent_limit: usize,
+}
+
+fn default_recent_limit() -> usize {
+    10
+}
+
+impl Default for CollectionsConfig {
+    fn default() -> Self {
+        Self {
+            auto_capture: false,
+            recent_limit: default_recent_limit(),
+        }
+    }
+}
+
+/// Record a multi-screen apply as a recent collection if enabled.
+/// Returns the new collection's name, or `None` when nothing was captured.
+pub fn capture_recent(
+    config: &CollectionsConfig,
+    wallpapers: &HashMap<String, PathBuf>,
+) -> Result<Option<String>> {
+    if !config.auto_capture || wallpapers.len() < 2 {
+        return Ok(None);
+    }
+    let mut store = CollectionStore::load()?;
+    let name = store.push_recent(wallpapers.clone(), config.recent_limit, unix_now());
+    if name.is_some() {
+        store.save()?;
+    }
+    Ok(name)
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 /// A saved wallpaper collection (preset)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WallpaperCollection {
@@ -55,6 +109,9 @@ impl CollectionStore {
 
     /// Save collections to disk
     pub fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
         let path = Self::storage_path();
 
         if let Some(parent) = path.parent() {
@@ -101,6 +158,78 @@ impl CollectionStore {
         self.collections.iter().find(|c| c.name == name)
     }
 
+    /// Like `get`, but `latest` means the newest recent capture unless a
+    /// collection is literally named that.
+    pub fn resolve(&self, name: &str) -> Option<&WallpaperCollection> {
+        self.get(name).or_else(|| {
+            (name == LATEST)
+                .then(|| self.recent().into_iter().next())
+                .flatten()
+        })
+    }
+
+    /// Automatically captured collections, newest first
+    pub fn recent(&self) -> Vec<&WallpaperCollection> {
+        let mut recent: Vec<_> = self
+            .collections
+            .iter()
+            .filter(|c| c.tags.iter().any(|t| t == RECENT_TAG))
+            .collect();
+        // Later entries were captured later, so they win ties within a second
+        recent.reverse();
+        recent.sort_by_key(|c| std::cmp::Reverse(c.created_at));
+        recent
+    }
+
+    /// Add a recent capture without touching disk, dropping the oldest beyond `keep`.
+    /// Returns `None` if the set is the same as the newest capture.
+    fn push_recent(
+        &mut self,
+        wallpapers: HashMap<String, PathBuf>,
+        keep: usize,
+        timestamp: u64,
+    ) -> Option<String> {
+        if self
+            .recent()
+            .first()
+            .is_some_and(|newest| newest.wallpapers == wallpapers)
+        {
+            return None;
+        }
+
+        let stamp = chrono::DateTime::from_timestamp(timestamp as i64, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y%m%d-%H%M%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| timestamp.to_string());
+        let mut name = format!("recent-{}", stamp);
+        let mut suffix = 2;
+        while self.get(&name).is_some() {
+            name = format!("recent-{}-{}", stamp, suffix);
+            suffix += 1;
+        }
+
+        self.collections.push(WallpaperCollection {
+            name: name.clone(),
+            wallpapers,
+            created_at: timestamp,
+            description: None,
+            tags: vec![RECENT_TAG.to_string()],
+        });
+
+        let stale: Vec<String> = self
+            .recent()
+            .iter()
+            .skip(keep.max(1))
+            .map(|c| c.name.clone())
+            .collect();
+        self.collections.retain(|c| !stale.contains(&c.name));
+
+        Some(name)
+    }
+
     /// Delete a collection by name
     pub fn delete(&mut self, name: &str) -> Result<bool> {
         let initial_len = self.collections.len();
@@ -150,6 +279,166 @@ impl CollectionStore {
     }
 }
 
+/// Where one collection entry goes on this machine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Placement {
+    /// Output name stored in the collection
+    pub saved: String,
+    /// Output it is applied to, `None` when no output is left for it
+    pub target: Option<String>,
+    pub path: PathBuf,
+}
+
+impl Placement {
+    /// Whether the wallpaper lands on a different output than it was saved for
+    pub fn is_remapped(&self) -> bool {
+        self.target.as_ref().is_some_and(|t| *t != self.saved)
+    }
+}
+
+/// Parse `--map` entries such as `DP-1=eDP-1` (saved name = output here).
+pub fn parse_screen_map(entries: &[String]) -> Result<HashMap<String, String>> {
+    entries
+        .iter()
+        .map(|entry| {
+            let (from, to) = entry
+                .split_once('=')
+                .map(|(from, to)| (from.trim(), to.trim()))
+                .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+                .with_context(|| {
+                    format!("Invalid screen mapping '{}' (expected SAVED=OUTPUT)", entry)
+                })?;
+            Ok((from.to_string(), to.to_string()))
+        })
+        .collect()
+}
+
+/// Decide which output each saved wallpaper goes to.
+///
+/// `map` renames saved outputs. Without a map, wallpapers whose output is not
+/// connected fill the unused detected outputs in order. `connected` is `None`
+/// when detection failed, in which case every entry is tried as-is.
+pub fn place(
+    wallpapers: &HashMap<String, PathBuf>,
+    map: &HashMap<String, String>,
+    connected: Option<&[String]>,
+) -> Vec<Placement> {
+    let mut entries: Vec<_> = wallpapers.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut placements: Vec<Placement> = entries
+        .into_iter()
+        .map(|(saved, path)| {
+            let target = map.get(saved).unwrap_or(saved);
+            let available = connected.is_none_or(|outputs| outputs.contains(target));
+            Placement {
+                saved: saved.clone(),
+                target: available.then(|| target.clone()),
+                path: path.clone(),
+            }
+        })
+        .collect();
+
+    if let (true, Some(outputs)) = (map.is_empty(), connected) {
+        let free: Vec<String> = outputs
+            .iter()
+            .filter(|output| !placements.iter().any(|p| p.target.as_ref() == Some(output)))
+            .cloned()
+            .collect();
+        let mut free = free.into_iter();
+        for placement in placements.iter_mut().filter(|p| p.target.is_none()) {
+            placement.target = free.next();
+        }
+    }
+
+    placements
+}
+
+/// Apply `collection` to the connected outputs, printing one line per output.
+/// Failed outputs are warnings so the remaining ones still get their wallpaper.
+pub fn apply(
+    collection: &WallpaperCollection,
+    map: &HashMap<String, String>,
+    connected: Option<&[String]>,
+    config: &crate::app::Config,
+) {
+    let transition = config.transition();
+    // Only for per-wallpaper settings; applying never triggers a scan
+    let cache = crate::wallpaper::WallpaperCache::load_cached()
+        .ok()
+        .flatten();
+    let placements = place(&collection.wallpapers, map, connected);
+    let mut skipped = 0;
+
+    for placement in &placements {
+        let wp_path = &placement.path;
+        let Some(screen_name) = &placement.target else {
+            eprintln!(
+                "Warning: Skipping {}: no connected output for {}",
+                wp_path.display(),
+                placement.saved
+            );
+            skipped += 1;
+            continue;
+        };
+        if let Err(e) = crate::swww::apply_wallpaper(
+            screen_name,
+            wp_path,
+            &transition,
+            cache.as_ref().map_or(config.display.resize_mode, |cache| {
+                cache.resize_mode_for(wp_path, config.display.resize_mode)
+            }),
+            &config.display.fill_color_for(
+                cache
+                    .as_ref()
+                    .map_or(&[][..], |cache| cache.colors_for(wp_path)),
+            ),
+            config.scan.gif_mode,
+        ) {
+            eprintln!(
+                "Warning: Failed to set {} on {}: {}",
+                wp_path.display(),
+                screen_name,
+                e
+            );
+            continue;
+        }
+        crate::current::update_or_warn(&config.display, screen_name, wp_path);
+        if placement.is_remapped() {
+            println!(
+                "✓ {}: {} (saved for {})",
+                screen_name,
+                wp_path.display(),
+                placement.saved
+            );
+        } else {
+            println!("✓ {}: {}", screen_name, wp_path.display());
+        }
+    }
+
+    let remapped: Vec<String> = placements
+        .iter()
+        .filter(|p| p.is_remapped())
+        .filter_map(|p| Some(format!("{} → {}", p.saved, p.target.as_ref()?)))
+        .collect();
+    let mut notes = Vec::new();
+    if !remapped.is_empty() {
+        notes.push(format!("remapped {}", remapped.join(", ")));
+    }
+    if skipped > 0 {
+        notes.push(format!("{} output(s) not connected", skipped));
+    }
+    if notes.is_empty() {
+        println!("Applied collection '{}'", collection.name);
+    } else {
+        println!(
+            "Applied collection '{}' ({})",
+            collection.name,
+            notes.join("; ")
+        );
+    }
+}
+
 /// CLI commands for collection management
 pub fn cmd_collection_list() -> Result<()> {
     let store = CollectionStore::load()?;
@@ -176,10 +465,37 @@ pub fn cmd_collection_list() -> Result<()> {
     Ok(())
 }
 
+/// List automatic captures, newest first
+pub fn cmd_collection_recent() -> Result<()> {
+    let store = CollectionStore::load()?;
+    let recent = store.recent();
+
+    if recent.is_empty() {
+        println!("No recent collections.");
+        println!("Enable them with `auto_capture = true` under [collections] in config.toml.");
+        return Ok(());
+    }
+
+    println!(
+        "Recent collections (newest first, `{}` applies the top one):",
+        LATEST
+    );
+    for collection in recent {
+        let mut screens: Vec<_> = collection.wallpapers.iter().collect();
+        screens.sort_by(|a, b| a.0.cmp(b.0));
+        println!("  {}", collection.name);
+        for (screen, path) in screens {
+            println!("    {}: {}", screen, path.display());
+        }
+    }
+
+    Ok(())
+}
+
 pub fn cmd_collection_show(name: &str) -> Result<()> {
     let store = CollectionStore::load()?;
 
-    if let Some(collection) = store.get(name) {
+    if let Some(collection) = store.resolve(name) {
         println!("Collection: {}", collection.name);
         if let Some(desc) = &collection.description {
             println!("Description: {}", desc);
@@ -206,3 +522,130 @@ pub fn cmd_collection_delete(name: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collection(entries: &[(&str, &str)]) -> HashMap<String, PathBuf> {
+        entries
+            .iter()
+            .map(|(screen, path)| (screen.to_string(), PathBuf::from(path)))
+            .collect()
+    }
+
+    fn outputs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    fn targets(placements: &[Placement]) -> Vec<(&str, Option<&str>)> {
+        placements
+            .iter()
+            .map(|p| (p.saved.as_str(), p.target.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_push_recent_keeps_newest_and_skips_repeats() {
+        let mut store = CollectionStore::default();
+        let set = |path: &str| collection(&[("DP-1", path), ("DP-2", "/w/b.jpg")]);
+
+        assert!(store.push_recent(set("/w/1.jpg"), 2, 100).is_some());
+        assert!(
+            store.push_recent(set("/w/1.jpg"), 2, 200).is_none(),
+            "same set as the newest capture"
+        );
+        store.push_recent(set("/w/2.jpg"), 2, 300).unwrap();
+        let newest = store.push_recent(set("/w/3.jpg"), 2, 400).unwrap();
+
+        let recent = store.recent();
+        assert_eq!(recent.len(), 2, "oldest capture dropped");
+        assert_eq!(recent[0].name, newest);
+        assert_eq!(store.resolve(LATEST).unwrap().name, newest);
+    }
+
+    #[test]
+    fn test_push_recent_leaves_named_collections() {
+        let mut store = CollectionStore::default();
+        store.collections.push(WallpaperCollection {
+            name: "work".into(),
+            wallpapers: collection(&[("DP-1", "/w/work.jpg")]),
+            created_at: 0,
+            description: None,
+            tags: Vec::new(),
+        });
+        let first = store
+            .push_recent(collection(&[("DP-1", "/w/1.jpg")]), 1, 100)
+            .unwrap();
+        let second = store
+            .push_recent(collection(&[("DP-1", "/w/2.jpg")]), 1, 100)
+            .unwrap();
+
+        assert_ne!(first, second, "same second gets a suffix");
+        assert!(store.get("work").is_some());
+        assert!(store.get(&first).is_none());
+        assert_eq!(store.resolve(LATEST).unwrap().name, second);
+    }
+
+    #[test]
+    fn test_parse_screen_map() {
+        let map = parse_screen_map(&["DP-1=eDP-1".into(), " DP-2 = HDMI-A-1 ".into()]).unwrap();
+        assert_eq!(map.get("DP-1").map(String::as_str), Some("eDP-1"));
+        assert_eq!(map.get("DP-2").map(String::as_str), Some("HDMI-A-1"));
+
+        assert!(parse_screen_map(&["DP-1".into()]).is_err());
+        assert!(parse_screen_map(&["=eDP-1".into()]).is_err());
+    }
+
+    #[test]
+    fn test_place_keeps_connected_outputs() {
+        let wallpapers = collection(&[("DP-1", "/w/a.jpg"), ("DP-2", "/w/b.jpg")]);
+        let connected = outputs(&["DP-1", "DP-2"]);
+        let placements = place(&wallpapers, &HashMap::new(), Some(&connected));
+        assert_eq!(
+            targets(&placements),
+            vec![("DP-1", Some("DP-1")), ("DP-2", Some("DP-2"))]
+        );
+        assert!(!placements.iter().any(Placement::is_remapped));
+    }
+
+    #[test]
+    fn test_place_explicit_map() {
+        let wallpapers = collection(&[("DP-1", "/w/a.jpg"), ("DP-2", "/w/b.jpg")]);
+        let map = parse_screen_map(&["DP-1=eDP-1".into()]).unwrap();
+        let connected = outputs(&["eDP-1", "HDMI-A-1"]);
+        let placements = place(&wallpapers, &map, Some(&connected));
+        assert_eq!(
+            targets(&placements),
+            vec![("DP-1", Some("eDP-1")), ("DP-2", None)],
+            "with a map, unmapped missing outputs are skipped"
+        );
+    }
+
+    #[test]
+    fn test_place_falls_back_to_detected_order() {
+        let wallpapers = collection(&[
+            ("DP-1", "/w/a.jpg"),
+            ("DP-2", "/w/b.jpg"),
+            ("DP-3", "/w/c.jpg"),
+        ]);
+        let connected = outputs(&["eDP-1", "DP-2"]);
+        let placements = place(&wallpapers, &HashMap::new(), Some(&connected));
+        assert_eq!(
+            targets(&placements),
+            vec![
+                ("DP-1", Some("eDP-1")),
+                ("DP-2", Some("DP-2")),
+                ("DP-3", None)
+            ]
+        );
+        assert!(placements[0].is_remapped());
+    }
+
+    #[test]
+    fn test_place_without_detection_tries_saved_names() {
+        let wallpapers = collection(&[("DP-1", "/w/a.jpg")]);
+        let placements = place(&wallpapers, &HashMap::new(), None);
+        assert_eq!(targets(&placements), vec![("DP-1", Some("DP-1"))]);
+    }
+}
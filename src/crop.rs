@@ -0,0 +1,201 @@
+//! Crop geometry for `ResizeMode::Crop`.
+//!
+//! swww's `--resize crop` scales the image until it covers the whole output and
+//! cuts the overflow evenly from both sides. In source pixels that is the
+//! largest centered rectangle with the screen's aspect ratio.
+
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+/// Dim factor for the parts of the image that get cut
+const DIM_FACTOR: f32 = 0.3;
+/// Border drawn around the kept region
+const BORDER_COLOR: Rgba<u8> = Rgba([136, 192, 208, 255]);
+
+/// Region of the source image that stays visible, in source pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CropRect {
+    /// Fraction of the source image area that stays visible
+    pub fn coverage(&self, img_width: u32, img_height: u32) -> f32 {
+        let total = img_width as f64 * img_height as f64;
+        if total == 0.0 {
+            return 0.0;
+        }
+        (self.width as f64 * self.height as f64 / total) as f32
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Compute the centered crop that fills a `screen_width`x`screen_height` output.
+pub fn center_crop(
+    img_width: u32,
+    img_height: u32,
+    screen_width: u32,
+    screen_height: u32,
+) -> CropRect {
+    if img_width == 0 || img_height == 0 || screen_width == 0 || screen_height == 0 {
+        return CropRect {
+            x: 0,
+            y: 0,
+            width: img_width,
+            height: img_height,
+        };
+    }
+
+    // Compare aspect ratios without floats: img_w/img_h vs screen_w/screen_h
+    let img_cross = img_width as u64 * screen_height as u64;
+    let screen_cross = screen_width as u64 * img_height as u64;
+
+    if img_cross > screen_cross {
+        // Image is wider than the screen: keep full height, trim the sides
+        let width =
+            ((screen_cross as f64 / screen_height as f64).round() as u32).clamp(1, img_width);
+        CropRect {
+            x: (img_width - width) / 2,
+            y: 0,
+            width,
+            height: img_height,
+        }
+    } else {
+        // Image is taller (or equal): keep full width, trim top and bottom
+        let height = ((img_cross as f64 / screen_width as f64).round() as u32).clamp(1, img_height);
+        CropRect {
+            x: 0,
+            y: (img_height - height) / 2,
+            width: img_width,
+            height,
+        }
+    }
+}
+
+/// Draw the image with everything outside `rect` dimmed and the kept region outlined.
+pub fn render_preview(img: &RgbaImage, rect: CropRect) -> RgbaImage {
+    let mut out = img.clone();
+    let border = (img.width().max(img.height()) / 400).max(2);
+
+    for (x, y, pixel) in out.enumerate_pixels_mut() {
+        if !rect.contains(x, y) {
+            for channel in pixel.0.iter_mut().take(3) {
+                *channel = (*channel as f32 * DIM_FACTOR) as u8;
+            }
+            continue;
+        }
+
+        let on_border = x < rect.x + border
+            || x >= rect.x + rect.width - border.min(rect.width)
+            || y < rect.y + border
+            || y >= rect.y + rect.height - border.min(rect.height);
+        if on_border {
+            *pixel = BORDER_COLOR;
+        }
+    }
+
+    out
+}
+
+/// Write a crop preview of `image_path` for the given screen size.
+pub fn write_preview(
+    image_path: &Path,
+    screen_width: u32,
+    screen_height: u32,
+    output: &Path,
+) -> Result<CropRect> {
+    let img = image::open(image_path)
+        .with_context(|| format!("Failed to open image: {}", image_path.display()))?
+        .to_rgba8();
+    let rect = center_crop(img.width(), img.height(), screen_width, screen_height);
+
+    render_preview(&img, rect)
+        .save(output)
+        .with_context(|| format!("Failed to write preview: {}", output.display()))?;
+
+    Ok(rect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- center_crop ---
+
+    #[test]
+    fn test_center_crop_same_ratio_keeps_everything() {
+        let rect = center_crop(3840, 2160, 1920, 1080);
+        assert_eq!(
+            rect,
+            CropRect {
+                x: 0,
+                y: 0,
+                width: 3840,
+                height: 2160
+            }
+        );
+        assert!((rect.coverage(3840, 2160) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_center_crop_16_9_on_ultrawide_trims_top_and_bottom() {
+        let rect = center_crop(3840, 2160, 3440, 1440);
+        assert_eq!(rect.width, 3840);
+        assert_eq!(rect.height, 1607, "3840 * 1440 / 3440 rounded");
+        assert_eq!(rect.y, (2160 - 1607) / 2, "crop is centered vertically");
+        assert_eq!(rect.x, 0);
+    }
+
+    #[test]
+    fn test_center_crop_landscape_on_portrait_trims_sides() {
+        let rect = center_crop(1920, 1080, 1080, 1920);
+        assert_eq!(rect.height, 1080);
+        assert_eq!(rect.width, 608, "1080 * 1080 / 1920 rounded");
+        assert_eq!(rect.x, (1920 - 608) / 2, "crop is centered horizontally");
+        assert_eq!(rect.y, 0);
+    }
+
+    #[test]
+    fn test_center_crop_square_image_on_widescreen() {
+        let rect = center_crop(2000, 2000, 2560, 1440);
+        assert_eq!(rect.width, 2000);
+        assert_eq!(rect.height, 1125);
+        assert_eq!(rect.y, 437);
+    }
+
+    #[test]
+    fn test_center_crop_zero_dimensions_returns_full_image() {
+        let rect = center_crop(800, 600, 0, 1080);
+        assert_eq!(rect.width, 800);
+        assert_eq!(rect.height, 600);
+    }
+
+    // --- render_preview ---
+
+    #[test]
+    fn test_render_preview_dims_cut_region() {
+        let img = RgbaImage::from_pixel(100, 50, Rgba([200, 200, 200, 255]));
+        let rect = center_crop(100, 50, 50, 50);
+        let preview = render_preview(&img, rect);
+
+        assert_eq!(preview.dimensions(), (100, 50));
+        assert_eq!(preview.get_pixel(5, 25).0[0], 60, "outside region dimmed");
+        assert_eq!(
+            preview.get_pixel(50, 25).0[0],
+            200,
+            "inside region untouched"
+        );
+        assert_eq!(
+            *preview.get_pixel(rect.x, 25),
+            BORDER_COLOR,
+            "edge outlined"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Stable "current wallpaper" paths for lock screens, bars and other tools.
+//!
+//! After every apply, `current-<output>.img` in the link directory (default
+//! `~/.cache/frostwall/`) points at the image shown on that output, and
+//! `current.img` at the primary output's image. Links are symlinks where the
+//! filesystem allows it and plain copies otherwise; either way they are replaced
+//! atomically, so readers never see a missing or half-written file.
+
+use crate::app::DisplayConfig;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory holding the links: `display.current_link_dir` or the cache dir
+pub fn link_dir(display: &DisplayConfig) -> PathBuf {
+    match &display.current_link_dir {
+        Some(dir) => crate::utils::expand_tilde(dir),
+        None => directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+            .map(|dirs| dirs.cache_dir().to_path_buf())
+            .unwrap_or_else(|| PathBuf::from("/tmp/frostwall")),
+    }
+}
+
+/// Link for one output
+pub fn output_link(dir: &Path, output: &str) -> PathBuf {
+    dir.join(format!("current-{}.img", output))
+}
+
+/// Whether `output` should also update `current.img`. Without a configured
+/// `primary_output` the most recently applied output wins.
+fn is_primary(display: &DisplayConfig, output: &str) -> bool {
+    display
+        .primary_output
+        .as_deref()
+        .is_none_or(|primary| primary == output)
+}
+
+/// Point the current-wallpaper links for `output` at `image`.
+/// Does nothing when `display.current_symlink` is off.
+pub fn update(display: &DisplayConfig, output: &str, image: &Path) -> Result<()> {
+    if !display.current_symlink {
+        return Ok(());
+    }
+    let dir = link_dir(display);
+    fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    // Relative paths would resolve against the link's directory
+    let image = image.canonicalize().unwrap_or_else(|_| image.to_path_buf());
+
+    replace_link(&image, &output_link(&dir, output))?;
+    if is_primary(display, output) {
+        replace_link(&image, &dir.join("current.img"))?;
+    }
+    Ok(())
+}
+
+/// `update` for CLI commands: a failed link is a warning, not an error
+pub fn update_or_warn(display: &DisplayConfig, output: &str, image: &Path) {
+    if let Err(e) = update(display, output, image) {
+        eprintln!("Warning: Failed to update current wallpaper link: {:#}", e);
+    }
+}
+
+/// Image the link for `output` points at, if it is a symlink (copies can't
+/// tell where they came from)
+pub fn read(display: &DisplayConfig, output: &str) -> Option<PathBuf> {
+    fs::read_link(output_link(&link_dir(display), output)).ok()
+}
+
+/// Replace `link` with a symlink to `target` (or a copy if symlinks fail).
+/// The new entry is created beside `link` and renamed over it.
+fn replace_link(target: &Path, link: &Path) -> Result<()> {
+    let mut tmp_name = link.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+    let _ = fs::remove_file(&tmp);
+
+    if symlink(target, &tmp).is_err() {
+        fs::copy(target, &tmp)
+            .with_context(|| format!("Failed to copy {} to {}", target.display(), tmp.display()))?;
+    }
+    fs::rename(&tmp, link).with_context(|| format!("Failed to update {}", link.display()))
+}
+
+#[cfg(unix)]
+fn symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn symlink(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display_in(dir: &Path) -> DisplayConfig {
+        DisplayConfig {
+            current_link_dir: Some(dir.to_string_lossy().into_owned()),
+            ..DisplayConfig::default()
+        }
+    }
+
+    // --- update ---
+
+    #[test]
+    fn test_update_links_output_and_primary() {
+        let dir = std::env::temp_dir().join("frostwall_test_current_links");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.png");
+        let b = dir.join("b.png");
+        fs::write(&a, b"a").unwrap();
+        fs::write(&b, b"b").unwrap();
+
+        let mut display = display_in(&dir);
+        update(&display, "DP-1", &a).unwrap();
+        update(&display, "HDMI-A-1", &b).unwrap();
+        assert_eq!(fs::read(output_link(&dir, "DP-1")).unwrap(), b"a");
+        assert_eq!(fs::read(output_link(&dir, "HDMI-A-1")).unwrap(), b"b");
+        assert_eq!(
+            fs::read(dir.join("current.img")).unwrap(),
+            b"b",
+            "without primary_output the last apply wins"
+        );
+
+        display.primary_output = Some("DP-1".to_string());
+        update(&display, "DP-1", &a).unwrap();
+        update(&display, "HDMI-A-1", &a).unwrap();
+        update(&display, "HDMI-A-1", &b).unwrap();
+        assert_eq!(fs::read(dir.join("current.img")).unwrap(), b"a");
+        assert!(!dir.join("current.img.tmp").exists());
+        #[cfg(unix)]
+        assert_eq!(read(&display, "HDMI-A-1"), Some(b.canonicalize().unwrap()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_update_disabled_writes_nothing() {
+        let dir = std::env::temp_dir().join("frostwall_test_current_links_off");
+        let _ = fs::remove_dir_all(&dir);
+        let display = DisplayConfig {
+            current_symlink: false,
+            ..display_in(&dir)
+        };
+        update(&display, "DP-1", Path::new("/nonexistent.png")).unwrap();
+        assert!(!dir.exists());
+    }
+}
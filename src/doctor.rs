@@ -0,0 +1,316 @@
+//! `frostwall doctor`: environment checks for the usual first-run failures.
+//!
+//! Each check prints pass/warn/fail with a hint. Failures make the command
+//! exit non-zero so setup scripts can stop early.
+
+use crate::app::{self, Config, GraphicsProtocol};
+use crate::screen;
+use crate::utils::find_on_path;
+use crate::wallpaper::{GifMode, WallpaperCache};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+}
+
+struct Check {
+    name: &'static str,
+    status: Status,
+    detail: String,
+    hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn print(&self) {
+        let symbol = match self.status {
+            Status::Pass => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        };
+        println!("{} {:<18} {}", symbol, self.name, self.detail);
+        if let Some(hint) = &self.hint {
+            println!("  → {}", hint);
+        }
+    }
+}
+
+/// Run every check. Reads config.toml itself (instead of `Config::load`) so a
+/// broken file is reported rather than replaced with defaults.
+pub async fn run(dir_override: Option<PathBuf>) -> Result<()> {
+    let mut checks = Vec::new();
+
+    let (config, config_check) = check_config();
+    checks.push(config_check);
+    checks.push(check_wayland());
+    checks.extend(check_swww());
+    if config.scan.gif_mode == GifMode::Animated {
+        checks.push(check_mpvpaper());
+    }
+    checks.push(check_screens().await);
+    checks.push(check_graphics(config.terminal.graphics_protocol));
+    let wallpaper_dir = dir_override.unwrap_or_else(|| config.wallpaper_dir());
+    checks.push(check_wallpaper_dir(&wallpaper_dir, &config));
+    #[cfg(feature = "clip")]
+    checks.push(check_clip_model());
+
+    for check in &checks {
+        check.print();
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let failed = count(Status::Fail);
+    println!(
+        "\n{} passed, {} warnings, {} failed",
+        count(Status::Pass),
+        count(Status::Warn),
+        failed
+    );
+    if failed > 0 {
+        anyhow::bail!("{} check(s) failed", failed);
+    }
+    Ok(())
+}
+
+fn check_config() -> (Config, Check) {
+    let path = Config::config_path();
+    if !path.exists() {
+        return (
+            Config::default(),
+            Check::warn(
+                "config",
+                format!("{} not found, using defaults", path.display()),
+                "Run 'frostwall init' to create one",
+            ),
+        );
+    }
+
+    let parsed = std::fs::read_to_string(&path)
+        .map_err(anyhow::Error::from)
+        .and_then(|data| toml::from_str::<Config>(&data).map_err(anyhow::Error::from));
+    match parsed {
+        Ok(config) => (config, Check::pass("config", path.display().to_string())),
+        Err(e) => (
+            Config::default(),
+            Check::fail(
+                "config",
+                format!(
+                    "{} is invalid: {}",
+                    path.display(),
+                    first_line(&e.to_string())
+                ),
+                "Fix the file, or move it away and run 'frostwall init'",
+            ),
+        ),
+    }
+}
+
+fn check_wayland() -> Check {
+    match std::env::var("WAYLAND_DISPLAY") {
+        Ok(display) if !display.is_empty() => Check::pass("wayland", display),
+        _ => Check::fail(
+            "wayland",
+            "WAYLAND_DISPLAY is not set",
+            "swww needs a Wayland session (niri, Sway, Hyprland, ...)",
+        ),
+    }
+}
+
+fn check_swww() -> Vec<Check> {
+    let swww = match find_on_path("swww") {
+        Some(_) => {
+            let version = Command::new("swww")
+                .arg("--version")
+                .output()
+                .ok()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| "version unknown".to_string());
+            Check::pass("swww", version)
+        }
+        None => Check::fail(
+            "swww",
+            "not found on PATH",
+            "Install swww (https://github.com/LGFae/swww)",
+        ),
+    };
+
+    let daemon = match find_on_path("swww-daemon") {
+        None => Check::fail(
+            "swww-daemon",
+            "not found on PATH",
+            "It ships with swww; check your installation",
+        ),
+        Some(path) => {
+            let running = Command::new("swww")
+                .arg("query")
+                .output()
+                .is_ok_and(|o| o.status.success());
+            if running {
+                Check::pass("swww-daemon", "running")
+            } else {
+                Check::warn(
+                    "swww-daemon",
+                    format!("{} is not running", path.display()),
+                    "frostwall starts it on first apply; or add it to your compositor autostart",
+                )
+            }
+        }
+    };
+
+    vec![swww, daemon]
+}
+
+fn check_mpvpaper() -> Check {
+    match find_on_path("mpvpaper") {
+        Some(path) => Check::pass("mpvpaper", path.display().to_string()),
+        None => Check::fail(
+            "mpvpaper",
+            "not found, but scan.gif_mode = \"animated\"",
+            "Install mpvpaper or set scan.gif_mode to \"static\"",
+        ),
+    }
+}
+
+async fn check_screens() -> Check {
+    match screen::detect_screens().await {
+        Ok(screens) if !screens.is_empty() => {
+            let names: Vec<&str> = screens.iter().map(|s| s.name.as_str()).collect();
+            Check::pass(
+                "screens",
+                format!("{} detected: {}", screens.len(), names.join(", ")),
+            )
+        }
+        Ok(_) => Check::fail(
+            "screens",
+            "no outputs detected",
+            "Check that niri or wlr-randr lists your monitors",
+        ),
+        Err(e) => Check::fail(
+            "screens",
+            first_line(&e.to_string()),
+            "Screen detection needs niri (preferred) or wlr-randr",
+        ),
+    }
+}
+
+fn check_graphics(configured: GraphicsProtocol) -> Check {
+    let Some(detected) = app::detect_picker(GraphicsProtocol::Auto) else {
+        return Check::warn(
+            "graphics",
+            format!(
+                "could not query the terminal (not a TTY?), config: {}",
+                configured.name()
+            ),
+            "Run doctor from the terminal you use for the TUI",
+        );
+    };
+
+    let tmux = if detected.is_tmux {
+        ", inside tmux"
+    } else {
+        ""
+    };
+    match configured.forced() {
+        None => Check::pass(
+            "graphics",
+            format!("detected {:?}{}", detected.protocol_type, tmux),
+        ),
+        Some(forced) if forced == detected.protocol_type => Check::pass(
+            "graphics",
+            format!("{:?} (configured, matches detection{})", forced, tmux),
+        ),
+        Some(forced) => Check::warn(
+            "graphics",
+            format!(
+                "using {:?} from config, detected {:?}{}",
+                forced, detected.protocol_type, tmux
+            ),
+            "Set terminal.graphics_protocol = \"auto\" if thumbnails look broken",
+        ),
+    }
+}
+
+fn check_wallpaper_dir(dir: &Path, config: &Config) -> Check {
+    if !dir.is_dir() {
+        return Check::fail(
+            "wallpapers",
+            format!("{} does not exist", dir.display()),
+            "Create it, pass -d <dir>, or set wallpaper.directory",
+        );
+    }
+    match WallpaperCache::discover(dir, config.wallpaper.recursive, &config.scan) {
+        Ok(images) if images.is_empty() => Check::warn(
+            "wallpapers",
+            format!("no images in {}", dir.display()),
+            if config.wallpaper.recursive {
+                "Add some images or import with 'frostwall import'"
+            } else {
+                "Add some images, or set wallpaper.recursive = true for subfolders"
+            },
+        ),
+        Ok(images) => Check::pass(
+            "wallpapers",
+            format!("{} images in {}", images.len(), dir.display()),
+        ),
+        Err(e) => Check::fail(
+            "wallpapers",
+            first_line(&e.to_string()),
+            "Check the directory permissions",
+        ),
+    }
+}
+
+#[cfg(feature = "clip")]
+fn check_clip_model() -> Check {
+    let manager = crate::clip::ModelManager::new();
+    let (path, valid) = manager.visual_model_status();
+    match valid {
+        None => Check::warn(
+            "clip model",
+            format!("{} not downloaded yet", path.display()),
+            "It is fetched on the first 'frostwall auto-tag'",
+        ),
+        Some(true) => Check::pass("clip model", format!("{} (checksum ok)", path.display())),
+        Some(false) => Check::fail(
+            "clip model",
+            format!("{} has the wrong checksum", path.display()),
+            "Delete it; 'frostwall auto-tag' downloads it again",
+        ),
+    }
+}
+
+fn first_line(text: &str) -> String {
+    text.lines().next().unwrap_or_default().to_string()
+}
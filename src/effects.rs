@@ -0,0 +1,194 @@
+//! Blur and dim effects for `frostwall focus`.
+//!
+//! A focus variant is written as a PNG in the cache directory (keyed like the
+//! adjusted copies in `adjust.rs`) and applied in place of the wallpaper. The
+//! original per output is remembered in `focus.json`, so running `focus` again
+//! can put it back.
+
+use anyhow::{Context, Result};
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+fn cache_dir() -> PathBuf {
+    directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/frostwall"))
+}
+
+/// Check `blur` (sigma in pixels, >= 0) and `dim` (0.0-1.0)
+pub fn validate(blur: f32, dim: f32) -> Result<()> {
+    if !blur.is_finite() || blur < 0.0 {
+        anyhow::bail!("blur must be 0 or more, got {}", blur);
+    }
+    if !(0.0..=1.0).contains(&dim) {
+        anyhow::bail!("dim must be between 0.0 and 1.0, got {}", dim);
+    }
+    Ok(())
+}
+
+/// Blur `img` with a Gaussian of `blur` sigma (0 = none), then darken every
+/// channel by the fraction `dim`. Alpha is kept.
+pub fn apply_effects(img: &RgbaImage, blur: f32, dim: f32) -> RgbaImage {
+    let mut out = if blur > 0.0 {
+        // Box-blur approximation of a Gaussian; exact blur is far slower on 4K images
+        imageops::fast_blur(img, blur)
+    } else {
+        img.clone()
+    };
+    if dim > 0.0 {
+        let keep = 1.0 - dim.clamp(0.0, 1.0);
+        for pixel in out.pixels_mut() {
+            for channel in pixel.0.iter_mut().take(3) {
+                *channel = (*channel as f32 * keep).round() as u8;
+            }
+        }
+    }
+    out
+}
+
+/// Cache file for `image_path` with the given effects
+pub fn focused_path(image_path: &Path, blur: f32, dim: f32) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    image_path.to_string_lossy().hash(&mut hasher);
+    if let Ok(modified) = fs::metadata(image_path).and_then(|m| m.modified()) {
+        modified.hash(&mut hasher);
+    }
+    blur.to_bits().hash(&mut hasher);
+    dim.to_bits().hash(&mut hasher);
+
+    cache_dir()
+        .join("focus")
+        .join(format!("{:016x}.png", hasher.finish()))
+}
+
+/// Return the focus variant of `image_path`, writing it only if not cached yet.
+/// GIFs use their first frame.
+pub fn ensure_focused(image_path: &Path, blur: f32, dim: f32) -> Result<PathBuf> {
+    let output = focused_path(image_path, blur, dim);
+    if output.exists() {
+        return Ok(output);
+    }
+
+    let img = crate::utils::open_still(image_path)
+        .with_context(|| format!("Failed to open image: {}", image_path.display()))?
+        .to_rgba8();
+
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    apply_effects(&img, blur, dim)
+        .save(&output)
+        .with_context(|| format!("Failed to write image: {}", output.display()))?;
+
+    Ok(output)
+}
+
+/// A focused output and what it showed before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FocusEntry {
+    pub original: PathBuf,
+    pub focused: PathBuf,
+}
+
+/// Outputs currently showing a focus variant
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FocusState {
+    pub outputs: HashMap<String, FocusEntry>,
+}
+
+impl FocusState {
+    fn storage_path() -> PathBuf {
+        cache_dir().join("focus.json")
+    }
+
+    pub fn load() -> Result<Self> {
+        let path = Self::storage_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).context("Failed to read focus state")?;
+        serde_json::from_str(&content).context("Failed to parse focus state")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
+        let path = Self::storage_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_image() -> RgbaImage {
+        RgbaImage::from_fn(16, 4, |x, _| {
+            if x < 8 {
+                image::Rgba([0, 0, 0, 200])
+            } else {
+                image::Rgba([200, 200, 200, 200])
+            }
+        })
+    }
+
+    // --- validate ---
+
+    #[test]
+    fn test_validate_rejects_out_of_range() {
+        assert!(validate(8.0, 0.4).is_ok());
+        assert!(validate(-1.0, 0.4).is_err());
+        assert!(validate(8.0, 1.5).is_err());
+    }
+
+    // --- apply_effects ---
+
+    #[test]
+    fn test_apply_effects_dims_and_keeps_alpha() {
+        let out = apply_effects(&edge_image(), 0.0, 0.5);
+        assert_eq!(out.get_pixel(12, 0).0, [100, 100, 100, 200]);
+        assert_eq!(out.get_pixel(0, 0).0, [0, 0, 0, 200]);
+    }
+
+    #[test]
+    fn test_apply_effects_blur_softens_edge() {
+        let img = edge_image();
+        let out = apply_effects(&img, 2.0, 0.0);
+        assert_eq!(out.dimensions(), img.dimensions());
+        let left = out.get_pixel(7, 2).0[0];
+        let right = out.get_pixel(8, 2).0[0];
+        assert!(
+            left > 0 && right < 200,
+            "edge pixels mix: {} {}",
+            left,
+            right
+        );
+    }
+
+    #[test]
+    fn test_apply_effects_noop() {
+        let img = edge_image();
+        assert_eq!(apply_effects(&img, 0.0, 0.0), img);
+    }
+
+    // --- focused_path ---
+
+    #[test]
+    fn test_focused_path_depends_on_amounts() {
+        let path = Path::new("/tmp/frostwall-test/a.png");
+        let a = focused_path(path, 8.0, 0.4);
+        assert_eq!(a, focused_path(path, 8.0, 0.4));
+        assert_ne!(a, focused_path(path, 8.0, 0.5));
+        assert_ne!(a, focused_path(path, 4.0, 0.4));
+    }
+}
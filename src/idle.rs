@@ -0,0 +1,159 @@
+//! Idle detection for `frostwall watch --on-idle`.
+//!
+//! Wayland has no portable "how long has the user been idle" query, so the
+//! default source is a marker file kept by swayidle (or any idle daemon):
+//!
+//! ```text
+//! swayidle timeout 1 'touch ~/.cache/frostwall/idle' resume 'rm -f ~/.cache/frostwall/idle'
+//! ```
+//!
+//! While the file exists the user counts as idle since its modification time.
+//! `--idle-command` replaces the marker with a command that prints the idle
+//! time in milliseconds, e.g. `xprintidle` on X11.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// Something that can tell how long the user has been idle
+pub trait IdleSource {
+    /// Time since the last user input (zero while active)
+    fn idle_time(&self) -> Result<Duration>;
+    /// Short description for the watch banner
+    fn describe(&self) -> String;
+}
+
+/// Idle since the marker file was written; active while it is missing
+pub struct MarkerFile {
+    pub path: PathBuf,
+}
+
+impl IdleSource for MarkerFile {
+    fn idle_time(&self) -> Result<Duration> {
+        let modified = match std::fs::metadata(&self.path) {
+            Ok(meta) => meta.modified()?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Duration::ZERO),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.path.display()))
+            }
+        };
+        Ok(SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or(Duration::ZERO))
+    }
+
+    fn describe(&self) -> String {
+        format!("marker file {}", self.path.display())
+    }
+}
+
+/// Shell command printing the idle time in milliseconds
+pub struct IdleCommand {
+    pub command: String,
+}
+
+impl IdleSource for IdleCommand {
+    fn idle_time(&self) -> Result<Duration> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .output()
+            .with_context(|| format!("Failed to run idle command: {}", self.command))?;
+        if !output.status.success() {
+            anyhow::bail!("Idle command failed ({}): {}", output.status, self.command);
+        }
+        parse_idle_ms(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    fn describe(&self) -> String {
+        format!("command `{}`", self.command)
+    }
+}
+
+/// Parse command output such as `"12345\n"` (milliseconds)
+pub fn parse_idle_ms(output: &str) -> Result<Duration> {
+    let trimmed = output.trim();
+    trimmed
+        .parse::<u64>()
+        .map(Duration::from_millis)
+        .with_context(|| format!("Expected idle milliseconds, got '{}'", trimmed))
+}
+
+/// Default marker path: `~/.cache/frostwall/idle`
+pub fn default_marker_path() -> PathBuf {
+    directories::ProjectDirs::from("com", "mrmattias", "frostwall")
+        .map(|dirs| dirs.cache_dir().to_path_buf())
+        .unwrap_or_else(|| PathBuf::from("/tmp/frostwall"))
+        .join("idle")
+}
+
+/// `--idle-command` if given, otherwise the default marker file
+pub fn source(command: Option<&str>) -> Box<dyn IdleSource + Send> {
+    match command {
+        Some(command) => Box::new(IdleCommand {
+            command: command.to_string(),
+        }),
+        None => Box::new(MarkerFile {
+            path: default_marker_path(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- parse_idle_ms ---
+
+    #[test]
+    fn test_parse_idle_ms() {
+        assert_eq!(
+            parse_idle_ms("1500\n").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert!(parse_idle_ms("").is_err());
+        assert!(parse_idle_ms("5m").is_err());
+    }
+
+    // --- MarkerFile ---
+
+    #[test]
+    fn test_marker_file_idle_only_while_present() {
+        let dir = std::env::temp_dir().join("frostwall_test_idle_marker");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let marker = MarkerFile {
+            path: dir.join("idle"),
+        };
+
+        assert_eq!(marker.idle_time().unwrap(), Duration::ZERO);
+
+        std::fs::write(&marker.path, b"").unwrap();
+        let since = SystemTime::now() - Duration::from_secs(600);
+        std::fs::File::options()
+            .write(true)
+            .open(&marker.path)
+            .unwrap()
+            .set_modified(since)
+            .unwrap();
+        assert!(marker.idle_time().unwrap() >= Duration::from_secs(600));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- IdleCommand ---
+
+    #[test]
+    fn test_idle_command_reads_stdout() {
+        let command = IdleCommand {
+            command: "echo 42000".to_string(),
+        };
+        assert_eq!(command.idle_time().unwrap(), Duration::from_secs(42));
+
+        let failing = IdleCommand {
+            command: "exit 3".to_string(),
+        };
+        assert!(failing.idle_time().is_err());
+    }
+}
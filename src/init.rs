@@ -2,9 +2,41 @@ use crate::app::Config;
 use crate::screen;
 use crate::swww::ResizeMode;
 use crate::wallpaper::MatchMode;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use dialoguer::{theme::ColorfulTheme, Confirm, Input, Select};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Non-interactive setup for scripts: write a default config without prompting.
+/// Refuses to replace an existing config unless `force` is set.
+pub fn run_defaults(dir: Option<PathBuf>, recursive: bool, force: bool) -> Result<()> {
+    let config_path = Config::config_path();
+    if config_path.exists() && !force {
+        anyhow::bail!(
+            "Config already exists at {} (use --force to overwrite)",
+            config_path.display()
+        );
+    }
+
+    let wallpaper_dir = dir.unwrap_or_else(default_wallpaper_dir);
+    let expanded_dir = expand_tilde(&wallpaper_dir.to_string_lossy());
+    if !expanded_dir.exists() {
+        std::fs::create_dir_all(&expanded_dir)
+            .with_context(|| format!("Failed to create {}", expanded_dir.display()))?;
+        println!("✓ Created {}", expanded_dir.display());
+    }
+
+    let config = build_config(
+        &wallpaper_dir,
+        recursive,
+        MatchMode::Flexible,
+        ResizeMode::Fit,
+        "fade",
+    );
+    config.save()?;
+    println!("✓ Config saved to {}", config_path.display());
+
+    Ok(())
+}
 
 /// Interactive setup wizard for new users
 pub async fn run_init() -> Result<()> {
@@ -28,9 +60,7 @@ pub async fn run_init() -> Result<()> {
     }
 
     // Step 1: Wallpaper directory
-    let default_dir = dirs::picture_dir()
-        .map(|p| p.join("wallpapers"))
-        .unwrap_or_else(|| PathBuf::from("~/Pictures/wallpapers"));
+    let default_dir = default_wallpaper_dir();
 
     let wallpaper_dir: String = Input::with_theme(&theme)
         .with_prompt("Wallpaper directory")
@@ -142,12 +172,13 @@ pub async fn run_init() -> Result<()> {
         .interact()?;
 
     // Build config
-    let mut config = Config::default();
-    config.wallpaper.directory = PathBuf::from(wallpaper_dir);
-    config.wallpaper.recursive = recursive;
-    config.display.match_mode = match_mode;
-    config.display.resize_mode = resize_mode;
-    config.transition.transition_type = transition_type.to_string();
+    let config = build_config(
+        Path::new(&wallpaper_dir),
+        recursive,
+        match_mode,
+        resize_mode,
+        transition_type,
+    );
 
     // Save config
     config.save()?;
@@ -161,7 +192,7 @@ pub async fn run_init() -> Result<()> {
 
     if scan_now {
         println!("\nScanning {}...", expanded_dir.display());
-        match crate::wallpaper::WallpaperCache::scan(&expanded_dir) {
+        match crate::wallpaper::WallpaperCache::scan(&expanded_dir, &config.scan) {
             Ok(cache) => {
                 cache.save()?;
                 let stats = cache.stats();
@@ -182,6 +213,28 @@ pub async fn run_init() -> Result<()> {
     Ok(())
 }
 
+fn default_wallpaper_dir() -> PathBuf {
+    dirs::picture_dir()
+        .map(|p| p.join("wallpapers"))
+        .unwrap_or_else(|| PathBuf::from("~/Pictures/wallpapers"))
+}
+
+fn build_config(
+    wallpaper_dir: &Path,
+    recursive: bool,
+    match_mode: MatchMode,
+    resize_mode: ResizeMode,
+    transition_type: &str,
+) -> Config {
+    let mut config = Config::default();
+    config.wallpaper.directory = wallpaper_dir.to_path_buf();
+    config.wallpaper.recursive = recursive;
+    config.display.match_mode = match_mode;
+    config.display.resize_mode = resize_mode;
+    config.transition.transition_type = transition_type.to_string();
+    config
+}
+
 fn expand_tilde(path: &str) -> PathBuf {
     crate::utils::expand_tilde(path)
 }
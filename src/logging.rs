@@ -0,0 +1,119 @@
+//! Leveled diagnostics on stderr, controlled by the global `-v`/`-q` flags.
+//!
+//! At the default level only info, warnings and errors are shown, formatted
+//! like frostwall's other terminal output (`Warning: ...`), so nothing changes
+//! for users who don't ask for more. `-v` adds debug lines (swww commands,
+//! HTTP requests, CLIP steps) with level and module, `-vv` adds trace lines
+//! and `-q` keeps only warnings and errors.
+//!
+//! Stdout results (`println!`) are not logs and are never filtered.
+
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Level for `-v` given `verbose` times, or `-q`
+pub fn level(verbose: u8, quiet: bool) -> LevelFilter {
+    if quiet {
+        return LevelFilter::WARN;
+    }
+    match verbose {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Install the stderr subscriber. Call once, before anything logs.
+pub fn init(level: LevelFilter) {
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(level)
+        .with_writer(writer)
+        .with_ansi(io::stderr().is_terminal());
+
+    if level > LevelFilter::INFO {
+        builder.without_time().init();
+    } else {
+        builder.event_format(Plain).init();
+    }
+}
+
+/// Drop log output while the TUI owns the terminal; stray stderr lines would
+/// be drawn over the interface
+pub fn set_tui_active(active: bool) {
+    TUI_ACTIVE.store(active, Ordering::Relaxed);
+}
+
+fn writer() -> Box<dyn io::Write> {
+    if TUI_ACTIVE.load(Ordering::Relaxed) {
+        Box::new(io::sink())
+    } else {
+        Box::new(io::stderr())
+    }
+}
+
+/// Whether CLI progress should be drawn in place on one line. Only at the
+/// default level: `-q` hides it and `-v` logs each phase as its own line.
+pub fn inline_progress() -> bool {
+    tracing::enabled!(Level::INFO) && !tracing::enabled!(Level::DEBUG)
+}
+
+/// Default-level format: the message alone, with the `Warning:`/`Error:`
+/// prefixes frostwall used before it had logging
+struct Plain;
+
+impl<S, N> FormatEvent<S, N> for Plain
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        write!(writer, "{}", prefix(*event.metadata().level()))?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+fn prefix(level: Level) -> &'static str {
+    match level {
+        Level::ERROR => "Error: ",
+        Level::WARN => "Warning: ",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- level ---
+
+    #[test]
+    fn test_level_from_flags() {
+        assert_eq!(level(0, false), LevelFilter::INFO);
+        assert_eq!(level(1, false), LevelFilter::DEBUG);
+        assert_eq!(level(3, false), LevelFilter::TRACE);
+        assert_eq!(level(2, true), LevelFilter::WARN, "quiet wins");
+    }
+
+    // --- prefix ---
+
+    #[test]
+    fn test_prefix_matches_plain_output() {
+        assert_eq!(prefix(Level::WARN), "Warning: ");
+        assert_eq!(prefix(Level::ERROR), "Error: ");
+        assert_eq!(prefix(Level::INFO), "");
+    }
+}
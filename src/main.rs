@@ -1,12 +1,21 @@
+mod adjust;
 mod app;
 mod clip;
 #[cfg(feature = "clip")]
 mod clip_embeddings_bin;
 mod collections;
+mod crop;
+mod current;
+mod doctor;
+mod effects;
+mod idle;
 mod init;
+mod logging;
 mod pairing;
 mod profile;
 mod pywal;
+mod recolor;
+mod schedule;
 mod screen;
 mod swww;
 mod thumbnail;
@@ -17,8 +26,9 @@ mod wallpaper;
 mod watch;
 mod webimport;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
 use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
@@ -33,41 +43,234 @@ struct Cli {
     /// Wallpaper directory
     #[arg(short, long)]
     dir: Option<PathBuf>,
+
+    /// Use settings from a named profile (see `frostwall profile list`)
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Don't write config, cache or pairing history for this invocation
+    #[arg(long)]
+    no_save: bool,
+
+    /// Fail instead of starting swww-daemon when it isn't running
+    /// (also FROSTWALL_NO_DAEMON=1 or display.auto_start_daemon = false)
+    #[arg(long)]
+    no_daemon: bool,
+
+    /// Print machine-readable JSON from read-only commands
+    /// (screens, scan, stats, validate, tag list, similar, pair stats)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Seed random picks so the same library and screens give the same result
+    #[arg(long, visible_alias = "shuffle-seed")]
+    seed: Option<u64>,
+
+    /// More diagnostics on stderr: -v debug (swww, HTTP, CLIP steps), -vv trace
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print warnings and errors on stderr
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+}
+
+/// How read-only commands report their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Human,
+    Json,
+}
+
+impl Output {
+    /// Informational line. Goes to stderr in JSON mode so stdout stays parseable.
+    fn note(self, message: impl std::fmt::Display) {
+        match self {
+            Output::Human => println!("{}", message),
+            Output::Json => eprintln!("{}", message),
+        }
+    }
+
+    fn json<T: Serialize>(value: &T) -> Result<()> {
+        println!("{}", serde_json::to_string_pretty(value)?);
+        Ok(())
+    }
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Set a random wallpaper (smart-matched to screens)
-    Random,
+    Random {
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+    },
     /// Set next wallpaper in sequence
-    Next,
+    Next {
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+        /// Advance the primary screen and fill the others with its best pairing matches
+        #[arg(long)]
+        paired: bool,
+    },
     /// Set previous wallpaper in sequence
-    Prev,
+    Prev {
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+        /// Step back on the primary screen and fill the others with its best pairing matches
+        #[arg(long)]
+        paired: bool,
+    },
+    /// Set a specific image, bypassing selection logic
+    Apply {
+        /// Image to apply
+        path: PathBuf,
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+        /// Brighten (positive) or darken (negative), -1.0 to 1.0
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        brightness: f32,
+        /// Raise (positive) or lower (negative) contrast, -1.0 to 1.0
+        #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+        contrast: f32,
+    },
+    /// Random wallpaper on the primary screen, color-harmonious companions on the rest
+    /// (or the same wallpaper everywhere with `display.mirror_screens`)
+    Surprise {
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+        /// Leave these outputs untouched (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        skip: Vec<String>,
+    },
+    /// Toggle a blurred, dimmed variant of the current wallpaper (e.g. for presentations)
+    Focus {
+        /// Only change these outputs (comma-separated, e.g. DP-1,HDMI-A-1)
+        #[arg(short, long, value_delimiter = ',')]
+        output: Vec<String>,
+        /// Blur strength (Gaussian sigma in pixels, 0 = none)
+        #[arg(long, default_value_t = 8.0)]
+        blur: f32,
+        /// Darken by this fraction, 0.0 to 1.0
+        #[arg(long, default_value_t = 0.4)]
+        dim: f32,
+    },
     /// List available screens
-    Screens,
+    Screens {
+        /// Keep running and print outputs as they are plugged in, removed or resized
+        #[arg(long, conflicts_with = "json")]
+        watch: bool,
+        /// How often to check for changes with --watch (e.g. "2s", "1m")
+        #[arg(long, default_value = "2s", requires = "watch")]
+        interval: String,
+    },
+    /// List cached wallpapers
+    List {
+        /// Only wallpapers added or modified within this window (e.g. "7d", "12h")
+        #[arg(long)]
+        recent: Option<String>,
+    },
     /// Rescan wallpaper directory and update cache
-    Scan,
+    Scan {
+        /// Leave out directories or files matching this glob, on top of `scan.exclude_dirs`
+        /// (repeatable)
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+    },
+    /// Show cached wallpaper counts by aspect ratio (no rescan)
+    Stats,
+    /// Check swww, Wayland, screens, terminal graphics, config and wallpaper directory
+    Doctor,
+    /// Check the cache and pairing history for stale or incomplete entries
+    Validate {
+        /// Drop dead entries, fix dimensions and re-extract missing colors
+        #[arg(long)]
+        fix: bool,
+    },
     /// Interactive setup wizard for new users
-    Init,
+    Init {
+        /// Write a default config without prompting (for setup scripts)
+        #[arg(long)]
+        defaults: bool,
+
+        /// Wallpaper directory for --defaults (created if missing)
+        #[arg(long, requires = "defaults")]
+        dir: Option<PathBuf>,
+
+        /// Scan subdirectories (with --defaults)
+        #[arg(long, requires = "defaults")]
+        recursive: bool,
+
+        /// Overwrite an existing config (with --defaults)
+        #[arg(long, requires = "defaults")]
+        force: bool,
+    },
+    /// Find, show or edit the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
     /// Run watch daemon for automatic wallpaper rotation
     Watch {
         /// Rotation interval (e.g., "30m", "1h", "90s")
         #[arg(short, long, default_value = "30m")]
         interval: String,
 
+        /// Vary each wait by up to ± this fraction of the interval (0 = exact)
+        #[arg(long, default_value = "0")]
+        jitter: f64,
+
         /// Shuffle wallpapers randomly
         #[arg(short, long, default_value = "true")]
         shuffle: bool,
 
+        /// How shuffled picks are made: random, or similar (drift through related colors)
+        #[arg(long, default_value = "random", value_parser = watch::WatchMode::parse)]
+        mode: watch::WatchMode,
+
         /// Watch directory for new files
         #[arg(short = 'w', long, default_value = "true")]
         watch_dir: bool,
+
+        /// Wait until the directory has been quiet this many milliseconds before rescanning
+        #[arg(long, default_value_t = 2000)]
+        watch_debounce: u64,
+
+        /// Ignore file events matching these name patterns (comma-separated, "" for none)
+        #[arg(long, value_delimiter = ',', default_values = watch::DEFAULT_IGNORE_GLOBS)]
+        ignore: Vec<String>,
+
+        /// Only rotate after being idle this long (e.g. "5m"); reads the idle marker
+        /// file kept by swayidle unless --idle-command is given
+        #[arg(long)]
+        on_idle: Option<String>,
+
+        /// Command that prints idle time in milliseconds (e.g. xprintidle)
+        #[arg(long, requires = "on_idle")]
+        idle_command: Option<String>,
+
+        /// Don't repeat a wallpaper on a screen for this many rotations ("5") or
+        /// this long ("2h"); relaxed when nothing else is left
+        #[arg(long, value_parser = watch::Cooldown::parse)]
+        cooldown: Option<watch::Cooldown>,
     },
     /// Manage configuration profiles
     Profile {
         #[command(subcommand)]
         action: ProfileAction,
     },
+    /// Rate a wallpaper 1-5 stars (0 clears); `display.random_mode = "rated"` favors high ratings
+    Rate {
+        /// Path to the wallpaper
+        path: PathBuf,
+        /// Stars, 0-5
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=5))]
+        rating: u8,
+    },
     /// Manage wallpaper tags
     Tag {
         #[command(subcommand)]
@@ -80,12 +283,27 @@ enum Commands {
         /// Apply colors immediately (xrdb merge)
         #[arg(short, long)]
         apply: bool,
+        /// Render every template file in this directory
+        #[arg(long)]
+        templates: Option<PathBuf>,
+        /// Where rendered templates go (default: ~/.cache/wal)
+        #[arg(long, requires = "templates")]
+        templates_out: Option<PathBuf>,
+        /// Also print the wallpaper's palette, written as hex, rgb or hsl
+        #[arg(long, value_name = "FORMAT", value_parser = utils::ColorFormat::parse)]
+        color_format: Option<utils::ColorFormat>,
     },
     /// Manage intelligent wallpaper pairing
     Pair {
         #[command(subcommand)]
         action: PairAction,
     },
+    /// Show recently applied wallpaper arrangements, newest first
+    History {
+        /// Number of records to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
     /// Auto-tag wallpapers using CLIP AI model (requires --features clip)
     #[cfg(feature = "clip")]
     AutoTag {
@@ -101,15 +319,25 @@ enum Commands {
         #[arg(short = 'n', long, default_value = "5")]
         max_tags: usize,
 
-        /// Show detailed progress
-        #[arg(short, long)]
-        verbose: bool,
+        /// Number of images per CLIP inference batch
+        #[arg(short, long, default_value = "8")]
+        batch_size: usize,
     },
     /// Manage wallpaper collections (saved presets)
     Collection {
         #[command(subcommand)]
         action: CollectionAction,
     },
+    /// Show the `[schedule]` of collections applied by `watch`
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+    /// Manage the TUI thumbnail cache
+    Thumbnails {
+        #[command(subcommand)]
+        action: ThumbnailsAction,
+    },
     /// Find similar wallpapers based on color profile
     Similar {
         /// Path to wallpaper to find similar ones for
@@ -117,13 +345,65 @@ enum Commands {
         /// Maximum number of results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+        /// Skip this many results (for paging)
+        #[arg(long, default_value = "0")]
+        offset: usize,
+        /// Rank by CLIP embedding instead of color profile
+        #[arg(long)]
+        semantic: bool,
+        /// Also show each palette, written as hex, rgb or hsl
+        #[arg(long, value_name = "FORMAT", value_parser = utils::ColorFormat::parse)]
+        color_format: Option<utils::ColorFormat>,
+        /// Apply the best match (after --offset) instead of listing
+        #[arg(long)]
+        apply: bool,
+        /// With --apply, only change these outputs (comma-separated)
+        #[arg(short, long, value_delimiter = ',', requires = "apply")]
+        output: Vec<String>,
+    },
+    /// Override the resize mode for a single wallpaper
+    SetResize {
+        /// Path to wallpaper
+        path: PathBuf,
+        /// crop, fit, center, stretch, or "default" to use the global mode
+        mode: String,
+    },
+    /// Show who made an imported wallpaper and where it came from
+    Attribution {
+        /// Path to wallpaper
+        path: PathBuf,
+    },
+    /// Preview what `--resize crop` keeps of a wallpaper on a screen
+    CropPreview {
+        /// Path to wallpaper
+        path: PathBuf,
+        /// Screen to preview for (default: first detected screen)
+        #[arg(short, long)]
+        screen: Option<String>,
+        /// Where to write the preview PNG (default: <name>-crop-<screen>.png)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Write a copy of a wallpaper with its hues shifted toward an accent color
+    Recolor {
+        /// Path to wallpaper
+        path: PathBuf,
+        /// Target accent color, e.g. "#88c0d0"
+        #[arg(long)]
+        accent: String,
+        /// How far to turn toward the accent hue (0 = unchanged, 1 = all the way)
+        #[arg(long, default_value = "0.3")]
+        strength: f32,
+        /// Where to write the recolored image
+        #[arg(long)]
+        out: PathBuf,
     },
     /// Manage time-based wallpaper profiles
     TimeProfile {
         #[command(subcommand)]
         action: TimeProfileAction,
     },
-    /// Import wallpapers from web galleries (Unsplash, Wallhaven)
+    /// Import wallpapers from web galleries (Unsplash, Wallhaven, Reddit)
     Import {
         #[command(subcommand)]
         action: ImportAction,
@@ -148,10 +428,36 @@ enum TagAction {
         /// Tag to remove
         tag: String,
     },
+    /// Rename a tag on every wallpaper
+    Rename {
+        /// Current tag name (case-insensitive)
+        old: String,
+        /// New tag name
+        new: String,
+    },
+    /// Merge several tags into one on every wallpaper
+    Merge {
+        /// Tags to merge, followed by the tag to merge them into
+        #[arg(required = true, num_args = 2.., value_name = "TAG")]
+        tags: Vec<String>,
+    },
+    /// Tag every wallpaper with the name of the folder it is in
+    AutoFromPath {
+        /// Add every folder between the wallpaper directory and the file, not
+        /// just the parent
+        #[arg(long)]
+        all: bool,
+    },
     /// Show wallpapers with a specific tag
     Show {
         /// Tag to filter by
         tag: String,
+        /// Maximum number of wallpapers to print
+        #[arg(short, long)]
+        limit: Option<usize>,
+        /// Skip this many wallpapers (for paging)
+        #[arg(long, default_value = "0")]
+        offset: usize,
     },
 }
 
@@ -166,12 +472,57 @@ enum PairAction {
         /// Path to wallpaper
         path: PathBuf,
     },
+    /// Export pairing history and affinities to a JSON file
+    Export {
+        /// Destination file
+        file: PathBuf,
+    },
+    /// Import pairing history from a JSON file
+    Import {
+        /// File written by `pair export`
+        file: PathBuf,
+        /// Merge with existing history instead of replacing it
+        #[arg(long)]
+        merge: bool,
+        /// Rewrite a path prefix, e.g. "/home/old/wallpapers:/home/new/wallpapers"
+        #[arg(long)]
+        remap: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print where config.toml lives
+    Path,
+    /// Print the effective config, with defaults for missing keys and the
+    /// active profile applied
+    Show,
+    /// Open config.toml in $VISUAL or $EDITOR (creating a default one first if missing)
+    Edit,
+}
+
+#[derive(Subcommand)]
+enum ThumbnailsAction {
+    /// Clear the cache and generate every thumbnail at the configured size
+    Regenerate {
+        /// Only delete thumbnails whose wallpaper is gone or changed
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// List schedule slots and which one is active now
+    List,
 }
 
 #[derive(Subcommand)]
 enum CollectionAction {
     /// List all saved collections
     List,
+    /// List automatically captured multi-screen sets, newest first
+    Recent,
     /// Show details of a collection
     Show {
         /// Collection name
@@ -189,6 +540,9 @@ enum CollectionAction {
     Apply {
         /// Collection name
         name: String,
+        /// Rename saved outputs (comma-separated SAVED=OUTPUT, e.g. DP-1=eDP-1)
+        #[arg(long, visible_alias = "screen-map", value_delimiter = ',')]
+        map: Vec<String>,
     },
     /// Delete a collection
     Delete {
@@ -220,7 +574,8 @@ enum ProfileAction {
     Set {
         /// Profile name
         name: String,
-        /// Setting key (directory, match_mode, resize_mode, transition, recursive)
+        /// Setting key (directory, match_mode, resize_mode, transition, recursive,
+        /// pairing.enabled, pairing.style_mode, time_profiles.enabled)
         key: String,
         /// Setting value
         value: String,
@@ -243,6 +598,15 @@ enum TimeProfileAction {
     },
     /// Set a random wallpaper based on current time
     Apply,
+    /// Edit a period's settings (brightness_range, preferred_tags, brightness_weight, tag_weight)
+    Set {
+        /// Period: morning, afternoon, evening, night
+        period: String,
+        /// Setting key
+        key: String,
+        /// New value, e.g. "0.2,0.6" for brightness_range or "dark,space" for preferred_tags
+        value: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -254,6 +618,11 @@ enum ImportAction {
         /// Number of images to show
         #[arg(short, long, default_value = "10")]
         count: u32,
+        #[command(flatten)]
+        filter: ImageFilterArgs,
+        /// Download every result into the wallpaper directory
+        #[arg(long)]
+        download_all: bool,
     },
     /// Search and import from Wallhaven
     Wallhaven {
@@ -262,59 +631,250 @@ enum ImportAction {
         /// Number of images to show
         #[arg(short, long, default_value = "10")]
         count: u32,
+        #[command(flatten)]
+        filter: ImageFilterArgs,
+        /// Download every result into the wallpaper directory
+        #[arg(long)]
+        download_all: bool,
+    },
+    /// Top image posts of the week from a subreddit
+    Reddit {
+        /// Subreddit name (e.g. "wallpapers" or "r/EarthPorn")
+        subreddit: String,
+        /// Number of images to show
+        #[arg(short, long, default_value = "10")]
+        count: u32,
+        #[command(flatten)]
+        filter: ImageFilterArgs,
+        /// Download every result into the wallpaper directory
+        #[arg(long)]
+        download_all: bool,
     },
     /// Get featured/top wallpapers from Wallhaven
     Featured {
         /// Number of images to show
         #[arg(short, long, default_value = "10")]
         count: u32,
+        #[command(flatten)]
+        filter: ImageFilterArgs,
+        /// Download every result into the wallpaper directory
+        #[arg(long)]
+        download_all: bool,
+    },
+    /// List images in a local folder (e.g. a synced collection) to copy from
+    Local {
+        /// Folder to browse (searched recursively)
+        dir: PathBuf,
+        /// Number of images to show
+        #[arg(short, long, default_value = "10")]
+        count: u32,
+        #[command(flatten)]
+        filter: ImageFilterArgs,
+        /// Copy every listed image into the wallpaper directory
+        #[arg(long)]
+        download_all: bool,
     },
-    /// Download a specific image by URL or ID
+    /// Download a specific image by URL, ID or local path
     Download {
-        /// Image URL or Wallhaven ID (e.g., "w8x7y9")
+        /// Image URL (Wallhaven, i.redd.it), Wallhaven ID (e.g., "w8x7y9") or
+        /// path of a local image to copy
         url: String,
     },
 }
 
+/// Size filters shared by the import search commands
+#[derive(Args)]
+struct ImageFilterArgs {
+    /// Minimum image width in pixels
+    #[arg(long)]
+    min_width: Option<u32>,
+    /// Minimum image height in pixels
+    #[arg(long)]
+    min_height: Option<u32>,
+    /// Aspect ratio, e.g. 21:9 (matched within 5%)
+    #[arg(long, value_parser = webimport::ImageFilter::parse_ratio)]
+    ratio: Option<(u32, u32)>,
+}
+
+impl From<ImageFilterArgs> for webimport::ImageFilter {
+    fn from(args: ImageFilterArgs) -> Self {
+        Self {
+            min_width: args.min_width,
+            min_height: args.min_height,
+            ratio: args.ratio,
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    logging::init(logging::level(cli.verbose, cli.quiet));
+    if cli.no_save {
+        utils::disable_saves();
+    }
+    utils::set_rng_seed(cli.seed);
+
+    // Init runs before the config is loaded, since loading creates a default one
+    if let Some(Commands::Init {
+        defaults,
+        dir,
+        recursive,
+        force,
+    }) = cli.command
+    {
+        if defaults {
+            init::run_defaults(dir.or(cli.dir), recursive, force)?;
+        } else {
+            init::run_init().await?;
+        }
+        return Ok(());
+    }
+    // Doctor reports a broken config instead of letting load() replace it
+    if let Some(Commands::Doctor) = cli.command {
+        return doctor::run(cli.dir).await;
+    }
+    // Same for finding and fixing the file by hand
+    match &cli.command {
+        Some(Commands::Config {
+            action: ConfigAction::Path,
+        }) => {
+            println!("{}", app::Config::config_path().display());
+            return Ok(());
+        }
+        Some(Commands::Config {
+            action: ConfigAction::Edit,
+        }) => return cmd_config_edit(),
+        _ => {}
+    }
 
-    let config = app::Config::load()?;
+    let mut config = app::Config::load()?;
+    // --profile wins over the one chosen with `frostwall profile use`
+    let manager = profile::ProfileManager::load()?;
+    let selected = match &cli.profile {
+        Some(name) => match manager.get(name) {
+            Some(selected) => Some(selected.clone()),
+            None => anyhow::bail!(
+                "Profile '{}' not found. Available profiles: {:?}",
+                name,
+                manager.list()
+            ),
+        },
+        None => manager.active().cloned(),
+    };
+    if let Some(selected) = &selected {
+        selected.apply_to(&mut config);
+    }
+    swww::set_auto_start_daemon(config.display.auto_start_daemon && !cli.no_daemon);
+    wallpaper::set_prefer_native_resolution(config.display.prefer_native_resolution);
     let wallpaper_dir = cli.dir.unwrap_or_else(|| config.wallpaper_dir());
+    let out = if cli.json {
+        Output::Json
+    } else {
+        Output::Human
+    };
 
     match cli.command {
-        Some(Commands::Random) => {
-            cmd_random(&wallpaper_dir).await?;
+        Some(Commands::Random { output }) => {
+            cmd_random(&wallpaper_dir, &config, &output).await?;
+        }
+        Some(Commands::Next { output, paired }) => {
+            let paired = paired || config.pairing.paired_navigation;
+            cmd_step(&wallpaper_dir, &config, &output, true, paired).await?;
         }
-        Some(Commands::Next) => {
-            cmd_next(&wallpaper_dir).await?;
+        Some(Commands::Prev { output, paired }) => {
+            let paired = paired || config.pairing.paired_navigation;
+            cmd_step(&wallpaper_dir, &config, &output, false, paired).await?;
         }
-        Some(Commands::Prev) => {
-            cmd_prev(&wallpaper_dir).await?;
+        Some(Commands::Apply {
+            path,
+            output,
+            brightness,
+            contrast,
+        }) => {
+            let adjustment = adjust::Adjustment::new(brightness, contrast)?;
+            cmd_apply(&path, &wallpaper_dir, &config, &output, adjustment).await?;
         }
-        Some(Commands::Screens) => {
-            cmd_screens().await?;
+        Some(Commands::Surprise { output, skip }) => {
+            cmd_surprise(&wallpaper_dir, &config, &output, &skip).await?;
         }
-        Some(Commands::Scan) => {
-            cmd_scan(&wallpaper_dir).await?;
+        Some(Commands::Focus { output, blur, dim }) => {
+            cmd_focus(&config, &output, blur, dim).await?;
         }
-        Some(Commands::Init) => {
-            init::run_init().await?;
+        Some(Commands::Screens { watch, interval }) => {
+            if watch {
+                // clap only sees the conflict when --json comes after `screens`
+                if out == Output::Json {
+                    anyhow::bail!("screens --watch has no JSON output");
+                }
+                let interval = watch::parse_interval(&interval)
+                    .with_context(|| format!("Invalid --interval duration: {}", interval))?;
+                cmd_screens(&config, out).await?;
+                cmd_screens_watch(interval).await?;
+            } else {
+                cmd_screens(&config, out).await?;
+            }
+        }
+        Some(Commands::List { recent }) => {
+            cmd_list(&wallpaper_dir, recent.as_deref(), &config.scan)?;
         }
+        Some(Commands::Scan { exclude }) => {
+            let mut scan = config.scan.clone();
+            scan.exclude_dirs.extend(exclude);
+            cmd_scan(&wallpaper_dir, &scan, out).await?;
+        }
+        Some(Commands::Stats) => {
+            cmd_stats(&wallpaper_dir, &config.scan, out)?;
+        }
+        Some(Commands::Doctor) => unreachable!("doctor is handled before loading config"),
+        Some(Commands::Validate { fix }) => {
+            cmd_validate(fix, &config, out)?;
+        }
+        Some(Commands::Init { .. }) => unreachable!("init is handled before loading config"),
+        Some(Commands::Config { action }) => match action {
+            ConfigAction::Show => cmd_config_show(&config, out)?,
+            ConfigAction::Path | ConfigAction::Edit => {
+                unreachable!("config path/edit are handled before loading config")
+            }
+        },
         Some(Commands::Watch {
             interval,
+            jitter,
             shuffle,
+            mode,
             watch_dir,
+            watch_debounce,
+            ignore,
+            on_idle,
+            idle_command,
+            cooldown,
         }) => {
             let interval = watch::parse_interval(&interval)
                 .unwrap_or_else(|| std::time::Duration::from_secs(30 * 60));
+            let on_idle = on_idle
+                .map(|s| {
+                    watch::parse_interval(&s)
+                        .with_context(|| format!("Invalid --on-idle duration: {}", s))
+                })
+                .transpose()?;
+            if !(0.0..=1.0).contains(&jitter) {
+                anyhow::bail!("--jitter must be between 0.0 and 1.0, got {}", jitter);
+            }
             let watch_config = watch::WatchConfig {
                 interval,
+                jitter,
                 shuffle,
+                mode,
                 watch_dir,
+                wallpaper_dir,
+                profile: cli.profile,
+                debounce_ms: watch_debounce,
+                ignore_globs: ignore.into_iter().filter(|g| !g.is_empty()).collect(),
+                on_idle,
+                idle_command,
+                cooldown,
             };
-            watch::run_watch(watch_config).await?;
+            watch::run_watch(watch_config, config).await?;
         }
         Some(Commands::Profile { action }) => match action {
             ProfileAction::List => profile::cmd_profile_list()?,
@@ -325,48 +885,133 @@ async fn main() -> Result<()> {
                 profile::cmd_profile_set(&name, &key, &value)?
             }
         },
+        Some(Commands::Rate { path, rating }) => {
+            cmd_rate(&path, rating, &wallpaper_dir, &config.scan)?;
+        }
         Some(Commands::Tag { action }) => {
-            cmd_tag(action, &wallpaper_dir)?;
+            cmd_tag(action, &wallpaper_dir, &config.scan, out)?;
         }
-        Some(Commands::Pywal { path, apply }) => {
-            pywal::cmd_pywal(&path, apply)?;
+        Some(Commands::Pywal {
+            path,
+            apply,
+            templates,
+            templates_out,
+            color_format,
+        }) => {
+            pywal::cmd_pywal(
+                &path,
+                apply,
+                templates.as_deref(),
+                templates_out.as_deref(),
+                color_format,
+                &config.scan,
+            )?;
         }
         Some(Commands::Pair { action }) => {
-            cmd_pair(action, &wallpaper_dir)?;
+            cmd_pair(action, &wallpaper_dir, &config, out)?;
+        }
+        Some(Commands::History { limit }) => {
+            cmd_history(&config, limit, out)?;
         }
         #[cfg(feature = "clip")]
         Some(Commands::AutoTag {
             incremental,
             threshold,
             max_tags,
-            verbose,
+            batch_size,
         }) => {
-            cmd_auto_tag(&wallpaper_dir, incremental, threshold, max_tags, verbose).await?;
+            cmd_auto_tag(
+                &wallpaper_dir,
+                incremental,
+                threshold,
+                max_tags,
+                cli.verbose > 0,
+                batch_size,
+                &config.scan,
+            )
+            .await?;
         }
         Some(Commands::Collection { action }) => {
-            cmd_collection(action).await?;
+            cmd_collection(action, &config).await?;
+        }
+        Some(Commands::Schedule { action }) => match action {
+            ScheduleAction::List => schedule::cmd_schedule_list(&config.schedule)?,
+        },
+        Some(Commands::Thumbnails { action }) => match action {
+            ThumbnailsAction::Regenerate { prune } => {
+                cmd_thumbnails_regenerate(&wallpaper_dir, &config, prune)?
+            }
+        },
+        Some(Commands::Similar {
+            path,
+            limit,
+            offset,
+            semantic,
+            color_format,
+            apply,
+            output,
+        }) => {
+            if apply && out == Output::Json {
+                anyhow::bail!("similar --apply has no JSON output");
+            }
+            let page = Page {
+                offset,
+                limit: Some(limit),
+            };
+            cmd_similar(
+                &wallpaper_dir,
+                &path,
+                page,
+                semantic,
+                color_format,
+                apply.then_some(output.as_slice()),
+                &config,
+                out,
+            )
+            .await?;
         }
-        Some(Commands::Similar { path, limit }) => {
-            cmd_similar(&wallpaper_dir, &path, limit)?;
+        Some(Commands::SetResize { path, mode }) => {
+            cmd_set_resize(&wallpaper_dir, &path, &mode, &config.scan)?;
+        }
+        Some(Commands::Attribution { path }) => {
+            cmd_attribution(&path)?;
+        }
+        Some(Commands::CropPreview { path, screen, out }) => {
+            cmd_crop_preview(&path, screen.as_deref(), out.as_deref(), &config).await?;
+        }
+        Some(Commands::Recolor {
+            path,
+            accent,
+            strength,
+            out,
+        }) => {
+            cmd_recolor(&path, &accent, strength, &out, &config.scan)?;
         }
         Some(Commands::TimeProfile { action }) => {
-            cmd_time_profile(action, &wallpaper_dir).await?;
+            cmd_time_profile(action, &wallpaper_dir, &config).await?;
         }
         Some(Commands::Import { action }) => {
-            cmd_import(action, &wallpaper_dir)?;
+            // The blocking HTTP client (and its retry sleeps) must not run on
+            // an async worker; dropping it there panics
+            tokio::task::block_in_place(|| cmd_import(action, &wallpaper_dir, &config))?;
         }
         None => {
             // TUI mode
-            app::run_tui(wallpaper_dir).await?;
+            swww::require_installed()?;
+            app::run_tui(wallpaper_dir, config, selected).await?;
         }
     }
 
     Ok(())
 }
 
-async fn cmd_random(wallpaper_dir: &Path) -> Result<()> {
-    let screens = screen::detect_screens().await?;
-    let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir)?;
+async fn cmd_random(wallpaper_dir: &Path, config: &app::Config, outputs: &[String]) -> Result<()> {
+    swww::require_installed()?;
+    let screens = screen::select_outputs(
+        screen::detect_screens_ordered(&config.display.output_order).await?,
+        outputs,
+    )?;
+    let mut cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, &config.scan)?;
 
     if cache.wallpapers.is_empty() {
         eprintln!("No wallpapers found in: {}", wallpaper_dir.display());
@@ -374,19 +1019,225 @@ async fn cmd_random(wallpaper_dir: &Path) -> Result<()> {
         return Ok(());
     }
 
+    // With mirror_screens, one pick for the primary screen goes everywhere
+    let mirrored = if config.display.mirror_screens && !screens.is_empty() {
+        let primary = &screens[primary_screen_index(&screens, config)];
+        cache
+            .random_with_mode(primary, config.display.random_mode)
+            .map(|wp| wp.path.clone())
+    } else {
+        None
+    };
+
+    let mut current = swww::query_current();
     for screen in &screens {
-        if let Some(wp) = cache.random_for_screen(screen) {
-            swww::set_wallpaper(&screen.name, &wp.path, &swww::Transition::default())?;
+        let picked = match &mirrored {
+            Some(path) => cache.wallpapers.iter().find(|wp| &wp.path == path),
+            None => cache.random_with_mode(screen, config.display.random_mode),
+        };
+        if let Some(wp) = picked {
+            swww::apply_wallpaper(
+                &screen.name,
+                &wp.path,
+                &config.transition(),
+                wp.resize_mode(config.display.resize_mode),
+                &config.display.fill_color_for(&wp.colors),
+                config.scan.gif_mode,
+            )?;
+            current::update_or_warn(&config.display, &screen.name, &wp.path);
             println!("{}: {}", screen.name, wp.path.display());
+            let shown = wp.path.clone();
+            cache.mark_shown(&shown);
+            current.insert(screen.name.clone(), shown);
         }
     }
 
+    cache.save()?;
+    if current.len() > 1 {
+        if let Err(e) = collections::capture_recent(&config.collections, &current) {
+            eprintln!("Warning: Failed to save recent collection: {}", e);
+        }
+    }
     Ok(())
 }
 
-async fn cmd_next(wallpaper_dir: &Path) -> Result<()> {
-    let screens = screen::detect_screens().await?;
-    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir)?;
+async fn cmd_apply(
+    path: &Path,
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    outputs: &[String],
+    adjustment: adjust::Adjustment,
+) -> Result<()> {
+    if !utils::is_image_file(path) {
+        anyhow::bail!(
+            "{} is not a supported image (expected one of: {})",
+            path.display(),
+            utils::IMAGE_EXTENSIONS.join(", ")
+        );
+    }
+    let path = path
+        .canonicalize()
+        .with_context(|| format!("Image not found: {}", path.display()))?;
+    utils::oriented_dimensions(&path)
+        .with_context(|| format!("Failed to read image: {}", path.display()))?;
+    swww::require_installed()?;
+
+    let screens = screen::select_outputs(
+        screen::detect_screens_ordered(&config.display.output_order).await?,
+        outputs,
+    )?;
+
+    // swww gets the adjusted copy; everything else keeps tracking the original
+    let shown = if adjustment.is_identity() {
+        path.clone()
+    } else {
+        adjust::ensure_adjusted(&path, adjustment)?
+    };
+
+    // Only use the cache if it already covers this library; a one-shot apply
+    // should never trigger a full scan.
+    let mut cache =
+        wallpaper::WallpaperCache::load_cached()?.filter(|cache| cache.source_dir == wallpaper_dir);
+    let cached = cache
+        .as_ref()
+        .and_then(|cache| cache.wallpapers.iter().find(|wp| wp.path == path));
+    let resize_mode = cached
+        .map(|wp| wp.resize_mode(config.display.resize_mode))
+        .unwrap_or(config.display.resize_mode);
+    let fill_color = config
+        .display
+        .fill_color_for(cached.map_or(&[][..], |wp| &wp.colors));
+
+    let mut current = swww::query_current();
+    for screen in &screens {
+        swww::apply_wallpaper(
+            &screen.name,
+            &shown,
+            &config.transition(),
+            resize_mode,
+            &fill_color,
+            config.scan.gif_mode,
+        )?;
+        current::update_or_warn(&config.display, &screen.name, &shown);
+        println!("{}: {}", screen.name, path.display());
+        current.insert(screen.name.clone(), path.clone());
+    }
+
+    if let Some(cache) = cache.as_mut() {
+        // Every screen gets its position, so no short-circuiting `any`
+        let mut changed = false;
+        for screen in &screens {
+            changed |= cache.set_current_for_screen(screen, &path);
+        }
+        if changed {
+            cache.save()?;
+        }
+    }
+
+    if current.len() > 1 {
+        if let Err(e) = collections::capture_recent(&config.collections, &current) {
+            eprintln!("Warning: Failed to save recent collection: {}", e);
+        }
+        let mut history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+        history.record_pairing(current, true);
+    }
+
+    Ok(())
+}
+
+/// Swap each output to a blurred/dimmed copy of what it shows, or back to the
+/// original if it is already showing its focus variant.
+async fn cmd_focus(config: &app::Config, outputs: &[String], blur: f32, dim: f32) -> Result<()> {
+    effects::validate(blur, dim)?;
+    swww::require_installed()?;
+    let screens = screen::select_outputs(
+        screen::detect_screens_ordered(&config.display.output_order).await?,
+        outputs,
+    )?;
+    let mut state = effects::FocusState::load()?;
+    let current = swww::query_current();
+    let cache = wallpaper::WallpaperCache::load_cached()?;
+    let resize_mode = |path: &Path| {
+        cache
+            .as_ref()
+            .and_then(|cache| cache.wallpapers.iter().find(|wp| wp.path == path))
+            .map(|wp| wp.resize_mode(config.display.resize_mode))
+            .unwrap_or(config.display.resize_mode)
+    };
+    let fill_color = |path: &Path| {
+        let colors = cache
+            .as_ref()
+            .map_or(&[][..], |cache| cache.colors_for(path));
+        config.display.fill_color_for(colors)
+    };
+
+    for screen in &screens {
+        let shown = current.get(&screen.name);
+
+        if let Some(entry) = state.outputs.remove(&screen.name) {
+            // Only restore if nothing else was applied since focusing
+            if shown.is_none_or(|shown| *shown == entry.focused) {
+                swww::apply_wallpaper(
+                    &screen.name,
+                    &entry.original,
+                    &config.transition(),
+                    resize_mode(&entry.original),
+                    &fill_color(&entry.original),
+                    config.scan.gif_mode,
+                )?;
+                current::update_or_warn(&config.display, &screen.name, &entry.original);
+                println!("{}: restored {}", screen.name, entry.original.display());
+                continue;
+            }
+        }
+
+        // swww knows best; the current-wallpaper link covers a daemon restart
+        let Some(original) = shown
+            .cloned()
+            .or_else(|| current::read(&config.display, &screen.name))
+        else {
+            eprintln!(
+                "Warning: No current wallpaper known for {}, skipping",
+                screen.name
+            );
+            continue;
+        };
+        let focused = effects::ensure_focused(&original, blur, dim)?;
+        swww::apply_wallpaper(
+            &screen.name,
+            &focused,
+            &config.transition(),
+            resize_mode(&original),
+            &fill_color(&original),
+            config.scan.gif_mode,
+        )?;
+        current::update_or_warn(&config.display, &screen.name, &focused);
+        println!("{}: focus (blur {}, dim {})", screen.name, blur, dim);
+        state.outputs.insert(
+            screen.name.clone(),
+            effects::FocusEntry { original, focused },
+        );
+    }
+
+    state.save()
+}
+
+/// Move every selected screen one step through its sequence (`forward` = next).
+/// With `paired`, only the primary screen steps and the others get its best
+/// pairing matches; without pairing history the screens step independently.
+async fn cmd_step(
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    outputs: &[String],
+    forward: bool,
+    paired: bool,
+) -> Result<()> {
+    swww::require_installed()?;
+    let screens = screen::select_outputs(
+        screen::detect_screens_ordered(&config.display.output_order).await?,
+        outputs,
+    )?;
+    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir, &config.scan)?;
 
     if cache.wallpapers.is_empty() {
         eprintln!("No wallpapers found in: {}", wallpaper_dir.display());
@@ -394,57 +1245,554 @@ async fn cmd_next(wallpaper_dir: &Path) -> Result<()> {
         return Ok(());
     }
 
-    for screen in &screens {
-        if let Some(wp) = cache.next_for_screen(screen) {
-            swww::set_wallpaper(&screen.name, &wp.path, &swww::Transition::default())?;
-            println!("{}: {}", screen.name, wp.path.display());
-        }
+    let history = if paired && config.pairing.enabled && screens.len() > 1 {
+        Some(pairing::PairingHistory::load(
+            config.pairing.max_history_records,
+        )?)
+        .filter(|history| history.affinity_count() > 0)
+    } else {
+        None
+    };
+
+    let picks = match &history {
+        Some(history) => paired_step(&mut cache, &screens, config, history, forward),
+        None => screens
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, screen)| step_screen(&mut cache, screen, forward).map(|p| (idx, p)))
+            .collect(),
+    };
+
+    for (idx, path) in picks {
+        let screen = &screens[idx];
+        let Some(wp) = cache.wallpapers.iter().find(|wp| wp.path == path) else {
+            continue;
+        };
+        swww::apply_wallpaper(
+            &screen.name,
+            &wp.path,
+            &config.transition(),
+            wp.resize_mode(config.display.resize_mode),
+            &config.display.fill_color_for(&wp.colors),
+            config.scan.gif_mode,
+        )?;
+        current::update_or_warn(&config.display, &screen.name, &wp.path);
+        println!("{}: {}", screen.name, wp.path.display());
+        cache.mark_shown(&path);
     }
 
     cache.save()?;
     Ok(())
 }
 
-async fn cmd_prev(wallpaper_dir: &Path) -> Result<()> {
-    let screens = screen::detect_screens().await?;
-    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir)?;
+fn step_screen(
+    cache: &mut wallpaper::WallpaperCache,
+    screen: &screen::Screen,
+    forward: bool,
+) -> Option<PathBuf> {
+    let wp = if forward {
+        cache.next_for_screen(screen)
+    } else {
+        cache.prev_for_screen(screen)
+    };
+    wp.map(|wp| wp.path.clone())
+}
+
+/// `display.primary_output` if it is among `screens`, else the first screen
+fn primary_screen_index(screens: &[screen::Screen], config: &app::Config) -> usize {
+    config
+        .display
+        .primary_output
+        .as_deref()
+        .and_then(|name| screens.iter().position(|s| s.name == name))
+        .unwrap_or(0)
+}
+
+/// Best pairing match for `primary` on every other screen, never repeating a
+/// wallpaper unless `pairing.allow_duplicates`. Returns `(screen index, path)`
+/// picks starting with the primary, and the screens that got no match.
+fn pick_companions(
+    cache: &wallpaper::WallpaperCache,
+    screens: &[screen::Screen],
+    primary_idx: usize,
+    primary: &wallpaper::Wallpaper,
+    config: &app::Config,
+    history: &pairing::PairingHistory,
+) -> (Vec<(usize, PathBuf)>, Vec<usize>) {
+    let selected_tags = primary.all_tags();
+    let selected_style_tags = pairing::extract_style_tags(&selected_tags);
+    let mut picks = vec![(primary_idx, primary.path.clone())];
+    let mut unmatched = Vec::new();
+
+    for (idx, screen) in screens.iter().enumerate() {
+        if idx == primary_idx {
+            continue;
+        }
+        let matching: Vec<_> = cache
+            .wallpapers
+            .iter()
+            .filter(|wp| wp.matches_screen_with_mode(screen, config.display.match_mode))
+            .filter(|wp| {
+                config.pairing.allow_duplicates || picks.iter().all(|(_, path)| *path != wp.path)
+            })
+            .collect();
+        let context = pairing::MatchContext {
+            selected_wp: &primary.path,
+            target_screen: &screen.name,
+            selected_colors: &primary.colors,
+            selected_weights: &primary.color_weights,
+            selected_tags: &selected_tags,
+            selected_embedding: primary.embedding.as_deref(),
+            screen_context_weight: config.pairing.screen_context_weight,
+            visual_weight: config.pairing.visual_weight,
+            harmony_weight: config.pairing.harmony_weight,
+            harmony_mode: config.pairing.harmony_mode,
+            tag_weight: config.pairing.tag_weight,
+            semantic_weight: config.pairing.semantic_weight,
+            repetition_penalty_weight: config.pairing.repetition_penalty_weight,
+            style_mode: config.pairing.style_mode,
+            selected_style_tags: &selected_style_tags,
+        };
+        match history
+            .get_top_matches(&context, &matching, 1)
+            .into_iter()
+            .next()
+        {
+            Some((path, _)) => picks.push((idx, path)),
+            None => unmatched.push(idx),
+        }
+    }
+
+    (picks, unmatched)
+}
+
+/// Step the primary screen (`display.primary_output`, else the first one) and
+/// pick each other screen's best pairing match for it. Returns `(screen index, path)`.
+fn paired_step(
+    cache: &mut wallpaper::WallpaperCache,
+    screens: &[screen::Screen],
+    config: &app::Config,
+    history: &pairing::PairingHistory,
+    forward: bool,
+) -> Vec<(usize, PathBuf)> {
+    let primary_idx = primary_screen_index(screens, config);
+    let Some(primary_path) = step_screen(cache, &screens[primary_idx], forward) else {
+        return Vec::new();
+    };
+    let Some(primary) = cache.wallpapers.iter().find(|wp| wp.path == primary_path) else {
+        return Vec::new();
+    };
+    let (mut picks, unmatched) =
+        pick_companions(cache, screens, primary_idx, primary, config, history);
+
+    // Keep each screen's sequence position in step with what it now shows
+    for (idx, path) in &picks[1..] {
+        cache.set_current_for_screen(&screens[*idx], path);
+    }
+    for idx in unmatched {
+        if let Some(path) = step_screen(cache, &screens[idx], forward) {
+            picks.push((idx, path));
+        }
+    }
 
+    picks
+}
+
+/// Random wallpaper on the primary screen plus its most harmonious companions
+/// on the others; screens in `skip` are left alone.
+async fn cmd_surprise(
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    outputs: &[String],
+    skip: &[String],
+) -> Result<()> {
+    swww::require_installed()?;
+    let detected = screen::detect_screens_ordered(&config.display.output_order).await?;
+    for name in skip {
+        screen::check_output(&detected, name)?;
+    }
+    let mut screens = screen::select_outputs(detected, outputs)?;
+    screens.retain(|s| !skip.contains(&s.name));
+    if screens.is_empty() {
+        anyhow::bail!("No outputs left to change after --skip");
+    }
+
+    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir, &config.scan)?;
     if cache.wallpapers.is_empty() {
         eprintln!("No wallpapers found in: {}", wallpaper_dir.display());
         eprintln!("Run 'frostwall init' to configure your wallpaper directory.");
         return Ok(());
     }
+    let mut history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
 
-    for screen in &screens {
-        if let Some(wp) = cache.prev_for_screen(screen) {
-            swww::set_wallpaper(&screen.name, &wp.path, &swww::Transition::default())?;
-            println!("{}: {}", screen.name, wp.path.display());
+    let primary_idx = primary_screen_index(&screens, config);
+    let Some(primary) = cache
+        .random_with_mode(&screens[primary_idx], config.display.random_mode)
+        .cloned()
+    else {
+        return Ok(());
+    };
+    let mirror = config.display.mirror_screens;
+    let (mut picks, unmatched) = if mirror {
+        let everywhere = (0..screens.len()).map(|idx| (idx, primary.path.clone()));
+        (everywhere.collect(), Vec::new())
+    } else {
+        pick_companions(&cache, &screens, primary_idx, &primary, config, &history)
+    };
+    for idx in unmatched {
+        if let Some(wp) = cache.random_with_mode(&screens[idx], config.display.random_mode) {
+            picks.push((idx, wp.path.clone()));
         }
     }
 
+    let mut current = std::collections::HashMap::new();
+    for (idx, path) in picks {
+        let screen = &screens[idx];
+        let Some(wp) = cache.wallpapers.iter().find(|wp| wp.path == path) else {
+            continue;
+        };
+        swww::apply_wallpaper(
+            &screen.name,
+            &wp.path,
+            &config.transition(),
+            wp.resize_mode(config.display.resize_mode),
+            &config.display.fill_color_for(&wp.colors),
+            config.scan.gif_mode,
+        )?;
+        current::update_or_warn(&config.display, &screen.name, &wp.path);
+        if idx == primary_idx || mirror {
+            println!("{}: {}", screen.name, wp.path.display());
+        } else {
+            let (harmony, strength) = utils::detect_harmony_for(
+                &primary.colors,
+                &primary.color_weights,
+                &wp.colors,
+                &wp.color_weights,
+                config.pairing.harmony_mode,
+            );
+            println!(
+                "{}: {} ({}, {:.0}%)",
+                screen.name,
+                wp.path.display(),
+                harmony.name(),
+                strength * 100.0
+            );
+        }
+        cache.set_current_for_screen(screen, &path);
+        cache.mark_shown(&path);
+        current.insert(screen.name.clone(), path);
+    }
     cache.save()?;
+
+    if current.len() > 1 {
+        if let Err(e) = collections::capture_recent(&config.collections, &current) {
+            eprintln!("Warning: Failed to save recent collection: {}", e);
+        }
+        // One wallpaper everywhere says nothing about what pairs well
+        if !mirror {
+            history.record_pairing(current, false);
+        }
+    }
+
     Ok(())
 }
 
-async fn cmd_screens() -> Result<()> {
-    let screens = screen::detect_screens().await?;
+async fn cmd_screens(config: &app::Config, out: Output) -> Result<()> {
+    let screens = screen::detect_screens_ordered(&config.display.output_order).await?;
+    let mut current = swww::query_current();
 
-    for screen in &screens {
+    let infos: Vec<screen::ScreenInfo> = screens
+        .into_iter()
+        .map(|screen| screen::ScreenInfo {
+            wallpaper: current.remove(&screen.name),
+            screen,
+        })
+        .collect();
+
+    if out == Output::Json {
+        return Output::json(&infos);
+    }
+
+    for info in &infos {
+        let screen = &info.screen;
+        let refresh = screen
+            .refresh
+            .map(|hz| format!(" @ {:.2}Hz", hz))
+            .unwrap_or_default();
+        let position = screen
+            .position
+            .map(|(x, y)| format!(", pos {},{}", x, y))
+            .unwrap_or_default();
         println!(
-            "{}: {}x{} ({:?}) - {:?}",
-            screen.name, screen.width, screen.height, screen.orientation, screen.aspect_category
+            "{}: {}x{}{} (scale {}{}) - {:?} / {:?}",
+            screen.name,
+            screen.width,
+            screen.height,
+            refresh,
+            screen.scale,
+            position,
+            screen.orientation,
+            screen.aspect_category
         );
+        match &info.wallpaper {
+            Some(path) => println!("  wallpaper: {}", path.display()),
+            None => println!("  wallpaper: (unknown)"),
+        }
+    }
+
+    Ok(())
+}
+
+/// Poll the connected outputs and print each change until interrupted
+async fn cmd_screens_watch(interval: std::time::Duration) -> Result<()> {
+    let interval = interval.max(std::time::Duration::from_millis(500));
+    let mut known = screen::detect_screens().await?;
+    let mut failing = false;
+    println!("Watching for output changes (Ctrl+C to stop)...");
+
+    loop {
+        tokio::time::sleep(interval).await;
+        let screens = match screen::detect_screens().await {
+            Ok(screens) => screens,
+            Err(e) => {
+                // Detection fails briefly while a compositor restarts; say so once
+                if !failing {
+                    eprintln!("Warning: Screen detection failed: {:#}", e);
+                    failing = true;
+                }
+                continue;
+            }
+        };
+        failing = false;
+
+        let changes = screen::diff_screens(&known, &screens);
+        if changes.is_empty() {
+            continue;
+        }
+        let now = chrono::Local::now().format("%H:%M:%S");
+        for change in &changes {
+            println!("[{}] {}", now, change);
+        }
+        known = screens;
+    }
+}
+
+fn cmd_list(
+    wallpaper_dir: &Path,
+    recent: Option<&str>,
+    scan: &wallpaper::ScanConfig,
+) -> Result<()> {
+    let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, scan)?;
+
+    let Some(window) = recent else {
+        let mut paths: Vec<_> = cache.wallpapers.iter().map(|wp| &wp.path).collect();
+        paths.sort();
+        for path in &paths {
+            println!("{}", path.display());
+        }
+        println!("{} wallpaper(s)", paths.len());
+        return Ok(());
+    };
+
+    let Some(duration) = watch::parse_interval(window) else {
+        anyhow::bail!("Invalid duration '{}'. Use e.g. 7d, 12h, 30m", window);
+    };
+    let recent = cache.recent(wallpaper::recent_cutoff(duration));
+    for wp in &recent {
+        let modified = chrono::DateTime::from_timestamp(wp.modified_at as i64, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "?".to_string());
+        println!("{}  {}", modified, wp.path.display());
+    }
+    println!(
+        "{} of {} wallpaper(s) added in the last {}",
+        recent.len(),
+        cache.wallpapers.len(),
+        window
+    );
+    Ok(())
+}
+
+async fn cmd_scan(wallpaper_dir: &Path, scan: &wallpaper::ScanConfig, out: Output) -> Result<()> {
+    out.note(format!("Scanning {}...", wallpaper_dir.display()));
+    let cache = wallpaper::WallpaperCache::scan(wallpaper_dir, scan)?;
+    cache.save()?;
+
+    print_cache_stats(&cache.stats(), out)
+}
+
+/// `config show`: the config commands run with, as TOML (or JSON)
+fn cmd_config_show(config: &app::Config, out: Output) -> Result<()> {
+    if out == Output::Json {
+        return Output::json(config);
+    }
+    print!("{}", toml::to_string_pretty(config)?);
+    Ok(())
+}
+
+/// `config edit`: open config.toml in $VISUAL (or $EDITOR, or vi), then check
+/// that it still parses
+fn cmd_config_edit() -> Result<()> {
+    let path = app::Config::config_path();
+    if !path.exists() {
+        if !utils::saves_enabled() {
+            anyhow::bail!(
+                "{} doesn't exist and --no-save keeps the default from being written",
+                path.display()
+            );
+        }
+        app::Config::default().save()?;
+        println!("✓ Created default config at {}", path.display());
+    }
+
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    // Allow editors with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{}' exited with {}", editor, status);
     }
 
+    // Loading a broken file falls back to (and saves) the defaults, so say so now
+    let data = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if let Err(e) = toml::from_str::<app::Config>(&data) {
+        eprintln!("Warning: {} no longer parses: {}", path.display(), e);
+        eprintln!(
+            "Run 'frostwall config edit' again to fix it; other commands would replace it with defaults."
+        );
+    }
     Ok(())
 }
 
-async fn cmd_scan(wallpaper_dir: &Path) -> Result<()> {
-    println!("Scanning {}...", wallpaper_dir.display());
-    let cache = wallpaper::WallpaperCache::scan(wallpaper_dir)?;
+fn cmd_stats(wallpaper_dir: &Path, scan: &wallpaper::ScanConfig, out: Output) -> Result<()> {
+    let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, scan)?;
+    print_cache_stats(&cache.stats(), out)
+}
+
+/// How many paths `cmd_validate` lists per problem before summarizing
+const VALIDATE_LIST_LIMIT: usize = 10;
+
+fn cmd_validate(fix: bool, config: &app::Config, out: Output) -> Result<()> {
+    let Some(mut cache) = wallpaper::WallpaperCache::load_cached()? else {
+        anyhow::bail!("No usable wallpaper cache. Run 'frostwall scan' first.");
+    };
+    let mut history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+
+    let report = cache.check();
+    let orphans = history.orphaned_affinities(|p| p.exists()).len();
+
+    if out == Output::Json && !fix {
+        return Output::json(&serde_json::json!({
+            "wallpapers": cache.wallpapers.len(),
+            "report": report,
+            "orphaned_affinities": orphans,
+        }));
+    }
+
+    println!(
+        "Checked {} cached wallpapers in {}",
+        cache.wallpapers.len(),
+        cache.source_dir.display()
+    );
+
+    let print_paths = |label: &str, paths: Vec<&Path>| {
+        if paths.is_empty() {
+            return;
+        }
+        println!("{} ({}):", label, paths.len());
+        for path in paths.iter().take(VALIDATE_LIST_LIMIT) {
+            println!("  {}", path.display());
+        }
+        if paths.len() > VALIDATE_LIST_LIMIT {
+            println!("  ... and {} more", paths.len() - VALIDATE_LIST_LIMIT);
+        }
+    };
+    print_paths(
+        "Missing files",
+        report.missing.iter().map(PathBuf::as_path).collect(),
+    );
+    print_paths(
+        "No colors",
+        report.no_colors.iter().map(PathBuf::as_path).collect(),
+    );
+    print_paths(
+        "No color weights",
+        report.no_weights.iter().map(PathBuf::as_path).collect(),
+    );
+    if !report.wrong_dimensions.is_empty() {
+        println!("Wrong dimensions ({}):", report.wrong_dimensions.len());
+        for m in report.wrong_dimensions.iter().take(VALIDATE_LIST_LIMIT) {
+            println!(
+                "  {} (cached {}x{}, actual {}x{})",
+                m.path.display(),
+                m.cached.0,
+                m.cached.1,
+                m.actual.0,
+                m.actual.1
+            );
+        }
+        if report.wrong_dimensions.len() > VALIDATE_LIST_LIMIT {
+            println!(
+                "  ... and {} more",
+                report.wrong_dimensions.len() - VALIDATE_LIST_LIMIT
+            );
+        }
+    }
+    if orphans > 0 {
+        println!(
+            "Orphaned affinity pairs: {} (reference deleted files)",
+            orphans
+        );
+    }
+
+    if report.is_clean() && orphans == 0 {
+        println!("✓ No problems found");
+        return Ok(());
+    }
+    if !fix {
+        println!("Run 'frostwall validate --fix' to repair.");
+        return Ok(());
+    }
+
+    let summary = cache.repair(&report, &config.scan);
     cache.save()?;
+    let pruned = history.prune_orphaned_affinities(|p| p.exists());
+    if pruned > 0 {
+        history.save()?;
+    }
+
+    println!(
+        "✓ Removed {} dead entries, fixed {} dimensions, re-extracted colors for {}, pruned {} affinity pairs",
+        summary.removed, summary.resized, summary.recolored, pruned
+    );
+    if summary.failed > 0 {
+        eprintln!(
+            "Warning: {} wallpapers could not be decoded for colors",
+            summary.failed
+        );
+    }
+    Ok(())
+}
+
+fn print_cache_stats(stats: &wallpaper::CacheStats, out: Output) -> Result<()> {
+    if out == Output::Json {
+        return Output::json(stats);
+    }
 
-    let stats = cache.stats();
     println!("Found {} wallpapers:", stats.total);
     println!("  Ultrawide: {}", stats.ultrawide);
     println!("  Landscape: {}", stats.landscape);
@@ -454,12 +1802,23 @@ async fn cmd_scan(wallpaper_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-fn cmd_pair(action: PairAction, wallpaper_dir: &Path) -> Result<()> {
-    let config = app::Config::load()?;
-
+fn cmd_pair(
+    action: PairAction,
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    out: Output,
+) -> Result<()> {
     match action {
         PairAction::Stats => {
             let history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+            if out == Output::Json {
+                return Output::json(&serde_json::json!({
+                    "records": history.record_count(),
+                    "affinity_pairs": history.affinity_count(),
+                    "pairing_enabled": config.pairing.enabled,
+                    "auto_apply": config.pairing.auto_apply,
+                }));
+            }
             println!("Pairing Statistics");
             println!("==================");
             println!("  Records: {}", history.record_count());
@@ -487,9 +1846,44 @@ fn cmd_pair(action: PairAction, wallpaper_dir: &Path) -> Result<()> {
             history.save()?;
             println!("✓ Pairing history cleared");
         }
+        PairAction::Export { file } => {
+            let history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+            history.export(&file)?;
+            println!(
+                "✓ Exported {} records and {} affinity pairs to {}",
+                history.record_count(),
+                history.affinity_count(),
+                file.display()
+            );
+        }
+        PairAction::Import { file, merge, remap } => {
+            let mut data = pairing::PairingHistory::read_export(&file)?;
+
+            if let Some(remap) = remap {
+                let Some((from, to)) = remap.split_once(':') else {
+                    anyhow::bail!("Invalid --remap '{}', expected old:new", remap);
+                };
+                data.remap_prefix(Path::new(from), Path::new(to));
+            }
+
+            let mut history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+            if merge {
+                history.merge_data(data);
+            } else {
+                history.replace_data(data);
+            }
+            history.save()?;
+
+            println!(
+                "✓ {} pairing history: {} records, {} affinity pairs",
+                if merge { "Merged" } else { "Imported" },
+                history.record_count(),
+                history.affinity_count()
+            );
+        }
         PairAction::Suggest { path } => {
             let history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
-            let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir)?;
+            let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, &config.scan)?;
 
             // Find wallpapers with affinity to the given path
             let mut suggestions: Vec<_> = cache
@@ -521,17 +1915,53 @@ fn cmd_pair(action: PairAction, wallpaper_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-async fn cmd_collection(action: CollectionAction) -> Result<()> {
+fn cmd_history(config: &app::Config, limit: usize, out: Output) -> Result<()> {
+    let history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
+    let records = history.recent_records(limit);
+    if out == Output::Json {
+        return Output::json(&records);
+    }
+
+    if records.is_empty() {
+        println!("No pairing history yet.");
+        return Ok(());
+    }
+
+    for (i, record) in records.iter().enumerate() {
+        let kept = match record.duration {
+            Some(secs) => format!("kept {}", utils::format_duration(secs)),
+            None if i == 0 => "current".to_string(),
+            None => "kept ?".to_string(),
+        };
+        println!(
+            "{}  {}  {}",
+            utils::format_timestamp(record.timestamp),
+            if record.manual { "manual" } else { "auto  " },
+            kept
+        );
+        let mut screens: Vec<_> = record.wallpapers.iter().collect();
+        screens.sort();
+        for (screen, path) in screens {
+            println!("  {} -> {}", screen, path.display());
+        }
+    }
+
+    Ok(())
+}
+
+async fn cmd_collection(action: CollectionAction, config: &app::Config) -> Result<()> {
     match action {
         CollectionAction::List => {
             collections::cmd_collection_list()?;
         }
+        CollectionAction::Recent => {
+            collections::cmd_collection_recent()?;
+        }
         CollectionAction::Show { name } => {
             collections::cmd_collection_show(&name)?;
         }
         CollectionAction::Save { name, description } => {
             // Get the most recent pairing from history
-            let config = app::Config::load()?;
             let history = pairing::PairingHistory::load(config.pairing.max_history_records)?;
 
             // Find the most recent record with multiple screens
@@ -559,50 +1989,162 @@ async fn cmd_collection(action: CollectionAction) -> Result<()> {
                 println!("No pairing history found. Apply wallpapers to screens first.");
             }
         }
-        CollectionAction::Apply { name } => {
+        CollectionAction::Apply { name, map } => {
             let store = collections::CollectionStore::load()?;
+            let map = collections::parse_screen_map(&map)?;
+
+            if let Some(collection) = store.resolve(&name) {
+                swww::require_installed()?;
+                // If detection fails we can't tell, so let swww try every output
+                let connected: Option<Vec<String>> =
+                    screen::detect_screens_ordered(&config.display.output_order)
+                        .await
+                        .ok()
+                        .map(|screens| screens.into_iter().map(|s| s.name).collect());
+                collections::apply(collection, &map, connected.as_deref(), config);
+            } else {
+                println!("Collection '{}' not found", name);
+            }
+        }
+        CollectionAction::Delete { name } => {
+            collections::cmd_collection_delete(&name)?;
+        }
+    }
 
-            if let Some(collection) = store.get(&name) {
-                let config = app::Config::load()?;
-                let transition = config.transition();
+    Ok(())
+}
+
+fn cmd_thumbnails_regenerate(
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    prune: bool,
+) -> Result<()> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let cache = wallpaper::WallpaperCache::load_or_scan_recursive(
+        wallpaper_dir,
+        config.wallpaper.recursive,
+        &config.scan,
+    )?;
+    let thumbs = &config.thumbnails;
+    let disk_cache = thumbnail::ThumbnailCache::new()
+        .with_size(thumbs.width, thumbs.height, thumbs.quality)
+        .with_max_decode_dimension(config.scan.max_decode_dimension);
+
+    if prune {
+        let removed = disk_cache.prune(cache.wallpapers.iter().map(|wp| wp.path.as_path()))?;
+        println!(
+            "✓ Removed {} orphaned thumbnail(s) from {}",
+            removed,
+            disk_cache.cache_dir().display()
+        );
+        return Ok(());
+    }
 
-                for (screen_name, wp_path) in &collection.wallpapers {
-                    if let Err(e) = swww::set_wallpaper_with_resize(
-                        screen_name,
-                        wp_path,
-                        &transition,
-                        config.display.resize_mode,
-                        &config.display.fill_color,
-                    ) {
-                        eprintln!(
-                            "Warning: Failed to set {} on {}: {}",
-                            wp_path.display(),
-                            screen_name,
-                            e
-                        );
-                    } else {
-                        println!("✓ {}: {}", screen_name, wp_path.display());
+    disk_cache.clear()?;
+    let total = cache.wallpapers.len();
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let failed = AtomicUsize::new(0);
+    let inline = logging::inline_progress();
+
+    std::thread::scope(|scope| {
+        for _ in 0..thumbs.worker_count().min(total.max(1)) {
+            scope.spawn(|| {
+                while let Some(wp) = cache.wallpapers.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if let Err(e) = disk_cache.generate(&wp.path) {
+                        if inline {
+                            eprintln!();
+                        }
+                        tracing::warn!("{:#}", e);
+                        failed.fetch_add(1, Ordering::Relaxed);
+                    }
+                    let n = done.fetch_add(1, Ordering::Relaxed) + 1;
+                    if inline {
+                        eprint!("\rGenerating thumbnails {}", progress_bar(n, total));
                     }
                 }
-                println!("Applied collection '{}'", name);
-            } else {
-                println!("Collection '{}' not found", name);
-            }
-        }
-        CollectionAction::Delete { name } => {
-            collections::cmd_collection_delete(&name)?;
+            });
         }
+    });
+    if inline && total > 0 {
+        eprintln!();
     }
 
+    let failed = failed.into_inner();
+    println!(
+        "✓ Regenerated {} thumbnail(s) at {}x{} in {}",
+        total - failed,
+        thumbs.width,
+        thumbs.height,
+        disk_cache.cache_dir().display()
+    );
+    if failed > 0 {
+        println!("  {} wallpaper(s) could not be read", failed);
+    }
+    Ok(())
+}
+
+/// `[#########-----------] 42/100`
+fn progress_bar(done: usize, total: usize) -> String {
+    const WIDTH: usize = 30;
+    let filled = (done * WIDTH).checked_div(total).unwrap_or(WIDTH);
+    format!(
+        "[{}{}] {}/{}",
+        "#".repeat(filled),
+        "-".repeat(WIDTH - filled),
+        done,
+        total
+    )
+}
+
+fn cmd_rate(
+    path: &Path,
+    rating: u8,
+    wallpaper_dir: &Path,
+    scan: &wallpaper::ScanConfig,
+) -> Result<()> {
+    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir, scan)?;
+
+    // The cache keeps paths as scanned; fall back to the resolved path for relative input
+    let found = cache.set_rating(path, rating)
+        || path
+            .canonicalize()
+            .is_ok_and(|resolved| cache.set_rating(&resolved, rating));
+    if !found {
+        println!("Wallpaper not found: {}", path.display());
+        return Ok(());
+    }
+
+    cache.save()?;
+    println!(
+        "✓ Rated {}: {}",
+        path.display(),
+        wallpaper::rating_stars(rating)
+    );
     Ok(())
 }
 
-fn cmd_tag(action: TagAction, wallpaper_dir: &Path) -> Result<()> {
-    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir)?;
+fn cmd_tag(
+    action: TagAction,
+    wallpaper_dir: &Path,
+    scan: &wallpaper::ScanConfig,
+    out: Output,
+) -> Result<()> {
+    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir, scan)?;
 
     match action {
         TagAction::List => {
             let tags = cache.all_tags();
+            if out == Output::Json {
+                let counts: Vec<_> = tags
+                    .iter()
+                    .map(
+                        |tag| serde_json::json!({ "tag": tag, "count": cache.with_tag(tag).len() }),
+                    )
+                    .collect();
+                return Output::json(&counts);
+            }
             if tags.is_empty() {
                 println!("No tags defined.");
                 println!("Add tags with: frostwall tag add <path> <tag>");
@@ -630,15 +2172,34 @@ fn cmd_tag(action: TagAction, wallpaper_dir: &Path) -> Result<()> {
                 println!("Wallpaper not found: {}", path.display());
             }
         }
-        TagAction::Show { tag } => {
+        TagAction::Rename { old, new } => {
+            merge_tags(&mut cache, &[old], &new)?;
+        }
+        TagAction::Merge { mut tags } => {
+            // num_args = 2.. guarantees a target and at least one source
+            let into = tags.pop().unwrap_or_default();
+            merge_tags(&mut cache, &tags, &into)?;
+        }
+        TagAction::AutoFromPath { all } => {
+            let added = cache.merge_dir_tags(all);
+            if added > 0 {
+                cache.save()?;
+            }
+            println!("✓ Added {} tag(s) from folder names", added);
+        }
+        TagAction::Show { tag, limit, offset } => {
             let wallpapers = cache.with_tag(&tag);
+            let page = Page { offset, limit };
             if wallpapers.is_empty() {
                 println!("No wallpapers with tag '{}'", tag);
             } else {
                 println!("Wallpapers with tag '{}':", tag);
-                for wp in wallpapers {
+                for wp in page.slice(&wallpapers) {
                     println!("  {}", wp.path.display());
                 }
+                if let Some(footer) = page.footer(wallpapers.len()) {
+                    println!("{}", footer);
+                }
             }
         }
     }
@@ -646,39 +2207,299 @@ fn cmd_tag(action: TagAction, wallpaper_dir: &Path) -> Result<()> {
     Ok(())
 }
 
-fn cmd_similar(wallpaper_dir: &Path, target_path: &Path, limit: usize) -> Result<()> {
-    let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir)?;
+/// Shared by `tag rename` and `tag merge`; saves the cache once
+fn merge_tags(cache: &mut wallpaper::WallpaperCache, from: &[String], into: &str) -> Result<()> {
+    if into.trim().is_empty() {
+        anyhow::bail!("Target tag must not be empty");
+    }
+    let changed = cache.merge_tags(from, into);
+    if changed > 0 {
+        cache.save()?;
+    }
+    println!(
+        "✓ {} → '{}': {} wallpaper(s) updated",
+        from.join(", "),
+        into.trim().to_lowercase(),
+        changed
+    );
+    Ok(())
+}
+
+/// A window into a result list, from `--limit`/`--offset`
+#[derive(Debug, Clone, Copy)]
+struct Page {
+    offset: usize,
+    /// `None` shows everything after `offset`
+    limit: Option<usize>,
+}
+
+impl Page {
+    /// Index range this page covers in a list of `total` items
+    fn range(&self, total: usize) -> std::ops::Range<usize> {
+        let start = self.offset.min(total);
+        let end = match self.limit {
+            Some(limit) => start.saturating_add(limit).min(total),
+            None => total,
+        };
+        start..end
+    }
+
+    fn slice<'a, T>(&self, items: &'a [T]) -> &'a [T] {
+        &items[self.range(items.len())]
+    }
+
+    /// "showing 11–20 of 143", or `None` when the page already covers everything
+    fn footer(&self, total: usize) -> Option<String> {
+        let range = self.range(total);
+        if range.len() == total {
+            return None;
+        }
+        if range.is_empty() {
+            return Some(format!(
+                "showing none of {} (offset {})",
+                total, self.offset
+            ));
+        }
+        Some(format!(
+            "showing {}–{} of {}",
+            range.start + 1,
+            range.end,
+            total
+        ))
+    }
+}
+
+/// One `similar` result, for `--json`
+#[derive(Serialize)]
+struct SimilarMatch<'a> {
+    path: &'a Path,
+    score: f32,
+    colors: Vec<String>,
+}
+
+/// Print `similar` results as text or JSON
+fn print_similar(
+    cache: &wallpaper::WallpaperCache,
+    target: &Path,
+    method: &str,
+    similar: &[(f32, usize)],
+    page: Page,
+    color_format: Option<utils::ColorFormat>,
+    out: Output,
+) -> Result<()> {
+    let format = color_format.unwrap_or_default();
+    let matches: Vec<SimilarMatch> = page
+        .slice(similar)
+        .iter()
+        .map(|&(score, idx)| SimilarMatch {
+            path: &cache.wallpapers[idx].path,
+            score,
+            colors: format.format_all(&cache.wallpapers[idx].colors),
+        })
+        .collect();
+    let target_colors = cache
+        .wallpapers
+        .iter()
+        .find(|wp| wp.path == target)
+        .map(|wp| format.format_all(&wp.colors))
+        .unwrap_or_default();
+
+    if out == Output::Json {
+        return Output::json(&serde_json::json!({
+            "target": target,
+            "target_colors": target_colors,
+            "method": method,
+            "total": similar.len(),
+            "offset": page.offset,
+            "results": matches,
+        }));
+    }
+
+    if similar.is_empty() {
+        println!("No similar wallpapers found.");
+        return Ok(());
+    }
+    let label = if method == "clip" {
+        "CLIP embedding"
+    } else {
+        "color profile"
+    };
+    println!("Similar wallpapers (by {}):", label);
+    if color_format.is_some() && !target_colors.is_empty() {
+        println!("  Target palette: {}", target_colors.join(" "));
+    }
+    for m in &matches {
+        let filename = m.path.file_name().and_then(|n| n.to_str()).unwrap_or("?");
+        if color_format.is_some() && !m.colors.is_empty() {
+            println!(
+                "  {:.0}% - {}  {}",
+                m.score * 100.0,
+                filename,
+                m.colors.join(" ")
+            );
+        } else {
+            println!("  {:.0}% - {}", m.score * 100.0, filename);
+        }
+    }
+    if let Some(footer) = page.footer(similar.len()) {
+        println!("{}", footer);
+    }
+    Ok(())
+}
+
+/// `similar --apply`: set the first match of `page` on `outputs` (all if empty)
+async fn apply_top_similar(
+    cache: &wallpaper::WallpaperCache,
+    similar: &[(f32, usize)],
+    page: Page,
+    wallpaper_dir: &Path,
+    config: &app::Config,
+    outputs: &[String],
+) -> Result<()> {
+    let Some(&(score, idx)) = page.slice(similar).first() else {
+        anyhow::bail!("No similar wallpapers found");
+    };
+    let path = cache.wallpapers[idx].path.clone();
+    println!("Best match ({:.0}%):", score * 100.0);
+    cmd_apply(
+        &path,
+        wallpaper_dir,
+        config,
+        outputs,
+        adjust::Adjustment::default(),
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn cmd_similar(
+    wallpaper_dir: &Path,
+    target_path: &Path,
+    page: Page,
+    semantic: bool,
+    color_format: Option<utils::ColorFormat>,
+    apply: Option<&[String]>,
+    config: &app::Config,
+    out: Output,
+) -> Result<()> {
+    #[cfg_attr(not(feature = "clip"), allow(unused_mut))]
+    let mut cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, &config.scan)?;
 
     // Find the target wallpaper
-    let target = cache
+    let target_idx = cache
         .wallpapers
         .iter()
-        .find(|wp| wp.path == target_path)
+        .position(|wp| wp.path == target_path)
         .or_else(|| {
             // Try matching by filename
             let target_name = target_path.file_name();
             cache
                 .wallpapers
                 .iter()
-                .find(|wp| wp.path.file_name() == target_name)
+                .position(|wp| wp.path.file_name() == target_name)
         });
 
-    let target = match target {
-        Some(t) => t,
+    let target_idx = match target_idx {
+        Some(i) => i,
         None => {
+            if out == Output::Json {
+                anyhow::bail!("Wallpaper not found in cache: {}", target_path.display());
+            }
             println!("Wallpaper not found in cache: {}", target_path.display());
             println!("Run 'frostwall scan' first to index wallpapers.");
             return Ok(());
         }
     };
 
+    if semantic {
+        #[cfg(feature = "clip")]
+        if cache.wallpapers[target_idx].embedding.is_none() {
+            let path = cache.wallpapers[target_idx].path.clone();
+            out.note(format!(
+                "Computing CLIP embedding for {}...",
+                path.display()
+            ));
+            let mut tagger = clip::ClipTagger::new().await?;
+            let analysis = tagger.analyze_image(&path, config.clip.threshold)?;
+            cache.wallpapers[target_idx].set_embedding(analysis.embedding);
+            if config.clip.cache_embeddings {
+                cache.save()?;
+            }
+        }
+
+        let target = &cache.wallpapers[target_idx];
+        match &target.embedding {
+            Some(target_embedding) => {
+                let candidates: Vec<(usize, &[f32])> = cache
+                    .wallpapers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, wp)| wp.path != target.path)
+                    .filter_map(|(i, wp)| wp.embedding.as_deref().map(|e| (i, e)))
+                    .collect();
+
+                if candidates.is_empty() {
+                    out.note("No other wallpapers have CLIP embeddings yet.");
+                    out.note("Run 'frostwall auto-tag' to compute them; using color similarity.");
+                    out.note("");
+                } else {
+                    out.note(format!(
+                        "Finding similar wallpapers to: {}",
+                        target.path.display()
+                    ));
+                    out.note("");
+
+                    // Rank everything so paging can report the total
+                    let similar =
+                        utils::find_similar_by_embedding(target_embedding, &candidates, usize::MAX);
+                    if let Some(outputs) = apply {
+                        return apply_top_similar(
+                            &cache,
+                            &similar,
+                            page,
+                            wallpaper_dir,
+                            config,
+                            outputs,
+                        )
+                        .await;
+                    }
+                    return print_similar(
+                        &cache,
+                        &target.path,
+                        "clip",
+                        &similar,
+                        page,
+                        color_format,
+                        out,
+                    );
+                }
+            }
+            None => {
+                out.note(
+                    "Semantic search needs CLIP (build with --features clip); using color similarity.",
+                );
+                out.note("");
+            }
+        }
+    }
+
+    let target = &cache.wallpapers[target_idx];
+
     if target.colors.is_empty() {
+        if out == Output::Json {
+            anyhow::bail!(
+                "No color data for this wallpaper. Run 'frostwall scan' to extract colors."
+            );
+        }
         println!("No color data for this wallpaper. Run 'frostwall scan' to extract colors.");
         return Ok(());
     }
 
-    println!("Finding similar wallpapers to: {}", target.path.display());
-    println!();
+    out.note(format!(
+        "Finding similar wallpapers to: {}",
+        target.path.display()
+    ));
+    out.note("");
 
     // Build list of (index, colors) excluding target
     let wallpaper_colors: Vec<(usize, &[String])> = cache
@@ -689,20 +2510,20 @@ fn cmd_similar(wallpaper_dir: &Path, target_path: &Path, limit: usize) -> Result
         .map(|(i, wp)| (i, wp.colors.as_slice()))
         .collect();
 
-    let similar = utils::find_similar_wallpapers(&target.colors, &wallpaper_colors, limit);
-
-    if similar.is_empty() {
-        println!("No similar wallpapers found.");
-    } else {
-        println!("Similar wallpapers (by color profile):");
-        for (score, idx) in similar {
-            let wp = &cache.wallpapers[idx];
-            let filename = wp.path.file_name().and_then(|n| n.to_str()).unwrap_or("?");
-            println!("  {:.0}% - {}", score * 100.0, filename);
-        }
+    // Rank everything so paging can report the total
+    let similar = utils::find_similar_wallpapers(&target.colors, &wallpaper_colors, usize::MAX);
+    if let Some(outputs) = apply {
+        return apply_top_similar(&cache, &similar, page, wallpaper_dir, config, outputs).await;
     }
-
-    Ok(())
+    print_similar(
+        &cache,
+        &target.path,
+        "color",
+        &similar,
+        page,
+        color_format,
+        out,
+    )
 }
 
 #[cfg(feature = "clip")]
@@ -712,6 +2533,8 @@ async fn cmd_auto_tag(
     threshold: f32,
     max_tags: usize,
     verbose: bool,
+    batch_size: usize,
+    scan: &wallpaper::ScanConfig,
 ) -> Result<()> {
     use clip::ClipTagger;
 
@@ -719,7 +2542,7 @@ async fn cmd_auto_tag(
 
     let mut tagger = ClipTagger::new().await?;
 
-    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir)?;
+    let mut cache = wallpaper::WallpaperCache::load_or_scan_for_ai(wallpaper_dir, scan)?;
 
     let to_process: Vec<usize> = cache
         .wallpapers
@@ -734,53 +2557,79 @@ async fn cmd_auto_tag(
         return Ok(());
     }
 
-    println!("Auto-tagging {} wallpapers...", to_process.len());
+    println!(
+        "Auto-tagging {} wallpapers (batch size {})...",
+        to_process.len(),
+        batch_size.max(1)
+    );
+
+    if verbose {
+        tracing::debug!("CLIP debug output for the first batch follows");
+    }
+
+    let started = std::time::Instant::now();
+    let mut done = 0usize;
+    let mut tagged = 0usize;
 
-    for (progress, idx) in to_process.iter().enumerate() {
-        let wp = &cache.wallpapers[*idx];
-        let path = wp.path.clone();
+    for chunk in to_process.chunks(batch_size.max(1)) {
+        let paths: Vec<PathBuf> = chunk
+            .iter()
+            .map(|&idx| cache.wallpapers[idx].path.clone())
+            .collect();
+
+        let results = tagger.analyze_batch(&paths, threshold, batch_size, verbose && done == 0);
+
+        for ((&idx, path), result) in chunk.iter().zip(&paths).zip(results) {
+            done += 1;
+            match result {
+                Ok(mut analysis) => {
+                    // Limit to max_tags (tags are already sorted by confidence)
+                    if max_tags > 0 && analysis.tags.len() > max_tags {
+                        analysis.tags.truncate(max_tags);
+                    }
 
-        // Show verbose debug output only for first image
-        let show_debug = verbose && progress == 0;
-        if show_debug {
-            eprintln!("\n=== Debug output for first image ===");
-            eprintln!("Image: {}", path.display());
-        }
+                    if verbose {
+                        let tag_names: Vec<_> = analysis.tags.iter().map(|t| &t.name).collect();
+                        println!(
+                            "[{}/{}] {}: {:?} (emb={})",
+                            done,
+                            to_process.len(),
+                            path.file_name().unwrap_or_default().to_string_lossy(),
+                            tag_names,
+                            analysis.embedding.len(),
+                        );
+                    }
 
-        match tagger.analyze_image_verbose(&path, threshold, show_debug) {
-            Ok(mut analysis) => {
-                // Limit to max_tags (tags are already sorted by confidence)
-                if max_tags > 0 && analysis.tags.len() > max_tags {
-                    analysis.tags.truncate(max_tags);
+                    cache.wallpapers[idx].set_auto_tags(analysis.tags);
+                    cache.wallpapers[idx].set_embedding(analysis.embedding);
+                    tagged += 1;
                 }
-
-                if verbose {
-                    let tag_names: Vec<_> = analysis.tags.iter().map(|t| &t.name).collect();
-                    println!(
-                        "[{}/{}] {}: {:?} (emb={})",
-                        progress + 1,
-                        to_process.len(),
-                        path.file_name().unwrap_or_default().to_string_lossy(),
-                        tag_names,
-                        analysis.embedding.len(),
-                    );
-                } else if (progress + 1) % 10 == 0 || progress + 1 == to_process.len() {
-                    eprint!("\rProgress: {}/{}", progress + 1, to_process.len());
+                Err(e) => {
+                    eprintln!("\nWarning: Failed to tag {}: {}", path.display(), e);
                 }
-
-                cache.wallpapers[*idx].set_auto_tags(analysis.tags);
-                cache.wallpapers[*idx].set_embedding(analysis.embedding);
-            }
-            Err(e) => {
-                eprintln!("\nWarning: Failed to tag {}: {}", path.display(), e);
             }
         }
+
+        if !verbose {
+            eprint!("\rProgress: {}/{}", done, to_process.len());
+        }
     }
 
     if !verbose {
         eprintln!(); // Newline after progress
     }
 
+    let elapsed = started.elapsed().as_secs_f64();
+    let throughput = if elapsed > 0.0 {
+        tagged as f64 / elapsed
+    } else {
+        0.0
+    };
+    println!(
+        "Tagged {} images in {:.1}s ({:.1} images/sec)",
+        tagged, elapsed, throughput
+    );
+
     cache.save()?;
 
     // Show summary
@@ -801,17 +2650,160 @@ async fn cmd_auto_tag(
     Ok(())
 }
 
-async fn cmd_time_profile(action: TimeProfileAction, wallpaper_dir: &Path) -> Result<()> {
-    use timeprofile::TimePeriod;
+fn cmd_attribution(path: &Path) -> Result<()> {
+    if !path.exists() {
+        anyhow::bail!("File not found: {}", path.display());
+    }
+    let Some(attribution) = webimport::Attribution::load_for(path) else {
+        println!("No attribution recorded for {}", path.display());
+        println!("(only wallpapers downloaded with `frostwall import` have one)");
+        return Ok(());
+    };
 
-    let mut config = app::Config::load()?;
+    println!("Source: {} [{}]", attribution.source, attribution.id);
+    println!(
+        "Author: {}",
+        attribution.author.as_deref().unwrap_or("Unknown")
+    );
+    println!("URL:    {}", attribution.url);
+    Ok(())
+}
+
+fn cmd_set_resize(
+    wallpaper_dir: &Path,
+    path: &Path,
+    mode: &str,
+    scan: &wallpaper::ScanConfig,
+) -> Result<()> {
+    let mode = match mode.to_lowercase().as_str() {
+        "default" | "none" | "global" => None,
+        other => match swww::ResizeMode::parse(other) {
+            Some(mode) => Some(mode),
+            None => {
+                anyhow::bail!(
+                    "Invalid resize mode '{}'. Use: crop, fit, center, stretch, or default",
+                    other
+                )
+            }
+        },
+    };
+
+    let mut cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, scan)?;
+    if !cache.set_resize_override(path, mode) {
+        println!("Wallpaper not found: {}", path.display());
+        return Ok(());
+    }
+    cache.save()?;
+
+    match mode {
+        Some(mode) => println!("✓ {} will use {}", path.display(), mode.display_name()),
+        None => println!("✓ {} uses the global resize mode", path.display()),
+    }
+    Ok(())
+}
+
+async fn cmd_crop_preview(
+    path: &Path,
+    screen: Option<&str>,
+    out: Option<&Path>,
+    config: &app::Config,
+) -> Result<()> {
+    let requested: Vec<String> = screen.map(str::to_string).into_iter().collect();
+    let screens = screen::select_outputs(
+        screen::detect_screens_ordered(&config.display.output_order).await?,
+        &requested,
+    )?;
+    let Some(target) = screens.first() else {
+        anyhow::bail!("No screens detected");
+    };
+
+    let output = match out {
+        Some(out) => out.to_path_buf(),
+        None => {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_else(|| "wallpaper".to_string());
+            PathBuf::from(format!("{}-crop-{}.png", stem, target.name))
+        }
+    };
+
+    let (img_width, img_height) = image::image_dimensions(path)?;
+    let rect = crop::write_preview(path, target.width, target.height, &output)?;
+
+    println!(
+        "{} ({}x{}): crop keeps {}x{} at ({}, {}) of {}x{} ({:.0}% of the image)",
+        target.name,
+        target.width,
+        target.height,
+        rect.width,
+        rect.height,
+        rect.x,
+        rect.y,
+        img_width,
+        img_height,
+        rect.coverage(img_width, img_height) * 100.0
+    );
+    println!("✓ Preview written to {}", output.display());
+
+    Ok(())
+}
+
+fn cmd_recolor(
+    path: &Path,
+    accent: &str,
+    strength: f32,
+    out: &Path,
+    scan: &wallpaper::ScanConfig,
+) -> Result<()> {
+    if !(0.0..=1.0).contains(&strength) {
+        anyhow::bail!("--strength must be between 0 and 1, got {}", strength);
+    }
+    let Some((accent_hue, accent_saturation, _)) = utils::hex_to_hsl(accent) else {
+        anyhow::bail!("Invalid accent color '{}' (expected #RRGGBB)", accent);
+    };
+    if accent_saturation == 0.0 {
+        anyhow::bail!("Accent {} is a gray and has no hue to shift toward", accent);
+    }
+
+    let mut wp = wallpaper::Wallpaper::from_path_fast(path)?;
+    wp.extract_colors(scan)?;
+    let Some(dominant) = recolor::dominant_hue(&wp.colors) else {
+        anyhow::bail!(
+            "{} is nearly grayscale; there is no hue to shift",
+            path.display()
+        );
+    };
+
+    let degrees = recolor::hue_delta(dominant, accent_hue) * strength;
+    recolor::write_recolored(path, degrees, out)?;
+
+    println!(
+        "✓ Shifted hue {:+.0}° ({:.0}° → {:.0}°) and saved {}",
+        degrees,
+        dominant,
+        (dominant + degrees).rem_euclid(360.0),
+        out.display()
+    );
+    Ok(())
+}
+
+async fn cmd_time_profile(
+    action: TimeProfileAction,
+    wallpaper_dir: &Path,
+    config: &app::Config,
+) -> Result<()> {
+    use timeprofile::TimePeriod;
 
     match action {
         TimeProfileAction::Status => {
-            let period = TimePeriod::current();
+            let period = config.time_profiles.current_period();
             let settings = config.time_profiles.settings_for(period);
 
             println!("{} Current time period: {}", period.emoji(), period.name());
+            if let Some((lat, lon)) = config.time_profiles.location {
+                println!("  Following the sun at {:.2}, {:.2}", lat, lon);
+            }
             println!();
             println!(
                 "Time profiles: {}",
@@ -835,20 +2827,39 @@ async fn cmd_time_profile(action: TimeProfileAction, wallpaper_dir: &Path) -> Re
             );
             println!("  Tag weight: {:.0}%", settings.tag_weight * 100.0);
         }
+        // Writes start from config.toml itself, so a profile's overrides
+        // don't end up saved
         TimeProfileAction::Enable => {
+            let mut config = app::Config::load()?;
             config.time_profiles.enabled = true;
             config.save()?;
             println!("Time-based profiles enabled.");
             println!("Run 'frostwall time-profile status' to see current settings.");
         }
         TimeProfileAction::Disable => {
+            let mut config = app::Config::load()?;
             config.time_profiles.enabled = false;
             config.save()?;
             println!("Time-based profiles disabled.");
         }
+        TimeProfileAction::Set { period, key, value } => {
+            let Some(period) = TimePeriod::parse(&period) else {
+                anyhow::bail!(
+                    "Unknown period '{}'. Use: morning, afternoon, evening, night",
+                    period
+                );
+            };
+            let mut config = app::Config::load()?;
+            config
+                .time_profiles
+                .settings_for_mut(period)
+                .set(&key, &value)?;
+            config.save()?;
+            println!("✓ Set time_profiles.{}.{} = {}", period.name(), key, value);
+        }
         TimeProfileAction::Preview { limit } => {
-            let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir)?;
-            let period = TimePeriod::current();
+            let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, &config.scan)?;
+            let period = config.time_profiles.current_period();
 
             println!(
                 "{} Previewing wallpapers for {} period:",
@@ -881,10 +2892,11 @@ async fn cmd_time_profile(action: TimeProfileAction, wallpaper_dir: &Path) -> Re
             }
         }
         TimeProfileAction::Apply => {
-            let cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir)?;
-            let screens = screen::detect_screens().await?;
+            swww::require_installed()?;
+            let mut cache = wallpaper::WallpaperCache::load_or_scan(wallpaper_dir, &config.scan)?;
+            let screens = screen::detect_screens_ordered(&config.display.output_order).await?;
             let transition = config.transition();
-            let period = TimePeriod::current();
+            let period = config.time_profiles.current_period();
 
             println!(
                 "{} Setting wallpapers for {} period...",
@@ -895,36 +2907,87 @@ async fn cmd_time_profile(action: TimeProfileAction, wallpaper_dir: &Path) -> Re
             // Get top wallpapers for current time
             let sorted =
                 timeprofile::sort_by_time_profile(&cache.wallpapers, &config.time_profiles);
+            let mut shown = Vec::new();
 
             for (i, screen) in screens.iter().enumerate() {
                 if let Some(wp) = sorted.get(i) {
-                    swww::set_wallpaper_with_resize(
+                    swww::apply_wallpaper(
                         &screen.name,
                         &wp.path,
                         &transition,
-                        config.display.resize_mode,
-                        &config.display.fill_color,
+                        wp.resize_mode(config.display.resize_mode),
+                        &config.display.fill_color_for(&wp.colors),
+                        config.scan.gif_mode,
                     )?;
+                    current::update_or_warn(&config.display, &screen.name, &wp.path);
                     println!(
                         "  {}: {}",
                         screen.name,
                         wp.path.file_name().and_then(|n| n.to_str()).unwrap_or("?")
                     );
+                    shown.push(wp.path.clone());
                 }
             }
+
+            for path in &shown {
+                cache.mark_shown(path);
+            }
+            cache.save()?;
         }
     }
 
     Ok(())
 }
 
-fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
+/// Download every search result, skipping files that are already there
+fn download_results(
+    importer: &webimport::WebImporter,
+    results: &[webimport::GalleryImage],
+    wallpaper_dir: &Path,
+) {
+    let total = results.len();
+    let (mut downloaded, mut existing, mut failed) = (0, 0, 0);
+
+    println!();
+    for (i, image) in results.iter().enumerate() {
+        if webimport::WebImporter::existing_download(image, wallpaper_dir).is_some() {
+            println!("  [{}/{}] {} already exists", i + 1, total, image.id);
+            existing += 1;
+            continue;
+        }
+        match importer.download(image, wallpaper_dir) {
+            Ok(path) => {
+                println!("  [{}/{}] ✓ {}", i + 1, total, path.display());
+                downloaded += 1;
+            }
+            Err(e) => {
+                eprintln!("  [{}/{}] ✗ {}: {:#}", i + 1, total, image.id, e);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "\nDownloaded {}, skipped {} existing, {} failed.",
+        downloaded, existing, failed
+    );
+    if downloaded > 0 {
+        println!("Run 'frostwall scan' to add them to the cache.");
+    }
+}
+
+fn cmd_import(action: ImportAction, wallpaper_dir: &Path, config: &app::Config) -> Result<()> {
     use webimport::{Gallery, WebImporter};
 
-    let importer = WebImporter::new();
+    let importer = WebImporter::new(&config.import);
 
     match action {
-        ImportAction::Unsplash { query, count } => {
+        ImportAction::Unsplash {
+            query,
+            count,
+            filter,
+            download_all,
+        } => {
             if !importer.is_available(Gallery::Unsplash) {
                 println!("Unsplash requires an API key.");
                 println!("1. Get a free key at: https://unsplash.com/developers");
@@ -933,7 +2996,7 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
             }
 
             println!("Searching Unsplash for \"{}\"...", query);
-            let results = importer.search(Gallery::Unsplash, &query, 1, count)?;
+            let results = importer.search(Gallery::Unsplash, &query, 1, count, &filter.into())?;
 
             if results.is_empty() {
                 println!("No results found.");
@@ -953,12 +3016,23 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                 );
             }
 
-            println!("\nDownload with: frostwall import download <id>");
-            println!("Or download all with: frostwall import download unsplash_<id>");
+            if download_all {
+                download_results(&importer, &results, wallpaper_dir);
+            } else {
+                println!(
+                    "\nDownload all with: frostwall import unsplash \"{}\" --download-all",
+                    query
+                );
+            }
         }
-        ImportAction::Wallhaven { query, count } => {
+        ImportAction::Wallhaven {
+            query,
+            count,
+            filter,
+            download_all,
+        } => {
             println!("Searching Wallhaven for \"{}\"...", query);
-            let results = importer.search(Gallery::Wallhaven, &query, 1, count)?;
+            let results = importer.search(Gallery::Wallhaven, &query, 1, count, &filter.into())?;
 
             if results.is_empty() {
                 println!("No results found.");
@@ -970,12 +3044,53 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                 println!("  {}. {}x{} [{}]", i + 1, img.width, img.height, img.id);
             }
 
-            println!("\nDownload with: frostwall import download <id>");
-            println!("  e.g.: frostwall import download {}", results[0].id);
+            if download_all {
+                download_results(&importer, &results, wallpaper_dir);
+            } else {
+                println!("\nDownload with: frostwall import download <id>");
+                println!("  e.g.: frostwall import download {}", results[0].id);
+            }
+        }
+        ImportAction::Reddit {
+            subreddit,
+            count,
+            filter,
+            download_all,
+        } => {
+            println!("Fetching top posts of the week from r/{}...", subreddit);
+            let results = importer.search(Gallery::Reddit, &subreddit, 1, count, &filter.into())?;
+
+            if results.is_empty() {
+                println!("No image posts found.");
+                return Ok(());
+            }
+
+            println!("\nFound {} images:\n", results.len());
+            for (i, img) in results.iter().enumerate() {
+                let author = img.author.as_deref().unwrap_or("unknown");
+                println!(
+                    "  {}. {}x{} by u/{} {}",
+                    i + 1,
+                    img.width,
+                    img.height,
+                    author,
+                    img.url
+                );
+            }
+
+            if download_all {
+                download_results(&importer, &results, wallpaper_dir);
+            } else {
+                println!("\nDownload with: frostwall import download <url>");
+            }
         }
-        ImportAction::Featured { count } => {
+        ImportAction::Featured {
+            count,
+            filter,
+            download_all,
+        } => {
             println!("Fetching top wallpapers from Wallhaven...");
-            let results = importer.featured_wallhaven(count)?;
+            let results = importer.featured_wallhaven(count, &filter.into())?;
 
             if results.is_empty() {
                 println!("No results found.");
@@ -987,7 +3102,52 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                 println!("  {}. {}x{} [{}]", i + 1, img.width, img.height, img.id);
             }
 
-            println!("\nDownload with: frostwall import download <id>");
+            if download_all {
+                download_results(&importer, &results, wallpaper_dir);
+            } else {
+                println!("\nDownload with: frostwall import download <id>");
+            }
+        }
+        ImportAction::Local {
+            dir,
+            count,
+            filter,
+            download_all,
+        } => {
+            let dir = utils::expand_tilde(&dir.to_string_lossy());
+            println!("Listing images in {}...", dir.display());
+            let results =
+                importer.search(Gallery::LocalDir(dir.clone()), "", 1, count, &filter.into())?;
+
+            if results.is_empty() {
+                println!("No images found.");
+                return Ok(());
+            }
+
+            println!("\nFound {} images:\n", results.len());
+            for (i, img) in results.iter().enumerate() {
+                let marker =
+                    if webimport::WebImporter::existing_download(img, wallpaper_dir).is_some() {
+                        " (in library)"
+                    } else {
+                        ""
+                    };
+                println!(
+                    "  {}. {}x{} {}{}",
+                    i + 1,
+                    img.width,
+                    img.height,
+                    img.id,
+                    marker
+                );
+            }
+
+            if download_all {
+                download_results(&importer, &results, wallpaper_dir);
+            } else {
+                println!("\nCopy one with: frostwall import download <path>");
+                println!("  e.g.: frostwall import download {}", results[0].url);
+            }
         }
         ImportAction::Download { url } => {
             // Determine source from URL/ID
@@ -1013,10 +3173,27 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                         author: None,
                         source: Gallery::Wallhaven,
                     }
+                } else if url.contains("i.redd.it") && webimport::is_direct_image_url(&url) {
+                    // Direct Reddit image: the file name is the media ID
+                    let name = url.rsplit('/').next().unwrap_or(&url);
+                    let id = name.split('.').next().unwrap_or(name);
+                    webimport::GalleryImage {
+                        id: id.to_string(),
+                        url: url.clone(),
+                        thumb_url: String::new(),
+                        width: 0,
+                        height: 0,
+                        author: None,
+                        source: Gallery::Reddit,
+                    }
                 } else {
-                    println!("Unknown URL source. Supported: Unsplash, Wallhaven");
+                    println!("Unknown URL source. Supported: Unsplash, Wallhaven, i.redd.it");
                     return Ok(());
                 }
+            } else if Path::new(&url).is_file() {
+                let path = Path::new(&url);
+                let root = path.parent().unwrap_or(Path::new("."));
+                webimport::local_image(path, root)?
             } else {
                 // Assume Wallhaven ID
                 let full_url = format!(
@@ -1035,6 +3212,16 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                 }
             };
 
+            // The guessed Wallhaven URL assumes .jpg; the API knows the real file
+            let image = if image.source == Gallery::Wallhaven {
+                importer.wallhaven_image(&image.id).unwrap_or_else(|e| {
+                    tracing::debug!("Wallhaven lookup failed, guessing the URL: {:#}", e);
+                    image
+                })
+            } else {
+                image
+            };
+
             println!("Downloading {}...", image.id);
 
             match importer.download(&image, wallpaper_dir) {
@@ -1043,21 +3230,7 @@ fn cmd_import(action: ImportAction, wallpaper_dir: &Path) -> Result<()> {
                     println!("\nRun 'frostwall scan' to add it to the cache.");
                 }
                 Err(e) => {
-                    // Try alternative URL formats for Wallhaven
-                    if image.source == Gallery::Wallhaven {
-                        // Try PNG format
-                        let png_url = image.url.replace(".jpg", ".png");
-                        let png_image = webimport::GalleryImage {
-                            url: png_url,
-                            ..image.clone()
-                        };
-                        if let Ok(path) = importer.download(&png_image, wallpaper_dir) {
-                            println!("Downloaded to: {}", path.display());
-                            println!("\nRun 'frostwall scan' to add it to the cache.");
-                            return Ok(());
-                        }
-                    }
-                    println!("Download failed: {}", e);
+                    println!("Download failed: {:#}", e);
                     println!("The image might not exist or the URL format has changed.");
                 }
             }
@@ -44,6 +44,29 @@ pub struct PairingHistoryData {
     pub affinity_scores: Vec<AffinityScore>,
 }
 
+impl PairingHistoryData {
+    /// Rewrite every wallpaper path starting with `from` to start with `to` instead.
+    /// Used when importing history from a machine with a different wallpaper location.
+    pub fn remap_prefix(&mut self, from: &Path, to: &Path) {
+        let remap = |path: &mut PathBuf| {
+            if let Ok(rest) = path.strip_prefix(from) {
+                *path = to.join(rest);
+            }
+        };
+
+        for record in &mut self.records {
+            record.wallpapers.values_mut().for_each(remap);
+        }
+        for score in &mut self.affinity_scores {
+            remap(&mut score.wallpaper_a);
+            remap(&mut score.wallpaper_b);
+            if score.wallpaper_b < score.wallpaper_a {
+                std::mem::swap(&mut score.wallpaper_a, &mut score.wallpaper_b);
+            }
+        }
+    }
+}
+
 /// Runtime state for undo functionality
 pub struct UndoState {
     pub previous_wallpapers: HashMap<String, PathBuf>,
@@ -58,6 +81,8 @@ pub struct PairingHistory {
     cache_path: PathBuf,
     /// Current active pairing (for duration tracking)
     current_pairing_start: Option<u64>,
+    /// Duration of the current pairing already folded into affinity by a flush
+    current_pairing_flushed: Option<u64>,
     /// Undo state
     undo_state: Option<UndoState>,
     /// Maximum records to keep
@@ -83,6 +108,16 @@ impl PairingStyleMode {
         }
     }
 
+    /// Parse a config name ("off", "soft" or "strict")
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "off" => Some(PairingStyleMode::Off),
+            "soft" => Some(PairingStyleMode::Soft),
+            "strict" => Some(PairingStyleMode::Strict),
+            _ => None,
+        }
+    }
+
     /// Return human-readable display name for this style mode.
     pub fn display_name(self) -> &'static str {
         match self {
@@ -103,6 +138,7 @@ pub struct MatchContext<'a> {
     pub screen_context_weight: f32,
     pub visual_weight: f32,
     pub harmony_weight: f32,
+    pub harmony_mode: crate::utils::HarmonyMode,
     pub tag_weight: f32,
     pub semantic_weight: f32,
     pub repetition_penalty_weight: f32,
@@ -204,6 +240,157 @@ fn is_content_tag(tag: &str) -> bool {
     )
 }
 
+/// Per-screen picks for applying a pairing preview
+#[derive(Debug, Default, PartialEq)]
+pub struct DistinctSelection {
+    /// (screen, wallpaper) to apply
+    pub picks: Vec<(String, PathBuf)>,
+    /// Screens whose preferred pick was a duplicate and got the next match
+    pub replaced: Vec<String>,
+    /// Screens left unchanged because every candidate was already in use
+    pub skipped: Vec<String>,
+}
+
+/// Pick one wallpaper per screen from ranked candidates, starting at
+/// `preferred_idx` (clamped to each list).
+///
+/// Unless `allow_duplicates` is set, a screen whose preferred pick is already
+/// shown elsewhere (in `taken` or on an earlier screen) falls through to its
+/// next unused candidate.
+pub fn select_distinct_wallpapers(
+    candidates: &[(String, Vec<PathBuf>)],
+    preferred_idx: usize,
+    taken: &HashSet<PathBuf>,
+    allow_duplicates: bool,
+) -> DistinctSelection {
+    let mut used = taken.clone();
+    let mut selection = DistinctSelection::default();
+
+    for (screen, paths) in candidates {
+        if paths.is_empty() {
+            continue;
+        }
+        let start = preferred_idx.min(paths.len() - 1);
+        let preferred = &paths[start];
+
+        if allow_duplicates || !used.contains(preferred) {
+            used.insert(preferred.clone());
+            selection.picks.push((screen.clone(), preferred.clone()));
+            continue;
+        }
+
+        // Walk forward from the preferred slot, wrapping around
+        let fallback = (1..paths.len())
+            .map(|offset| &paths[(start + offset) % paths.len()])
+            .find(|path| !used.contains(*path));
+
+        match fallback {
+            Some(path) => {
+                used.insert(path.clone());
+                selection.picks.push((screen.clone(), path.clone()));
+                selection.replaced.push(screen.clone());
+            }
+            None => selection.skipped.push(screen.clone()),
+        }
+    }
+
+    selection
+}
+
+/// How well two wallpapers work side by side (0.0-1.0): the mean of their
+/// weighted visual similarity and color harmony strength.
+pub fn pair_coherence(
+    a: &crate::wallpaper::Wallpaper,
+    b: &crate::wallpaper::Wallpaper,
+    harmony_mode: crate::utils::HarmonyMode,
+) -> f32 {
+    let weights = |wp: &crate::wallpaper::Wallpaper| -> Vec<f32> {
+        if wp.color_weights.is_empty() {
+            vec![1.0 / wp.colors.len().max(1) as f32; wp.colors.len()]
+        } else {
+            wp.color_weights.clone()
+        }
+    };
+    let (wa, wb) = (weights(a), weights(b));
+
+    let visual = crate::utils::image_similarity_weighted(&a.colors, &wa, &b.colors, &wb);
+    let (harmony, strength) =
+        crate::utils::detect_harmony_for(&a.colors, &wa, &b.colors, &wb, harmony_mode);
+    ((visual + harmony.bonus() * strength) / 2.0).clamp(0.0, 1.0)
+}
+
+/// Beam search for the multi-screen sets that score best as a whole.
+///
+/// `per_screen` holds each screen's ranked candidates with their individual
+/// scores against the selected wallpaper. A set's score is the sum of those
+/// scores (normalized per screen to 0-1) plus `coherence_weight` times the
+/// pairwise `coherence` of every two picks in the set. The same path is never
+/// used twice in one set. Returns up to `limit` sets, best first, as one
+/// candidate index per screen.
+pub fn optimize_screen_sets(
+    per_screen: &[Vec<(PathBuf, f32)>],
+    coherence: impl Fn(&Path, &Path) -> f32,
+    coherence_weight: f32,
+    beam_width: usize,
+    limit: usize,
+) -> Vec<Vec<usize>> {
+    if per_screen.is_empty() || per_screen.iter().any(Vec::is_empty) || limit == 0 {
+        return Vec::new();
+    }
+
+    let normalized: Vec<Vec<f32>> = per_screen
+        .iter()
+        .map(|candidates| {
+            let max = candidates.iter().map(|(_, s)| *s).fold(0.0f32, f32::max);
+            candidates
+                .iter()
+                .map(|(_, s)| if max > 0.0 { s / max } else { 0.0 })
+                .collect()
+        })
+        .collect();
+
+    let width = beam_width.max(limit).max(1);
+    let mut beams: Vec<(Vec<usize>, f32)> = vec![(Vec::new(), 0.0)];
+
+    for (screen, candidates) in per_screen.iter().enumerate() {
+        let mut next: Vec<(Vec<usize>, f32)> = Vec::new();
+        for (picks, score) in &beams {
+            for (idx, (path, _)) in candidates.iter().enumerate() {
+                let chosen = picks
+                    .iter()
+                    .enumerate()
+                    .map(|(s, &i)| per_screen[s][i].0.as_path());
+                if chosen.clone().any(|p| p == path) {
+                    continue;
+                }
+                let bonus: f32 = chosen.map(|p| coherence(p, path)).sum();
+                let mut extended = picks.clone();
+                extended.push(idx);
+                next.push((
+                    extended,
+                    score + normalized[screen][idx] + coherence_weight * bonus,
+                ));
+            }
+        }
+        next.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        next.truncate(width);
+        if next.is_empty() {
+            return Vec::new();
+        }
+        beams = next;
+    }
+
+    beams
+        .into_iter()
+        .take(limit)
+        .map(|(picks, _)| picks)
+        .collect()
+}
+
 fn compare_scored_match(a: &(PathBuf, f32), b: &(PathBuf, f32)) -> std::cmp::Ordering {
     match b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal) {
         std::cmp::Ordering::Equal => a.0.cmp(&b.0),
@@ -222,6 +409,7 @@ impl PairingHistory {
             data: PairingHistoryData::default(),
             cache_path,
             current_pairing_start: None,
+            current_pairing_flushed: None,
             undo_state: None,
             max_records,
         }
@@ -243,6 +431,9 @@ impl PairingHistory {
 
     /// Save history to cache file
     pub fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
         if let Some(parent) = self.cache_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
@@ -253,58 +444,229 @@ impl PairingHistory {
         Ok(())
     }
 
+    /// Write the full history (records + affinities) to a portable JSON file
+    pub fn export(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&self.data)?;
+        std::fs::write(path, content)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Read history previously written by `export`
+    pub fn read_export(path: &Path) -> Result<PairingHistoryData> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse pairing export {}", path.display()))
+    }
+
+    /// Replace all history with imported data
+    pub fn replace_data(&mut self, data: PairingHistoryData) {
+        self.data = data;
+        self.current_pairing_start = None;
+        self.current_pairing_flushed = None;
+        self.prune_old_records();
+    }
+
+    /// Merge imported history into the current one.
+    ///
+    /// Records are concatenated (exact duplicates skipped) and affinities are
+    /// rebuilt from the combined records. Affinity pairs that aren't backed by
+    /// any record (e.g. pruned on the source machine) are kept with their
+    /// pair counts combined.
+    pub fn merge_data(&mut self, other: PairingHistoryData) {
+        // Combine existing and imported affinities before rebuilding from records
+        let mut combined: HashMap<(PathBuf, PathBuf), (u32, f32)> = HashMap::new();
+        for score in self
+            .data
+            .affinity_scores
+            .drain(..)
+            .chain(other.affinity_scores)
+        {
+            let (a, b) = Self::ordered_pair(&score.wallpaper_a, &score.wallpaper_b);
+            let entry = combined
+                .entry((a.to_path_buf(), b.to_path_buf()))
+                .or_insert((0, 0.0));
+            let total = entry.0 + score.pair_count;
+            if total > 0 {
+                entry.1 = (entry.1 * entry.0 as f32
+                    + score.avg_duration_secs * score.pair_count as f32)
+                    / total as f32;
+            }
+            entry.0 = total;
+        }
+
+        for record in other.records {
+            let duplicate = self
+                .data
+                .records
+                .iter()
+                .any(|r| r.timestamp == record.timestamp && r.wallpapers == record.wallpapers);
+            if !duplicate {
+                self.data.records.push(record);
+            }
+        }
+        self.data.records.sort_by_key(|r| r.timestamp);
+        if self.data.records.len() > self.max_records {
+            let to_remove = self.data.records.len() - self.max_records;
+            self.data.records.drain(0..to_remove);
+        }
+        self.current_pairing_start = None;
+        self.current_pairing_flushed = None;
+
+        self.recompute_affinity();
+
+        let rebuilt: HashSet<(PathBuf, PathBuf)> = self
+            .data
+            .affinity_scores
+            .iter()
+            .map(|s| (s.wallpaper_a.clone(), s.wallpaper_b.clone()))
+            .collect();
+        for ((a, b), (pair_count, avg_duration_secs)) in combined {
+            if pair_count == 0 || rebuilt.contains(&(a.clone(), b.clone())) {
+                continue;
+            }
+            self.data.affinity_scores.push(AffinityScore {
+                wallpaper_a: a,
+                wallpaper_b: b,
+                score: Self::calculate_base_score(pair_count, avg_duration_secs),
+                pair_count,
+                avg_duration_secs,
+            });
+        }
+    }
+
     /// Record a new pairing
     pub fn record_pairing(&mut self, wallpapers: HashMap<String, PathBuf>, manual: bool) {
-        // End previous pairing (for duration tracking — also updates affinity)
-        self.end_current_pairing();
-
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
-        let record = PairingRecord {
+        self.record_pairing_at(wallpapers, manual, timestamp);
+
+        // Auto-save
+        let _ = self.save();
+    }
+
+    /// Record a pairing that started at `timestamp` without touching disk
+    fn record_pairing_at(
+        &mut self,
+        wallpapers: HashMap<String, PathBuf>,
+        manual: bool,
+        timestamp: u64,
+    ) {
+        // End previous pairing (for duration tracking)
+        self.end_current_pairing_at(timestamp);
+
+        let paths: Vec<PathBuf> = wallpapers.values().cloned().collect();
+        self.data.records.push(PairingRecord {
             wallpapers,
             timestamp,
             duration: None,
             manual,
-        };
-
-        self.data.records.push(record);
+        });
         self.current_pairing_start = Some(timestamp);
-
-        // NOTE: affinity is updated in end_current_pairing() when the *next*
-        // pairing starts (or on shutdown), so we have duration data.  Updating
-        // here too would double-count pair_count.
+        self.current_pairing_flushed = None;
+
+        // Count the pairing now so suggestions reflect it immediately. Its
+        // duration is unknown until the next pairing starts, and is folded
+        // into the average then without counting the pair a second time.
+        for i in 0..paths.len() {
+            for j in (i + 1)..paths.len() {
+                self.update_affinity(&paths[i], &paths[j], None);
+            }
+        }
 
         // Prune old records if needed
         self.prune_old_records();
-
-        // Auto-save
-        let _ = self.save();
     }
 
-    /// Mark end of current pairing (for duration tracking)
-    fn end_current_pairing(&mut self) {
+    /// Mark end of current pairing at `now` (for duration tracking)
+    fn end_current_pairing_at(&mut self, now: u64) {
         if let Some(start) = self.current_pairing_start.take() {
-            let now = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-            let duration = now.saturating_sub(start);
-
-            // Update the last record with duration
-            if let Some(last) = self.data.records.last_mut() {
-                last.duration = Some(duration);
-
-                // Update affinity scores with duration info
-                let paths: Vec<_> = last.wallpapers.values().cloned().collect();
-                for i in 0..paths.len() {
-                    for j in (i + 1)..paths.len() {
-                        self.update_affinity(&paths[i], &paths[j], Some(duration));
+            self.apply_current_duration(now.saturating_sub(start));
+            self.current_pairing_flushed = None;
+        }
+    }
+
+    /// Save the running duration of the current pairing so that a crash or
+    /// killed TUI loses at most one flush interval of usage data.
+    /// Returns whether there was an active pairing to flush.
+    pub fn flush_current_pairing(&mut self) -> Result<bool> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if !self.flush_current_pairing_at(now) {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Write the running duration into the last record and affinity, keeping
+    /// the pairing open
+    fn flush_current_pairing_at(&mut self, now: u64) -> bool {
+        let Some(start) = self.current_pairing_start else {
+            return false;
+        };
+        self.apply_current_duration(now.saturating_sub(start));
+        true
+    }
+
+    /// Set the current pairing's duration on its record and affinity entries,
+    /// replacing whatever an earlier flush contributed
+    fn apply_current_duration(&mut self, duration: u64) {
+        let previous = self.current_pairing_flushed;
+        let Some(last) = self.data.records.last_mut() else {
+            return;
+        };
+        last.duration = Some(duration);
+
+        // The pair was already counted when recorded; only adjust duration
+        let paths: Vec<_> = last.wallpapers.values().cloned().collect();
+        for i in 0..paths.len() {
+            for j in (i + 1)..paths.len() {
+                self.add_affinity_duration(&paths[i], &paths[j], previous, duration);
+            }
+        }
+        self.current_pairing_flushed = Some(duration);
+    }
+
+    /// Set an already-counted pairing's contribution to the rolling average
+    /// to `duration`, replacing the `previous` one from an earlier flush
+    fn add_affinity_duration(
+        &mut self,
+        wp_a: &Path,
+        wp_b: &Path,
+        previous: Option<u64>,
+        duration: u64,
+    ) {
+        let (a, b) = Self::ordered_pair(wp_a, wp_b);
+        let entry = self
+            .data
+            .affinity_scores
+            .iter_mut()
+            .find(|s| s.wallpaper_a == a && s.wallpaper_b == b);
+
+        match entry {
+            Some(score) if score.pair_count > 0 => {
+                let count = score.pair_count as f32;
+                score.avg_duration_secs = match previous {
+                    // Average so far covers only the earlier pairings
+                    None => (score.avg_duration_secs * (count - 1.0) + duration as f32) / count,
+                    Some(prev) => {
+                        (score.avg_duration_secs + (duration as f32 - prev as f32) / count).max(0.0)
                     }
-                }
+                };
+                score.score = Self::calculate_base_score(score.pair_count, score.avg_duration_secs);
             }
+            // Entry was pruned or never counted: count it now
+            _ => self.update_affinity(a, b, Some(duration)),
         }
     }
 
@@ -362,20 +724,6 @@ impl PairingHistory {
         }
     }
 
-    /// Get the best matching wallpaper for other screens
-    /// Returns the wallpaper with highest affinity score, or falls back to
-    /// a wallpaper with similar colors if no history exists.
-    pub fn get_best_match(
-        &self,
-        context: &MatchContext<'_>,
-        available_wallpapers: &[&crate::wallpaper::Wallpaper],
-    ) -> Option<PathBuf> {
-        self.get_top_matches(context, available_wallpapers, 1)
-            .into_iter()
-            .next()
-            .map(|(path, _)| path)
-    }
-
     /// Get top N matching wallpapers for other screens
     /// Returns wallpapers sorted by affinity score (highest first)
     ///
@@ -613,11 +961,12 @@ impl PairingHistory {
                 score += visual_similarity * visual_weight;
 
                 // Color harmony bonus (0-3 points)
-                let (harmony, strength) = crate::utils::detect_harmony(
+                let (harmony, strength) = crate::utils::detect_harmony_for(
                     context.selected_colors,
                     selected_weights.as_ref(),
                     &wp.colors,
                     wp_weights.as_ref(),
+                    context.harmony_mode,
                 );
                 let harmony_bonus = harmony.bonus() * strength * harmony_weight;
                 score += harmony_bonus;
@@ -817,7 +1166,6 @@ impl PairingHistory {
     }
 
     /// Begin undo window
-    #[allow(dead_code)]
     pub fn begin_undo(
         &mut self,
         previous: HashMap<String, PathBuf>,
@@ -882,6 +1230,11 @@ impl PairingHistory {
         self.data.records.len()
     }
 
+    /// Up to `limit` records, newest first
+    pub fn recent_records(&self, limit: usize) -> Vec<&PairingRecord> {
+        self.data.records.iter().rev().take(limit).collect()
+    }
+
     /// Get the most recent pairing with multiple screens
     pub fn get_last_multi_screen_pairing(&self) -> Option<HashMap<String, PathBuf>> {
         self.data
@@ -895,6 +1248,12 @@ impl PairingHistory {
     /// Rebuild affinity scores from scratch based on current records.
     /// Use this after fixing bugs in the scoring logic to reset stale data.
     pub fn rebuild_affinity(&mut self) {
+        self.recompute_affinity();
+        let _ = self.save();
+    }
+
+    /// Recompute affinity scores from records without touching disk
+    fn recompute_affinity(&mut self) {
         self.data.affinity_scores.clear();
 
         // Collect all pairs from records first to avoid borrow conflict
@@ -915,16 +1274,34 @@ impl PairingHistory {
                 }
             }
         }
-        let _ = self.save();
     }
 
     /// Get number of affinity pairs
     pub fn affinity_count(&self) -> usize {
         self.data.affinity_scores.len()
     }
+
+    /// Affinity pairs where either wallpaper fails `exists` (deleted files)
+    pub fn orphaned_affinities(&self, exists: impl Fn(&Path) -> bool) -> Vec<&AffinityScore> {
+        self.data
+            .affinity_scores
+            .iter()
+            .filter(|score| !exists(&score.wallpaper_a) || !exists(&score.wallpaper_b))
+            .collect()
+    }
+
+    /// Drop orphaned affinity pairs, returning how many were removed
+    pub fn prune_orphaned_affinities(&mut self, exists: impl Fn(&Path) -> bool) -> usize {
+        let before = self.data.affinity_scores.len();
+        self.data
+            .affinity_scores
+            .retain(|score| exists(&score.wallpaper_a) && exists(&score.wallpaper_b));
+        before - self.data.affinity_scores.len()
+    }
 }
 
-fn normalize_cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+/// Cosine similarity of two embeddings mapped from [-1, 1] to [0, 1].
+pub(crate) fn normalize_cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     let len = a.len().min(b.len());
     if len == 0 {
         return 0.0;
@@ -951,6 +1328,108 @@ fn normalize_cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
 mod tests {
     use super::*;
 
+    // --- optimize_screen_sets ---
+
+    #[test]
+    fn test_optimize_screen_sets_prefers_coherent_set() {
+        // Independently, DP-1 would take /x, but /x clashes with DP-2's /z
+        let per_screen = vec![
+            vec![(PathBuf::from("/x"), 1.0), (PathBuf::from("/y"), 0.9)],
+            vec![(PathBuf::from("/z"), 1.0)],
+        ];
+        let coherence = |a: &Path, b: &Path| {
+            let pair = [a.to_str().unwrap(), b.to_str().unwrap()];
+            if pair.contains(&"/y") && pair.contains(&"/z") {
+                1.0
+            } else {
+                0.0
+            }
+        };
+
+        let sets = optimize_screen_sets(&per_screen, coherence, 1.0, 4, 2);
+        assert_eq!(sets[0], vec![1, 0], "coherent set should rank first");
+        assert_eq!(sets[1], vec![0, 0]);
+    }
+
+    #[test]
+    fn test_optimize_screen_sets_never_repeats_a_path() {
+        let per_screen = vec![
+            vec![(PathBuf::from("/a"), 1.0), (PathBuf::from("/b"), 0.5)],
+            vec![(PathBuf::from("/a"), 1.0), (PathBuf::from("/b"), 0.5)],
+        ];
+        let sets = optimize_screen_sets(&per_screen, |_, _| 1.0, 1.0, 8, 10);
+        assert!(!sets.is_empty());
+        for set in &sets {
+            assert_ne!(per_screen[0][set[0]].0, per_screen[1][set[1]].0);
+        }
+    }
+
+    #[test]
+    fn test_optimize_screen_sets_empty_screen() {
+        let per_screen = vec![vec![(PathBuf::from("/a"), 1.0)], vec![]];
+        assert!(optimize_screen_sets(&per_screen, |_, _| 0.0, 1.0, 4, 4).is_empty());
+    }
+
+    // --- select_distinct_wallpapers ---
+
+    fn candidates(lists: &[(&str, &[&str])]) -> Vec<(String, Vec<PathBuf>)> {
+        lists
+            .iter()
+            .map(|(screen, paths)| {
+                (
+                    screen.to_string(),
+                    paths.iter().map(PathBuf::from).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_select_distinct_skips_taken_and_repeats() {
+        let cands = candidates(&[("DP-1", &["/a", "/b"]), ("DP-2", &["/a", "/c"])]);
+        let taken: HashSet<PathBuf> = [PathBuf::from("/a")].into_iter().collect();
+
+        let selection = select_distinct_wallpapers(&cands, 0, &taken, false);
+        assert_eq!(
+            selection.picks,
+            vec![
+                ("DP-1".to_string(), PathBuf::from("/b")),
+                ("DP-2".to_string(), PathBuf::from("/c")),
+            ]
+        );
+        assert_eq!(
+            selection.replaced,
+            vec!["DP-1", "DP-2"],
+            "both preferred picks collided"
+        );
+    }
+
+    #[test]
+    fn test_select_distinct_allows_mirrored() {
+        let cands = candidates(&[("DP-1", &["/a"]), ("DP-2", &["/a"])]);
+        let taken: HashSet<PathBuf> = [PathBuf::from("/a")].into_iter().collect();
+
+        let selection = select_distinct_wallpapers(&cands, 0, &taken, true);
+        assert_eq!(selection.picks.len(), 2);
+        assert!(selection.picks.iter().all(|(_, p)| p == Path::new("/a")));
+        assert!(selection.replaced.is_empty() && selection.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_select_distinct_reports_exhausted_screen() {
+        let cands = candidates(&[("DP-1", &["/a"]), ("DP-2", &["/b", "/c"])]);
+        let taken: HashSet<PathBuf> = [PathBuf::from("/a")].into_iter().collect();
+
+        // preferred_idx past the end clamps to the last candidate
+        let selection = select_distinct_wallpapers(&cands, 5, &taken, false);
+        assert_eq!(
+            selection.picks,
+            vec![("DP-2".to_string(), PathBuf::from("/c"))]
+        );
+        assert_eq!(selection.skipped, vec!["DP-1"]);
+        assert!(selection.replaced.is_empty());
+    }
+
     // --- PairingStyleMode ---
 
     #[test]
@@ -972,6 +1451,26 @@ mod tests {
         assert_eq!(PairingStyleMode::default(), PairingStyleMode::Soft);
     }
 
+    #[test]
+    fn test_pairing_style_mode_config_names() {
+        let mode: PairingStyleMode = serde_json::from_str("\"strict\"").unwrap();
+        assert_eq!(mode, PairingStyleMode::Strict);
+        assert_eq!(
+            serde_json::to_string(&PairingStyleMode::Off).unwrap(),
+            "\"off\""
+        );
+    }
+
+    #[test]
+    fn test_pairing_style_mode_parse() {
+        assert_eq!(
+            PairingStyleMode::parse("Strict"),
+            Some(PairingStyleMode::Strict)
+        );
+        assert_eq!(PairingStyleMode::parse("off"), Some(PairingStyleMode::Off));
+        assert_eq!(PairingStyleMode::parse("loose"), None);
+    }
+
     // --- canonical_style_tag ---
 
     #[test]
@@ -1147,4 +1646,212 @@ mod tests {
         // Equal scores should sort by path
         assert_eq!(compare_scored_match(&a, &b), std::cmp::Ordering::Less);
     }
+
+    // --- export / import ---
+
+    fn record(ts: u64, pairs: &[(&str, &str)]) -> PairingRecord {
+        PairingRecord {
+            wallpapers: pairs
+                .iter()
+                .map(|(screen, path)| (screen.to_string(), PathBuf::from(path)))
+                .collect(),
+            timestamp: ts,
+            duration: Some(600),
+            manual: true,
+        }
+    }
+
+    #[test]
+    fn test_remap_prefix_rewrites_records_and_affinities() {
+        let mut data = PairingHistoryData {
+            records: vec![record(
+                1,
+                &[("DP-1", "/old/a.jpg"), ("DP-2", "/other/b.jpg")],
+            )],
+            affinity_scores: vec![AffinityScore {
+                wallpaper_a: PathBuf::from("/old/a.jpg"),
+                wallpaper_b: PathBuf::from("/other/b.jpg"),
+                score: 0.5,
+                pair_count: 1,
+                avg_duration_secs: 600.0,
+            }],
+        };
+        data.remap_prefix(Path::new("/old"), Path::new("/new"));
+
+        assert_eq!(
+            data.records[0].wallpapers["DP-1"],
+            PathBuf::from("/new/a.jpg")
+        );
+        assert_eq!(
+            data.records[0].wallpapers["DP-2"],
+            PathBuf::from("/other/b.jpg"),
+            "Paths outside the prefix should be untouched"
+        );
+        let score = &data.affinity_scores[0];
+        assert!(
+            score.wallpaper_a < score.wallpaper_b,
+            "Affinity pair should stay ordered after remap"
+        );
+    }
+
+    // --- orphaned affinities ---
+
+    #[test]
+    fn test_prune_orphaned_affinities() {
+        let mut history = PairingHistory::new(100);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_000);
+        history.record_pairing_at(pair("/a.jpg", "/gone.jpg"), true, 1_100);
+
+        let exists = |p: &Path| p != Path::new("/gone.jpg");
+        let orphans = history.orphaned_affinities(exists);
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].wallpaper_b, PathBuf::from("/gone.jpg"));
+
+        assert_eq!(history.prune_orphaned_affinities(exists), 1);
+        assert_eq!(history.affinity_count(), 1);
+        assert!(history.orphaned_affinities(exists).is_empty());
+    }
+
+    // --- recent_records ---
+
+    #[test]
+    fn test_recent_records_newest_first_with_durations() {
+        let mut history = PairingHistory::new(100);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_000);
+        history.record_pairing_at(pair("/c.jpg", "/d.jpg"), false, 1_300);
+        history.record_pairing_at(pair("/e.jpg", "/f.jpg"), true, 1_500);
+
+        let recent = history.recent_records(2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].timestamp, 1_500);
+        assert_eq!(recent[0].duration, None, "newest pairing is still showing");
+        assert_eq!(recent[1].timestamp, 1_300);
+        assert_eq!(recent[1].duration, Some(200));
+        assert!(!recent[1].manual);
+        assert_eq!(history.recent_records(10).len(), 3);
+    }
+
+    // --- record_pairing_at ---
+
+    fn pair(a: &str, b: &str) -> HashMap<String, PathBuf> {
+        HashMap::from([
+            ("DP-1".to_string(), PathBuf::from(a)),
+            ("DP-2".to_string(), PathBuf::from(b)),
+        ])
+    }
+
+    #[test]
+    fn test_record_pairing_updates_affinity_immediately() {
+        let mut history = PairingHistory::new(100);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_000);
+
+        assert_eq!(
+            history.get_affinity(Path::new("/a.jpg"), Path::new("/b.jpg")),
+            PairingHistory::calculate_base_score(1, 0.0),
+            "Affinity should exist before the next pairing starts"
+        );
+    }
+
+    #[test]
+    fn test_record_pairing_does_not_double_count() {
+        let mut history = PairingHistory::new(100);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_000);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_100);
+        history.record_pairing_at(pair("/c.jpg", "/d.jpg"), true, 1_400);
+
+        let score = history
+            .data
+            .affinity_scores
+            .iter()
+            .find(|s| s.wallpaper_a == Path::new("/a.jpg"))
+            .cloned()
+            .unwrap();
+        assert_eq!(score.pair_count, 2, "Each pairing is counted exactly once");
+        assert!(
+            (score.avg_duration_secs - 200.0).abs() < 0.001,
+            "Average of 100s and 300s, got {}",
+            score.avg_duration_secs
+        );
+
+        // Incremental state must agree with a full rebuild from records
+        let incremental: Vec<(PathBuf, u32, f32)> = history
+            .data
+            .affinity_scores
+            .iter()
+            .map(|s| (s.wallpaper_a.clone(), s.pair_count, s.avg_duration_secs))
+            .collect();
+        history.recompute_affinity();
+        let rebuilt: Vec<(PathBuf, u32, f32)> = history
+            .data
+            .affinity_scores
+            .iter()
+            .map(|s| (s.wallpaper_a.clone(), s.pair_count, s.avg_duration_secs))
+            .collect();
+        assert_eq!(incremental, rebuilt);
+    }
+
+    #[test]
+    fn test_flush_current_pairing_replaces_running_duration() {
+        let mut history = PairingHistory::new(100);
+        assert!(
+            !history.flush_current_pairing_at(1_000),
+            "Nothing to flush before any pairing"
+        );
+
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_000);
+        history.record_pairing_at(pair("/a.jpg", "/b.jpg"), true, 1_200);
+        assert!(history.flush_current_pairing_at(1_300));
+        assert!(history.flush_current_pairing_at(1_600));
+
+        assert_eq!(history.data.records.last().unwrap().duration, Some(400));
+        let score = history.data.affinity_scores[0].clone();
+        assert_eq!(score.pair_count, 2, "Flushing never adds a pairing");
+        assert!(
+            (score.avg_duration_secs - 300.0).abs() < 0.001,
+            "Average of 200s and the latest flush (400s), got {}",
+            score.avg_duration_secs
+        );
+
+        // Ending the pairing later replaces the flushed value again
+        history.record_pairing_at(pair("/c.jpg", "/d.jpg"), true, 1_800);
+        let score = history.data.affinity_scores[0].clone();
+        assert_eq!(score.pair_count, 2);
+        assert!((score.avg_duration_secs - 400.0).abs() < 0.001);
+
+        history.recompute_affinity();
+        let rebuilt = &history.data.affinity_scores[0];
+        assert_eq!(rebuilt.pair_count, score.pair_count);
+        assert!((rebuilt.avg_duration_secs - score.avg_duration_secs).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_merge_data_concatenates_and_rebuilds() {
+        let mut history = PairingHistory::new(100);
+        history.replace_data(PairingHistoryData {
+            records: vec![record(1, &[("DP-1", "/a.jpg"), ("DP-2", "/b.jpg")])],
+            affinity_scores: Vec::new(),
+        });
+        history.recompute_affinity();
+
+        history.merge_data(PairingHistoryData {
+            records: vec![
+                record(1, &[("DP-1", "/a.jpg"), ("DP-2", "/b.jpg")]), // duplicate
+                record(2, &[("DP-1", "/a.jpg"), ("DP-2", "/b.jpg")]),
+            ],
+            affinity_scores: Vec::new(),
+        });
+
+        assert_eq!(
+            history.record_count(),
+            2,
+            "Duplicate record should be skipped"
+        );
+        assert_eq!(history.affinity_count(), 1);
+        let score = &history.data.affinity_scores[0];
+        assert_eq!(score.pair_count, 2);
+        assert!(
+            (score.score - PairingHistory::calculate_base_score(2, 600.0)).abs() < 0.001,
+            "Score should be recomputed from the combined pair count"
+        );
+    }
 }
@@ -1,3 +1,5 @@
+use crate::app::Config;
+use crate::pairing::PairingStyleMode;
 use crate::swww::ResizeMode;
 use crate::wallpaper::MatchMode;
 use anyhow::{Context, Result};
@@ -22,6 +24,15 @@ pub struct Profile {
     pub transition_duration: Option<f32>,
     #[serde(default)]
     pub recursive: Option<bool>,
+    /// Overrides `pairing.enabled`
+    #[serde(default)]
+    pub pairing_enabled: Option<bool>,
+    /// Overrides `pairing.style_mode`
+    #[serde(default)]
+    pub pairing_style_mode: Option<PairingStyleMode>,
+    /// Overrides `time_profiles.enabled`
+    #[serde(default)]
+    pub time_profiles_enabled: Option<bool>,
 }
 
 impl Profile {
@@ -34,10 +45,85 @@ impl Profile {
             transition_type: None,
             transition_duration: None,
             recursive: None,
+            pairing_enabled: None,
+            pairing_style_mode: None,
+            time_profiles_enabled: None,
+        }
+    }
+
+    /// Overlay this profile's settings onto a loaded config
+    pub fn apply_to(&self, config: &mut Config) {
+        if let Some(dir) = &self.directory {
+            config.wallpaper.directory = dir.clone();
+        }
+        if let Some(mode) = self.match_mode {
+            config.display.match_mode = mode;
+        }
+        if let Some(mode) = self.resize_mode {
+            config.display.resize_mode = mode;
+        }
+        if let Some(transition) = &self.transition_type {
+            config.transition.transition_type = transition.clone();
+        }
+        if let Some(duration) = self.transition_duration {
+            config.transition.duration = duration;
+        }
+        if let Some(recursive) = self.recursive {
+            config.wallpaper.recursive = recursive;
+        }
+        if let Some(enabled) = self.pairing_enabled {
+            config.pairing.enabled = enabled;
+        }
+        if let Some(mode) = self.pairing_style_mode {
+            config.pairing.style_mode = mode;
+        }
+        if let Some(enabled) = self.time_profiles_enabled {
+            config.time_profiles.enabled = enabled;
+        }
+    }
+
+    /// Undo `apply_to`: put `base`'s values back for every setting this
+    /// profile overrides, so saving the config doesn't bake the profile in
+    pub fn restore_from(&self, config: &mut Config, base: &Config) {
+        if self.directory.is_some() {
+            config.wallpaper.directory = base.wallpaper.directory.clone();
+        }
+        if self.match_mode.is_some() {
+            config.display.match_mode = base.display.match_mode;
+        }
+        if self.resize_mode.is_some() {
+            config.display.resize_mode = base.display.resize_mode;
+        }
+        if self.transition_type.is_some() {
+            config.transition.transition_type = base.transition.transition_type.clone();
+        }
+        if self.transition_duration.is_some() {
+            config.transition.duration = base.transition.duration;
+        }
+        if self.recursive.is_some() {
+            config.wallpaper.recursive = base.wallpaper.recursive;
+        }
+        if self.pairing_enabled.is_some() {
+            config.pairing.enabled = base.pairing.enabled;
+        }
+        if self.pairing_style_mode.is_some() {
+            config.pairing.style_mode = base.pairing.style_mode;
+        }
+        if self.time_profiles_enabled.is_some() {
+            config.time_profiles.enabled = base.time_profiles.enabled;
         }
     }
 }
 
+/// Parse a profile boolean: true/false, yes/no, on/off or 1/0
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
 /// Manages multiple profiles
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ProfileManager {
@@ -82,7 +168,6 @@ impl ProfileManager {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn get(&self, name: &str) -> Option<&Profile> {
         self.profiles.get(name)
     }
@@ -113,7 +198,6 @@ impl ProfileManager {
         }
     }
 
-    #[allow(dead_code)]
     pub fn active(&self) -> Option<&Profile> {
         self.active_profile
             .as_ref()
@@ -147,6 +231,15 @@ pub fn cmd_profile_list() -> Result<()> {
         if let Some(mode) = &profile.resize_mode {
             println!("    resize_mode: {}", mode.display_name());
         }
+        if let Some(enabled) = profile.pairing_enabled {
+            println!("    pairing.enabled: {}", enabled);
+        }
+        if let Some(mode) = profile.pairing_style_mode {
+            println!("    pairing.style_mode: {}", mode.display_name());
+        }
+        if let Some(enabled) = profile.time_profiles_enabled {
+            println!("    time_profiles.enabled: {}", enabled);
+        }
         println!();
     }
 
@@ -236,15 +329,9 @@ pub fn cmd_profile_set(name: &str, key: &str, value: &str) -> Result<()> {
             println!("✓ Set {}.match_mode = {:?}", name, mode);
         }
         "resize_mode" | "resize" => {
-            let mode = match value.to_lowercase().as_str() {
-                "crop" => ResizeMode::Crop,
-                "fit" => ResizeMode::Fit,
-                "no" | "center" => ResizeMode::No,
-                "stretch" => ResizeMode::Stretch,
-                _ => {
-                    println!("Invalid resize_mode. Use: crop, fit, center, or stretch");
-                    return Ok(());
-                }
+            let Some(mode) = ResizeMode::parse(value) else {
+                println!("Invalid resize_mode. Use: crop, fit, center, or stretch");
+                return Ok(());
             };
             profile.resize_mode = Some(mode);
             println!("✓ Set {}.resize_mode = {}", name, mode.display_name());
@@ -254,15 +341,47 @@ pub fn cmd_profile_set(name: &str, key: &str, value: &str) -> Result<()> {
             println!("✓ Set {}.transition = {}", name, value);
         }
         "recursive" => {
-            let recursive = value
-                .parse::<bool>()
-                .unwrap_or(value == "1" || value == "yes");
+            let Some(recursive) = parse_bool(value) else {
+                println!("Invalid recursive. Use: true or false");
+                return Ok(());
+            };
             profile.recursive = Some(recursive);
             println!("✓ Set {}.recursive = {}", name, recursive);
         }
+        "pairing.enabled" => {
+            let Some(enabled) = parse_bool(value) else {
+                println!("Invalid pairing.enabled. Use: true or false");
+                return Ok(());
+            };
+            profile.pairing_enabled = Some(enabled);
+            println!("✓ Set {}.pairing.enabled = {}", name, enabled);
+        }
+        "pairing.style_mode" => {
+            let Some(mode) = PairingStyleMode::parse(value) else {
+                println!("Invalid pairing.style_mode. Use: off, soft, or strict");
+                return Ok(());
+            };
+            profile.pairing_style_mode = Some(mode);
+            println!(
+                "✓ Set {}.pairing.style_mode = {}",
+                name,
+                mode.display_name()
+            );
+        }
+        "time_profiles.enabled" => {
+            let Some(enabled) = parse_bool(value) else {
+                println!("Invalid time_profiles.enabled. Use: true or false");
+                return Ok(());
+            };
+            profile.time_profiles_enabled = Some(enabled);
+            println!("✓ Set {}.time_profiles.enabled = {}", name, enabled);
+        }
         _ => {
             println!("Unknown setting: {}", key);
-            println!("Available: directory, match_mode, resize_mode, transition, recursive");
+            println!(
+                "Available: directory, match_mode, resize_mode, transition, recursive, \
+                 pairing.enabled, pairing.style_mode, time_profiles.enabled"
+            );
             return Ok(());
         }
     }
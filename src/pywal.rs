@@ -5,7 +5,12 @@
 //! - ~/.cache/wal/colors (newline-separated hex)
 //! - ~/.cache/wal/colors.sh (shell variables)
 //! - ~/.cache/wal/colors.Xresources (X11 format)
+//! - ~/.cache/wal/colors-kitty.conf (kitty `include` file)
+//!
+//! User templates with `{color0}`..`{color15}`, `{background}`, `{foreground}`,
+//! `{cursor}` and `{wallpaper}` placeholders can be rendered alongside.
 
+use crate::wallpaper::ScanConfig;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -47,6 +52,30 @@ pub struct WalColorMap {
     pub color15: String,
 }
 
+impl WalColorMap {
+    /// All 16 colors in order (color0..color15)
+    pub fn as_array(&self) -> [&str; 16] {
+        [
+            &self.color0,
+            &self.color1,
+            &self.color2,
+            &self.color3,
+            &self.color4,
+            &self.color5,
+            &self.color6,
+            &self.color7,
+            &self.color8,
+            &self.color9,
+            &self.color10,
+            &self.color11,
+            &self.color12,
+            &self.color13,
+            &self.color14,
+            &self.color15,
+        ]
+    }
+}
+
 /// Get pywal cache directory
 pub fn wal_cache_dir() -> PathBuf {
     dirs::cache_dir()
@@ -63,13 +92,17 @@ pub fn wal_cache_dir() -> PathBuf {
 /// - color8-14: lighter variants
 /// - color15: brightest (white)
 pub fn generate_palette(dominant_colors: &[String], wallpaper_path: &Path) -> WalColors {
-    // Ensure we have at least some colors
-    let colors: Vec<&str> = dominant_colors
-        .iter()
-        .map(|s| s.as_str())
-        .chain(std::iter::repeat("#808080"))
-        .take(5)
-        .collect();
+    // Ensure five colors: merged palettes can be shorter, so repeat what's there
+    let colors: Vec<&str> = if dominant_colors.is_empty() {
+        vec!["#808080"; 5]
+    } else {
+        dominant_colors
+            .iter()
+            .map(|s| s.as_str())
+            .cycle()
+            .take(5)
+            .collect()
+    };
 
     // Sort by luminance to find darkest/lightest
     let mut sorted_colors: Vec<(f32, &str)> = colors.iter().map(|&c| (luminance(c), c)).collect();
@@ -241,9 +274,68 @@ color15='{}'
     );
     fs::write(&xres_path, &xres)?;
 
+    // Export colors-kitty.conf (include from kitty.conf)
+    let kitty_path = cache_dir.join("colors-kitty.conf");
+    let mut kitty = format!(
+        "# Generated by FrostWall\nforeground {}\nbackground {}\ncursor {}\n",
+        colors.special.foreground, colors.special.background, colors.special.cursor
+    );
+    for (i, color) in colors.colors.as_array().iter().enumerate() {
+        kitty.push_str(&format!("color{} {}\n", i, color));
+    }
+    fs::write(&kitty_path, &kitty)?;
+
     Ok(cache_dir)
 }
 
+/// Fill `{color0}`..`{color15}`, `{background}`, `{foreground}`, `{cursor}`
+/// and `{wallpaper}` placeholders. Unknown placeholders are left untouched.
+pub fn render_template(template: &str, colors: &WalColors) -> String {
+    let mut out = template
+        .replace("{background}", &colors.special.background)
+        .replace("{foreground}", &colors.special.foreground)
+        .replace("{cursor}", &colors.special.cursor)
+        .replace("{wallpaper}", &colors.wallpaper);
+    for (i, color) in colors.colors.as_array().iter().enumerate() {
+        out = out.replace(&format!("{{color{}}}", i), color);
+    }
+    out
+}
+
+/// Render every file in `template_dir` into `output_dir` under the same name.
+/// Returns the written paths.
+pub fn render_templates(
+    template_dir: &Path,
+    output_dir: &Path,
+    colors: &WalColors,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create {}", output_dir.display()))?;
+
+    let mut written = Vec::new();
+    let entries = fs::read_dir(template_dir)
+        .with_context(|| format!("Failed to read templates: {}", template_dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let template = match fs::read_to_string(&path) {
+            Ok(t) => t,
+            Err(e) => {
+                eprintln!("Warning: Skipping template {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let dest = output_dir.join(entry.file_name());
+        fs::write(&dest, render_template(&template, colors))
+            .with_context(|| format!("Failed to write {}", dest.display()))?;
+        written.push(dest);
+    }
+    written.sort();
+    Ok(written)
+}
+
 /// Apply exported colors (reload terminals, etc.)
 pub fn apply_colors() -> Result<()> {
     use std::process::Command;
@@ -330,12 +422,19 @@ fn blend(hex1: &str, hex2: &str) -> String {
 }
 
 /// CLI command to generate and export pywal colors
-pub fn cmd_pywal(wallpaper_path: &Path, apply: bool) -> Result<()> {
+pub fn cmd_pywal(
+    wallpaper_path: &Path,
+    apply: bool,
+    templates: Option<&Path>,
+    templates_out: Option<&Path>,
+    color_format: Option<crate::utils::ColorFormat>,
+    scan: &ScanConfig,
+) -> Result<()> {
     use crate::wallpaper::WallpaperCache;
 
     // Load cache to get colors
     let cache_dir = wallpaper_path.parent().unwrap_or(Path::new("."));
-    let cache = WallpaperCache::load_or_scan(cache_dir)?;
+    let cache = WallpaperCache::load_or_scan(cache_dir, scan)?;
 
     // Find the wallpaper in cache or scan it fresh
     let colors = cache
@@ -345,7 +444,7 @@ pub fn cmd_pywal(wallpaper_path: &Path, apply: bool) -> Result<()> {
         .map(|wp| wp.colors.clone())
         .unwrap_or_else(|| {
             // Not in cache, scan the file directly
-            crate::wallpaper::Wallpaper::from_path(wallpaper_path)
+            crate::wallpaper::Wallpaper::from_path(wallpaper_path, scan)
                 .map(|wp| wp.colors)
                 .unwrap_or_default()
         });
@@ -366,6 +465,26 @@ pub fn cmd_pywal(wallpaper_path: &Path, apply: bool) -> Result<()> {
     println!("  - colors");
     println!("  - colors.sh");
     println!("  - colors.Xresources");
+    println!("  - colors-kitty.conf");
+
+    if let Some(format) = color_format {
+        println!("Palette: {}", format.format_all(&colors).join(" "));
+    }
+
+    if let Some(template_dir) = templates {
+        let output_dir = templates_out.map(Path::to_path_buf).unwrap_or(cache_path);
+        let written = render_templates(template_dir, &output_dir, &palette)?;
+        println!(
+            "✓ Rendered {} template(s) to {}",
+            written.len(),
+            output_dir.display()
+        );
+        for path in &written {
+            if let Some(name) = path.file_name() {
+                println!("  - {}", name.to_string_lossy());
+            }
+        }
+    }
 
     // Apply if requested
     if apply {
@@ -383,3 +502,37 @@ pub fn generate_from_wallpaper(colors: &[String], wallpaper_path: &Path) -> Resu
     export_colors(&palette)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- render_template ---
+
+    #[test]
+    fn test_render_template_fills_placeholders() {
+        let colors = [
+            "#112233", "#aa0000", "#00aa00", "#aaaa00", "#0000aa", "#aa00aa",
+        ]
+        .map(String::from);
+        let palette = generate_palette(&colors, Path::new("/walls/frost.png"));
+
+        let rendered = render_template(
+            "bg={background} fg={foreground} c1={color1} c10={color10} c15={color15} \
+             wp={wallpaper} keep={unknown}",
+            &palette,
+        );
+
+        assert!(rendered.contains(&format!("bg={}", palette.special.background)));
+        assert!(rendered.contains(&format!("fg={}", palette.special.foreground)));
+        assert!(rendered.contains(&format!("c1={} ", palette.colors.color1)));
+        assert!(rendered.contains(&format!("c10={}", palette.colors.color10)));
+        assert!(rendered.contains(&format!("c15={}", palette.colors.color15)));
+        assert!(rendered.contains("wp=/walls/frost.png"));
+        assert!(
+            rendered.contains("keep={unknown}"),
+            "unknown placeholders stay"
+        );
+        assert!(!rendered.contains("{color"), "all color slots filled");
+    }
+}
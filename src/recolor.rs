@@ -0,0 +1,168 @@
+//! Hue-shift a wallpaper toward an accent color.
+//!
+//! The whole image is rotated around the HSL hue wheel by the same angle, so
+//! relative colors survive; saturation and lightness are left alone. The angle
+//! is the shortest turn from the image's dominant hue to the accent hue, scaled
+//! by a strength in 0-1.
+
+use crate::utils::{hex_to_hsl, hsl_to_rgb, rgb_to_hsl};
+use anyhow::{Context, Result};
+use image::{DynamicImage, RgbaImage};
+use std::path::Path;
+
+/// Palette colors less saturated than this don't count as "the" hue
+const MIN_DOMINANT_SATURATION: f32 = 0.15;
+
+/// Rotate one pixel's hue by `degrees`, keeping saturation and lightness.
+pub fn shift_hue(rgb: [u8; 3], degrees: f32) -> [u8; 3] {
+    let (h, s, l) = rgb_to_hsl(rgb[0], rgb[1], rgb[2]);
+    if s == 0.0 {
+        // Grays have no hue to rotate
+        return rgb;
+    }
+    let (r, g, b) = hsl_to_rgb(h + degrees, s, l);
+    [r, g, b]
+}
+
+/// Signed shortest angle from hue `from` to hue `to`, in (-180, 180].
+pub fn hue_delta(from: f32, to: f32) -> f32 {
+    let delta = (to - from).rem_euclid(360.0);
+    if delta > 180.0 {
+        delta - 360.0
+    } else {
+        delta
+    }
+}
+
+/// Hue of the most prominent colorful entry of a palette (ordered by weight).
+pub fn dominant_hue(colors: &[String]) -> Option<f32> {
+    colors
+        .iter()
+        .filter_map(|c| hex_to_hsl(c))
+        .find(|&(_, s, _)| s >= MIN_DOMINANT_SATURATION)
+        .map(|(h, _, _)| h)
+}
+
+/// Rotate every pixel of `img` by `degrees`. Alpha is kept.
+pub fn recolor(img: &RgbaImage, degrees: f32) -> RgbaImage {
+    let mut out = img.clone();
+    for pixel in out.pixels_mut() {
+        let [r, g, b] = shift_hue([pixel[0], pixel[1], pixel[2]], degrees);
+        pixel.0[..3].copy_from_slice(&[r, g, b]);
+    }
+    out
+}
+
+/// Write a copy of `image_path` shifted `degrees` around the hue wheel.
+/// Alpha is dropped for formats that can't store it (JPEG, BMP).
+pub fn write_recolored(image_path: &Path, degrees: f32, output: &Path) -> Result<()> {
+    let img = crate::utils::open_oriented(image_path)
+        .with_context(|| format!("Failed to open image: {}", image_path.display()))?
+        .to_rgba8();
+
+    let recolored = DynamicImage::ImageRgba8(recolor(&img, degrees));
+    let recolored = if supports_alpha(output) {
+        recolored
+    } else {
+        DynamicImage::ImageRgb8(recolored.to_rgb8())
+    };
+    recolored
+        .save(output)
+        .with_context(|| format!("Failed to write image: {}", output.display()))
+}
+
+fn supports_alpha(path: &Path) -> bool {
+    !path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| ["jpg", "jpeg", "bmp"].contains(&e.to_ascii_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- shift_hue ---
+
+    #[test]
+    fn test_shift_hue_rotates_primaries() {
+        assert_eq!(shift_hue([255, 0, 0], 120.0), [0, 255, 0]);
+        assert_eq!(shift_hue([255, 0, 0], 240.0), [0, 0, 255]);
+        assert_eq!(shift_hue([0, 0, 255], 120.0), [255, 0, 0], "wraps past 360");
+        assert_eq!(
+            shift_hue([255, 0, 0], -120.0),
+            [0, 0, 255],
+            "negative turns"
+        );
+    }
+
+    #[test]
+    fn test_shift_hue_keeps_grays_and_lightness() {
+        assert_eq!(shift_hue([128, 128, 128], 90.0), [128, 128, 128]);
+        assert_eq!(shift_hue([0, 0, 0], 90.0), [0, 0, 0]);
+
+        let shifted = shift_hue([200, 120, 80], 45.0);
+        let (_, s1, l1) = rgb_to_hsl(200, 120, 80);
+        let (_, s2, l2) = rgb_to_hsl(shifted[0], shifted[1], shifted[2]);
+        assert!((s1 - s2).abs() < 0.02);
+        assert!((l1 - l2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_shift_hue_zero_is_identity() {
+        for rgb in [[12, 200, 99], [250, 250, 1], [94, 129, 172]] {
+            assert_eq!(shift_hue(rgb, 0.0), rgb);
+        }
+    }
+
+    // --- hue_delta ---
+
+    #[test]
+    fn test_hue_delta_takes_short_way_round() {
+        assert_eq!(hue_delta(10.0, 50.0), 40.0);
+        assert_eq!(hue_delta(350.0, 10.0), 20.0);
+        assert_eq!(hue_delta(10.0, 350.0), -20.0);
+        assert_eq!(hue_delta(0.0, 180.0), 180.0);
+    }
+
+    // --- dominant_hue ---
+
+    #[test]
+    fn test_dominant_hue_skips_neutral_colors() {
+        let palette = vec![
+            "#202020".to_string(),
+            "#f0f0f0".to_string(),
+            "#0000ff".to_string(),
+            "#ff0000".to_string(),
+        ];
+        assert_eq!(dominant_hue(&palette), Some(240.0));
+        assert_eq!(dominant_hue(&["#808080".to_string()]), None);
+    }
+
+    #[test]
+    fn test_recolor_keeps_alpha() {
+        let img = RgbaImage::from_pixel(2, 2, image::Rgba([255, 0, 0, 77]));
+        let out = recolor(&img, 120.0);
+        assert_eq!(out.get_pixel(1, 1).0, [0, 255, 0, 77]);
+    }
+
+    // --- write_recolored ---
+
+    #[test]
+    fn test_write_recolored_as_jpeg() {
+        let dir = std::env::temp_dir().join("frostwall_test_recolor_jpeg");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("red.png");
+        RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 200]))
+            .save(&input)
+            .unwrap();
+
+        let output = dir.join("out.jpg");
+        write_recolored(&input, 120.0, &output).unwrap();
+        let [r, g, b] = image::open(&output).unwrap().to_rgb8().get_pixel(4, 4).0;
+        assert!(g > 200 && r < 60 && b < 60, "shifted to green: {r} {g} {b}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,289 @@
+//! Collection schedule for `frostwall watch`.
+//!
+//! `[schedule]` in config.toml maps times to collection names:
+//!
+//! ```toml
+//! [schedule]
+//! "07:00" = "morning"
+//! "0 18 * * 1-5" = "workday-evening"
+//! ```
+//!
+//! A time is either `HH:MM` (every day) or a cron-like `MIN HOUR * * DOW`
+//! where day-of-month and month must be `*` and the day of week is `*`, a
+//! number (0 or 7 = Sunday), a range like `1-5` or a comma list of those.
+//! The watch daemon applies a slot's collection when its time is crossed;
+//! at startup it applies the most recent slot that already passed.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// `[schedule]`: time spec -> collection name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScheduleConfig {
+    pub slots: BTreeMap<String, String>,
+}
+
+/// One parsed schedule entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slot {
+    /// Time as written in the config
+    pub spec: String,
+    pub time: NaiveTime,
+    /// Days of week it fires on (0 = Sunday), `None` for every day
+    pub days: Option<Vec<u32>>,
+    pub collection: String,
+}
+
+impl Slot {
+    /// Latest time at or before `now` this slot fired
+    pub fn last_before(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        (0..=7).find_map(|back| {
+            let date = now.date() - Duration::days(back);
+            let fires = self
+                .days
+                .as_ref()
+                .is_none_or(|days| days.contains(&date.weekday().num_days_from_sunday()));
+            let at = date.and_time(self.time);
+            (fires && at <= now).then_some(at)
+        })
+    }
+
+    /// Human-readable form, e.g. `07:00` or `18:00 Mon,Fri`
+    pub fn describe(&self) -> String {
+        let time = self.time.format("%H:%M").to_string();
+        match &self.days {
+            None => time,
+            Some(days) => {
+                let names: Vec<&str> = days.iter().map(|&d| DAY_NAMES[d as usize]).collect();
+                format!("{} {}", time, names.join(","))
+            }
+        }
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+impl ScheduleConfig {
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Parse every entry, sorted by time of day
+    pub fn parse(&self) -> Result<Vec<Slot>> {
+        let mut slots = self
+            .slots
+            .iter()
+            .map(|(spec, collection)| {
+                let (time, days) = parse_time_spec(spec)
+                    .with_context(|| format!("Invalid schedule time '{}'", spec))?;
+                Ok(Slot {
+                    spec: spec.clone(),
+                    time,
+                    days,
+                    collection: collection.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        slots.sort_by(|a, b| a.time.cmp(&b.time).then_with(|| a.spec.cmp(&b.spec)));
+        Ok(slots)
+    }
+}
+
+/// The slot that fired most recently at or before `now`, with when it fired
+pub fn active_slot(slots: &[Slot], now: NaiveDateTime) -> Option<(&Slot, NaiveDateTime)> {
+    slots
+        .iter()
+        .filter_map(|slot| Some((slot, slot.last_before(now)?)))
+        .max_by_key(|(_, at)| *at)
+}
+
+/// `frostwall schedule list`
+pub fn cmd_schedule_list(config: &ScheduleConfig) -> Result<()> {
+    if config.is_empty() {
+        println!("No schedule configured.");
+        println!("Map times to collections under [schedule] in config.toml, e.g. \"07:00\" = \"morning\".");
+        return Ok(());
+    }
+
+    let slots = config.parse()?;
+    let store = crate::collections::CollectionStore::load()?;
+    let active = active_slot(&slots, chrono::Local::now().naive_local()).map(|(slot, _)| slot);
+    let width = slots.iter().map(|s| s.describe().len()).max().unwrap_or(0);
+
+    println!("Schedule (applied by `frostwall watch`):");
+    for slot in &slots {
+        let marker = if active == Some(slot) { "▶" } else { " " };
+        let missing = if store.get(&slot.collection).is_none() {
+            " (collection not found)"
+        } else {
+            ""
+        };
+        println!(
+            "  {} {:<width$}  {}{}",
+            marker,
+            slot.describe(),
+            slot.collection,
+            missing,
+            width = width
+        );
+    }
+
+    Ok(())
+}
+
+/// Parse `HH:MM` or `MIN HOUR * * DOW`
+pub fn parse_time_spec(spec: &str) -> Result<(NaiveTime, Option<Vec<u32>>)> {
+    let fields: Vec<&str> = spec.split_whitespace().collect();
+    match fields.as_slice() {
+        [clock] => {
+            let time = NaiveTime::parse_from_str(clock, "%H:%M")
+                .with_context(|| format!("Expected HH:MM, got '{}'", clock))?;
+            Ok((time, None))
+        }
+        [minute, hour, dom, month, dow] => {
+            if *dom != "*" || *month != "*" {
+                bail!("Only `*` is supported for day of month and month");
+            }
+            let minute = parse_field(minute, 59, "minute")?;
+            let hour = parse_field(hour, 23, "hour")?;
+            let time = NaiveTime::from_hms_opt(hour, minute, 0).context("Invalid time")?;
+            let days = if *dow == "*" {
+                None
+            } else {
+                Some(parse_days(dow)?)
+            };
+            Ok((time, days))
+        }
+        _ => bail!("Expected HH:MM or 'MIN HOUR * * DOW'"),
+    }
+}
+
+fn parse_field(value: &str, max: u32, name: &str) -> Result<u32> {
+    let parsed: u32 = value
+        .parse()
+        .with_context(|| format!("{} must be a single number, got '{}'", name, value))?;
+    if parsed > max {
+        bail!("{} must be 0-{}, got {}", name, max, parsed);
+    }
+    Ok(parsed)
+}
+
+/// Day-of-week field: numbers, ranges and comma lists (7 is also Sunday)
+fn parse_days(field: &str) -> Result<Vec<u32>> {
+    let mut days = Vec::new();
+    for part in field.split(',') {
+        let (from, to) = match part.split_once('-') {
+            Some((from, to)) => (
+                parse_field(from, 7, "day of week")?,
+                parse_field(to, 7, "day of week")?,
+            ),
+            None => {
+                let day = parse_field(part, 7, "day of week")?;
+                (day, day)
+            }
+        };
+        if from > to {
+            bail!("Day range '{}' runs backwards", part);
+        }
+        days.extend((from..=to).map(|d| d % 7));
+    }
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-06-03 is a Monday
+        NaiveDate::from_ymd_opt(2024, 6, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn schedule(entries: &[(&str, &str)]) -> Vec<Slot> {
+        ScheduleConfig {
+            slots: entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+        .parse()
+        .unwrap()
+    }
+
+    // --- parse_time_spec ---
+
+    #[test]
+    fn test_parse_time_spec() {
+        let (time, days) = parse_time_spec("07:30").unwrap();
+        assert_eq!(time, NaiveTime::from_hms_opt(7, 30, 0).unwrap());
+        assert_eq!(days, None);
+
+        let (time, days) = parse_time_spec("0 18 * * 1-5").unwrap();
+        assert_eq!(time, NaiveTime::from_hms_opt(18, 0, 0).unwrap());
+        assert_eq!(days, Some(vec![1, 2, 3, 4, 5]));
+
+        assert_eq!(parse_time_spec("15 9 * * 6,7").unwrap().1, Some(vec![0, 6]));
+        assert_eq!(parse_time_spec("15 9 * * *").unwrap().1, None);
+
+        assert!(parse_time_spec("25:00").is_err());
+        assert!(parse_time_spec("*/5 * * * *").is_err());
+        assert!(parse_time_spec("0 9 1 * *").is_err());
+        assert!(parse_time_spec("0 9 * * 5-1").is_err());
+        assert!(parse_time_spec("morning").is_err());
+    }
+
+    // --- active_slot ---
+
+    #[test]
+    fn test_active_slot_picks_most_recent_past() {
+        let slots = schedule(&[("07:00", "morning"), ("18:00", "evening")]);
+
+        let (slot, fired) = active_slot(&slots, at(3, 12, 0)).unwrap();
+        assert_eq!(slot.collection, "morning");
+        assert_eq!(fired, at(3, 7, 0));
+
+        let (slot, _) = active_slot(&slots, at(3, 18, 0)).unwrap();
+        assert_eq!(slot.collection, "evening", "slot time itself counts");
+
+        let (slot, fired) = active_slot(&slots, at(3, 6, 59)).unwrap();
+        assert_eq!(slot.collection, "evening", "wraps to yesterday");
+        assert_eq!(fired, at(2, 18, 0));
+    }
+
+    #[test]
+    fn test_active_slot_respects_days() {
+        let slots = schedule(&[("0 9 * * 1-5", "work"), ("10:00", "daily")]);
+
+        // Monday 9:30: work fired today, daily last fired Sunday
+        let (slot, _) = active_slot(&slots, at(3, 9, 30)).unwrap();
+        assert_eq!(slot.collection, "work");
+
+        // Sunday 9:30: work last fired Friday, daily on Saturday
+        let (slot, fired) = active_slot(&slots, at(9, 9, 30)).unwrap();
+        assert_eq!(slot.collection, "daily");
+        assert_eq!(fired, at(8, 10, 0));
+
+        assert!(active_slot(&[], at(3, 9, 0)).is_none());
+    }
+
+    #[test]
+    fn test_parse_sorts_by_time() {
+        let slots = schedule(&[("22:00", "night"), ("0 6 * * *", "dawn")]);
+        let names: Vec<&str> = slots.iter().map(|s| s.collection.as_str()).collect();
+        assert_eq!(names, ["dawn", "night"]);
+        assert_eq!(slots[0].describe(), "06:00");
+        assert_eq!(
+            schedule(&[("0 9 * * 1,3", "x")])[0].describe(),
+            "09:00 Mon,Wed"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +10,32 @@ pub struct Screen {
     pub height: u32,
     pub orientation: Orientation,
     pub aspect_category: AspectCategory,
+    /// Output scale factor reported by the compositor
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Logical position of the output's top-left corner
+    #[serde(default)]
+    pub position: Option<(i32, i32)>,
+    /// Refresh rate of the current mode (Hz)
+    #[serde(default)]
+    pub refresh: Option<f32>,
+    /// Physical pixels when the compositor reports them directly (wlr-randr
+    /// modes); otherwise `width`/`height` are logical and scaled on demand
+    #[serde(default)]
+    pub pixel_size: Option<(u32, u32)>,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Serializable screen description including the live wallpaper state
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenInfo {
+    #[serde(flatten)]
+    pub screen: Screen,
+    /// Wallpaper currently displayed on this output, if known
+    pub wallpaper: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -35,9 +62,37 @@ impl Screen {
             height,
             orientation,
             aspect_category,
+            scale: default_scale(),
+            position: None,
+            refresh: None,
+            pixel_size: None,
         }
     }
 
+    /// Pixels a wallpaper is actually drawn at: logical size × scale
+    pub fn effective_size(&self) -> (u32, u32) {
+        self.pixel_size.unwrap_or_else(|| {
+            let scale = if self.scale > 0.0 { self.scale } else { 1.0 };
+            (
+                (self.width as f32 * scale).round() as u32,
+                (self.height as f32 * scale).round() as u32,
+            )
+        })
+    }
+
+    /// Attach compositor geometry details parsed alongside the size.
+    fn with_details(
+        mut self,
+        scale: Option<f32>,
+        position: Option<(i32, i32)>,
+        refresh: Option<f32>,
+    ) -> Self {
+        self.scale = scale.unwrap_or_else(default_scale);
+        self.position = position;
+        self.refresh = refresh;
+        self
+    }
+
     fn analyze_aspect(width: u32, height: u32) -> (Orientation, AspectCategory) {
         if width == 0 || height == 0 {
             return (Orientation::Landscape, AspectCategory::Square);
@@ -74,20 +129,146 @@ impl Screen {
 
 /// Detect connected screens using niri msg outputs
 pub async fn detect_screens() -> Result<Vec<Screen>> {
+    detect_screens_blocking()
+}
+
+/// Same as `detect_screens`, for callers outside the async runtime (the TUI event loop)
+pub fn detect_screens_blocking() -> Result<Vec<Screen>> {
     // Try niri first
-    if let Ok(screens) = detect_niri().await {
+    if let Ok(screens) = detect_niri() {
         return Ok(screens);
     }
 
     // Fallback to wlr-randr
-    if let Ok(screens) = detect_wlr_randr().await {
+    if let Ok(screens) = detect_wlr_randr() {
         return Ok(screens);
     }
 
     anyhow::bail!("Could not detect screens. Make sure niri or wlr-randr is available.")
 }
 
-async fn detect_niri() -> Result<Vec<Screen>> {
+/// `detect_screens`, sorted by `display.output_order`
+pub async fn detect_screens_ordered(order: &[String]) -> Result<Vec<Screen>> {
+    let mut screens = detect_screens().await?;
+    sort_by_output_order(&mut screens, order);
+    Ok(screens)
+}
+
+/// Put the screens named in `order` first, in that order; the rest keep
+/// their detection order after them.
+pub fn sort_by_output_order(screens: &mut [Screen], order: &[String]) {
+    if order.is_empty() {
+        return;
+    }
+    screens.sort_by_key(|s| {
+        order
+            .iter()
+            .position(|name| *name == s.name)
+            .unwrap_or(order.len())
+    });
+}
+
+/// Keep only the screens named in `names`, in detection order.
+///
+/// An empty `names` keeps every screen. Unknown names are an error listing
+/// the outputs that were detected.
+pub fn select_outputs(screens: Vec<Screen>, names: &[String]) -> Result<Vec<Screen>> {
+    if names.is_empty() {
+        return Ok(screens);
+    }
+
+    let missing: Vec<&str> = names
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !screens.iter().any(|s| s.name == *name))
+        .collect();
+    if !missing.is_empty() {
+        let available: Vec<&str> = screens.iter().map(|s| s.name.as_str()).collect();
+        anyhow::bail!(
+            "Output(s) not found: {}. Detected outputs: {}",
+            missing.join(", "),
+            available.join(", ")
+        );
+    }
+
+    Ok(screens
+        .into_iter()
+        .filter(|s| names.contains(&s.name))
+        .collect())
+}
+
+/// Fail with the currently connected output names if `name` is not among `screens`.
+///
+/// Saved targets (collections, pairing undo) can outlive a monitor; check before
+/// handing the name to swww, whose own error doesn't say what's wrong.
+pub fn check_output(screens: &[Screen], name: &str) -> Result<()> {
+    if screens.iter().any(|s| s.name == name) {
+        return Ok(());
+    }
+    let available: Vec<&str> = screens.iter().map(|s| s.name.as_str()).collect();
+    anyhow::bail!(
+        "Output {} is not connected. Current outputs: {}",
+        name,
+        if available.is_empty() {
+            "none".to_string()
+        } else {
+            available.join(", ")
+        }
+    )
+}
+
+/// How the connected outputs differ between two detections
+#[derive(Debug, Clone)]
+pub enum ScreenChange {
+    Added(Screen),
+    Removed(Screen),
+    /// Same output with a different mode or scale
+    Resized {
+        before: Screen,
+        after: Screen,
+    },
+}
+
+impl std::fmt::Display for ScreenChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mode = |s: &Screen| format!("{}x{} (scale {})", s.width, s.height, s.scale);
+        match self {
+            ScreenChange::Added(s) => write!(f, "+ {}: {}", s.name, mode(s)),
+            ScreenChange::Removed(s) => write!(f, "- {}", s.name),
+            ScreenChange::Resized { before, after } => {
+                write!(f, "~ {}: {} -> {}", after.name, mode(before), mode(after))
+            }
+        }
+    }
+}
+
+/// Outputs added, removed or resized going from `old` to `new`, matched by name
+pub fn diff_screens(old: &[Screen], new: &[Screen]) -> Vec<ScreenChange> {
+    let mut changes = Vec::new();
+    for before in old {
+        match new.iter().find(|s| s.name == before.name) {
+            None => changes.push(ScreenChange::Removed(before.clone())),
+            Some(after)
+                if (after.width, after.height) != (before.width, before.height)
+                    || after.scale != before.scale =>
+            {
+                changes.push(ScreenChange::Resized {
+                    before: before.clone(),
+                    after: after.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+    for after in new {
+        if !old.iter().any(|s| s.name == after.name) {
+            changes.push(ScreenChange::Added(after.clone()));
+        }
+    }
+    changes
+}
+
+fn detect_niri() -> Result<Vec<Screen>> {
     let output = Command::new("niri")
         .args(["msg", "outputs"])
         .output()
@@ -106,6 +287,9 @@ fn parse_niri_output(output: &str) -> Result<Vec<Screen>> {
     let mut current_name: Option<String> = None;
     let mut current_width: Option<u32> = None;
     let mut current_height: Option<u32> = None;
+    let mut current_scale: Option<f32> = None;
+    let mut current_position: Option<(i32, i32)> = None;
+    let mut current_refresh: Option<f32> = None;
 
     for line in output.lines() {
         let trimmed = line.trim();
@@ -114,7 +298,11 @@ fn parse_niri_output(output: &str) -> Result<Vec<Screen>> {
         if line.starts_with("Output ") {
             // Save previous screen if complete
             if let (Some(name), Some(w), Some(h)) = (&current_name, current_width, current_height) {
-                screens.push(Screen::new(name.clone(), w, h));
+                screens.push(Screen::new(name.clone(), w, h).with_details(
+                    current_scale,
+                    current_position,
+                    current_refresh,
+                ));
             }
 
             // Extract output name from parentheses: (DP-2)
@@ -125,6 +313,28 @@ fn parse_niri_output(output: &str) -> Result<Vec<Screen>> {
                 .map(String::from);
             current_width = None;
             current_height = None;
+            current_scale = None;
+            current_position = None;
+            current_refresh = None;
+        }
+
+        // Current mode line: "Current mode: 1920x1080 @ 144.000 Hz (preferred)"
+        if let Some(mode) = trimmed.strip_prefix("Current mode:") {
+            current_refresh = mode
+                .split('@')
+                .nth(1)
+                .and_then(|rate| rate.split_whitespace().next())
+                .and_then(|rate| rate.parse().ok());
+        }
+
+        // Logical position line: "Logical position: 1920, 0"
+        if let Some(pos) = trimmed.strip_prefix("Logical position:") {
+            current_position = parse_position(pos);
+        }
+
+        // Scale line: "Scale: 1.25"
+        if let Some(scale) = trimmed.strip_prefix("Scale:") {
+            current_scale = scale.trim().parse().ok();
         }
 
         // Logical size line: "Logical size: 1080x1920" (already includes rotation!)
@@ -141,7 +351,11 @@ fn parse_niri_output(output: &str) -> Result<Vec<Screen>> {
 
     // Don't forget the last screen
     if let (Some(name), Some(w), Some(h)) = (current_name, current_width, current_height) {
-        screens.push(Screen::new(name, w, h));
+        screens.push(Screen::new(name, w, h).with_details(
+            current_scale,
+            current_position,
+            current_refresh,
+        ));
     }
 
     if screens.is_empty() {
@@ -151,7 +365,7 @@ fn parse_niri_output(output: &str) -> Result<Vec<Screen>> {
     Ok(screens)
 }
 
-async fn detect_wlr_randr() -> Result<Vec<Screen>> {
+fn detect_wlr_randr() -> Result<Vec<Screen>> {
     let output = Command::new("wlr-randr")
         .output()
         .context("Failed to run wlr-randr")?;
@@ -170,6 +384,9 @@ fn parse_wlr_randr_output(output: &str) -> Result<Vec<Screen>> {
     let mut current_width: Option<u32> = None;
     let mut current_height: Option<u32> = None;
     let mut current_transform: Option<String> = None;
+    let mut current_scale: Option<f32> = None;
+    let mut current_position: Option<(i32, i32)> = None;
+    let mut current_refresh: Option<f32> = None;
 
     for line in output.lines() {
         // Output name: "DP-1 "Samsung Electric..." (or similar)
@@ -177,13 +394,23 @@ fn parse_wlr_randr_output(output: &str) -> Result<Vec<Screen>> {
             // Save previous screen if complete
             if let (Some(name), Some(w), Some(h)) = (&current_name, current_width, current_height) {
                 let (final_w, final_h) = apply_transform(w, h, current_transform.as_deref());
-                screens.push(Screen::new(name.clone(), final_w, final_h));
+                let mut screen = Screen::new(name.clone(), final_w, final_h).with_details(
+                    current_scale,
+                    current_position,
+                    current_refresh,
+                );
+                // wlr-randr reports mode pixels, not the logical size
+                screen.pixel_size = Some((final_w, final_h));
+                screens.push(screen);
             }
 
             current_name = line.split_whitespace().next().map(String::from);
             current_width = None;
             current_height = None;
             current_transform = None;
+            current_scale = None;
+            current_position = None;
+            current_refresh = None;
         }
 
         // Resolution line with "current"
@@ -197,6 +424,21 @@ fn parse_wlr_randr_output(output: &str) -> Result<Vec<Screen>> {
                     current_height = parts[1].parse().ok();
                 }
             }
+            current_refresh = trimmed
+                .split(',')
+                .nth(1)
+                .and_then(|rate| rate.split_whitespace().next())
+                .and_then(|rate| rate.parse().ok());
+        }
+
+        // Position line: "  Position: 1920,0"
+        if let Some(pos) = line.trim().strip_prefix("Position:") {
+            current_position = parse_position(pos);
+        }
+
+        // Scale line: "  Scale: 1.000000"
+        if let Some(scale) = line.trim().strip_prefix("Scale:") {
+            current_scale = scale.trim().parse().ok();
         }
 
         // Transform line: "  Transform: 90" or "  Transform: normal"
@@ -208,7 +450,13 @@ fn parse_wlr_randr_output(output: &str) -> Result<Vec<Screen>> {
     // Don't forget the last screen
     if let (Some(name), Some(w), Some(h)) = (current_name, current_width, current_height) {
         let (final_w, final_h) = apply_transform(w, h, current_transform.as_deref());
-        screens.push(Screen::new(name, final_w, final_h));
+        let mut screen = Screen::new(name, final_w, final_h).with_details(
+            current_scale,
+            current_position,
+            current_refresh,
+        );
+        screen.pixel_size = Some((final_w, final_h));
+        screens.push(screen);
     }
 
     if screens.is_empty() {
@@ -218,6 +466,12 @@ fn parse_wlr_randr_output(output: &str) -> Result<Vec<Screen>> {
     Ok(screens)
 }
 
+/// Parse an "x, y" or "x,y" position pair
+fn parse_position(s: &str) -> Option<(i32, i32)> {
+    let (x, y) = s.trim().split_once(',')?;
+    Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+}
+
 /// Apply transform rotation - swap dimensions for 90/270 degree rotations
 fn apply_transform(width: u32, height: u32, transform: Option<&str>) -> (u32, u32) {
     match transform {
@@ -230,6 +484,33 @@ fn apply_transform(width: u32, height: u32, transform: Option<&str>) -> (u32, u3
 mod tests {
     use super::*;
 
+    // --- diff_screens ---
+
+    #[test]
+    fn test_diff_screens_reports_hotplug_and_mode_changes() {
+        let old = vec![
+            Screen::new("DP-1".into(), 2560, 1440),
+            Screen::new("HDMI-A-1".into(), 1920, 1080),
+        ];
+        assert!(diff_screens(&old, &old).is_empty());
+
+        let mut scaled = Screen::new("DP-1".into(), 2560, 1440);
+        scaled.scale = 1.5;
+        let new = vec![scaled, Screen::new("DP-2".into(), 3440, 1440)];
+        let changes: Vec<String> = diff_screens(&old, &new)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                "~ DP-1: 2560x1440 (scale 1) -> 2560x1440 (scale 1.5)",
+                "- HDMI-A-1",
+                "+ DP-2: 3440x1440 (scale 1)",
+            ]
+        );
+    }
+
     #[test]
     fn test_aspect_categories() {
         // Ultrawide 21:9
@@ -379,4 +660,118 @@ DP-2 "LG Display" (connected)
         assert_eq!(screens[1].width, 1440);
         assert_eq!(screens[1].height, 2560);
     }
+
+    #[test]
+    fn test_parse_niri_output_details() {
+        let output = r#"Output "Samsung" (DP-1)
+  Current mode: 2560x1440 @ 143.912 Hz (preferred)
+  Variable refresh rate: not supported
+  Logical position: 1920, 0
+  Logical size: 2048x1152
+  Scale: 1.25
+  Transform: normal
+"#;
+        let screens = parse_niri_output(output).unwrap();
+        assert_eq!(screens.len(), 1);
+        assert_eq!(screens[0].scale, 1.25);
+        assert_eq!(screens[0].position, Some((1920, 0)));
+        assert_eq!(screens[0].refresh, Some(143.912));
+        assert_eq!(screens[0].effective_size(), (2560, 1440), "logical × scale");
+    }
+
+    #[test]
+    fn test_parse_wlr_randr_output_details() {
+        let output = r#"DP-1 "Samsung Electric Company" (connected)
+  Modes:
+    1920x1080 px, 60.000000 Hz
+    1920x1080 px, 144.000000 Hz (preferred, current)
+  Position: 0,1080
+  Transform: normal
+  Scale: 2.000000
+"#;
+        let screens = parse_wlr_randr_output(output).unwrap();
+        assert_eq!(screens.len(), 1);
+        assert_eq!(screens[0].scale, 2.0);
+        assert_eq!(screens[0].position, Some((0, 1080)));
+        assert_eq!(screens[0].refresh, Some(144.0));
+        assert_eq!(
+            screens[0].effective_size(),
+            (1920, 1080),
+            "mode pixels are already physical"
+        );
+    }
+
+    #[test]
+    fn test_screen_new_default_details() {
+        let screen = Screen::new("test".into(), 1920, 1080);
+        assert_eq!(screen.scale, 1.0);
+        assert_eq!(screen.position, None);
+        assert_eq!(screen.refresh, None);
+    }
+
+    #[test]
+    fn test_select_outputs_filters_by_name() {
+        let screens = vec![
+            Screen::new("DP-1".into(), 1920, 1080),
+            Screen::new("HDMI-A-1".into(), 2560, 1440),
+        ];
+        let all = select_outputs(screens.clone(), &[]).unwrap();
+        assert_eq!(all.len(), 2, "no names keeps every screen");
+
+        let picked = select_outputs(screens.clone(), &["HDMI-A-1".into()]).unwrap();
+        assert_eq!(picked.len(), 1);
+        assert_eq!(picked[0].name, "HDMI-A-1");
+
+        let err = select_outputs(screens, &["DP-1".into(), "DP-9".into()]).unwrap_err();
+        assert!(err.to_string().contains("DP-9"));
+    }
+
+    // --- sort_by_output_order ---
+
+    #[test]
+    fn test_sort_by_output_order() {
+        let names = |screens: &[Screen]| -> Vec<String> {
+            screens.iter().map(|s| s.name.clone()).collect()
+        };
+        let mut screens = vec![
+            Screen::new("HDMI-A-1".into(), 1920, 1080),
+            Screen::new("eDP-1".into(), 1920, 1200),
+            Screen::new("DP-1".into(), 2560, 1440),
+            Screen::new("DP-2".into(), 2560, 1440),
+        ];
+        sort_by_output_order(&mut screens, &[]);
+        assert_eq!(names(&screens), ["HDMI-A-1", "eDP-1", "DP-1", "DP-2"]);
+
+        sort_by_output_order(
+            &mut screens,
+            &["DP-1".into(), "DP-9".into(), "eDP-1".into()],
+        );
+        assert_eq!(
+            names(&screens),
+            ["DP-1", "eDP-1", "HDMI-A-1", "DP-2"],
+            "unlisted outputs keep detection order"
+        );
+    }
+
+    // --- check_output ---
+
+    #[test]
+    fn test_check_output_lists_current_outputs() {
+        let screens = vec![
+            Screen::new("DP-1".into(), 1920, 1080),
+            Screen::new("HDMI-A-1".into(), 2560, 1440),
+        ];
+        assert!(check_output(&screens, "DP-1").is_ok());
+
+        let msg = check_output(&screens, "DP-3").unwrap_err().to_string();
+        assert!(msg.contains("DP-3"), "names the missing output: {}", msg);
+        assert!(
+            msg.contains("DP-1, HDMI-A-1"),
+            "suggests connected outputs: {}",
+            msg
+        );
+
+        let msg = check_output(&[], "DP-1").unwrap_err().to_string();
+        assert!(msg.ends_with("none"), "no outputs at all: {}", msg);
+    }
 }
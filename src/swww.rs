@@ -1,13 +1,31 @@
+use crate::wallpaper::{is_gif, GifMode};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::process::Command;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Environment variable that stops frostwall from starting swww-daemon
+/// (any value except empty or "0")
+pub const NO_DAEMON_ENV: &str = "FROSTWALL_NO_DAEMON";
+
+/// Cleared by `--no-daemon` or `display.auto_start_daemon = false`
+static AUTO_START_DAEMON: AtomicBool = AtomicBool::new(true);
 
 #[derive(Debug, Clone)]
 pub struct Transition {
     pub transition_type: TransitionType,
     pub duration: f32,
     pub fps: u32,
+    /// Easing curve passed as `--transition-bezier` (e.g. ".54,0,.34,.99")
+    pub bezier: Option<String>,
+    /// Color step per frame passed as `--transition-step`
+    pub step: Option<u8>,
+    /// Wipe/wave angle in degrees passed as `--transition-angle`
+    pub angle: Option<f32>,
+    /// Grow/outer origin passed as `--transition-pos` (e.g. "center", "0.5,0.5")
+    pub pos: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -53,6 +71,17 @@ impl ResizeMode {
         }
     }
 
+    /// Parse a user-supplied mode name (crop, fit, no/center, stretch)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "crop" => Some(ResizeMode::Crop),
+            "fit" => Some(ResizeMode::Fit),
+            "no" | "center" => Some(ResizeMode::No),
+            "stretch" => Some(ResizeMode::Stretch),
+            _ => None,
+        }
+    }
+
     pub fn next(&self) -> Self {
         match self {
             ResizeMode::Crop => ResizeMode::Fit,
@@ -123,6 +152,10 @@ impl Default for Transition {
             transition_type: TransitionType::Fade,
             duration: 1.0,
             fps: 60,
+            bezier: None,
+            step: None,
+            angle: None,
+            pos: None,
         }
     }
 }
@@ -140,15 +173,54 @@ impl TransitionType {
     }
 }
 
-/// Initialize swww daemon if not running
+/// Allow or forbid `ensure_daemon` to start swww-daemon for this process
+pub fn set_auto_start_daemon(enabled: bool) {
+    AUTO_START_DAEMON.store(enabled, Ordering::Relaxed);
+}
+
+fn env_disables_daemon(value: Option<&str>) -> bool {
+    value.is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+fn auto_start_daemon() -> bool {
+    AUTO_START_DAEMON.load(Ordering::Relaxed)
+        && !env_disables_daemon(std::env::var(NO_DAEMON_ENV).ok().as_deref())
+}
+
+/// Fail early with install advice when swww isn't on `$PATH`, instead of a
+/// spawn error in the middle of an apply. swww-daemon is only required when
+/// frostwall may have to start it.
+pub fn require_installed() -> Result<()> {
+    if crate::utils::find_on_path("swww").is_none() {
+        anyhow::bail!(
+            "swww not found on PATH — install swww (https://github.com/LGFae/swww) \
+             to set wallpapers; `frostwall doctor` checks the setup"
+        );
+    }
+    if auto_start_daemon() && crate::utils::find_on_path("swww-daemon").is_none() {
+        anyhow::bail!(
+            "swww-daemon not found on PATH — it ships with swww; check your installation \
+             or start the daemon yourself and pass --no-daemon"
+        );
+    }
+    Ok(())
+}
+
+/// Initialize swww daemon if not running (or fail, if auto-start is disabled)
 pub fn ensure_daemon() -> Result<()> {
     // Check if daemon is running
     let status = Command::new("swww").arg("query").output();
 
     match status {
         Ok(output) if output.status.success() => Ok(()),
+        _ if !auto_start_daemon() => anyhow::bail!(
+            "swww-daemon is not running and auto-start is disabled \
+             (--no-daemon, {} or display.auto_start_daemon = false); start it with `swww-daemon &`",
+            NO_DAEMON_ENV
+        ),
         _ => {
             // Start daemon
+            tracing::debug!("swww query failed, starting swww-daemon");
             Command::new("swww-daemon")
                 .spawn()
                 .context("Failed to start swww-daemon")?;
@@ -161,6 +233,7 @@ pub fn ensure_daemon() -> Result<()> {
 }
 
 /// Set wallpaper on a specific output with resize options
+#[allow(dead_code)]
 pub fn set_wallpaper(output: &str, path: &Path, transition: &Transition) -> Result<()> {
     set_wallpaper_with_resize(
         output,
@@ -171,6 +244,54 @@ pub fn set_wallpaper(output: &str, path: &Path, transition: &Transition) -> Resu
     )
 }
 
+/// Set wallpaper on an output, routing GIFs to mpvpaper when `gif_mode` is
+/// animated. Any mpvpaper instance on the output is stopped before swww takes
+/// over again, otherwise its layer would keep covering the new image.
+pub fn apply_wallpaper(
+    output: &str,
+    path: &Path,
+    transition: &Transition,
+    resize_mode: ResizeMode,
+    fill_color: &FillColor,
+    gif_mode: GifMode,
+) -> Result<()> {
+    if gif_mode == GifMode::Animated {
+        if is_gif(path) {
+            return set_wallpaper_mpvpaper(output, path);
+        }
+        stop_mpvpaper(output);
+    }
+    set_wallpaper_with_resize(output, path, transition, resize_mode, fill_color)
+}
+
+/// Play an animated wallpaper on an output with mpvpaper.
+/// mpvpaper has no transitions or resize modes, so those settings are ignored.
+pub fn set_wallpaper_mpvpaper(output: &str, path: &Path) -> Result<()> {
+    stop_mpvpaper(output);
+
+    tracing::debug!("Starting mpvpaper on {} with {}", output, path.display());
+    Command::new("mpvpaper")
+        .arg("-o")
+        .arg("no-audio --loop")
+        .arg(output)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start mpvpaper (is it installed?)")?;
+
+    Ok(())
+}
+
+/// Stop any mpvpaper instance bound to an output (best-effort).
+fn stop_mpvpaper(output: &str) {
+    let _ = Command::new("pkill")
+        .arg("-f")
+        .arg(format!("^mpvpaper .* {} ", output))
+        .status();
+}
+
 /// Set wallpaper on a specific output with full control over resize behavior
 pub fn set_wallpaper_with_resize(
     output: &str,
@@ -186,18 +307,61 @@ pub fn set_wallpaper_with_resize(
         .arg("-o")
         .arg(output)
         .arg(path)
-        .arg("--resize")
-        .arg(resize_mode.as_str())
-        .arg("--fill-color")
-        .arg(fill_color.to_hex())
-        .arg("--transition-type")
-        .arg(transition.transition_type.as_str())
-        .arg("--transition-duration")
-        .arg(transition.duration.to_string())
-        .arg("--transition-fps")
-        .arg(transition.fps.to_string());
+        .args(img_args(transition, resize_mode, fill_color));
 
+    run_swww(&mut cmd)
+}
+
+/// Build the resize and transition arguments shared by every `swww img` call.
+/// Optional transition tweaks are only appended when configured.
+fn img_args(
+    transition: &Transition,
+    resize_mode: ResizeMode,
+    fill_color: &FillColor,
+) -> Vec<String> {
+    let mut args = vec![
+        "--resize".to_string(),
+        resize_mode.as_str().to_string(),
+        "--fill-color".to_string(),
+        fill_color.to_hex(),
+        "--transition-type".to_string(),
+        transition.transition_type.as_str().to_string(),
+        "--transition-duration".to_string(),
+        transition.duration.to_string(),
+        "--transition-fps".to_string(),
+        transition.fps.to_string(),
+    ];
+
+    if let Some(bezier) = &transition.bezier {
+        args.push("--transition-bezier".to_string());
+        args.push(bezier.clone());
+    }
+    if let Some(step) = transition.step {
+        args.push("--transition-step".to_string());
+        args.push(step.to_string());
+    }
+    if let Some(angle) = transition.angle {
+        args.push("--transition-angle".to_string());
+        args.push(angle.to_string());
+    }
+    if let Some(pos) = &transition.pos {
+        args.push("--transition-pos".to_string());
+        args.push(pos.clone());
+    }
+
+    args
+}
+
+/// Run a `swww img` command, failing with its stderr
+fn run_swww(cmd: &mut Command) -> Result<()> {
+    tracing::debug!("Running {:?}", cmd);
+    let started = std::time::Instant::now();
     let output = cmd.output().context("Failed to run swww")?;
+    tracing::debug!(
+        "swww exited with {} after {:?}",
+        output.status,
+        started.elapsed()
+    );
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -226,23 +390,117 @@ pub fn set_wallpaper_all_with_resize(
     let mut cmd = Command::new("swww");
     cmd.arg("img")
         .arg(path)
-        .arg("--resize")
-        .arg(resize_mode.as_str())
-        .arg("--fill-color")
-        .arg(fill_color.to_hex())
-        .arg("--transition-type")
-        .arg(transition.transition_type.as_str())
-        .arg("--transition-duration")
-        .arg(transition.duration.to_string())
-        .arg("--transition-fps")
-        .arg(transition.fps.to_string());
+        .args(img_args(transition, resize_mode, fill_color));
 
-    let output = cmd.output().context("Failed to run swww")?;
+    run_swww(&mut cmd)
+}
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("swww failed: {}", stderr);
+/// Query swww for the image currently displayed on each output.
+///
+/// Returns an empty map when the daemon isn't running, so callers can treat
+/// the live state as best-effort.
+pub fn query_current() -> HashMap<String, PathBuf> {
+    match Command::new("swww").arg("query").output() {
+        Ok(output) if output.status.success() => {
+            parse_query_output(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
     }
+}
 
-    Ok(())
+/// Parse `swww query` lines such as
+/// `DP-1: 1920x1080, scale: 1, currently displaying: image: /path/to/img.png`
+/// (newer swww versions prefix each line with `: `).
+fn parse_query_output(output: &str) -> HashMap<String, PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches(':').trim_start();
+            let (name, rest) = line.split_once(':')?;
+            let (_, image) = rest.split_once("image: ")?;
+            let image = image.trim();
+            if image.is_empty() {
+                return None;
+            }
+            Some((name.trim().to_string(), PathBuf::from(image)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- ensure_daemon ---
+
+    #[test]
+    fn test_env_disables_daemon_values() {
+        assert!(!env_disables_daemon(None));
+        assert!(!env_disables_daemon(Some("")));
+        assert!(!env_disables_daemon(Some("0")));
+        assert!(env_disables_daemon(Some("1")));
+        assert!(env_disables_daemon(Some("yes")));
+    }
+
+    // --- img_args ---
+
+    #[test]
+    fn test_img_args_defaults_have_no_extras() {
+        let args = img_args(
+            &Transition::default(),
+            ResizeMode::Crop,
+            &FillColor::black(),
+        );
+        assert!(args.windows(2).any(|w| w == ["--transition-type", "fade"]));
+        assert!(
+            !args.iter().any(|a| a == "--transition-bezier"
+                || a == "--transition-step"
+                || a == "--transition-angle"
+                || a == "--transition-pos"),
+            "Unset options should not be passed to swww: {:?}",
+            args
+        );
+    }
+
+    #[test]
+    fn test_img_args_include_extras_when_set() {
+        let transition = Transition {
+            transition_type: TransitionType::Wipe,
+            bezier: Some(".54,0,.34,.99".to_string()),
+            step: Some(90),
+            angle: Some(30.0),
+            pos: Some("0.5,0.5".to_string()),
+            ..Transition::default()
+        };
+        let args = img_args(&transition, ResizeMode::Fit, &FillColor::black());
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["--transition-bezier", ".54,0,.34,.99"]));
+        assert!(args.windows(2).any(|w| w == ["--transition-step", "90"]));
+        assert!(args.windows(2).any(|w| w == ["--transition-angle", "30"]));
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["--transition-pos", "0.5,0.5"]));
+    }
+
+    // --- parse_query_output ---
+
+    #[test]
+    fn test_parse_query_output_images() {
+        let output = "DP-1: 1920x1080, scale: 1, currently displaying: image: /wp/a.png\n\
+                      : HDMI-A-1: 2560x1440, scale: 1, currently displaying: image: /wp/b c.jpg\n";
+        let current = parse_query_output(output);
+        assert_eq!(current.len(), 2);
+        assert_eq!(current.get("DP-1"), Some(&PathBuf::from("/wp/a.png")));
+        assert_eq!(current.get("HDMI-A-1"), Some(&PathBuf::from("/wp/b c.jpg")));
+    }
+
+    #[test]
+    fn test_parse_query_output_color_is_skipped() {
+        let output = "DP-1: 1920x1080, scale: 1, currently displaying: color: 000000\n";
+        assert!(
+            parse_query_output(output).is_empty(),
+            "Solid colors have no wallpaper path"
+        );
+    }
 }
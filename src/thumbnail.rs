@@ -6,16 +6,30 @@ use std::fs::{self, File};
 use std::hash::{Hash, Hasher};
 use std::io::BufWriter;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-// Higher resolution for crisp thumbnails
+// Higher resolution for crisp thumbnails (defaults for `thumbnails.width`/`height`)
 pub const THUMB_WIDTH: u32 = 800;
 pub const THUMB_HEIGHT: u32 = 600;
 
 // JPEG quality (0-100) - 92 is high quality with good compression
 const JPEG_QUALITY: u8 = 92;
 
+/// Temp files older than this are leftovers of an interrupted write
+const STALE_TMP_AGE: std::time::Duration = std::time::Duration::from_secs(600);
+
+/// Distinguishes the temporary files of threads generating thumbnails at once
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
 pub struct ThumbnailCache {
     cache_dir: PathBuf,
+    /// Sources larger than this on their longer side are not decoded (0 = no limit)
+    max_decode_dimension: u32,
+    /// Bounding box new thumbnails are fitted into
+    width: u32,
+    height: u32,
+    quality: u8,
 }
 
 impl ThumbnailCache {
@@ -23,12 +37,34 @@ impl ThumbnailCache {
         let cache_dir = directories::ProjectDirs::from("com", "mrmattias", "frostwall")
             .map(|dirs| dirs.cache_dir().to_path_buf())
             .unwrap_or_else(|| PathBuf::from("/tmp/frostwall"))
-            .join("thumbs_v2"); // New version for higher quality
+            .join("thumbs_v3"); // v3: EXIF orientation applied
 
         // Ensure cache directory exists
         let _ = fs::create_dir_all(&cache_dir);
 
-        Self { cache_dir }
+        Self {
+            cache_dir,
+            max_decode_dimension: 0,
+            width: THUMB_WIDTH,
+            height: THUMB_HEIGHT,
+            quality: JPEG_QUALITY,
+        }
+    }
+
+    /// Generate thumbnails within `width` x `height` at JPEG `quality`
+    /// (`[thumbnails]` in config.toml). Existing thumbnails keep their size
+    /// until regenerated.
+    pub fn with_size(mut self, width: u32, height: u32, quality: u8) -> Self {
+        self.width = width.max(1);
+        self.height = height.max(1);
+        self.quality = quality.clamp(1, 100);
+        self
+    }
+
+    /// Refuse to thumbnail images larger than `max` pixels (see `scan.max_decode_dimension`)
+    pub fn with_max_decode_dimension(mut self, max: u32) -> Self {
+        self.max_decode_dimension = max;
+        self
     }
 
     /// Generate a hash-based filename for the thumbnail
@@ -66,8 +102,11 @@ impl ThumbnailCache {
             return Ok(thumb_path);
         }
 
-        // Load source image
-        let src_image = image::open(source_path)
+        crate::utils::check_decode_size(source_path, self.max_decode_dimension)
+            .with_context(|| format!("Skipping thumbnail: {}", source_path.display()))?;
+
+        // Load source image, upright per its EXIF orientation
+        let src_image = crate::utils::open_still(source_path)
             .with_context(|| format!("Failed to open image: {}", source_path.display()))?;
 
         // Convert to RGBA8
@@ -76,7 +115,7 @@ impl ThumbnailCache {
 
         // Calculate target dimensions maintaining aspect ratio
         let (dst_width, dst_height) =
-            Self::fit_dimensions(src_width, src_height, THUMB_WIDTH, THUMB_HEIGHT);
+            Self::fit_dimensions(src_width, src_height, self.width, self.height);
 
         // Create fast_image_resize source image
         let src_fir = Image::from_vec_u8(
@@ -107,8 +146,20 @@ impl ThumbnailCache {
         // Apply unsharp mask for crispness
         apply_unsharp_mask(&mut result_image, 0.5, 1.0);
 
-        // Save as high-quality JPEG
-        save_as_jpeg(&result_image, &thumb_path, JPEG_QUALITY)?;
+        // Save as high-quality JPEG beside the final name, then rename: another
+        // worker may be generating the same thumbnail, and readers must never
+        // open a half-written file
+        let tmp_path = thumb_path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        save_as_jpeg(&result_image, &tmp_path, self.quality)?;
+        if let Err(e) = fs::rename(&tmp_path, &thumb_path) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e)
+                .with_context(|| format!("Failed to save thumbnail: {}", thumb_path.display()));
+        }
 
         Ok(thumb_path)
     }
@@ -143,8 +194,38 @@ impl ThumbnailCache {
         (dst_w.max(1), dst_h.max(1))
     }
 
+    /// Delete thumbnails that belong to none of `sources`: their wallpaper
+    /// was removed, or changed so that it hashes to a new name. Leftover
+    /// temp files of interrupted writes go too. Returns how many were removed.
+    pub fn prune<'a>(&self, sources: impl IntoIterator<Item = &'a Path>) -> Result<usize> {
+        let keep: std::collections::HashSet<PathBuf> = sources
+            .into_iter()
+            .map(|source| self.thumb_filename(source))
+            .collect();
+
+        let entries = fs::read_dir(&self.cache_dir)
+            .with_context(|| format!("Failed to read {}", self.cache_dir.display()))?;
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let orphan = match path.extension().and_then(|e| e.to_str()) {
+                Some("jpg") => !keep.contains(&path),
+                Some("tmp") => entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age >= STALE_TMP_AGE),
+                _ => false,
+            };
+            if orphan && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
     /// Clear the thumbnail cache
-    #[allow(dead_code)]
     pub fn clear(&self) -> Result<()> {
         if self.cache_dir.exists() {
             fs::remove_dir_all(&self.cache_dir)?;
@@ -154,7 +235,6 @@ impl ThumbnailCache {
     }
 
     /// Get cache directory path
-    #[allow(dead_code)]
     pub fn cache_dir(&self) -> &Path {
         &self.cache_dir
     }
@@ -2,10 +2,20 @@
 //!
 //! Automatically select wallpapers based on time of day, preferring
 //! appropriate brightness levels and tags for each period.
+//!
+//! Periods follow fixed clock bands unless a `location` is configured, in
+//! which case they follow the sun: morning from sunrise to solar noon,
+//! afternoon until an hour before sunset, evening until two hours after.
 
-use chrono::{Local, Timelike};
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, Timelike, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Evening starts this many hours before sunset
+const EVENING_BEFORE_SUNSET_HOURS: f64 = 1.0;
+/// Night starts this many hours after sunset
+const NIGHT_AFTER_SUNSET_HOURS: f64 = 2.0;
+
 /// Time period of day
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -17,10 +27,23 @@ pub enum TimePeriod {
 }
 
 impl TimePeriod {
-    /// Get the current time period
-    pub fn current() -> Self {
-        let hour = Local::now().hour();
-        Self::from_hour(hour)
+    /// Get the current time period, from the sun when a location is given
+    pub fn current(location: Option<(f64, f64)>) -> Self {
+        match location {
+            Some((lat, lon)) => solar_period(lat, lon, Utc::now()),
+            None => Self::from_hour(Local::now().hour()),
+        }
+    }
+
+    /// Parse a period name (morning, afternoon, evening, night)
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "morning" => Some(TimePeriod::Morning),
+            "afternoon" => Some(TimePeriod::Afternoon),
+            "evening" => Some(TimePeriod::Evening),
+            "night" => Some(TimePeriod::Night),
+            _ => None,
+        }
     }
 
     /// Get time period from hour (0-23)
@@ -67,6 +90,59 @@ pub struct TimeProfileSettings {
     pub tag_weight: f32,
 }
 
+impl TimeProfileSettings {
+    /// Set one field from a CLI string, validating ranges.
+    ///
+    /// Keys: `brightness_range` ("min,max"), `preferred_tags` ("a,b,c"),
+    /// `brightness_weight`, `tag_weight` (0.0-1.0).
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "brightness_range" | "brightness" => {
+                let parts: Vec<&str> = value.split(',').map(str::trim).collect();
+                let [min, max] = parts.as_slice() else {
+                    bail!("brightness_range expects 'min,max', e.g. 0.2,0.6");
+                };
+                let min = parse_unit(min, "brightness_range")?;
+                let max = parse_unit(max, "brightness_range")?;
+                if min > max {
+                    bail!("brightness_range min ({}) exceeds max ({})", min, max);
+                }
+                self.brightness_range = (min, max);
+            }
+            "preferred_tags" | "tags" => {
+                self.preferred_tags = value
+                    .split(',')
+                    .map(|t| t.trim().to_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect();
+            }
+            "brightness_weight" => {
+                self.brightness_weight = parse_unit(value, "brightness_weight")?;
+            }
+            "tag_weight" => {
+                self.tag_weight = parse_unit(value, "tag_weight")?;
+            }
+            _ => bail!(
+                "Unknown key '{}'. Available: brightness_range, preferred_tags, brightness_weight, tag_weight",
+                key
+            ),
+        }
+        Ok(())
+    }
+}
+
+/// Parse a value that must lie in 0.0-1.0
+fn parse_unit(value: &str, key: &str) -> Result<f32> {
+    let parsed: f32 = value
+        .trim()
+        .parse()
+        .with_context(|| format!("{} expects a number, got '{}'", key, value))?;
+    if !(0.0..=1.0).contains(&parsed) {
+        bail!("{} must be between 0.0 and 1.0, got {}", key, parsed);
+    }
+    Ok(parsed)
+}
+
 impl Default for TimeProfileSettings {
     fn default() -> Self {
         Self {
@@ -82,6 +158,10 @@ impl Default for TimeProfileSettings {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimeProfiles {
     pub enabled: bool,
+    /// `[latitude, longitude]` in degrees (north/east positive). When set,
+    /// periods follow local sunrise and sunset instead of fixed hours.
+    #[serde(default)]
+    pub location: Option<(f64, f64)>,
     pub morning: TimeProfileSettings,
     pub afternoon: TimeProfileSettings,
     pub evening: TimeProfileSettings,
@@ -92,6 +172,7 @@ impl Default for TimeProfiles {
     fn default() -> Self {
         Self {
             enabled: false,
+            location: None,
             morning: TimeProfileSettings {
                 brightness_range: (0.5, 0.9),
                 preferred_tags: vec!["nature".into(), "bright".into(), "pastel".into()],
@@ -121,9 +202,14 @@ impl Default for TimeProfiles {
 }
 
 impl TimeProfiles {
+    /// Get the current time period, honoring the configured location
+    pub fn current_period(&self) -> TimePeriod {
+        TimePeriod::current(self.location)
+    }
+
     /// Get settings for the current time period
     pub fn current_settings(&self) -> &TimeProfileSettings {
-        self.settings_for(TimePeriod::current())
+        self.settings_for(self.current_period())
     }
 
     /// Get settings for a specific time period
@@ -136,6 +222,16 @@ impl TimeProfiles {
         }
     }
 
+    /// Get mutable settings for a specific time period
+    pub fn settings_for_mut(&mut self, period: TimePeriod) -> &mut TimeProfileSettings {
+        match period {
+            TimePeriod::Morning => &mut self.morning,
+            TimePeriod::Afternoon => &mut self.afternoon,
+            TimePeriod::Evening => &mut self.evening,
+            TimePeriod::Night => &mut self.night,
+        }
+    }
+
     /// Score a wallpaper based on current time profile
     /// Returns a score from 0.0 to 1.0
     pub fn score_wallpaper(&self, colors: &[String], tags: &[String]) -> f32 {
@@ -197,6 +293,90 @@ impl TimeProfiles {
     }
 }
 
+/// Sun events for one solar day, as Julian dates
+enum SunEvents {
+    /// Sun rises and sets
+    Normal { rise: f64, transit: f64, set: f64 },
+    /// Sun stays above the horizon (midnight sun)
+    AlwaysUp { transit: f64 },
+    /// Sun stays below the horizon (polar night)
+    AlwaysDown,
+}
+
+/// Julian date for a UTC timestamp
+fn julian_date(now: DateTime<Utc>) -> f64 {
+    now.timestamp() as f64 / 86400.0 + 2_440_587.5
+}
+
+/// Sunrise, solar noon and sunset for the solar day nearest to `jd`.
+///
+/// Uses the standard sunrise equation (NOAA approximation), accurate to
+/// a minute or two, which is plenty for picking a period.
+fn sun_events(lat: f64, lon: f64, jd: f64) -> SunEvents {
+    const J2000: f64 = 2_451_545.0;
+    // Day number whose solar noon is closest to `jd`
+    let n = (jd - J2000 - 0.0008 + lon / 360.0).round();
+    let mean_solar_time = n + 0.0008 - lon / 360.0;
+
+    let mean_anomaly = (357.5291 + 0.985_600_28 * mean_solar_time).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.0200 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_lon = (mean_anomaly + center + 180.0 + 102.9372).rem_euclid(360.0);
+    let l = ecliptic_lon.to_radians();
+
+    let transit = J2000 + mean_solar_time + 0.0053 * m.sin() - 0.0069 * (2.0 * l).sin();
+
+    let sin_decl = l.sin() * 23.4397f64.to_radians().sin();
+    let cos_decl = sin_decl.asin().cos();
+    let phi = lat.to_radians();
+    // -0.833° accounts for refraction and the solar disc radius
+    let cos_hour_angle =
+        ((-0.833f64).to_radians().sin() - phi.sin() * sin_decl) / (phi.cos() * cos_decl);
+
+    if cos_hour_angle < -1.0 {
+        SunEvents::AlwaysUp { transit }
+    } else if cos_hour_angle > 1.0 {
+        SunEvents::AlwaysDown
+    } else {
+        let half_day = cos_hour_angle.acos().to_degrees() / 360.0;
+        SunEvents::Normal {
+            rise: transit - half_day,
+            transit,
+            set: transit + half_day,
+        }
+    }
+}
+
+/// Time period at `now` for a location, based on computed sun times.
+///
+/// Morning runs from sunrise to solar noon, afternoon until an hour before
+/// sunset, evening until two hours after sunset, and night otherwise. During
+/// polar night it is always night; during midnight sun the fixed bands are
+/// applied to local solar time.
+pub fn solar_period(lat: f64, lon: f64, now: DateTime<Utc>) -> TimePeriod {
+    let jd = julian_date(now);
+    match sun_events(lat, lon, jd) {
+        SunEvents::AlwaysDown => TimePeriod::Night,
+        SunEvents::AlwaysUp { transit } => {
+            let solar_hour = (12.0 + (jd - transit) * 24.0).rem_euclid(24.0);
+            TimePeriod::from_hour(solar_hour as u32)
+        }
+        SunEvents::Normal { rise, transit, set } => {
+            let evening_start = set - EVENING_BEFORE_SUNSET_HOURS / 24.0;
+            let night_start = set + NIGHT_AFTER_SUNSET_HOURS / 24.0;
+            if jd < rise || jd >= night_start {
+                TimePeriod::Night
+            } else if jd < transit {
+                TimePeriod::Morning
+            } else if jd < evening_start {
+                TimePeriod::Afternoon
+            } else {
+                TimePeriod::Evening
+            }
+        }
+    }
+}
+
 /// Get wallpapers sorted by time profile score
 pub fn sort_by_time_profile<'a>(
     wallpapers: &'a [crate::wallpaper::Wallpaper],
@@ -264,6 +444,158 @@ mod tests {
         assert_eq!(TimePeriod::from_hour(23), TimePeriod::Night);
     }
 
+    // --- solar_period ---
+
+    fn utc(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        use chrono::TimeZone;
+        Utc.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    const STOCKHOLM: (f64, f64) = (59.33, 18.07);
+    const TROMSO: (f64, f64) = (69.65, 18.96);
+
+    #[test]
+    fn test_solar_period_stockholm_midsummer() {
+        let (lat, lon) = STOCKHOLM;
+        // Solar noon ~10:50 UTC, sunset ~20:08 UTC
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 5, 0)),
+            TimePeriod::Morning
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 12, 0)),
+            TimePeriod::Afternoon
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 19, 30)),
+            TimePeriod::Evening
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 23, 30)),
+            TimePeriod::Night
+        );
+    }
+
+    #[test]
+    fn test_solar_period_stockholm_winter_evening_comes_early() {
+        let (lat, lon) = STOCKHOLM;
+        // Sunrise ~07:44 UTC, sunset ~13:48 UTC
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 12, 21, 7, 0)),
+            TimePeriod::Night
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 12, 21, 9, 0)),
+            TimePeriod::Morning
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 12, 21, 15, 0)),
+            TimePeriod::Evening,
+            "16:00 local is already dusk in December"
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 12, 21, 17, 0)),
+            TimePeriod::Night
+        );
+    }
+
+    #[test]
+    fn test_solar_period_equator_equinox() {
+        // Sunrise ~06:04 UTC, sunset ~18:11 UTC at 0°N 0°E
+        assert_eq!(
+            solar_period(0.0, 0.0, utc(2024, 3, 20, 5, 30)),
+            TimePeriod::Night
+        );
+        assert_eq!(
+            solar_period(0.0, 0.0, utc(2024, 3, 20, 6, 30)),
+            TimePeriod::Morning
+        );
+        assert_eq!(
+            solar_period(0.0, 0.0, utc(2024, 3, 20, 14, 0)),
+            TimePeriod::Afternoon
+        );
+        assert_eq!(
+            solar_period(0.0, 0.0, utc(2024, 3, 20, 18, 0)),
+            TimePeriod::Evening
+        );
+    }
+
+    #[test]
+    fn test_solar_period_west_longitude() {
+        // New York: sunrise ~10:25 UTC, sunset ~00:31 UTC (next day) at midsummer
+        let (lat, lon) = (40.71, -74.01);
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 9, 0)),
+            TimePeriod::Night
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 14, 0)),
+            TimePeriod::Morning
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 20, 0)),
+            TimePeriod::Afternoon
+        );
+    }
+
+    #[test]
+    fn test_solar_period_polar_night_and_midnight_sun() {
+        let (lat, lon) = TROMSO;
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 12, 21, 11, 0)),
+            TimePeriod::Night,
+            "polar night stays night at noon"
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 11, 0)),
+            TimePeriod::Afternoon,
+            "midnight sun uses solar-time bands"
+        );
+        assert_eq!(
+            solar_period(lat, lon, utc(2024, 6, 21, 23, 0)),
+            TimePeriod::Night
+        );
+    }
+
+    // --- TimeProfileSettings::set ---
+
+    #[test]
+    fn test_settings_set_valid_values() {
+        let mut settings = TimeProfileSettings::default();
+        settings.set("brightness_range", "0.2, 0.6").unwrap();
+        settings
+            .set("preferred_tags", "Dark, space,,minimal")
+            .unwrap();
+        settings.set("tag_weight", "0.8").unwrap();
+        assert_eq!(settings.brightness_range, (0.2, 0.6));
+        assert_eq!(settings.preferred_tags, vec!["dark", "space", "minimal"]);
+        assert!((settings.tag_weight - 0.8).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn test_settings_set_rejects_invalid_values() {
+        let mut settings = TimeProfileSettings::default();
+        assert!(
+            settings.set("brightness_range", "0.7,0.2").is_err(),
+            "min > max"
+        );
+        assert!(
+            settings.set("brightness_range", "0.5").is_err(),
+            "one value"
+        );
+        assert!(
+            settings.set("brightness_weight", "1.5").is_err(),
+            "out of range"
+        );
+        assert!(settings.set("tag_weight", "lots").is_err(), "not a number");
+        assert!(settings.set("colour", "1").is_err(), "unknown key");
+        assert_eq!(
+            settings.brightness_range,
+            TimeProfileSettings::default().brightness_range,
+            "failed sets leave the value unchanged"
+        );
+    }
+
     #[test]
     fn test_time_period_names() {
         assert_eq!(TimePeriod::Morning.name(), "morning");
@@ -0,0 +1,98 @@
+//! Thumbnails drawn with `▀` cells for terminals without a graphics protocol.
+//!
+//! Each cell shows two pixels: the upper half in the foreground color and the
+//! lower half in the background color. The thumbnail workers shrink the image
+//! to a small RGB grid once; drawing only box-averages that grid down to the
+//! cells available, so it works over plain SSH at any thumbnail size.
+
+use image::{imageops::FilterType, DynamicImage, RgbImage};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+
+/// Largest grid kept per thumbnail, in pixels (two per cell vertically).
+/// Bigger than any thumbnail area, so drawing never has to scale up.
+const MAX_WIDTH: u32 = 160;
+const MAX_HEIGHT: u32 = 96;
+
+/// Downscaled pixels of one thumbnail
+#[derive(Clone)]
+pub struct Halfblocks {
+    pixels: RgbImage,
+}
+
+impl Halfblocks {
+    pub fn encode(image: &DynamicImage) -> Self {
+        let pixels = if image.width() > MAX_WIDTH || image.height() > MAX_HEIGHT {
+            image.resize(MAX_WIDTH, MAX_HEIGHT, FilterType::Triangle)
+        } else {
+            image.clone()
+        };
+        Self {
+            pixels: pixels.to_rgb8(),
+        }
+    }
+
+    /// Largest area with the image's aspect ratio inside `area`, centered.
+    /// Half blocks are assumed square (a 1:2 font).
+    fn fit(&self, area: Rect) -> Rect {
+        let (w, h) = (self.pixels.width().max(1), self.pixels.height().max(1));
+        let (cols, px_rows) = (area.width as u32, area.height as u32 * 2);
+        let (width, px_height) = if cols * h <= px_rows * w {
+            (cols, (cols * h / w).max(1))
+        } else {
+            ((px_rows * w / h).max(1), px_rows)
+        };
+        let height = px_height.div_ceil(2).min(area.height as u32);
+        Rect {
+            x: area.x + (area.width - width as u16) / 2,
+            y: area.y + (area.height - height as u16) / 2,
+            width: width as u16,
+            height: height as u16,
+        }
+    }
+
+    /// Mean color of the source pixels covering output pixel (`x`, `y`) of a
+    /// `width` x `height` grid
+    fn sample(&self, x: u32, y: u32, width: u32, height: u32) -> Color {
+        let (w, h) = (self.pixels.width(), self.pixels.height());
+        let x0 = x * w / width;
+        let x1 = ((x + 1) * w / width).max(x0 + 1).min(w);
+        let y0 = y * h / height;
+        let y1 = ((y + 1) * h / height).max(y0 + 1).min(h);
+
+        let mut sum = [0u32; 3];
+        for sy in y0..y1 {
+            for sx in x0..x1 {
+                let p = self.pixels.get_pixel(sx, sy).0;
+                for (total, channel) in sum.iter_mut().zip(p) {
+                    *total += channel as u32;
+                }
+            }
+        }
+        let count = ((x1 - x0) * (y1 - y0)).max(1);
+        Color::Rgb(
+            (sum[0] / count) as u8,
+            (sum[1] / count) as u8,
+            (sum[2] / count) as u8,
+        )
+    }
+}
+
+impl Widget for &Halfblocks {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() || self.pixels.width() == 0 || self.pixels.height() == 0 {
+            return;
+        }
+        let target = self.fit(area);
+        let (width, height) = (target.width as u32, target.height as u32 * 2);
+        for row in 0..target.height {
+            for col in 0..target.width {
+                let upper = self.sample(col as u32, row as u32 * 2, width, height);
+                let lower = self.sample(col as u32, row as u32 * 2 + 1, width, height);
+                buf[(target.x + col, target.y + row)]
+                    .set_char('▀')
+                    .set_fg(upper)
+                    .set_bg(lower);
+            }
+        }
+    }
+}
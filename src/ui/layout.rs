@@ -1,29 +1,47 @@
-use crate::app::App;
+use crate::app::{App, Thumbnail, ViewMode};
 use crate::ui::theme::FrostTheme;
 use crate::utils::ColorHarmony;
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, Paragraph},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph, Wrap},
     Frame,
 };
 use ratatui_image::StatefulImage;
 
 const THUMBNAIL_WIDTH: u16 = 48;
 const THUMBNAIL_HEIGHT: u16 = 28;
+/// Narrowest grid cell before columns get dropped
+const GRID_MIN_CELL_WIDTH: u16 = 12;
+
+/// Draw a loaded thumbnail through its graphics protocol or as half blocks
+fn render_thumbnail(f: &mut Frame, thumbnail: &mut Thumbnail, area: Rect) {
+    match thumbnail {
+        Thumbnail::Graphics(protocol) => {
+            f.render_stateful_widget(StatefulImage::new(None), area, protocol)
+        }
+        Thumbnail::Halfblocks(halfblocks) => f.render_widget(&*halfblocks, area),
+    }
+}
 
 pub fn draw(f: &mut Frame, app: &mut App) {
     let theme = app.ui.theme.clone();
     let area = f.area();
+    // Rebuilt by whichever thumbnail view draws this frame
+    app.ui.thumb_hits.clear();
 
     // Check if a popup is showing (need to skip image rendering)
     // ratatui-image renders directly to terminal, bypassing widget z-order
     // Note: show_pairing_preview renders thumbnails separately, so don't block carousel
     let popup_active = app.ui.show_help
+        || app.ui.show_info
+        || app.ui.show_history
+        || app.ui.scan.is_some()
         || app.ui.show_color_picker
         || app.pairing.history.can_undo()
-        || app.ui.command_mode;
+        || app.ui.command_mode
+        || app.ui.show_tag_editor;
 
     // Main container with frost border
     let block = Block::default()
@@ -107,7 +125,10 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         draw_carousel_single(f, app, split[0], &theme);
         draw_pairing_panel(f, app, split[1], &theme);
     } else {
-        draw_carousel(f, app, chunks[chunk_idx], &theme);
+        match app.ui.view_mode {
+            ViewMode::Carousel => draw_carousel(f, app, chunks[chunk_idx], &theme),
+            ViewMode::Grid => draw_grid(f, app, chunks[chunk_idx], &theme),
+        }
     }
     chunk_idx += 1;
 
@@ -121,14 +142,74 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     // Draw popups on top
     if app.ui.show_color_picker {
         draw_color_picker(f, app, area, &theme);
+    } else if app.ui.show_tag_editor {
+        draw_tag_editor(f, app, area, &theme);
     } else if app.ui.show_help {
         draw_help_popup(f, area, &theme);
+    } else if app.ui.show_info {
+        draw_info_popup(f, app, area, &theme);
+    } else if app.ui.show_history {
+        draw_history_popup(f, app, area, &theme);
     }
 
     // Draw undo popup (always on top if active)
     if app.pairing.history.can_undo() {
         draw_undo_popup(f, app, area, &theme);
     }
+
+    if let Some(progress) = &app.ui.scan {
+        draw_scan_popup(f, progress, area, &theme);
+    }
+}
+
+/// Progress of the first-launch library scan
+fn draw_scan_popup(
+    f: &mut Frame,
+    progress: &crate::app::ScanProgress,
+    area: Rect,
+    theme: &FrostTheme,
+) {
+    let popup_width = 54.min(area.width.saturating_sub(4));
+    let popup_height = 5.min(area.height.saturating_sub(2));
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    f.render_widget(Clear, popup_area);
+    let block = Block::default()
+        .title(format!(
+            " Scanning {}/2 · {} ",
+            progress.phase.number(),
+            progress.phase.label()
+        ))
+        .title_style(
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent_primary))
+        .style(Style::default().bg(theme.bg_dark));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1)])
+        .split(inner);
+
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(theme.accent_primary).bg(theme.bg_dark))
+        .label(format!("{}/{}", progress.done, progress.total))
+        .ratio(progress.ratio());
+    f.render_widget(gauge, rows[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled("Esc", Style::default().fg(theme.accent_primary)),
+        Span::styled(" cancel", Style::default().fg(theme.fg_muted)),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(hint, rows[1]);
 }
 
 fn draw_error(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
@@ -174,7 +255,11 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
 
     // Show current modes
     let match_mode = app.config.display.match_mode.display_name();
-    let resize_mode = app.config.display.resize_mode.display_name();
+    // A per-wallpaper override replaces the global resize mode for the selection
+    let resize_override = app.selected_wallpaper().and_then(|wp| wp.resize_override);
+    let resize_mode = resize_override
+        .unwrap_or(app.config.display.resize_mode)
+        .display_name();
     let sort_mode = app.filters.sort_mode.display_name();
 
     let mut header_spans = vec![Span::styled(
@@ -196,8 +281,16 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         ),
         Span::styled(" ", Style::default()),
         Span::styled(
-            format!("[{}]", resize_mode),
-            Style::default().fg(theme.accent_secondary),
+            if resize_override.is_some() {
+                format!("[{} ◆]", resize_mode)
+            } else {
+                format!("[{}]", resize_mode)
+            },
+            Style::default().fg(if resize_override.is_some() {
+                theme.warning
+            } else {
+                theme.accent_secondary
+            }),
         ),
         Span::styled(" ", Style::default()),
         Span::styled(
@@ -206,6 +299,15 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         ),
     ]);
 
+    // Pairing style indicator
+    if app.config.pairing.enabled {
+        header_spans.push(Span::styled(" ", Style::default()));
+        header_spans.push(Span::styled(
+            format!("[{}]", app.pairing.style_mode.display_name()),
+            Style::default().fg(theme.fg_secondary),
+        ));
+    }
+
     // Tag filter indicator
     if let Some(tag) = &app.filters.active_tag {
         header_spans.push(Span::styled(" ", Style::default()));
@@ -215,18 +317,61 @@ fn draw_header(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         ));
     }
 
+    // Recent filter indicator
+    if app.filters.recent_filter.is_some() {
+        header_spans.push(Span::styled(" ", Style::default()));
+        header_spans.push(Span::styled(
+            format!("[recent: {}]", app.selection.filtered_wallpapers.len()),
+            Style::default().fg(theme.accent_highlight),
+        ));
+    }
+
+    // Rating filter indicator
+    if let Some(range) = &app.filters.rating_filter {
+        header_spans.push(Span::styled(" ", Style::default()));
+        let label = if range.start() == range.end() {
+            format!("[★{}]", range.start())
+        } else {
+            format!("[★{}-{}]", range.start(), range.end())
+        };
+        header_spans.push(Span::styled(
+            label,
+            Style::default().fg(theme.accent_highlight),
+        ));
+    }
+
     // Color filter indicator
-    if let Some(color) = &app.filters.active_color {
+    if !app.filters.active_colors.is_empty() {
         header_spans.push(Span::styled(" ", Style::default()));
-        if let Some(c) = parse_hex_color(color) {
-            header_spans.push(Span::styled("█", Style::default().fg(c)));
+        for color in &app.filters.active_colors {
+            if let Some(c) = parse_hex_color(color) {
+                header_spans.push(Span::styled("█", Style::default().fg(c)));
+            }
         }
+        let label = if app.filters.active_colors.len() == 1 {
+            app.filters.active_colors[0].clone()
+        } else {
+            format!(
+                "{} {}",
+                app.filters.active_colors.len(),
+                app.config.color_filter.mode.display_name()
+            )
+        };
         header_spans.push(Span::styled(
-            format!("[{}]", color),
+            format!("[{} ≥{:.2}]", label, app.config.color_filter.threshold),
             Style::default().fg(theme.fg_secondary),
         ));
     }
 
+    // Mirror indicator
+    if app.config.display.mirror_screens {
+        header_spans.push(Span::styled(" ", Style::default()));
+        header_spans.push(Span::styled(
+            "[mirror]",
+            Style::default().fg(theme.accent_highlight),
+        ));
+    }
+
     // Pywal indicator
     if app.ui.pywal_export {
         header_spans.push(Span::styled(" ", Style::default()));
@@ -317,9 +462,8 @@ fn draw_carousel_single(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostT
     f.render_widget(block, thumb_area);
 
     // Render image
-    if let Some(protocol) = app.get_thumbnail(cache_idx) {
-        let image = StatefulImage::new(None);
-        f.render_stateful_widget(image, inner, protocol);
+    if let Some(thumbnail) = app.get_thumbnail(cache_idx) {
+        render_thumbnail(f, thumbnail, inner);
     } else {
         // Fallback: show filename
         let label = Paragraph::new(filename)
@@ -472,9 +616,8 @@ fn draw_pairing_panel(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostThe
 
         // Render thumbnail
         if let Some(ci) = cache_idx {
-            if let Some(protocol) = app.get_thumbnail(ci) {
-                let image = StatefulImage::new(None);
-                f.render_stateful_widget(image, thumb_inner, protocol);
+            if let Some(thumbnail) = app.get_thumbnail(ci) {
+                render_thumbnail(f, thumbnail, thumb_inner);
             } else {
                 // Fallback: filename
                 let name_short: String =
@@ -589,7 +732,7 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
         let is_selected = idx == clamped_idx;
 
         // Get wallpaper info before mutable borrow
-        let (filename, is_suggestion) = app
+        let (filename, is_suggestion, dims) = app
             .cache
             .wallpapers
             .get(cache_idx)
@@ -601,9 +744,9 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
                     .unwrap_or("?")
                     .to_string();
                 let suggested = app.is_pairing_suggestion(&wp.path);
-                (name, suggested)
+                (name, suggested, (wp.width, wp.height))
             })
-            .unwrap_or(("?".to_string(), false));
+            .unwrap_or(("?".to_string(), false, (0, 0)));
 
         let is_loading = app.is_loading(cache_idx);
 
@@ -618,6 +761,7 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
         }
 
         let thumb_area = Rect::new(thumb_x, thumb_y, THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT + 2);
+        app.ui.thumb_hits.push((thumb_area, idx));
 
         // Draw thumbnail frame - green for suggestions, highlight for selected
         let border_color = if is_selected {
@@ -646,11 +790,15 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
 
         let inner = block.inner(thumb_area);
         f.render_widget(block, thumb_area);
+        let image_area = if app.config.thumbnails.preserve_aspect {
+            letterbox(inner, dims)
+        } else {
+            inner
+        };
 
         // Try to render image if available
-        if let Some(protocol) = app.get_thumbnail(cache_idx) {
-            let image = StatefulImage::new(None);
-            f.render_stateful_widget(image, inner, protocol);
+        if let Some(thumbnail) = app.get_thumbnail(cache_idx) {
+            render_thumbnail(f, thumbnail, image_area);
         } else if is_loading {
             // Show loading indicator
             let loading = Paragraph::new("...")
@@ -660,17 +808,7 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
             f.render_widget(loading, loading_area);
         } else {
             // Fallback: show filename
-            let max_chars = inner.width as usize;
-            let display = if max_chars == 0 {
-                String::new()
-            } else if filename.chars().count() <= max_chars {
-                filename.clone()
-            } else {
-                // Safe truncation using char boundaries
-                let truncated: String =
-                    filename.chars().take(max_chars.saturating_sub(1)).collect();
-                format!("{}…", truncated)
-            };
+            let display = truncate_label(&filename, inner.width as usize);
 
             let label = Paragraph::new(display)
                 .style(Style::default().fg(theme.fg_secondary))
@@ -701,6 +839,136 @@ fn draw_thumbnails(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme)
     }
 }
 
+fn draw_grid(f: &mut Frame, app: &mut App, area: Rect, theme: &FrostTheme) {
+    if app.selection.filtered_wallpapers.is_empty() {
+        let empty = Paragraph::new("No matching wallpapers")
+            .style(Style::default().fg(theme.fg_muted))
+            .alignment(Alignment::Center);
+        f.render_widget(empty, center_vertically(area, 1));
+        return;
+    }
+
+    let total = app.selection.filtered_wallpapers.len();
+    let max_cols = (area.width / GRID_MIN_CELL_WIDTH).max(1) as usize;
+    let cols = app.config.thumbnails.grid_columns.clamp(1, max_cols);
+    let cell_w = area.width / cols as u16;
+
+    // Terminal cells are roughly twice as tall as wide: 16:9 ≈ width * 9/32 rows
+    let thumb_w = cell_w.saturating_sub(2);
+    // (capped so at least one row always fits)
+    let thumb_h = (thumb_w.saturating_sub(2) * 9 / 32)
+        .clamp(3, THUMBNAIL_HEIGHT)
+        .min(area.height.saturating_sub(3).max(1))
+        + 2;
+    let cell_h = thumb_h + 1; // filename / indicator line
+    let rows = (area.height / cell_h).max(1) as usize;
+    app.ui.grid_rows = rows;
+
+    // Page through rows so the selection is always visible
+    let clamped_idx = app.selection.wallpaper_idx.min(total - 1);
+    let page = cols * rows;
+    let start = (clamped_idx / page) * page;
+    let end = (start + page).min(total);
+
+    // Request thumbnails for this page plus the next row(s) ahead (non-blocking)
+    let preload = app.config.thumbnails.preload_count.max(cols);
+    let preload_start = start.saturating_sub(preload);
+    let preload_end = (end + preload).min(total);
+    for idx in preload_start..preload_end {
+        let cache_idx = app.selection.filtered_wallpapers[idx];
+        app.request_thumbnail(cache_idx);
+    }
+
+    // Center the grid horizontally
+    let start_x = area.x + (area.width - cell_w * cols as u16) / 2;
+
+    for (i, idx) in (start..end).enumerate() {
+        let cache_idx = app.selection.filtered_wallpapers[idx];
+        let is_selected = idx == clamped_idx;
+
+        let (filename, is_suggestion, dims) = app
+            .cache
+            .wallpapers
+            .get(cache_idx)
+            .map(|wp| {
+                let name = wp
+                    .path
+                    .file_stem()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("?")
+                    .to_string();
+                (
+                    name,
+                    app.is_pairing_suggestion(&wp.path),
+                    (wp.width, wp.height),
+                )
+            })
+            .unwrap_or(("?".to_string(), false, (0, 0)));
+        let is_loading = app.is_loading(cache_idx);
+
+        let col = (i % cols) as u16;
+        let row = (i / cols) as u16;
+        let cell_x = start_x + col * cell_w + 1;
+        let cell_y = area.y + row * cell_h;
+        if cell_y + cell_h > area.y + area.height {
+            continue;
+        }
+
+        let thumb_area = Rect::new(cell_x, cell_y, thumb_w, thumb_h);
+        app.ui.thumb_hits.push((thumb_area, idx));
+
+        let border_style = if is_selected {
+            Style::default().fg(theme.accent_highlight)
+        } else if is_suggestion {
+            Style::default()
+                .fg(theme.success)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.border)
+        };
+
+        // Clear previous image artifacts (Kitty protocol caches images)
+        f.render_widget(Clear, thumb_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .style(Style::default().bg(theme.bg_medium));
+        let inner = block.inner(thumb_area);
+        f.render_widget(block, thumb_area);
+        let image_area = if app.config.thumbnails.preserve_aspect {
+            letterbox(inner, dims)
+        } else {
+            inner
+        };
+
+        if let Some(thumbnail) = app.get_thumbnail(cache_idx) {
+            render_thumbnail(f, thumbnail, image_area);
+        } else if is_loading {
+            let loading = Paragraph::new("...")
+                .style(Style::default().fg(theme.accent_primary))
+                .alignment(Alignment::Center);
+            f.render_widget(loading, center_vertically(inner, 1));
+        }
+
+        // Filename under each cell; selection and suggestions are colored
+        let label_style = if is_selected {
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD)
+        } else if is_suggestion {
+            Style::default().fg(theme.success)
+        } else {
+            Style::default().fg(theme.fg_muted)
+        };
+        let label_area = Rect::new(cell_x, thumb_area.bottom(), thumb_w, 1);
+        let label = Paragraph::new(truncate_label(&filename, thumb_w as usize))
+            .style(label_style)
+            .alignment(Alignment::Center);
+        f.render_widget(label, label_area);
+    }
+}
+
 fn draw_footer(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
     // Command mode - show command input line
     if app.ui.command_mode {
@@ -777,6 +1045,19 @@ fn draw_help_line(f: &mut Frame, area: Rect, theme: &FrostTheme) {
     f.render_widget(paragraph, area);
 }
 
+/// Shorten `name` to `max_chars` characters, ending in an ellipsis when cut.
+fn truncate_label(name: &str, max_chars: usize) -> String {
+    if max_chars == 0 {
+        String::new()
+    } else if name.chars().count() <= max_chars {
+        name.to_string()
+    } else {
+        // Safe truncation using char boundaries
+        let truncated: String = name.chars().take(max_chars - 1).collect();
+        format!("{}…", truncated)
+    }
+}
+
 fn center_vertically(area: Rect, height: u16) -> Rect {
     let y = area.y + (area.height.saturating_sub(height)) / 2;
     Rect::new(area.x, y, area.width, height)
@@ -797,6 +1078,25 @@ fn fit_aspect(max_w: u16, max_h: u16, aspect_w: u32, aspect_h: u32) -> (u16, u16
     }
 }
 
+/// Largest rect inside `cell` with the image's aspect ratio, centered.
+/// Terminal cells are about twice as tall as wide, so rows count double.
+/// Unknown dimensions keep the whole cell.
+fn letterbox(cell: Rect, (width, height): (u32, u32)) -> Rect {
+    if width == 0 || height == 0 {
+        return cell;
+    }
+    let (w, h) = fit_aspect(cell.width, cell.height, width * 2, height);
+    if w == 0 || h == 0 {
+        return cell;
+    }
+    Rect::new(
+        cell.x + (cell.width - w) / 2,
+        cell.y + (cell.height - h) / 2,
+        w,
+        h,
+    )
+}
+
 fn draw_color_palette(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
     // Get colors from selected wallpaper
     let colors = app
@@ -853,8 +1153,27 @@ fn draw_color_palette(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme)
         }
     }
 
-    let line = Line::from(spans);
-    let paragraph = Paragraph::new(line).alignment(Alignment::Center);
+    let mut lines = vec![Line::from(spans)];
+
+    // Credit line for imported wallpapers
+    if let Some(wp) = app.selected_wallpaper() {
+        if wp.author.is_some() || wp.source_url.is_some() {
+            let mut credit = vec![Span::styled("by ", Style::default().fg(theme.fg_secondary))];
+            credit.push(Span::styled(
+                wp.author.as_deref().unwrap_or("unknown").to_string(),
+                Style::default().fg(theme.accent_primary),
+            ));
+            if let Some(url) = &wp.source_url {
+                credit.push(Span::styled(
+                    format!("  {}", url),
+                    Style::default().fg(theme.fg_muted),
+                ));
+            }
+            lines.push(Line::from(credit));
+        }
+    }
+
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center);
     f.render_widget(paragraph, area);
 }
 
@@ -890,10 +1209,14 @@ fn draw_color_picker(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
     f.render_widget(clear, popup_area);
 
     // Popup border
-    let title = if let Some(ref color) = app.filters.active_color {
-        format!(" Color Filter [{}] ", color)
-    } else {
+    let title = if app.filters.active_colors.is_empty() {
         " Color Filter ".to_string()
+    } else {
+        format!(
+            " Color Filter [{} selected, match {}] ",
+            app.filters.active_colors.len(),
+            app.config.color_filter.mode.display_name()
+        )
     };
 
     let block = Block::default()
@@ -931,8 +1254,9 @@ fn draw_color_picker(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         // Parse color
         let color = parse_hex_color(color_hex).unwrap_or(theme.fg_muted);
 
-        // Highlight selected
+        // Highlight cursor; mark colors in the active set
         let is_selected = i == app.filters.color_picker_idx;
+        let is_active = app.filters.active_colors.contains(color_hex);
         let style = if is_selected {
             Style::default()
                 .bg(color)
@@ -942,10 +1266,11 @@ fn draw_color_picker(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
             Style::default().bg(color)
         };
 
-        let text = if is_selected {
-            "▶▶▶▶"
-        } else {
-            "████"
+        let text = match (is_selected, is_active) {
+            (true, true) => "▶✓✓▶",
+            (true, false) => "▶▶▶▶",
+            (false, true) => "█✓✓█",
+            (false, false) => "████",
         };
         let swatch = Paragraph::new(text).style(style);
         f.render_widget(swatch, swatch_area);
@@ -957,7 +1282,11 @@ fn draw_color_picker(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         let footer_area = Rect::new(inner.x, footer_y, inner.width, 2);
         let footer = Line::from(vec![
             Span::styled("←/→", Style::default().fg(theme.accent_primary)),
-            Span::styled(" select ", Style::default().fg(theme.fg_muted)),
+            Span::styled(" move ", Style::default().fg(theme.fg_muted)),
+            Span::styled("Spc", Style::default().fg(theme.accent_primary)),
+            Span::styled(" pick ", Style::default().fg(theme.fg_muted)),
+            Span::styled("a", Style::default().fg(theme.accent_primary)),
+            Span::styled(" all/any ", Style::default().fg(theme.fg_muted)),
             Span::styled("Enter", Style::default().fg(theme.accent_primary)),
             Span::styled(" apply ", Style::default().fg(theme.fg_muted)),
             Span::styled("x", Style::default().fg(theme.accent_primary)),
@@ -970,10 +1299,98 @@ fn draw_color_picker(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
     }
 }
 
+fn draw_tag_editor(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
+    let Some(wp) = app.selected_wallpaper() else {
+        return;
+    };
+    let tags = &wp.tags;
+
+    let popup_width = 50.min(area.width.saturating_sub(4));
+    let popup_height = (tags.len().max(1) as u16 + 7).min(area.height.saturating_sub(4));
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    // Clear background
+    let clear = Block::default().style(Style::default().bg(theme.bg_dark));
+    f.render_widget(clear, popup_area);
+
+    let filename = wp
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let block = Block::default()
+        .title(format!(" Tags: {} ", filename))
+        .title_style(
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent_primary))
+        .style(Style::default().bg(theme.bg_dark));
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let mut lines = Vec::new();
+    if tags.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (no tags)",
+            Style::default().fg(theme.fg_muted),
+        )));
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        let selected = i == app.ui.tag_editor_idx;
+        let (marker, style) = if selected {
+            (
+                "▶ ",
+                Style::default()
+                    .fg(theme.accent_highlight)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            ("  ", Style::default().fg(theme.fg_secondary))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(marker, style),
+            Span::styled(format!("#{}", tag), style),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(
+            "+ ",
+            Style::default()
+                .fg(theme.accent_primary)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            &app.ui.tag_editor_buffer,
+            Style::default().fg(theme.fg_primary),
+        ),
+        Span::styled("█", Style::default().fg(theme.accent_primary)), // Cursor
+    ]));
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("Enter", Style::default().fg(theme.accent_primary)),
+        Span::styled(" add ", Style::default().fg(theme.fg_muted)),
+        Span::styled("↑/↓", Style::default().fg(theme.accent_primary)),
+        Span::styled(" select ", Style::default().fg(theme.fg_muted)),
+        Span::styled("Del", Style::default().fg(theme.accent_primary)),
+        Span::styled(" remove ", Style::default().fg(theme.fg_muted)),
+        Span::styled("Esc", Style::default().fg(theme.accent_primary)),
+        Span::styled(" close", Style::default().fg(theme.fg_muted)),
+    ]));
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
 fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
     // Center the popup
     let popup_width = 50.min(area.width.saturating_sub(4));
-    let popup_height = 35.min(area.height.saturating_sub(4));
+    let popup_height = 46.min(area.height.saturating_sub(4));
     let popup_x = (area.width.saturating_sub(popup_width)) / 2;
     let popup_y = (area.height.saturating_sub(popup_height)) / 2;
     let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
@@ -1016,6 +1433,20 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  l/→     ", Style::default().fg(theme.accent_primary)),
             Span::styled("Next wallpaper", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  PgUp/Dn ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Jump a page back/forward ({/})",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  Home/End", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                " First/last wallpaper",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("  Tab     ", Style::default().fg(theme.accent_primary)),
             Span::styled("Next screen", Style::default().fg(theme.fg_secondary)),
@@ -1024,6 +1455,20 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  S-Tab   ", Style::default().fg(theme.accent_primary)),
             Span::styled("Previous screen", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  g       ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Toggle grid view (↑/↓ move rows)",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  n/N     ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Next/prev pairing suggestion",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Actions",
@@ -1057,6 +1502,13 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  :t <tag>", Style::default().fg(theme.accent_primary)),
             Span::styled(" Filter by tag", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  :rating ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                " Filter by stars (>=4, <2, 5)",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("  :clear  ", Style::default().fg(theme.accent_primary)),
             Span::styled(
@@ -1123,6 +1575,25 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  T       ", Style::default().fg(theme.accent_primary)),
             Span::styled("Clear tag filter", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  e       ", Style::default().fg(theme.accent_primary)),
+            Span::styled("Edit tags", Style::default().fg(theme.fg_secondary)),
+        ]),
+        Line::from(vec![
+            Span::styled("  0-5     ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Rate wallpaper (0 clears)",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  i       ", Style::default().fg(theme.accent_primary)),
+            Span::styled("Wallpaper info", Style::default().fg(theme.fg_secondary)),
+        ]),
+        Line::from(vec![
+            Span::styled("  H       ", Style::default().fg(theme.accent_primary)),
+            Span::styled("Pairing history", Style::default().fg(theme.fg_secondary)),
+        ]),
         Line::from(vec![
             Span::styled("  C       ", Style::default().fg(theme.accent_primary)),
             Span::styled("Open color picker", Style::default().fg(theme.fg_secondary)),
@@ -1131,6 +1602,13 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  p       ", Style::default().fg(theme.accent_primary)),
             Span::styled("Pairing preview", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  S       ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Cycle pairing style",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
         Line::from(vec![
             Span::styled("  w       ", Style::default().fg(theme.accent_primary)),
             Span::styled(
@@ -1142,6 +1620,17 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
             Span::styled("  W       ", Style::default().fg(theme.accent_primary)),
             Span::styled("Toggle auto pywal", Style::default().fg(theme.fg_secondary)),
         ]),
+        Line::from(vec![
+            Span::styled("  M       ", Style::default().fg(theme.accent_primary)),
+            Span::styled(
+                "Mirror to all screens",
+                Style::default().fg(theme.fg_secondary),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  y       ", Style::default().fg(theme.accent_primary)),
+            Span::styled("Copy path", Style::default().fg(theme.fg_secondary)),
+        ]),
         Line::from(vec![
             Span::styled("  R       ", Style::default().fg(theme.accent_primary)),
             Span::styled(
@@ -1159,6 +1648,230 @@ fn draw_help_popup(f: &mut Frame, area: Rect, theme: &FrostTheme) {
     f.render_widget(paragraph, inner);
 }
 
+/// Past arrangements from the pairing history; Enter re-applies the highlighted one
+fn draw_history_popup(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
+    let records = app
+        .pairing
+        .history
+        .recent_records(crate::app::HISTORY_POPUP_LIMIT);
+
+    let popup_width = 72.min(area.width.saturating_sub(4));
+    let popup_height = 24.min(area.height.saturating_sub(4));
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    let clear = Block::default().style(Style::default().bg(theme.bg_dark));
+    f.render_widget(clear, popup_area);
+
+    let block = Block::default()
+        .title(" Pairing History (Enter apply · Esc close) ")
+        .title_style(
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent_primary))
+        .style(Style::default().bg(theme.bg_dark));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    // Two rows per record; scroll so the highlighted one stays visible
+    let visible = (inner.height as usize / 2).max(1);
+    let start = app.ui.history_idx.saturating_sub(visible - 1);
+
+    let mut lines = Vec::new();
+    for (i, record) in records.iter().enumerate().skip(start).take(visible) {
+        let selected = i == app.ui.history_idx;
+        let kept = match record.duration {
+            Some(secs) => format!("kept {}", crate::utils::format_duration(secs)),
+            None if i == 0 => "current".to_string(),
+            None => String::new(),
+        };
+        let header_style = if selected {
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(theme.accent_primary)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(if selected { "▸ " } else { "  " }, header_style),
+            Span::styled(
+                crate::utils::format_timestamp(record.timestamp),
+                header_style,
+            ),
+            Span::styled(
+                format!(
+                    "  {}  {}",
+                    if record.manual { "manual" } else { "auto" },
+                    kept
+                ),
+                Style::default().fg(theme.fg_muted),
+            ),
+        ]));
+
+        let mut screens: Vec<_> = record.wallpapers.iter().collect();
+        screens.sort();
+        let summary: Vec<String> = screens
+            .iter()
+            .map(|(screen, path)| {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string());
+                format!("{}: {}", screen, name)
+            })
+            .collect();
+        lines.push(Line::from(Span::styled(
+            format!("    {}", summary.join(" · ")),
+            Style::default().fg(theme.fg_secondary),
+        )));
+    }
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Metadata popup for the selected wallpaper
+fn draw_info_popup(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
+    let Some(wp) = app.selected_wallpaper() else {
+        return;
+    };
+
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("  {:<10}", label),
+                Style::default().fg(theme.accent_primary),
+            ),
+            Span::styled(value, Style::default().fg(theme.fg_secondary)),
+        ])
+    };
+    let heading = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD),
+        ))
+    };
+
+    let modified = chrono::DateTime::from_timestamp(wp.modified_at as i64, 0)
+        .filter(|_| wp.modified_at > 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut lines = vec![
+        heading("File"),
+        field("Path", wp.path.display().to_string()),
+        field("Size", crate::utils::format_size(wp.file_size)),
+        field("Modified", modified),
+        field("Rating", crate::wallpaper::rating_stars(wp.rating)),
+        Line::from(""),
+        heading("Image"),
+        field("Resolution", format!("{}x{}", wp.width, wp.height)),
+    ];
+    if let Some(screen) = app.selected_screen().filter(|s| !wp.meets_resolution(s)) {
+        let (width, height) = screen.effective_size();
+        lines.push(Line::from(Span::styled(
+            format!(
+                "  ⚠ Too low resolution for {} ({}x{} effective)",
+                screen.name, width, height
+            ),
+            Style::default().fg(theme.warning),
+        )));
+    }
+    lines.extend([
+        field("Aspect", format!("{:?}", wp.aspect_category)),
+        field(
+            "Resize",
+            match wp.resize_override {
+                Some(mode) => format!("{} (override)", mode.display_name()),
+                None => app.config.display.resize_mode.display_name().to_string(),
+            },
+        ),
+        field("Colors", wp.colors.join(" ")),
+    ]);
+
+    if wp.author.is_some() || wp.source_url.is_some() {
+        lines.push(Line::from(""));
+        lines.push(heading("Source"));
+        if let Some(author) = &wp.author {
+            lines.push(field("Author", author.clone()));
+        }
+        if let Some(url) = &wp.source_url {
+            lines.push(field("URL", url.clone()));
+        }
+    }
+
+    lines.push(Line::from(""));
+    lines.push(heading("Tags"));
+    lines.push(field(
+        "Manual",
+        if wp.tags.is_empty() {
+            "none".to_string()
+        } else {
+            wp.tags.join(", ")
+        },
+    ));
+    lines.push(field(
+        "Palette",
+        if wp.palette_tags.is_empty() {
+            "none".to_string()
+        } else {
+            wp.palette_tags.join(", ")
+        },
+    ));
+    if wp.auto_tags.is_empty() {
+        lines.push(field("Auto", "none".to_string()));
+    } else {
+        let mut auto_tags: Vec<_> = wp.auto_tags.iter().collect();
+        auto_tags.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+        for (i, tag) in auto_tags.iter().enumerate() {
+            lines.push(field(
+                if i == 0 { "Auto" } else { "" },
+                format!("{:<18} {:>3.0}%", tag.name, tag.confidence * 100.0),
+            ));
+        }
+    }
+
+    let popup_width = 64.min(area.width.saturating_sub(4));
+    // Long lines (usually the path) wrap onto extra rows
+    let inner_width = popup_width.saturating_sub(2).max(1) as usize;
+    let rows: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(inner_width).max(1))
+        .sum();
+    let popup_height = (rows as u16 + 2).min(area.height.saturating_sub(4));
+    let popup_x = (area.width.saturating_sub(popup_width)) / 2;
+    let popup_y = (area.height.saturating_sub(popup_height)) / 2;
+    let popup_area = Rect::new(popup_x, popup_y, popup_width, popup_height);
+
+    // Clear background
+    let clear = Block::default().style(Style::default().bg(theme.bg_dark));
+    f.render_widget(clear, popup_area);
+
+    let block = Block::default()
+        .title(" Wallpaper Info ")
+        .title_style(
+            Style::default()
+                .fg(theme.accent_highlight)
+                .add_modifier(Modifier::BOLD),
+        )
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent_primary))
+        .style(Style::default().bg(theme.bg_dark));
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
+
 /// Draw undo popup at bottom of screen
 fn draw_undo_popup(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
     let remaining_secs = app.pairing.history.undo_remaining_secs().unwrap_or(0);
@@ -1194,7 +1907,11 @@ fn draw_undo_popup(f: &mut Frame, app: &App, area: Rect, theme: &FrostTheme) {
         Span::styled(message, Style::default().fg(theme.fg_primary)),
         Span::styled(" | ", Style::default().fg(theme.fg_muted)),
         Span::styled(
-            format!("Undo (u) {}s", remaining_secs),
+            if app.pairing.auto_apply_pending.is_some() {
+                format!("Esc cancels {}s", remaining_secs)
+            } else {
+                format!("Undo (u) {}s", remaining_secs)
+            },
             Style::default()
                 .fg(theme.warning)
                 .add_modifier(Modifier::BOLD),
@@ -1,3 +1,4 @@
+pub mod halfblock;
 mod layout;
 pub mod theme;
 
@@ -1,4 +1,6 @@
+use crate::app::ThemeConfig;
 use ratatui::style::Color;
+use std::collections::BTreeMap;
 use std::fs;
 
 /// Frost theme colors - inspired by Nord/Catppuccin ice palette
@@ -90,6 +92,94 @@ impl FrostTheme {
     }
 }
 
+/// Keys accepted in `[theme.colors]`, `[theme.light_colors]` and `[theme.dark_colors]`
+pub const COLOR_NAMES: &[&str] = &[
+    "bg_dark",
+    "bg_medium",
+    "bg_light",
+    "fg_primary",
+    "fg_secondary",
+    "fg_muted",
+    "accent_primary",
+    "accent_secondary",
+    "accent_highlight",
+    "success",
+    "warning",
+    "error",
+    "border",
+    "border_focused",
+];
+
+impl FrostTheme {
+    fn color_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "bg_dark" => &mut self.bg_dark,
+            "bg_medium" => &mut self.bg_medium,
+            "bg_light" => &mut self.bg_light,
+            "fg_primary" => &mut self.fg_primary,
+            "fg_secondary" => &mut self.fg_secondary,
+            "fg_muted" => &mut self.fg_muted,
+            "accent_primary" => &mut self.accent_primary,
+            "accent_secondary" => &mut self.accent_secondary,
+            "accent_highlight" => &mut self.accent_highlight,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "border" => &mut self.border,
+            "border_focused" => &mut self.border_focused,
+            _ => return None,
+        })
+    }
+
+    /// Replace the colors named in `overrides`. Unknown names and values that
+    /// don't parse are skipped with a warning; the built-in color stays.
+    pub fn apply_overrides(&mut self, overrides: &BTreeMap<String, String>) {
+        for (name, value) in overrides {
+            let Some(color) = parse_color(value) else {
+                tracing::warn!(
+                    "Ignoring theme color {} = '{}': expected #rrggbb, #rgb or \"reset\"",
+                    name,
+                    value
+                );
+                continue;
+            };
+            match self.color_mut(name) {
+                Some(slot) => *slot = color,
+                None => tracing::warn!(
+                    "Ignoring unknown theme color '{}' (known: {})",
+                    name,
+                    COLOR_NAMES.join(", ")
+                ),
+            }
+        }
+    }
+}
+
+/// `#rrggbb`, `#rgb` (the `#` is optional) or `reset` for the terminal's own color
+fn parse_color(value: &str) -> Option<Color> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("reset") {
+        return Some(Color::Reset);
+    }
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        6 => Some(Color::Rgb(
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        3 => {
+            let short = |i: usize| channel(&hex[i..=i]).map(|v| v * 17);
+            Some(Color::Rgb(short(0)?, short(1)?, short(2)?))
+        }
+        _ => None,
+    }
+}
+
 impl Default for FrostTheme {
     fn default() -> Self {
         if detect_light_theme() {
@@ -164,12 +254,76 @@ fn detect_light_theme() -> bool {
     false
 }
 
-/// Get the current theme based on terminal detection
-pub fn frost_theme() -> FrostTheme {
-    FrostTheme::default()
+/// Get the current theme based on terminal detection, with the overrides
+/// from `[theme]`: `colors` first, then `light_colors` or `dark_colors`
+pub fn frost_theme(config: &ThemeConfig) -> FrostTheme {
+    let light = detect_light_theme();
+    let mut theme = if light {
+        FrostTheme::frostglow_light()
+    } else {
+        FrostTheme::deep_cracked_ice()
+    };
+    theme.apply_overrides(&config.colors);
+    theme.apply_overrides(if light {
+        &config.light_colors
+    } else {
+        &config.dark_colors
+    });
+    theme
 }
 
 /// Check if current theme is light (for change detection)
 pub fn is_light_theme() -> bool {
     detect_light_theme()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- parse_color ---
+
+    #[test]
+    fn test_parse_color_hex() {
+        assert_eq!(parse_color("#88c0d0"), Some(Color::Rgb(0x88, 0xc0, 0xd0)));
+        assert_eq!(parse_color("88C0D0"), Some(Color::Rgb(0x88, 0xc0, 0xd0)));
+        assert_eq!(parse_color(" #fa0 "), Some(Color::Rgb(0xff, 0xaa, 0x00)));
+    }
+
+    #[test]
+    fn test_parse_color_named_and_invalid() {
+        assert_eq!(parse_color("reset"), Some(Color::Reset));
+        assert_eq!(parse_color("RESET"), Some(Color::Reset));
+        assert_eq!(parse_color("red"), None, "only reset is a name");
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+        assert_eq!(parse_color("#ff00é"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    // --- apply_overrides ---
+
+    #[test]
+    fn test_apply_overrides_replaces_named_colors() {
+        let mut theme = FrostTheme::deep_cracked_ice();
+        let original = FrostTheme::deep_cracked_ice();
+        let overrides: BTreeMap<String, String> = [
+            ("accent_primary", "#ff0000"),
+            ("bg_dark", "reset"),
+            ("border", "not a color"),
+            ("no_such_color", "#00ff00"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        theme.apply_overrides(&overrides);
+        assert_eq!(theme.accent_primary, Color::Rgb(255, 0, 0));
+        assert_eq!(theme.bg_dark, Color::Reset);
+        assert_eq!(
+            theme.border, original.border,
+            "invalid value keeps the built-in"
+        );
+        assert_eq!(theme.fg_primary, original.fg_primary);
+    }
+}
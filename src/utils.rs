@@ -1,5 +1,16 @@
+use image::codecs::gif::GifDecoder;
+use image::error::{DecodingError, ImageError};
+use image::metadata::Orientation;
+use image::{AnimationDecoder, DynamicImage, ImageDecoder, ImageFormat, ImageReader, ImageResult};
 use palette::{IntoColor, Lab, Srgb};
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::BufReader;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 /// Supported image file extensions
 pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "gif"];
@@ -21,7 +32,6 @@ pub enum ColorHarmony {
 
 impl ColorHarmony {
     /// Get a display name for the harmony type
-    #[allow(dead_code)]
     pub fn name(&self) -> &'static str {
         match self {
             ColorHarmony::Analogous => "Analogous",
@@ -67,6 +77,11 @@ pub fn hex_to_lab(hex: &str) -> Option<Lab> {
 /// Convert hex color to HSL and return hue (0-360), saturation (0-1), lightness (0-1)
 pub fn hex_to_hsl(hex: &str) -> Option<(f32, f32, f32)> {
     let (r, g, b) = hex_to_rgb(hex)?;
+    Some(rgb_to_hsl(r, g, b))
+}
+
+/// Convert RGB to hue (0-360), saturation (0-1), lightness (0-1)
+pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f32, f32, f32) {
     let r = r as f32 / 255.0;
     let g = g as f32 / 255.0;
     let b = b as f32 / 255.0;
@@ -79,7 +94,7 @@ pub fn hex_to_hsl(hex: &str) -> Option<(f32, f32, f32)> {
 
     if delta < 0.0001 {
         // Achromatic (gray)
-        return Some((0.0, 0.0, lightness));
+        return (0.0, 0.0, lightness);
     }
 
     let saturation = if lightness > 0.5 {
@@ -98,7 +113,164 @@ pub fn hex_to_hsl(hex: &str) -> Option<(f32, f32, f32)> {
 
     let hue = if hue < 0.0 { hue + 360.0 } else { hue };
 
-    Some((hue, saturation, lightness))
+    (hue, saturation, lightness)
+}
+
+/// Inverse of `rgb_to_hsl`; hue wraps, saturation and lightness are clamped to 0-1
+pub fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (u8, u8, u8) {
+    let hue = hue.rem_euclid(360.0);
+    let saturation = saturation.clamp(0.0, 1.0);
+    let lightness = lightness.clamp(0.0, 1.0);
+
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let x = chroma * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = lightness - chroma / 2.0;
+
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let to_u8 = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// How colors are written in CLI output (`--color-format`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorFormat {
+    /// `#1a2b3c`, as stored in the cache
+    #[default]
+    Hex,
+    /// `rgb(26,43,60)`
+    Rgb,
+    /// `hsl(210,40%,17%)`
+    Hsl,
+}
+
+impl ColorFormat {
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "hex" => Ok(Self::Hex),
+            "rgb" => Ok(Self::Rgb),
+            "hsl" => Ok(Self::Hsl),
+            _ => anyhow::bail!("Unknown color format '{}' (expected hex, rgb or hsl)", s),
+        }
+    }
+
+    /// Write a `#RRGGBB` color in this format. Unparseable input is returned as is.
+    pub fn format(&self, hex: &str) -> String {
+        match self {
+            Self::Hex => hex.to_string(),
+            Self::Rgb => match hex_to_rgb(hex) {
+                Some((r, g, b)) => format!("rgb({},{},{})", r, g, b),
+                None => hex.to_string(),
+            },
+            Self::Hsl => match hex_to_hsl(hex) {
+                Some((h, s, l)) => format!(
+                    "hsl({:.0},{:.0}%,{:.0}%)",
+                    h.round() % 360.0,
+                    s * 100.0,
+                    l * 100.0
+                ),
+                None => hex.to_string(),
+            },
+        }
+    }
+
+    /// Format every color of a palette
+    pub fn format_all(&self, colors: &[String]) -> Vec<String> {
+        colors.iter().map(|c| self.format(c)).collect()
+    }
+}
+
+/// Color vision used when judging harmonies between palettes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HarmonyMode {
+    #[default]
+    Normal,
+    /// Red-blind
+    Protanopia,
+    /// Green-blind
+    Deuteranopia,
+    /// Blue-blind
+    Tritanopia,
+}
+
+impl HarmonyMode {
+    /// Full-severity simulation matrix in linear RGB (Machado, Oliveira & Fernandes 2009)
+    fn matrix(self) -> Option<[[f32; 3]; 3]> {
+        match self {
+            HarmonyMode::Normal => None,
+            HarmonyMode::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            HarmonyMode::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            HarmonyMode::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+        }
+    }
+}
+
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// How `hex` looks to someone with the given color vision deficiency.
+/// `Normal` and unparsable colors are returned unchanged.
+pub fn simulate_cvd(hex: &str, mode: HarmonyMode) -> String {
+    let (Some(matrix), Some((r, g, b))) = (mode.matrix(), hex_to_rgb(hex)) else {
+        return hex.to_string();
+    };
+
+    let linear = [r, g, b].map(|c| srgb_to_linear(c as f32 / 255.0));
+    let [r, g, b] = matrix.map(|row| {
+        let v = row[0] * linear[0] + row[1] * linear[1] + row[2] * linear[2];
+        (linear_to_srgb(v.clamp(0.0, 1.0)) * 255.0).round() as u8
+    });
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// `detect_harmony` on palettes as perceived under `mode`
+pub fn detect_harmony_for(
+    colors1: &[String],
+    weights1: &[f32],
+    colors2: &[String],
+    weights2: &[f32],
+    mode: HarmonyMode,
+) -> (ColorHarmony, f32) {
+    if mode == HarmonyMode::Normal {
+        return detect_harmony(colors1, weights1, colors2, weights2);
+    }
+    let perceived = |colors: &[String]| -> Vec<String> {
+        colors.iter().map(|c| simulate_cvd(c, mode)).collect()
+    };
+    detect_harmony(&perceived(colors1), weights1, &perceived(colors2), weights2)
 }
 
 /// Calculate the angular difference between two hue values (0-180)
@@ -421,6 +593,102 @@ pub fn color_saturation(hex: &str) -> f32 {
     }
 }
 
+/// Palette colors less saturated than this (HSL) count as neutral
+const NEUTRAL_SATURATION: f32 = 0.15;
+
+/// Name for a hex color: a hue family, or black/white/gray for neutrals
+pub fn color_name(hex: &str) -> Option<&'static str> {
+    let (h, s, l) = hex_to_hsl(hex)?;
+    if s < NEUTRAL_SATURATION || !(0.08..=0.95).contains(&l) {
+        return Some(if l < 0.2 {
+            "black"
+        } else if l > 0.85 {
+            "white"
+        } else {
+            "gray"
+        });
+    }
+    Some(match h {
+        h if !(15.0..345.0).contains(&h) => "red",
+        h if h < 45.0 => "orange",
+        h if h < 70.0 => "yellow",
+        h if h < 170.0 => "green",
+        h if h < 200.0 => "cyan",
+        h if h < 260.0 => "blue",
+        h if h < 300.0 => "purple",
+        _ => "pink",
+    })
+}
+
+/// Tags derived from a palette alone (no CLIP): `dark`/`bright`,
+/// `vibrant`/`muted`, `monochrome`, and the top-weighted color's name.
+/// Missing or mismatched weights count every color equally.
+pub fn palette_tags(colors: &[String], weights: &[f32]) -> Vec<String> {
+    if colors.is_empty() {
+        return Vec::new();
+    }
+    let weights: Vec<f32> = if weights.len() == colors.len() && weights.iter().sum::<f32>() > 0.0 {
+        weights.to_vec()
+    } else {
+        vec![1.0; colors.len()]
+    };
+    let total: f32 = weights.iter().sum();
+    let mean = |f: fn(&str) -> f32| -> f32 {
+        colors
+            .iter()
+            .zip(&weights)
+            .map(|(c, w)| f(c) * w)
+            .sum::<f32>()
+            / total
+    };
+
+    let mut tags = Vec::new();
+    let brightness = mean(color_brightness);
+    if brightness < 0.3 {
+        tags.push("dark");
+    } else if brightness > 0.65 {
+        tags.push("bright");
+    }
+    // HSV saturation is high for deep, dark colors too; those read as dark, not vibrant
+    let saturation = mean(color_saturation);
+    if saturation > 0.55 && brightness >= 0.3 {
+        tags.push("vibrant");
+    } else if saturation < 0.25 {
+        tags.push("muted");
+    }
+
+    // Monochrome: every colorful entry sits in one narrow hue band (or there
+    // are none). Uses the resultant length of the hue angles, weighted.
+    let (mut x, mut y, mut colorful) = (0.0f32, 0.0f32, 0.0f32);
+    for (c, w) in colors.iter().zip(&weights) {
+        if let Some((h, s, _)) = hex_to_hsl(c) {
+            if s >= NEUTRAL_SATURATION {
+                x += h.to_radians().cos() * w;
+                y += h.to_radians().sin() * w;
+                colorful += w;
+            }
+        }
+    }
+    // 0.97 ≈ a circular spread of about 14°
+    if colorful == 0.0 || x.hypot(y) / colorful > 0.97 {
+        tags.push("monochrome");
+    }
+
+    let dominant = colors
+        .iter()
+        .zip(&weights)
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .and_then(|(c, _)| color_name(c));
+    if let Some(name) = dominant {
+        tags.push(name);
+    }
+
+    let mut tags: Vec<String> = tags.into_iter().map(str::to_string).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
 /// Calculate overall image similarity based on color profile
 /// Returns a score from 0.0 (very different) to 1.0 (very similar)
 pub fn image_similarity(colors1: &[String], colors2: &[String]) -> f32 {
@@ -502,6 +770,67 @@ pub fn find_similar_wallpapers(
     similarities.into_iter().take(limit).collect()
 }
 
+/// Find semantically similar wallpapers based on CLIP embeddings
+/// Returns Vec of (similarity_score, wallpaper_index) sorted by similarity
+pub fn find_similar_by_embedding(
+    target_embedding: &[f32],
+    all_wallpapers: &[(usize, &[f32])], // (index, embedding)
+    limit: usize,
+) -> Vec<(f32, usize)> {
+    let mut similarities: Vec<(f32, usize)> = all_wallpapers
+        .iter()
+        .map(|(idx, embedding)| {
+            let sim = crate::pairing::normalize_cosine_similarity(target_embedding, embedding);
+            (sim, *idx)
+        })
+        .collect();
+
+    // Sort by similarity descending
+    similarities.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    similarities.into_iter().take(limit).collect()
+}
+
+/// Local date and time of a unix timestamp, e.g. "2025-03-01 14:05"
+pub fn format_timestamp(secs: u64) -> String {
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .filter(|_| secs > 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Compact duration with the two largest units, e.g. "45s", "12m", "3h 5m", "2d 4h"
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86_400, secs % 86_400 / 3600, secs % 3600 / 60);
+    match (days, hours, mins) {
+        (0, 0, 0) => format!("{}s", secs),
+        (0, 0, m) => format!("{}m", m),
+        (0, h, 0) => format!("{}h", h),
+        (0, h, m) => format!("{}h {}m", h, m),
+        (d, 0, _) => format!("{}d", d),
+        (d, h, _) => format!("{}d {}h", d, h),
+    }
+}
+
+/// Human-readable byte count, e.g. "2.4 MB"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// Check if a path is a supported image file
 pub fn is_image_file(path: &Path) -> bool {
     path.extension()
@@ -513,6 +842,111 @@ pub fn is_image_file(path: &Path) -> bool {
         .unwrap_or(false)
 }
 
+/// Shell-style wildcard match: `*` is any run of characters, `?` exactly one.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    // Position of the last `*` and the text index it currently absorbs up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((sp, st)) = star {
+            // Let the last `*` swallow one more character and retry
+            pi = sp + 1;
+            ti = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+/// Image dimensions as displayed, with the EXIF orientation applied.
+/// Only reads the header, like `image::image_dimensions`.
+pub fn oriented_dimensions(path: &Path) -> ImageResult<(u32, u32)> {
+    let mut decoder = ImageReader::open(path)?.into_decoder()?;
+    let (width, height) = decoder.dimensions();
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    if swaps_axes(orientation) {
+        Ok((height, width))
+    } else {
+        Ok((width, height))
+    }
+}
+
+/// Decode an image and rotate/flip it upright according to its EXIF orientation
+pub fn open_oriented(path: &Path) -> ImageResult<DynamicImage> {
+    let mut decoder = ImageReader::open(path)?.into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// Refuse to decode images whose longer side exceeds `max` pixels (0 = no
+/// limit). Only the header is read, so the check is cheap.
+pub fn check_decode_size(path: &Path, max: u32) -> anyhow::Result<()> {
+    if max == 0 {
+        return Ok(());
+    }
+    let (width, height) = oriented_dimensions(path)?;
+    ensure_decode_size(width, height, max)
+}
+
+/// `check_decode_size` for dimensions that are already known
+pub fn ensure_decode_size(width: u32, height: u32, max: u32) -> anyhow::Result<()> {
+    if max > 0 && width.max(height) > max {
+        anyhow::bail!(
+            "{}x{} is larger than scan.max_decode_dimension ({}px); not decoded to save memory",
+            width,
+            height,
+            max
+        );
+    }
+    Ok(())
+}
+
+/// Open an image as a single still frame.
+///
+/// Animated GIFs are decoded explicitly to frame 0 so colors and thumbnails
+/// always come from the same frame the static wallpaper shows.
+pub fn open_still(path: &Path) -> ImageResult<DynamicImage> {
+    if !crate::wallpaper::is_gif(path) {
+        return open_oriented(path);
+    }
+
+    let reader = BufReader::new(File::open(path)?);
+    let frame = GifDecoder::new(reader)?
+        .into_frames()
+        .next()
+        .ok_or_else(|| {
+            ImageError::Decoding(DecodingError::new(
+                ImageFormat::Gif.into(),
+                "GIF has no frames",
+            ))
+        })??;
+    Ok(DynamicImage::ImageRgba8(frame.into_buffer()))
+}
+
+/// Whether an orientation turns the image by 90° or 270°
+fn swaps_axes(orientation: Orientation) -> bool {
+    matches!(
+        orientation,
+        Orientation::Rotate90
+            | Orientation::Rotate270
+            | Orientation::Rotate90FlipH
+            | Orientation::Rotate270FlipH
+    )
+}
+
 /// Expand tilde (~) in path
 pub fn expand_tilde(path: &str) -> std::path::PathBuf {
     if let Some(rest) = path.strip_prefix("~/") {
@@ -523,6 +957,85 @@ pub fn expand_tilde(path: &str) -> std::path::PathBuf {
     std::path::PathBuf::from(path)
 }
 
+/// First match for an executable name in `$PATH`
+pub fn find_on_path(name: &str) -> Option<std::path::PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Put `text` on the system clipboard with wl-copy on Wayland or xclip/xsel
+/// on X11. Returns the tool that took it.
+pub fn copy_to_clipboard(text: &str) -> anyhow::Result<&'static str> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut tools: Vec<(&'static str, &[&str])> = Vec::new();
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        tools.push(("wl-copy", &[]));
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        tools.push(("xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", &["--clipboard", "--input"]));
+    }
+
+    for (tool, args) in tools {
+        if find_on_path(tool).is_none() {
+            continue;
+        }
+        // The tools fork to keep serving the selection; only the parent is waited on
+        let mut child = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(tool);
+        }
+    }
+    anyhow::bail!("No clipboard tool found (install wl-clipboard, xclip or xsel)")
+}
+
+/// Set by `--no-save`; checked by every config/cache writer
+static SAVES_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Turn config and cache writes into no-ops for the rest of this process
+pub fn disable_saves() {
+    SAVES_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether config and cache files may be written
+pub fn saves_enabled() -> bool {
+    !SAVES_DISABLED.load(Ordering::Relaxed)
+}
+
+/// Set by `--seed`; replaces `thread_rng` so random picks are reproducible
+static SEEDED_RNG: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Make every later random pick in this process follow `seed` (`None` goes
+/// back to `thread_rng`)
+pub fn set_rng_seed(seed: Option<u64>) {
+    *SEEDED_RNG.lock().unwrap_or_else(|e| e.into_inner()) = seed.map(StdRng::seed_from_u64);
+}
+
+/// Run `f` with the `--seed` RNG if one was set, otherwise with `thread_rng`.
+/// Don't call `with_rng` again from inside `f`.
+pub fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    let mut seeded = SEEDED_RNG.lock().unwrap_or_else(|e| e.into_inner());
+    match seeded.as_mut() {
+        Some(rng) => f(rng),
+        None => {
+            drop(seeded);
+            f(&mut rand::thread_rng())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -639,6 +1152,24 @@ mod tests {
         assert!(hex_to_hsl("invalid").is_none());
     }
 
+    // --- ColorFormat ---
+
+    #[test]
+    fn test_color_format() {
+        assert_eq!(ColorFormat::Hex.format("#1a2b3c"), "#1a2b3c");
+        assert_eq!(ColorFormat::Rgb.format("#1a2b3c"), "rgb(26,43,60)");
+        assert_eq!(ColorFormat::Hsl.format("#1a2b3c"), "hsl(210,40%,17%)");
+        assert_eq!(ColorFormat::Hsl.format("#808080"), "hsl(0,0%,50%)");
+        assert_eq!(ColorFormat::Rgb.format("oops"), "oops");
+    }
+
+    #[test]
+    fn test_color_format_parse() {
+        assert_eq!(ColorFormat::parse("RGB").unwrap(), ColorFormat::Rgb);
+        assert_eq!(ColorFormat::parse("hsl").unwrap(), ColorFormat::Hsl);
+        assert!(ColorFormat::parse("cmyk").is_err());
+    }
+
     // --- delta_e_2000 ---
 
     #[test]
@@ -690,6 +1221,61 @@ mod tests {
         );
     }
 
+    // --- simulate_cvd ---
+
+    #[test]
+    fn test_simulate_cvd_normal_is_identity() {
+        assert_eq!(simulate_cvd("#ff0000", HarmonyMode::Normal), "#ff0000");
+        assert_eq!(
+            simulate_cvd("not-a-color", HarmonyMode::Deuteranopia),
+            "not-a-color"
+        );
+    }
+
+    #[test]
+    fn test_simulate_cvd_keeps_grays() {
+        for mode in [
+            HarmonyMode::Protanopia,
+            HarmonyMode::Deuteranopia,
+            HarmonyMode::Tritanopia,
+        ] {
+            let (r, g, b) = hex_to_rgb(&simulate_cvd("#808080", mode)).unwrap();
+            assert!(
+                r.abs_diff(128) <= 2 && g.abs_diff(128) <= 2 && b.abs_diff(128) <= 2,
+                "{:?} should leave neutral gray alone, got {} {} {}",
+                mode,
+                r,
+                g,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulate_cvd_deuteranopia_confuses_red_and_green() {
+        let red = hex_to_lab(&simulate_cvd("#cc3333", HarmonyMode::Deuteranopia)).unwrap();
+        let green = hex_to_lab(&simulate_cvd("#33aa33", HarmonyMode::Deuteranopia)).unwrap();
+        let normal = delta_e_2000(
+            &hex_to_lab("#cc3333").unwrap(),
+            &hex_to_lab("#33aa33").unwrap(),
+        );
+        assert!(
+            delta_e_2000(&red, &green) < normal / 2.0,
+            "red and green should look much closer to a deuteranope"
+        );
+    }
+
+    #[test]
+    fn test_detect_harmony_for_normal_matches_detect_harmony() {
+        let colors1 = vec!["#ff0000".to_string()];
+        let colors2 = vec!["#00ffff".to_string()];
+        let weights = vec![1.0];
+        assert_eq!(
+            detect_harmony_for(&colors1, &weights, &colors2, &weights, HarmonyMode::Normal),
+            detect_harmony(&colors1, &weights, &colors2, &weights)
+        );
+    }
+
     // --- detect_harmony ---
 
     #[test]
@@ -834,6 +1420,47 @@ mod tests {
         assert!(s.abs() < 0.01, "White saturation should be 0.0, got {}", s);
     }
 
+    // --- palette_tags ---
+
+    #[test]
+    fn test_color_name_families() {
+        assert_eq!(color_name("#ff0000"), Some("red"));
+        assert_eq!(color_name("#ff8000"), Some("orange"));
+        assert_eq!(color_name("#2060e0"), Some("blue"));
+        assert_eq!(color_name("#20a040"), Some("green"));
+        assert_eq!(color_name("#ff40c0"), Some("pink"));
+        assert_eq!(color_name("#101010"), Some("black"));
+        assert_eq!(color_name("#f5f5f5"), Some("white"));
+        assert_eq!(color_name("#808080"), Some("gray"));
+        assert_eq!(color_name("nope"), None);
+    }
+
+    #[test]
+    fn test_palette_tags_dark_blue_monochrome() {
+        let colors = vec!["#0a1a40".to_string(), "#102a60".to_string()];
+        assert_eq!(
+            palette_tags(&colors, &[0.7, 0.3]),
+            vec!["blue", "dark", "monochrome"]
+        );
+    }
+
+    #[test]
+    fn test_palette_tags_bright_vibrant_mixed() {
+        let colors = vec![
+            "#ffe030".to_string(),
+            "#30e0ff".to_string(),
+            "#ff60ff".to_string(),
+        ];
+        let tags = palette_tags(&colors, &[0.5, 0.3, 0.2]);
+        assert!(tags.contains(&"bright".to_string()), "{tags:?}");
+        assert!(tags.contains(&"vibrant".to_string()), "{tags:?}");
+        assert!(tags.contains(&"yellow".to_string()), "top weight names it");
+        assert!(!tags.contains(&"monochrome".to_string()));
+
+        assert!(palette_tags(&[], &[]).is_empty());
+        assert!(palette_tags(&["#777777".to_string()], &[]).contains(&"muted".to_string()));
+    }
+
     // --- is_image_file ---
 
     #[test]
@@ -861,6 +1488,44 @@ mod tests {
         assert!(!is_image_file(Path::new(".hidden")));
     }
 
+    // --- glob_match ---
+
+    #[test]
+    fn test_glob_match_wildcards() {
+        assert!(glob_match("*.part", "sunset.jpg.part"));
+        assert!(!glob_match("*.part", "sunset.jpg"));
+        assert!(glob_match(".*", ".sunset.jpg.kate-swp"));
+        assert!(!glob_match(".*", "sunset.jpg"));
+        assert!(glob_match("img_????.png", "img_0042.png"));
+        assert!(!glob_match("img_????.png", "img_42.png"));
+        assert!(glob_match("*tmp*", "download.tmp.1"));
+        assert!(glob_match("exact.png", "exact.png"));
+        assert!(glob_match("*", ""));
+    }
+
+    // --- format_size ---
+
+    #[test]
+    fn test_format_size_units() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 300 * 1024), "5.3 MB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GB");
+    }
+
+    // --- format_duration ---
+
+    #[test]
+    fn test_format_duration_units() {
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(12 * 60 + 30), "12m");
+        assert_eq!(format_duration(3 * 3600), "3h");
+        assert_eq!(format_duration(3 * 3600 + 5 * 60), "3h 5m");
+        assert_eq!(format_duration(2 * 86_400 + 4 * 3600 + 59), "2d 4h");
+        assert_eq!(format_duration(86_400), "1d");
+    }
+
     // --- expand_tilde ---
 
     #[test]
@@ -948,4 +1613,19 @@ mod tests {
             );
         }
     }
+
+    // --- find_similar_by_embedding ---
+
+    #[test]
+    fn test_find_similar_by_embedding_ranks_closest_first() {
+        let target = [1.0, 0.0, 0.0];
+        let e0 = [0.0, 1.0, 0.0];
+        let e1 = [0.9, 0.1, 0.0];
+        let e2 = [-1.0, 0.0, 0.0];
+        let candidates: Vec<(usize, &[f32])> = vec![(0, &e0), (1, &e1), (2, &e2)];
+        let results = find_similar_by_embedding(&target, &candidates, 2);
+        assert_eq!(results.len(), 2, "limit should cap results");
+        assert_eq!(results[0].1, 1, "Nearly parallel embedding should be best");
+        assert_eq!(results[1].1, 0);
+    }
 }
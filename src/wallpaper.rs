@@ -1,17 +1,54 @@
 use crate::clip::AutoTag;
 use crate::screen::{AspectCategory, Screen};
+use crate::swww::ResizeMode;
 use anyhow::{Context, Result};
 use image::imageops::FilterType;
 use kmeans_colors::get_kmeans_hamerly;
 use palette::{IntoColor, Lab, Srgb};
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use walkdir::WalkDir;
 
+static PREFER_NATIVE_RESOLUTION: AtomicBool = AtomicBool::new(false);
+
+/// Make next/prev/random prefer wallpapers that meet a screen's effective
+/// resolution (`display.prefer_native_resolution`)
+pub fn set_prefer_native_resolution(prefer: bool) {
+    PREFER_NATIVE_RESOLUTION.store(prefer, Ordering::Relaxed);
+}
+
+/// Strict aspect matches for `screen`, narrowed by the resolution preference
+fn screen_candidates<'a>(wallpapers: &'a [Wallpaper], screen: &Screen) -> Vec<&'a Wallpaper> {
+    let matching: Vec<&Wallpaper> = wallpapers
+        .iter()
+        .filter(|wp| wp.matches_screen(screen))
+        .collect();
+    if PREFER_NATIVE_RESOLUTION.load(Ordering::Relaxed) {
+        prefer_native_resolution(matching, screen)
+    } else {
+        matching
+    }
+}
+
+/// Keep the candidates that meet `screen`'s effective resolution, or all of
+/// them when none do
+pub fn prefer_native_resolution<'a>(
+    candidates: Vec<&'a Wallpaper>,
+    screen: &Screen,
+) -> Vec<&'a Wallpaper> {
+    if !candidates.iter().any(|wp| wp.meets_resolution(screen)) {
+        return candidates;
+    }
+    candidates
+        .into_iter()
+        .filter(|wp| wp.meets_resolution(screen))
+        .collect()
+}
+
 /// How strictly to match wallpaper aspect ratio to screen
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 pub enum MatchMode {
@@ -56,6 +93,342 @@ impl SortMode {
     }
 }
 
+/// How GIF files are treated during scanning and apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GifMode {
+    /// Treat GIFs as still images; colors come from the first frame
+    #[default]
+    Static,
+    /// Play GIFs through mpvpaper instead of swww
+    Animated,
+    /// Leave GIFs out of the library entirely
+    Skip,
+}
+
+/// Which pixels of the downscaled image feed k-means color extraction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PixelSampling {
+    /// Every pixel
+    #[default]
+    All,
+    /// One random pixel per 2x2 cell (stratified, ~25% of pixels)
+    Random,
+    /// Favor the middle of the image; edges and letterbox bars count less
+    CenterWeighted,
+}
+
+/// How `random` picks among the wallpapers that fit a screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RandomMode {
+    /// Every candidate is equally likely
+    #[default]
+    Uniform,
+    /// Favor wallpapers that haven't been shown for a while
+    LeastRecent,
+    /// Favor highly rated wallpapers (see `frostwall rate`)
+    Rated,
+}
+
+/// Highest wallpaper rating; 0 means unrated
+pub const MAX_RATING: u8 = 5;
+
+/// Rating as stars, e.g. `★★★☆☆`, or "unrated"
+pub fn rating_stars(rating: u8) -> String {
+    if rating == 0 {
+        return "unrated".to_string();
+    }
+    let rating = rating.min(MAX_RATING) as usize;
+    format!(
+        "{}{}",
+        "★".repeat(rating),
+        "☆".repeat(MAX_RATING as usize - rating)
+    )
+}
+
+/// Parse a rating filter such as `>=4`, `<3` or `5` into the ratings it keeps
+pub fn parse_rating_filter(s: &str) -> Option<std::ops::RangeInclusive<u8>> {
+    let s = s.trim();
+    let (op, value) = [">=", "<=", "==", ">", "<", "="]
+        .iter()
+        .find_map(|op| s.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("=", s));
+    let value: u8 = value.trim().parse().ok().filter(|&v| v <= MAX_RATING)?;
+    let range = match op {
+        ">=" => value..=MAX_RATING,
+        "<=" => 0..=value,
+        ">" => value.checked_add(1)?..=MAX_RATING,
+        "<" => 0..=value.checked_sub(1)?,
+        _ => value..=value,
+    };
+    (!range.is_empty()).then_some(range)
+}
+
+/// Wallpapers unseen for this long (or never shown) get the full weight in
+/// least-recent mode
+const LEAST_RECENT_MAX_AGE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Scan-time settings (`[scan]` in config.toml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanConfig {
+    /// GIF handling: "static", "animated" or "skip"
+    #[serde(default)]
+    pub gif_mode: GifMode,
+    /// Pixel sampling for color extraction: "all", "random" or "center-weighted"
+    #[serde(default)]
+    pub pixel_sampling: PixelSampling,
+    /// GIFs whose longer side is below this many pixels are left out (0 = keep all)
+    #[serde(default = "default_min_gif_size")]
+    pub min_gif_size: u32,
+    /// Merge tags from sidecar files (see `tag_sidecar_paths`) during scans
+    #[serde(default = "default_read_tag_sidecars")]
+    pub read_tag_sidecars: bool,
+    /// Tag each wallpaper with the name of its folder below the wallpaper
+    /// directory during scans (`nature/forest.jpg` gets `nature`)
+    #[serde(default)]
+    pub tags_from_dirs: bool,
+    /// With `tags_from_dirs`, also add every folder above that up to the
+    /// wallpaper directory (`nature/forest/a.jpg` gets `nature` and `forest`)
+    #[serde(default)]
+    pub tags_from_all_dirs: bool,
+    /// Images whose longer side exceeds this are never fully decoded for colors
+    /// or thumbnails (0 = no limit). Guards low-RAM machines against huge files.
+    #[serde(default)]
+    pub max_decode_dimension: u32,
+    /// Images are shrunk to fit this many pixels per side before color
+    /// extraction. Halving it quarters the pixel count (faster, coarser palette).
+    #[serde(default = "default_color_sample_size")]
+    pub color_sample_size: u32,
+    /// K-means iteration cap for color extraction
+    #[serde(default = "default_max_iterations")]
+    pub max_iterations: u32,
+    /// K-means stops once centroids move less than this (Lab units)
+    #[serde(default = "default_convergence_threshold")]
+    pub convergence_threshold: f32,
+    /// Palette colors closer than this (CIEDE2000) are merged into one, so a
+    /// gradient doesn't fill the palette with near-identical shades (0 = off)
+    #[serde(default = "default_palette_merge_delta_e")]
+    pub palette_merge_delta_e: f32,
+    /// Leave out files and directories below the wallpaper directory whose
+    /// names start with `.` (`.git`, `.cache`, `.Trash-1000`, ...)
+    #[serde(default = "default_skip_hidden")]
+    pub skip_hidden: bool,
+    /// Glob patterns for directories (or files) to leave out, matched against
+    /// the name and the path relative to the wallpaper directory ("trash", "raw/*")
+    #[serde(default)]
+    pub exclude_dirs: Vec<String>,
+}
+
+fn default_skip_hidden() -> bool {
+    true
+}
+
+fn default_min_gif_size() -> u32 {
+    200
+}
+
+fn default_color_sample_size() -> u32 {
+    128
+}
+
+fn default_max_iterations() -> u32 {
+    30
+}
+
+fn default_convergence_threshold() -> f32 {
+    5.0
+}
+
+fn default_palette_merge_delta_e() -> f32 {
+    6.0
+}
+
+fn default_read_tag_sidecars() -> bool {
+    true
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            gif_mode: GifMode::Static,
+            pixel_sampling: PixelSampling::All,
+            min_gif_size: default_min_gif_size(),
+            read_tag_sidecars: default_read_tag_sidecars(),
+            tags_from_dirs: false,
+            tags_from_all_dirs: false,
+            max_decode_dimension: 0,
+            color_sample_size: default_color_sample_size(),
+            max_iterations: default_max_iterations(),
+            convergence_threshold: default_convergence_threshold(),
+            palette_merge_delta_e: default_palette_merge_delta_e(),
+            skip_hidden: default_skip_hidden(),
+            exclude_dirs: Vec::new(),
+        }
+    }
+}
+
+impl ScanConfig {
+    /// Whether a path should be part of the library under these settings.
+    pub fn includes(&self, path: &Path) -> bool {
+        if !crate::utils::is_image_file(path) {
+            return false;
+        }
+        if !is_gif(path) {
+            return true;
+        }
+        self.gif_mode != GifMode::Skip && !self.is_tiny_gif(path)
+    }
+
+    /// Whether `path` below `root` is hidden or matches `exclude_dirs`.
+    /// `root` itself is never excluded.
+    pub fn excludes(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let Some(name) = relative.file_name().map(|n| n.to_string_lossy()) else {
+            return false;
+        };
+        if self.skip_hidden && name.starts_with('.') {
+            return true;
+        }
+        let relative = relative.to_string_lossy();
+        self.exclude_dirs.iter().any(|pattern| {
+            let pattern = pattern.trim_end_matches('/');
+            crate::utils::glob_match(pattern, &name) || crate::utils::glob_match(pattern, &relative)
+        })
+    }
+
+    /// Small GIFs are usually emoji or reaction images, not wallpapers.
+    /// Only the header is read; unreadable files are kept so the scan reports them.
+    fn is_tiny_gif(&self, path: &Path) -> bool {
+        if self.min_gif_size == 0 {
+            return false;
+        }
+        crate::utils::oriented_dimensions(path).is_ok_and(|(w, h)| w.max(h) < self.min_gif_size)
+    }
+}
+
+/// Patterns from a `.frostwallignore`, one glob per line like `exclude_dirs`
+/// and matched against paths relative to the file's directory. `#` starts a
+/// comment, `!pattern` keeps what an earlier pattern ignored; the last match wins.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreFile {
+    /// (pattern, negated)
+    rules: Vec<(String, bool)>,
+}
+
+impl IgnoreFile {
+    pub const NAME: &'static str = ".frostwallignore";
+
+    pub fn parse(content: &str) -> Self {
+        let rules = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (pattern, negated) = match line.strip_prefix('!') {
+                    Some(rest) => (rest, true),
+                    None => (line, false),
+                };
+                let pattern = pattern.trim_start_matches('/').trim_end_matches('/');
+                (pattern.to_string(), negated)
+            })
+            .filter(|(pattern, _)| !pattern.is_empty())
+            .collect();
+        Self { rules }
+    }
+
+    /// The ignore file in `dir`, if there is a readable one
+    pub fn load(dir: &Path) -> Option<Self> {
+        fs::read_to_string(dir.join(Self::NAME))
+            .ok()
+            .map(|content| Self::parse(&content))
+    }
+
+    /// `Some(true)` if the last matching pattern ignores `relative`,
+    /// `Some(false)` if it re-includes it, `None` if nothing matches
+    pub fn matches(&self, relative: &Path) -> Option<bool> {
+        let name = relative.file_name()?.to_string_lossy();
+        let relative = relative.to_string_lossy();
+        self.rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                crate::utils::glob_match(pattern, &name)
+                    || crate::utils::glob_match(pattern, &relative)
+            })
+            .map(|(_, negated)| !negated)
+    }
+}
+
+/// `.frostwallignore` files below a wallpaper directory, read once per directory
+struct IgnoreRules<'a> {
+    root: &'a Path,
+    files: HashMap<PathBuf, Option<IgnoreFile>>,
+}
+
+impl<'a> IgnoreRules<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Whether an ignore file in `path`'s parent or any directory up to the
+    /// root ignores it. Deeper files override shallower ones.
+    fn ignores(&mut self, path: &Path) -> bool {
+        let Some(parent) = path.parent() else {
+            return false;
+        };
+        if path == self.root || !parent.starts_with(self.root) {
+            return false;
+        }
+        // Root first, so deeper files get the last word
+        let mut dirs: Vec<&Path> = parent
+            .ancestors()
+            .take_while(|dir| dir.starts_with(self.root))
+            .collect();
+        dirs.reverse();
+
+        let mut ignored = false;
+        for dir in dirs {
+            let file = self
+                .files
+                .entry(dir.to_path_buf())
+                .or_insert_with(|| IgnoreFile::load(dir));
+            let verdict = file
+                .as_ref()
+                .and_then(|file| file.matches(path.strip_prefix(dir).ok()?));
+            if let Some(verdict) = verdict {
+                ignored = verdict;
+            }
+        }
+        ignored
+    }
+}
+
+/// Check if a path has a .gif extension.
+pub fn is_gif(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("gif"))
+}
+
+/// Unix timestamp `window` before now, for filtering by `modified_at`.
+pub fn recent_cutoff(window: std::time::Duration) -> u64 {
+    unix_now().saturating_sub(window.as_secs())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 impl MatchMode {
     /// Return human-readable display name for this match mode.
     pub fn display_name(&self) -> &'static str {
@@ -89,6 +462,9 @@ pub struct Wallpaper {
     /// CLIP-generated auto tags with confidence scores
     #[serde(default)]
     pub auto_tags: Vec<AutoTag>,
+    /// Tags derived from the palette during color extraction (see `utils::palette_tags`)
+    #[serde(default)]
+    pub palette_tags: Vec<String>,
     /// Color weights/proportions (how much of the image each color represents, 0.0-1.0)
     #[serde(default)]
     pub color_weights: Vec<f32>,
@@ -101,10 +477,71 @@ pub struct Wallpaper {
     /// Modification timestamp (seconds since epoch, for sorting)
     #[serde(default)]
     pub modified_at: u64,
+    /// Resize mode for this wallpaper, overriding `display.resize_mode`
+    #[serde(default)]
+    pub resize_override: Option<ResizeMode>,
+    /// Original URL, from the import sidecar (`<name>.<ext>.json`)
+    #[serde(default)]
+    pub source_url: Option<String>,
+    /// Author credit, from the import sidecar
+    #[serde(default)]
+    pub author: Option<String>,
+    /// User rating, 1-5 stars (0 = unrated)
+    #[serde(default)]
+    pub rating: u8,
+    /// `content_hash` of the file when it was read, to catch edits that keep
+    /// the modification time. `None` in caches from before it existed.
+    #[serde(default)]
+    pub content_hash: Option<u64>,
+}
+
+/// Bytes hashed from each end of a file by `content_hash`
+const CONTENT_HASH_SAMPLE: u64 = 64 * 1024;
+
+/// Cheap fingerprint of a file: its size plus the first and last 64 KB, so
+/// even huge images cost two small reads. FNV-1a keeps the value stable
+/// across builds, since it is stored in the cache.
+pub fn content_hash(path: &Path) -> Option<u64> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut hash = fnv1a(FNV_OFFSET, &len.to_le_bytes());
+
+    let mut buf = Vec::with_capacity(CONTENT_HASH_SAMPLE as usize);
+    file.by_ref()
+        .take(CONTENT_HASH_SAMPLE)
+        .read_to_end(&mut buf)
+        .ok()?;
+    hash = fnv1a(hash, &buf);
+
+    // The tail, without re-reading bytes the head already covered
+    let tail_start = len
+        .saturating_sub(CONTENT_HASH_SAMPLE)
+        .max(CONTENT_HASH_SAMPLE);
+    if tail_start < len {
+        buf.clear();
+        file.seek(SeekFrom::Start(tail_start)).ok()?;
+        file.take(CONTENT_HASH_SAMPLE).read_to_end(&mut buf).ok()?;
+        hash = fnv1a(hash, &buf);
+    }
+    Some(hash)
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
 }
 
 /// Current cache format version — bump when the serialized shape changes
-const CACHE_VERSION: u32 = 1;
+/// and add the upgrade step to `WallpaperCache::migrate`
+const CACHE_VERSION: u32 = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WallpaperCache {
@@ -119,9 +556,81 @@ pub struct WallpaperCache {
     /// Whether the cache was built with recursive scanning
     #[serde(default)]
     pub recursive: bool,
+    /// When each wallpaper was last applied (unix seconds), for least-recent random
+    #[serde(default)]
+    pub last_shown_at: HashMap<PathBuf, u64>,
 }
 
-#[derive(Debug, Default)]
+/// Stage of a full scan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanPhase {
+    /// Reading image headers
+    Dimensions,
+    /// Extracting palettes
+    Colors,
+}
+
+impl ScanPhase {
+    pub fn number(&self) -> u8 {
+        match self {
+            ScanPhase::Dimensions => 1,
+            ScanPhase::Colors => 2,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScanPhase::Dimensions => "Reading dimensions",
+            ScanPhase::Colors => "Extracting colors",
+        }
+    }
+}
+
+/// Reported by `WallpaperCache::scan_with_progress`, possibly from several threads
+#[derive(Debug, Clone)]
+pub enum ScanUpdate {
+    /// `done` of `total` files finished in `phase`; `done == 0` marks the start
+    Progress {
+        phase: ScanPhase,
+        done: usize,
+        total: usize,
+    },
+    /// A file was skipped or has no palette
+    Failed { path: PathBuf, error: String },
+}
+
+/// Terminal progress for CLI scans: one line per phase, updated in place
+fn print_scan_update(update: ScanUpdate) {
+    let inline = crate::logging::inline_progress();
+    match update {
+        ScanUpdate::Progress { phase, done, total } => {
+            let label = format!("Phase {}/2: {}...", phase.number(), phase.label());
+            if !inline {
+                if done == 0 || done == total {
+                    tracing::debug!("{} {}/{}", label, done, total);
+                }
+                return;
+            }
+            if done == 0 {
+                eprint!("{}", label);
+            } else if phase == ScanPhase::Colors || done.is_multiple_of(50) || done == total {
+                eprint!("\r{} {}/{}", label, done, total);
+            }
+            if done == total {
+                eprintln!(" done!");
+            }
+        }
+        ScanUpdate::Failed { path, error } => {
+            if inline {
+                // Finish the progress line first
+                eprintln!();
+            }
+            tracing::warn!("Failed to read {}: {}", path.display(), error);
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
 pub struct CacheStats {
     pub total: usize,
     pub ultrawide: usize,
@@ -130,12 +639,51 @@ pub struct CacheStats {
     pub square: usize,
 }
 
+/// Problems found by `WallpaperCache::check`
+#[derive(Debug, Default, Serialize)]
+pub struct CacheReport {
+    /// Entries whose file is gone
+    pub missing: Vec<PathBuf>,
+    /// Entries without an extracted palette
+    pub no_colors: Vec<PathBuf>,
+    /// Entries with a palette but no color weights
+    pub no_weights: Vec<PathBuf>,
+    /// Entries whose cached size disagrees with the image header
+    pub wrong_dimensions: Vec<DimensionMismatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DimensionMismatch {
+    pub path: PathBuf,
+    pub cached: (u32, u32),
+    pub actual: (u32, u32),
+}
+
+impl CacheReport {
+    pub fn is_clean(&self) -> bool {
+        self.missing.is_empty()
+            && self.no_colors.is_empty()
+            && self.no_weights.is_empty()
+            && self.wrong_dimensions.is_empty()
+    }
+}
+
+/// What `WallpaperCache::repair` changed
+#[derive(Debug, Default)]
+pub struct RepairSummary {
+    pub removed: usize,
+    pub recolored: usize,
+    pub resized: usize,
+    pub failed: usize,
+}
+
 impl Wallpaper {
     /// Fast path: only read dimensions from image header (no full decode)
     pub fn from_path_fast(path: &Path) -> Result<Self> {
         // Only read image header - much faster than full decode!
+        // Phone portraits are often stored landscape with an EXIF rotation flag.
         let (width, height) =
-            image::image_dimensions(path).context("Failed to read image dimensions")?;
+            crate::utils::oriented_dimensions(path).context("Failed to read image dimensions")?;
         let aspect_category = Self::categorize_aspect(width, height);
 
         // Get file metadata for sorting
@@ -147,6 +695,8 @@ impl Wallpaper {
             .map(|d| d.as_secs())
             .unwrap_or(0);
 
+        let attribution = crate::webimport::Attribution::load_for(path);
+
         Ok(Self {
             path: path.to_path_buf(),
             width,
@@ -156,78 +706,35 @@ impl Wallpaper {
             color_weights: Vec::new(),
             tags: Vec::new(),
             auto_tags: Vec::new(),
+            palette_tags: Vec::new(),
             embedding: None,
             file_size,
             modified_at,
+            resize_override: None,
+            source_url: attribution.as_ref().map(|a| a.url.clone()),
+            author: attribution.and_then(|a| a.author),
+            rating: 0,
+            content_hash: content_hash(path),
         })
     }
 
     /// Extract colors for a wallpaper (call after from_path_fast if colors needed)
-    pub fn extract_colors(&mut self) -> Result<()> {
+    pub fn extract_colors(&mut self, scan: &ScanConfig) -> Result<()> {
         if !self.colors.is_empty() {
             return Ok(()); // Already extracted
         }
 
-        const K: usize = 5;
-        const CONVERGENCE_THRESHOLD: f32 = 5.0; // Looser convergence (was 2.0)
-        const MAX_ITERATIONS: u32 = 30; // Faster (was 100)
-        const THUMBNAIL_SIZE: u32 = 128; // Smaller (was 256)
-
-        let img = image::open(&self.path).context("Failed to open image")?;
-        let thumb = img.resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle);
-        let pixels: Vec<_> = thumb.to_rgb8().pixels().cloned().collect();
-
-        let lab: Vec<Lab> = pixels
-            .par_iter()
-            .map(|p| {
-                let rgb = Srgb::new(
-                    p.0[0] as f32 / 255.0,
-                    p.0[1] as f32 / 255.0,
-                    p.0[2] as f32 / 255.0,
-                );
-                rgb.into_color()
-            })
-            .collect();
-
-        let result = get_kmeans_hamerly(
-            K,
-            MAX_ITERATIONS as usize,
-            CONVERGENCE_THRESHOLD,
-            false,
-            &lab,
-            0,
-        );
-
-        // Calculate color weights (proportion of image each color represents)
-        let total_pixels = lab.len() as f32;
-        let mut counts = [0usize; K];
-        for &idx in &result.indices {
-            counts[idx as usize] += 1;
-        }
-
-        // Create paired colors and weights, then sort by weight descending
-        let mut color_weight_pairs: Vec<(String, f32)> = result
-            .centroids
-            .iter()
-            .zip(counts.iter())
-            .map(|(c, &count)| {
-                let rgb: Srgb = (*c).into_color();
-                let r = (rgb.red * 255.0) as u8;
-                let g = (rgb.green * 255.0) as u8;
-                let b = (rgb.blue * 255.0) as u8;
-                let hex = format!("#{:02x}{:02x}{:02x}", r, g, b);
-                let weight = count as f32 / total_pixels;
-                (hex, weight)
-            })
-            .collect();
-
-        // Sort by weight descending (most dominant color first)
-        color_weight_pairs
-            .sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        // Dimensions come from the header, so huge files are refused before decoding
+        crate::utils::ensure_decode_size(self.width, self.height, scan.max_decode_dimension)?;
+        let img = crate::utils::open_still(&self.path).context("Failed to open image")?;
+        let size = scan.color_sample_size.max(1);
+        let thumb = img.resize(size, size, FilterType::Triangle).to_rgb8();
+        let pixels = sample_pixels(&thumb, scan.pixel_sampling);
 
-        // Unzip into separate vectors
-        self.colors = color_weight_pairs.iter().map(|(c, _)| c.clone()).collect();
-        self.color_weights = color_weight_pairs.iter().map(|(_, w)| *w).collect();
+        let (colors, weights) = dominant_colors(&pixels, scan);
+        self.palette_tags = crate::utils::palette_tags(&colors, &weights);
+        self.colors = colors;
+        self.color_weights = weights;
 
         // Note: Auto-tags are now generated via CLIP (frostwall auto-tag command)
         // and not automatically from color extraction
@@ -236,9 +743,9 @@ impl Wallpaper {
     }
 
     /// Full path with colors (legacy, slower)
-    pub fn from_path(path: &Path) -> Result<Self> {
+    pub fn from_path(path: &Path, scan: &ScanConfig) -> Result<Self> {
         let mut wp = Self::from_path_fast(path)?;
-        wp.extract_colors()?;
+        wp.extract_colors(scan)?;
         Ok(wp)
     }
 
@@ -267,6 +774,12 @@ impl Wallpaper {
         self.aspect_category == screen.aspect_category
     }
 
+    /// Whether the image covers `screen`'s effective (scaled) pixels without upscaling
+    pub fn meets_resolution(&self, screen: &Screen) -> bool {
+        let (width, height) = screen.effective_size();
+        self.width >= width && self.height >= height
+    }
+
     /// Flexible match - allows compatible aspect ratios
     /// - Landscape wallpapers work on Ultrawide screens (will be cropped/padded)
     /// - Portrait wallpapers work on Portrait screens
@@ -313,17 +826,70 @@ impl Wallpaper {
         }
     }
 
+    /// Merge tags from the first existing tag sidecar into `tags`.
+    /// Only adds: tags edited in the TUI stay even if the sidecar doesn't list
+    /// them, and reading the same sidecar again changes nothing.
+    /// Returns the number of tags added.
+    pub fn merge_sidecar_tags(&mut self) -> usize {
+        let Some(contents) = tag_sidecar_paths(&self.path)
+            .into_iter()
+            .find_map(|sidecar| fs::read_to_string(sidecar).ok())
+        else {
+            return 0;
+        };
+
+        let before = self.tags.len();
+        for tag in parse_sidecar_tags(&contents) {
+            self.add_tag(&tag);
+        }
+        self.tags.len() - before
+    }
+
+    /// Add the folder names between `root` and this file as tags: the parent
+    /// only, or every folder with `all`. Files directly in `root` get none.
+    /// Returns the number of tags added.
+    pub fn merge_dir_tags(&mut self, root: &Path, all: bool) -> usize {
+        let before = self.tags.len();
+        for tag in dir_tags(&self.path, root, all) {
+            self.add_tag(&tag);
+        }
+        self.tags.len() - before
+    }
+
     /// Remove a tag from this wallpaper
     pub fn remove_tag(&mut self, tag: &str) {
         let tag = tag.to_lowercase();
         self.tags.retain(|t| t != &tag);
     }
 
+    /// Replace any of the `from` tags with `into` (case-insensitive). Auto-tags
+    /// are untouched. Returns whether the wallpaper had one of the tags.
+    pub fn merge_tags(&mut self, from: &[String], into: &str) -> bool {
+        let into = into.trim().to_lowercase();
+        let from: HashSet<String> = from
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| *tag != into)
+            .collect();
+        if !self.tags.iter().any(|t| from.contains(&t.to_lowercase())) {
+            return false;
+        }
+        self.tags.retain(|t| !from.contains(&t.to_lowercase()));
+        self.add_tag(&into);
+        true
+    }
+
+    /// Resize mode to use for this wallpaper, given the configured default
+    pub fn resize_mode(&self, default: ResizeMode) -> ResizeMode {
+        self.resize_override.unwrap_or(default)
+    }
+
     /// Check if wallpaper has a specific tag (manual or auto)
     pub fn has_tag(&self, tag: &str) -> bool {
         let tag = tag.to_lowercase();
         self.tags.iter().any(|t| t == &tag)
             || self.auto_tags.iter().any(|t| t.name.to_lowercase() == tag)
+            || self.palette_tags.iter().any(|t| t == &tag)
     }
 
     /// Check if wallpaper has any of the given tags
@@ -338,10 +904,31 @@ impl Wallpaper {
         tags.iter().all(|t| self.has_tag(t))
     }
 
+    /// Check if any palette color is perceptually similar to `color`
+    pub fn has_similar_color(&self, color: &str, threshold: f32) -> bool {
+        self.colors
+            .iter()
+            .any(|c| crate::utils::color_similarity(c, color) > threshold)
+    }
+
+    /// Check the palette against several filter colors: every one must be
+    /// present, or at least one when `require_all` is false. Empty matches all.
+    pub fn matches_colors(&self, colors: &[String], threshold: f32, require_all: bool) -> bool {
+        if colors.is_empty() {
+            return true;
+        }
+        if require_all {
+            colors.iter().all(|c| self.has_similar_color(c, threshold))
+        } else {
+            colors.iter().any(|c| self.has_similar_color(c, threshold))
+        }
+    }
+
     /// Get all tags (manual + auto tag names)
     pub fn all_tags(&self) -> Vec<String> {
         let mut all: Vec<String> = self.tags.clone();
         all.extend(self.auto_tags.iter().map(|t| t.name.clone()));
+        all.extend(self.palette_tags.iter().cloned());
         all.sort();
         all.dedup();
         all
@@ -375,7 +962,241 @@ impl Wallpaper {
     }
 }
 
+/// Pick the pixels used for color extraction according to `sampling`.
+///
+/// Sampling is seeded so rescans of an unchanged image give the same palette.
+fn sample_pixels(img: &image::RgbImage, sampling: PixelSampling) -> Vec<image::Rgb<u8>> {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const CELL: u32 = 2;
+
+    let (width, height) = img.dimensions();
+    let mut rng = StdRng::seed_from_u64(u64::from(width) << 32 | u64::from(height));
+
+    match sampling {
+        PixelSampling::All => img.pixels().copied().collect(),
+        PixelSampling::Random => {
+            let mut out = Vec::with_capacity((width * height / (CELL * CELL)) as usize + 1);
+            for cy in (0..height).step_by(CELL as usize) {
+                for cx in (0..width).step_by(CELL as usize) {
+                    let x = (cx + rng.gen_range(0..CELL)).min(width - 1);
+                    let y = (cy + rng.gen_range(0..CELL)).min(height - 1);
+                    out.push(*img.get_pixel(x, y));
+                }
+            }
+            out
+        }
+        PixelSampling::CenterWeighted => {
+            let cx = (width as f32 - 1.0) / 2.0;
+            let cy = (height as f32 - 1.0) / 2.0;
+            let max_dist = (cx * cx + cy * cy).sqrt().max(1.0);
+            img.enumerate_pixels()
+                .filter(|(x, y, _)| {
+                    let dx = *x as f32 - cx;
+                    let dy = *y as f32 - cy;
+                    let d = (dx * dx + dy * dy).sqrt() / max_dist;
+                    // Linear falloff from the center down to 10% at the corners
+                    let keep = (1.0 - d).max(0.1);
+                    rng.gen::<f32>() < keep
+                })
+                .map(|(_, _, p)| *p)
+                .collect()
+        }
+    }
+}
+
+/// Run k-means in Lab space and return hex colors with their weights,
+/// most dominant first. Iterations and convergence come from `scan`.
+fn dominant_colors(pixels: &[image::Rgb<u8>], scan: &ScanConfig) -> (Vec<String>, Vec<f32>) {
+    const K: usize = 5;
+
+    if pixels.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let lab: Vec<Lab> = pixels
+        .par_iter()
+        .map(|p| {
+            let rgb = Srgb::new(
+                p.0[0] as f32 / 255.0,
+                p.0[1] as f32 / 255.0,
+                p.0[2] as f32 / 255.0,
+            );
+            rgb.into_color()
+        })
+        .collect();
+
+    let result = get_kmeans_hamerly(
+        K,
+        scan.max_iterations.max(1) as usize,
+        scan.convergence_threshold,
+        false,
+        &lab,
+        0,
+    );
+
+    // Calculate color weights (proportion of image each color represents)
+    let total_pixels = lab.len() as f32;
+    let mut counts = [0usize; K];
+    for &idx in &result.indices {
+        counts[idx as usize] += 1;
+    }
+    let clusters: Vec<(Lab, f32)> = result
+        .centroids
+        .iter()
+        .zip(counts.iter())
+        .map(|(c, &count)| (*c, count as f32 / total_pixels))
+        .collect();
+    let clusters = merge_similar_colors(clusters, scan.palette_merge_delta_e);
+
+    // Create paired colors and weights, then sort by weight descending
+    let mut color_weight_pairs: Vec<(String, f32)> = clusters
+        .iter()
+        .map(|&(c, weight)| {
+            let rgb: Srgb = c.into_color();
+            let r = (rgb.red * 255.0) as u8;
+            let g = (rgb.green * 255.0) as u8;
+            let b = (rgb.blue * 255.0) as u8;
+            let hex = format!("#{:02x}{:02x}{:02x}", r, g, b);
+            (hex, weight)
+        })
+        .collect();
+
+    // Sort by weight descending (most dominant color first)
+    color_weight_pairs.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    // Unzip into separate vectors
+    color_weight_pairs.into_iter().unzip()
+}
+
+/// Fold palette entries closer than `max_delta_e` into the heavier one they
+/// are near, moving the color to the weighted mean and adding the weights.
+/// Heaviest entries are placed first, so they anchor the merged colors.
+fn merge_similar_colors(mut clusters: Vec<(Lab, f32)>, max_delta_e: f32) -> Vec<(Lab, f32)> {
+    if max_delta_e <= 0.0 {
+        return clusters;
+    }
+    clusters.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut merged: Vec<(Lab, f32)> = Vec::with_capacity(clusters.len());
+    for (color, weight) in clusters {
+        let near = merged
+            .iter_mut()
+            .find(|(kept, _)| crate::utils::delta_e_2000(kept, &color) < max_delta_e);
+        match near {
+            Some((kept, kept_weight)) => {
+                let total = *kept_weight + weight;
+                if total > 0.0 {
+                    let (a, b) = (*kept_weight / total, weight / total);
+                    *kept = Lab::new(
+                        kept.l * a + color.l * b,
+                        kept.a * a + color.a * b,
+                        kept.b * a + color.b * b,
+                    );
+                }
+                *kept_weight = total;
+            }
+            None => merged.push((color, weight)),
+        }
+    }
+    merged
+}
+
+/// Tag sidecar candidates for an image, in lookup order; the first that
+/// exists wins. For `sunset.jpg`:
+/// - `sunset.jpg.tags`
+/// - `sunset.tags`
+/// - `sunset.jpg.txt`
+///
+/// (`sunset.txt` is deliberately not read; plain notes next to images are common.)
+pub fn tag_sidecar_paths(path: &Path) -> Vec<PathBuf> {
+    let appended = |ext: &str| {
+        let mut name = path.as_os_str().to_owned();
+        name.push(ext);
+        PathBuf::from(name)
+    };
+    vec![
+        appended(".tags"),
+        path.with_extension("tags"),
+        appended(".txt"),
+    ]
+}
+
+/// Tags in a sidecar, separated by commas and/or newlines. Blank entries and
+/// `#` comment lines are ignored.
+pub fn parse_sidecar_tags(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'))
+        .flat_map(|line| line.split(','))
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// Folder names of `path` below `root`, lowercased and without duplicates:
+/// the immediate parent only, or all of them (outermost first) with `all`.
+pub fn dir_tags(path: &Path, root: &Path, all: bool) -> Vec<String> {
+    let Some(dirs) = path
+        .parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+    else {
+        return Vec::new();
+    };
+    let names: Vec<String> = dirs
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(name) => {
+                Some(name.to_string_lossy().trim().to_lowercase())
+            }
+            _ => None,
+        })
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    let mut tags = Vec::new();
+    let picked = if all {
+        &names[..]
+    } else {
+        &names[names.len().saturating_sub(1)..]
+    };
+    for name in picked {
+        if !tags.contains(name) {
+            tags.push(name.clone());
+        }
+    }
+    tags
+}
+
 impl WallpaperCache {
+    /// Merge tags that come from outside the cache into every wallpaper:
+    /// sidecar files with `read_tag_sidecars`, folder names with
+    /// `tags_from_dirs`. Returns tags added.
+    fn merge_external_tags(&mut self, scan: &ScanConfig) -> usize {
+        let mut added = 0;
+        if scan.read_tag_sidecars {
+            added += self
+                .wallpapers
+                .iter_mut()
+                .map(Wallpaper::merge_sidecar_tags)
+                .sum::<usize>();
+        }
+        if scan.tags_from_dirs {
+            added += self.merge_dir_tags(scan.tags_from_all_dirs);
+        }
+        added
+    }
+
+    /// Tag every wallpaper with its folder names below `source_dir`
+    /// (see `Wallpaper::merge_dir_tags`). Returns tags added.
+    pub fn merge_dir_tags(&mut self, all: bool) -> usize {
+        let root = self.source_dir.clone();
+        self.wallpapers
+            .iter_mut()
+            .map(|wp| wp.merge_dir_tags(&root, all))
+            .sum()
+    }
+
     fn cache_path() -> PathBuf {
         directories::ProjectDirs::from("com", "mrmattias", "frostwall")
             .map(|dirs| dirs.cache_dir().to_path_buf())
@@ -384,76 +1205,173 @@ impl WallpaperCache {
     }
 
     /// Load cached wallpapers or scan the directory if cache is invalid.
-    pub fn load_or_scan(source_dir: &Path) -> Result<Self> {
-        Self::load_or_scan_recursive(source_dir, false)
+    pub fn load_or_scan(source_dir: &Path, scan: &ScanConfig) -> Result<Self> {
+        Self::load_or_scan_recursive(source_dir, false, scan)
     }
 
     /// Load cache for AI operations without forcing color extraction.
     /// Falls back to metadata-only scan when no valid cache exists.
-    pub fn load_or_scan_for_ai(source_dir: &Path) -> Result<Self> {
-        Self::load_or_scan_for_ai_recursive(source_dir, false)
+    pub fn load_or_scan_for_ai(source_dir: &Path, scan: &ScanConfig) -> Result<Self> {
+        Self::load_or_scan_for_ai_recursive(source_dir, false, scan)
     }
 
-    pub fn load_or_scan_recursive(source_dir: &Path, recursive: bool) -> Result<Self> {
-        let cache_path = Self::cache_path();
-
-        if cache_path.exists() {
-            let data = fs::read_to_string(&cache_path)?;
-            if let Ok(cache) = serde_json::from_str::<WallpaperCache>(&data) {
-                if cache.version != CACHE_VERSION {
-                    eprintln!(
-                        "Cache format changed (v{} -> v{}), rescanning...",
-                        cache.version, CACHE_VERSION
-                    );
-                    return Self::scan_recursive(source_dir, recursive);
-                }
-                // Verify source dir matches and files still exist
-                if cache.source_dir == source_dir && cache.validate() {
-                    return Ok(cache);
-                }
-            }
+    pub fn load_or_scan_recursive(
+        source_dir: &Path,
+        recursive: bool,
+        scan: &ScanConfig,
+    ) -> Result<Self> {
+        if let Some(cache) = Self::load_valid(source_dir, scan)? {
+            return Ok(cache);
         }
 
         // Scan fresh
-        Self::scan_recursive(source_dir, recursive)
+        Self::scan_recursive(source_dir, recursive, scan)
     }
 
-    pub fn load_or_scan_for_ai_recursive(source_dir: &Path, recursive: bool) -> Result<Self> {
-        let cache_path = Self::cache_path();
+    /// The cached library for `source_dir`, if it is still up to date.
+    pub fn load_valid(source_dir: &Path, scan: &ScanConfig) -> Result<Option<Self>> {
+        // Verify source dir matches and files still exist
+        Ok(Self::load_cached()?
+            .filter(|cache| cache.source_dir == source_dir && cache.validate(scan)))
+    }
 
-        if cache_path.exists() {
-            let data = fs::read_to_string(&cache_path)?;
-            if let Ok(cache) = serde_json::from_str::<WallpaperCache>(&data) {
-                if cache.version != CACHE_VERSION {
-                    eprintln!(
-                        "Cache format changed (v{} -> v{}), rescanning...",
-                        cache.version, CACHE_VERSION
-                    );
-                    return Self::scan_metadata_only_recursive(source_dir, recursive);
-                }
-                // For AI tagging we only need metadata/path validity, not extracted color palettes.
-                if cache.source_dir == source_dir && cache.validate_for_ai() {
-                    return Ok(cache);
-                }
-            }
-        }
+    /// A library with no wallpapers yet, to be filled by a background scan.
+    pub fn empty(source_dir: &Path, recursive: bool) -> Self {
+        Self {
+            version: CACHE_VERSION,
+            wallpapers: Vec::new(),
+            source_dir: source_dir.to_path_buf(),
+            screen_indices: HashMap::new(),
+            recursive,
+            last_shown_at: HashMap::new(),
+        }
+    }
+
+    pub fn load_or_scan_for_ai_recursive(
+        source_dir: &Path,
+        recursive: bool,
+        scan: &ScanConfig,
+    ) -> Result<Self> {
+        if let Some(cache) = Self::load_cached()? {
+            // For AI tagging we only need metadata/path validity, not extracted color palettes.
+            if cache.source_dir == source_dir && cache.validate_for_ai(scan) {
+                return Ok(cache);
+            }
+        }
+
+        Self::scan_metadata_only_recursive(source_dir, recursive, scan)
+    }
+
+    /// Read the cache file, upgrading older formats in place.
+    /// `None` when there is no usable cache (missing, corrupt, or from a newer version).
+    pub fn load_cached() -> Result<Option<Self>> {
+        let cache_path = Self::cache_path();
+        if !cache_path.exists() {
+            return Ok(None);
+        }
+
+        let data = fs::read_to_string(&cache_path)?;
+        let Ok(mut cache) = serde_json::from_str::<WallpaperCache>(&data) else {
+            return Ok(None);
+        };
+
+        if cache.version != CACHE_VERSION {
+            let from = cache.version;
+            if !cache.migrate() {
+                tracing::info!(
+                    "Cache is from a newer frostwall (v{} > v{}), rescanning...",
+                    from,
+                    CACHE_VERSION
+                );
+                return Ok(None);
+            }
+            tracing::info!("Upgraded cache format v{} -> v{}", from, CACHE_VERSION);
+            cache.save()?;
+        }
+
+        Ok(Some(cache))
+    }
+
+    /// Upgrade a cache written by an older version, keeping tags, embeddings and
+    /// overrides. Returns false for caches from a newer version.
+    fn migrate(&mut self) -> bool {
+        if self.version > CACHE_VERSION {
+            return false;
+        }
+
+        while self.version < CACHE_VERSION {
+            match self.version {
+                // v0 → v1: no version field yet; palettes may predate color weights
+                // and entries may predate the size/mtime sort keys
+                0 => {
+                    for wp in &mut self.wallpapers {
+                        if !wp.colors.is_empty() && wp.color_weights.len() != wp.colors.len() {
+                            wp.color_weights = vec![1.0 / wp.colors.len() as f32; wp.colors.len()];
+                        }
+                        if wp.file_size == 0 || wp.modified_at == 0 {
+                            if let Ok(meta) = fs::metadata(&wp.path) {
+                                wp.file_size = meta.len();
+                                wp.modified_at = meta
+                                    .modified()
+                                    .ok()
+                                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                                    .map(|d| d.as_secs())
+                                    .unwrap_or(0);
+                            }
+                        }
+                    }
+                }
+                // v1 → v2: attribution from import sidecars
+                1 => {
+                    for wp in &mut self.wallpapers {
+                        if let Some(attribution) = crate::webimport::Attribution::load_for(&wp.path)
+                        {
+                            wp.source_url = Some(attribution.url);
+                            wp.author = attribution.author;
+                        }
+                    }
+                }
+                // v2 → v3: dimensions respect EXIF orientation
+                2 => {
+                    for wp in &mut self.wallpapers {
+                        if let Ok((width, height)) = crate::utils::oriented_dimensions(&wp.path) {
+                            wp.width = width;
+                            wp.height = height;
+                            wp.aspect_category = Wallpaper::categorize_aspect(width, height);
+                        }
+                    }
+                }
+                // v3 → v4: palette tags, from the palette already stored
+                3 => {
+                    for wp in &mut self.wallpapers {
+                        wp.palette_tags = crate::utils::palette_tags(&wp.colors, &wp.color_weights);
+                    }
+                }
+                _ => {}
+            }
+            self.version += 1;
+        }
 
-        Self::scan_metadata_only_recursive(source_dir, recursive)
+        true
     }
 
-    pub fn scan(source_dir: &Path) -> Result<Self> {
-        Self::scan_recursive(source_dir, false)
+    pub fn scan(source_dir: &Path, scan: &ScanConfig) -> Result<Self> {
+        Self::scan_recursive(source_dir, false, scan)
     }
 
-    pub fn scan_recursive(source_dir: &Path, recursive: bool) -> Result<Self> {
-        let entries: Vec<PathBuf> = if recursive {
-            // Use walkdir for recursive scanning
+    /// List image files in `source_dir` that the scan settings and any
+    /// `.frostwallignore` files accept.
+    pub fn discover(source_dir: &Path, recursive: bool, scan: &ScanConfig) -> Result<Vec<PathBuf>> {
+        let mut ignore = IgnoreRules::new(source_dir);
+        let entries = if recursive {
+            // Use walkdir for recursive scanning; excluded directories are not entered
             WalkDir::new(source_dir)
                 .follow_links(true)
                 .into_iter()
+                .filter_entry(|e| !scan.excludes(source_dir, e.path()) && !ignore.ignores(e.path()))
                 .filter_map(|e| e.ok())
                 .map(|e| e.path().to_path_buf())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
+                .filter(|p| p.is_file() && scan.includes(p))
                 .collect()
         } else {
             // Non-recursive: just read the directory
@@ -461,149 +1379,188 @@ impl WallpaperCache {
                 .with_context(|| format!("Failed to read directory: {}", source_dir.display()))?
                 .flatten()
                 .map(|e| e.path())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
+                .filter(|p| {
+                    p.is_file()
+                        && !scan.excludes(source_dir, p)
+                        && !ignore.ignores(p)
+                        && scan.includes(p)
+                })
                 .collect()
         };
+        Ok(entries)
+    }
+
+    pub fn scan_recursive(source_dir: &Path, recursive: bool, scan: &ScanConfig) -> Result<Self> {
+        let never = AtomicBool::new(false);
+        Self::scan_with_progress(source_dir, recursive, scan, &print_scan_update, &never)?
+            .context("Scan cancelled")
+    }
+
+    /// Full two-phase scan that reports through `report` instead of stderr.
+    /// Returns `None` if `cancel` was set before the scan finished.
+    pub fn scan_with_progress(
+        source_dir: &Path,
+        recursive: bool,
+        scan: &ScanConfig,
+        report: &(dyn Fn(ScanUpdate) + Sync),
+        cancel: &AtomicBool,
+    ) -> Result<Option<Self>> {
+        let entries = Self::discover(source_dir, recursive, scan)?;
 
         let total = entries.len();
         let processed = AtomicUsize::new(0);
 
         // Phase 1: Fast parallel scan (header only - dimensions)
-        eprint!("Phase 1/2: Reading dimensions...");
+        report(ScanUpdate::Progress {
+            phase: ScanPhase::Dimensions,
+            done: 0,
+            total,
+        });
         let mut wallpapers: Vec<Wallpaper> = entries
             .par_iter()
             .filter_map(|path| {
-                let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
-                if count.is_multiple_of(50) || count == total {
-                    eprint!("\rPhase 1/2: Reading dimensions... {}/{}", count, total);
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
                 }
-
-                match Wallpaper::from_path_fast(path) {
+                let wp = match Wallpaper::from_path_fast(path) {
                     Ok(wp) => Some(wp),
                     Err(e) => {
-                        eprintln!("\nWarning: Failed to read {}: {}", path.display(), e);
+                        report(ScanUpdate::Failed {
+                            path: path.clone(),
+                            error: e.to_string(),
+                        });
                         None
                     }
-                }
+                };
+                let done = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                report(ScanUpdate::Progress {
+                    phase: ScanPhase::Dimensions,
+                    done,
+                    total,
+                });
+                wp
             })
             .collect();
 
-        eprintln!(" done!");
+        if cancel.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
 
         // Phase 2: Batched parallel color extraction (10 at a time)
         let color_total = wallpapers.len();
         const BATCH_SIZE: usize = 10;
 
+        report(ScanUpdate::Progress {
+            phase: ScanPhase::Colors,
+            done: 0,
+            total: color_total,
+        });
         for (batch_idx, chunk) in wallpapers.chunks_mut(BATCH_SIZE).enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(None);
+            }
             let batch_start = batch_idx * BATCH_SIZE;
 
             chunk.par_iter_mut().for_each(|wp| {
-                if let Err(e) = wp.extract_colors() {
-                    eprintln!(
-                        "\nWarning: Failed to extract colors for {}: {}",
-                        wp.path.display(),
-                        e
-                    );
+                if let Err(e) = wp.extract_colors(scan) {
+                    report(ScanUpdate::Failed {
+                        path: wp.path.clone(),
+                        error: format!("color extraction: {}", e),
+                    });
                 }
             });
 
-            let progress = (batch_start + chunk.len()).min(color_total);
-            eprint!(
-                "\rPhase 2/2: Extracting colors... {}/{}",
-                progress, color_total
-            );
+            report(ScanUpdate::Progress {
+                phase: ScanPhase::Colors,
+                done: (batch_start + chunk.len()).min(color_total),
+                total: color_total,
+            });
         }
 
-        eprintln!(" done!");
-
         // Sort by filename for consistent ordering
         wallpapers.sort_by(|a, b| a.path.cmp(&b.path));
 
-        Ok(Self {
+        let mut cache = Self {
             version: CACHE_VERSION,
             wallpapers,
             source_dir: source_dir.to_path_buf(),
             screen_indices: HashMap::new(),
             recursive,
-        })
+            last_shown_at: HashMap::new(),
+        };
+        cache.merge_external_tags(scan);
+        Ok(Some(cache))
     }
 
     /// Fast scan for AI operations (dimensions + metadata only, no color extraction).
-    pub fn scan_metadata_only_recursive(source_dir: &Path, recursive: bool) -> Result<Self> {
-        let entries: Vec<PathBuf> = if recursive {
-            WalkDir::new(source_dir)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .map(|e| e.path().to_path_buf())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
-                .collect()
-        } else {
-            fs::read_dir(source_dir)
-                .with_context(|| format!("Failed to read directory: {}", source_dir.display()))?
-                .flatten()
-                .map(|e| e.path())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
-                .collect()
-        };
+    pub fn scan_metadata_only_recursive(
+        source_dir: &Path,
+        recursive: bool,
+        scan: &ScanConfig,
+    ) -> Result<Self> {
+        let entries = Self::discover(source_dir, recursive, scan)?;
 
         let total = entries.len();
         let processed = AtomicUsize::new(0);
 
-        eprint!("Phase 1/1: Reading metadata...");
+        let inline = crate::logging::inline_progress();
+        if inline {
+            eprint!("Phase 1/1: Reading metadata...");
+        } else {
+            tracing::debug!("Phase 1/1: Reading metadata of {} files", total);
+        }
         let mut wallpapers: Vec<Wallpaper> = entries
             .par_iter()
             .filter_map(|path| {
                 let count = processed.fetch_add(1, Ordering::Relaxed) + 1;
-                if count.is_multiple_of(50) || count == total {
+                if inline && (count.is_multiple_of(50) || count == total) {
                     eprint!("\rPhase 1/1: Reading metadata... {}/{}", count, total);
                 }
 
                 match Wallpaper::from_path_fast(path) {
                     Ok(wp) => Some(wp),
                     Err(e) => {
-                        eprintln!("\nWarning: Failed to read {}: {}", path.display(), e);
+                        if inline {
+                            eprintln!();
+                        }
+                        tracing::warn!("Failed to read {}: {}", path.display(), e);
                         None
                     }
                 }
             })
             .collect();
-        eprintln!(" done!");
+        if inline {
+            eprintln!(" done!");
+        } else {
+            tracing::debug!("Phase 1/1: Read {} of {} files", wallpapers.len(), total);
+        }
 
         wallpapers.sort_by(|a, b| a.path.cmp(&b.path));
 
-        Ok(Self {
+        let mut cache = Self {
             version: CACHE_VERSION,
             wallpapers,
             source_dir: source_dir.to_path_buf(),
             screen_indices: HashMap::new(),
             recursive,
-        })
+            last_shown_at: HashMap::new(),
+        };
+        cache.merge_external_tags(scan);
+        Ok(cache)
     }
 
     /// Incremental rescan: discover new files and remove deleted ones while
     /// preserving all existing data (tags, auto_tags, embeddings, colors).
     /// Returns (added, removed) counts.
-    pub fn incremental_rescan(&mut self, recursive: bool) -> Result<(usize, usize)> {
+    pub fn incremental_rescan(
+        &mut self,
+        recursive: bool,
+        scan: &ScanConfig,
+    ) -> Result<(usize, usize)> {
         let source_dir = self.source_dir.clone();
 
         // Discover current files on disk
-        let on_disk: Vec<PathBuf> = if recursive {
-            WalkDir::new(&source_dir)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .map(|e| e.path().to_path_buf())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
-                .collect()
-        } else {
-            fs::read_dir(&source_dir)
-                .with_context(|| format!("Failed to read directory: {}", source_dir.display()))?
-                .flatten()
-                .map(|e| e.path())
-                .filter(|p| p.is_file() && crate::utils::is_image_file(p))
-                .collect()
-        };
+        let on_disk = Self::discover(&source_dir, recursive, scan)?;
 
         // Build lookup of existing wallpapers by path
         let mut existing: HashMap<PathBuf, Wallpaper> = self
@@ -637,8 +1594,10 @@ impl WallpaperCache {
                             fresh.tags = wp.tags;
                             fresh.auto_tags = wp.auto_tags;
                             fresh.embedding = wp.embedding;
+                            fresh.resize_override = wp.resize_override;
+                            fresh.rating = wp.rating;
                             // Re-extract colors for modified file
-                            let _ = fresh.extract_colors();
+                            let _ = fresh.extract_colors(scan);
                             kept.push(fresh);
                         }
                         Err(_) => kept.push(wp), // Keep old data on error
@@ -650,12 +1609,12 @@ impl WallpaperCache {
                 // New file — scan dimensions + colors
                 match Wallpaper::from_path_fast(path) {
                     Ok(mut wp) => {
-                        let _ = wp.extract_colors();
+                        let _ = wp.extract_colors(scan);
                         kept.push(wp);
                         added += 1;
                     }
                     Err(e) => {
-                        eprintln!("Warning: Failed to read {}: {}", path.display(), e);
+                        tracing::warn!("Failed to read {}: {}", path.display(), e);
                     }
                 }
             }
@@ -667,6 +1626,8 @@ impl WallpaperCache {
         kept.sort_by(|a, b| a.path.cmp(&b.path));
         self.wallpapers = kept;
         self.version = CACHE_VERSION;
+        // Sidecars may have changed even when the images didn't
+        self.merge_external_tags(scan);
 
         // Auto-save after rescan
         self.save()?;
@@ -675,6 +1636,9 @@ impl WallpaperCache {
     }
 
     pub fn save(&self) -> Result<()> {
+        if !crate::utils::saves_enabled() {
+            return Ok(());
+        }
         let cache_path = Self::cache_path();
 
         if let Some(parent) = cache_path.parent() {
@@ -687,15 +1651,15 @@ impl WallpaperCache {
         Ok(())
     }
 
-    fn validate(&self) -> bool {
-        self.validate_impl(true)
+    fn validate(&self, scan: &ScanConfig) -> bool {
+        self.validate_impl(true, scan)
     }
 
-    fn validate_for_ai(&self) -> bool {
-        self.validate_impl(false)
+    fn validate_for_ai(&self, scan: &ScanConfig) -> bool {
+        self.validate_impl(false, scan)
     }
 
-    fn validate_impl(&self, require_color_data: bool) -> bool {
+    fn validate_impl(&self, require_color_data: bool, scan: &ScanConfig) -> bool {
         // Check if source directory still exists
         if !self.source_dir.exists() {
             return false;
@@ -721,7 +1685,16 @@ impl WallpaperCache {
             }
 
             // Full runtime needs color data; AI tagging path does not.
-            if require_color_data && wp.colors.is_empty() {
+            // Oversize images never get colors, so they don't count as stale.
+            let decodable =
+                crate::utils::ensure_decode_size(wp.width, wp.height, scan.max_decode_dimension)
+                    .is_ok();
+            if require_color_data && wp.colors.is_empty() && decodable {
+                return false;
+            }
+
+            // Same mtime but different bytes (restored backups, some editors)
+            if wp.content_hash.is_some() && content_hash(&wp.path) != wp.content_hash {
                 return false;
             }
 
@@ -742,20 +1715,9 @@ impl WallpaperCache {
         }
 
         // Quick check: count files in directory to detect additions/removals
-        let current_count = if self.recursive {
-            WalkDir::new(&self.source_dir)
-                .follow_links(true)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().is_file() && crate::utils::is_image_file(e.path()))
-                .count()
-        } else if let Ok(entries) = std::fs::read_dir(&self.source_dir) {
-            entries
-                .filter_map(|e| e.ok())
-                .filter(|e| e.path().is_file() && crate::utils::is_image_file(&e.path()))
-                .count()
-        } else {
-            return false;
+        let current_count = match Self::discover(&self.source_dir, self.recursive, scan) {
+            Ok(entries) => entries.len(),
+            Err(_) => return false,
         };
 
         if current_count != self.wallpapers.len() {
@@ -765,37 +1727,189 @@ impl WallpaperCache {
         true
     }
 
-    pub fn for_screen(&self, screen: &Screen) -> Vec<&Wallpaper> {
-        self.wallpapers
+    /// Wallpapers modified at or after `cutoff` (unix seconds), newest first
+    pub fn recent(&self, cutoff: u64) -> Vec<&Wallpaper> {
+        let mut recent: Vec<&Wallpaper> = self
+            .wallpapers
             .iter()
-            .filter(|wp| wp.matches_screen(screen))
-            .collect()
+            .filter(|wp| wp.modified_at >= cutoff)
+            .collect();
+        recent.sort_by_key(|wp| std::cmp::Reverse(wp.modified_at));
+        recent
+    }
+
+    /// Wallpapers matching `screen`'s aspect; with native-resolution
+    /// preference on, only those covering its effective pixels if any do
+    pub fn for_screen(&self, screen: &Screen) -> Vec<&Wallpaper> {
+        screen_candidates(&self.wallpapers, screen)
+    }
+
+    /// Wallpapers for `screen`, or every wallpaper when none match it
+    fn screen_or_all(&self, screen: &Screen) -> Vec<&Wallpaper> {
+        let candidates = self.for_screen(screen);
+        if candidates.is_empty() {
+            self.wallpapers.iter().collect()
+        } else {
+            candidates
+        }
     }
 
     pub fn random_for_screen(&self, screen: &Screen) -> Option<&Wallpaper> {
+        self.pick_with_mode(&self.screen_or_all(screen), RandomMode::Uniform)
+    }
+
+    /// Like `random_for_screen`, but each candidate's chance grows with the time
+    /// since it was last shown (capped at 30 days; never shown counts as capped).
+    pub fn weighted_random_for_screen(&self, screen: &Screen) -> Option<&Wallpaper> {
+        self.pick_with_mode(&self.screen_or_all(screen), RandomMode::LeastRecent)
+    }
+
+    /// Like `random_for_screen`, but a wallpaper rated N stars is N + 1 times as
+    /// likely as an unrated one.
+    pub fn rated_random_for_screen(&self, screen: &Screen) -> Option<&Wallpaper> {
+        self.pick_with_mode(&self.screen_or_all(screen), RandomMode::Rated)
+    }
+
+    /// Like `random_with_mode`, but skips anything in `exclude` unless that
+    /// would leave nothing to pick for the screen.
+    pub fn random_excluding(
+        &self,
+        screen: &Screen,
+        mode: RandomMode,
+        exclude: &[PathBuf],
+    ) -> Option<&Wallpaper> {
+        let mut candidates = self.screen_or_all(screen);
+        candidates.retain(|wp| !exclude.contains(&wp.path));
+        if candidates.is_empty() {
+            return self.random_with_mode(screen, mode);
+        }
+        self.pick_with_mode(&candidates, mode)
+    }
+
+    /// Pick one of `candidates` the way `mode` weighs them
+    fn pick_with_mode<'a>(
+        &self,
+        candidates: &[&'a Wallpaper],
+        mode: RandomMode,
+    ) -> Option<&'a Wallpaper> {
+        use rand::distributions::{Distribution, WeightedIndex};
         use rand::Rng;
 
-        let matching: Vec<_> = self.for_screen(screen);
-        if matching.is_empty() {
-            // Fallback: any wallpaper
-            if self.wallpapers.is_empty() {
-                return None;
+        if candidates.is_empty() {
+            return None;
+        }
+        let weights: Vec<u64> = match mode {
+            RandomMode::Uniform => {
+                let idx = crate::utils::with_rng(|rng| rng.gen_range(0..candidates.len()));
+                return Some(candidates[idx]);
             }
-            let idx = rand::thread_rng().gen_range(0..self.wallpapers.len());
-            return Some(&self.wallpapers[idx]);
+            RandomMode::LeastRecent => {
+                let now = unix_now();
+                candidates
+                    .iter()
+                    .map(|wp| self.least_recent_weight(&wp.path, now))
+                    .collect()
+            }
+            RandomMode::Rated => candidates
+                .iter()
+                .map(|wp| wp.rating.min(MAX_RATING) as u64 + 1)
+                .collect(),
+        };
+        let dist = WeightedIndex::new(&weights).ok()?;
+        Some(candidates[crate::utils::with_rng(|rng| dist.sample(rng))])
+    }
+
+    /// Selection weight in least-recent mode: seconds since last shown, capped.
+    /// Never below 1 so a wallpaper that was just shown can still come up.
+    fn least_recent_weight(&self, path: &Path, now: u64) -> u64 {
+        let age = self
+            .last_shown_at
+            .get(path)
+            .map_or(LEAST_RECENT_MAX_AGE_SECS, |&shown| {
+                now.saturating_sub(shown)
+            });
+        age.clamp(1, LEAST_RECENT_MAX_AGE_SECS)
+    }
+
+    /// Pick among the `top_k` wallpapers for `screen` whose colors are closest
+    /// to `current`, skipping `current` itself and anything in `exclude`.
+    /// Falls back to a uniform random pick when there is nothing to compare.
+    pub fn similar_for_screen(
+        &self,
+        screen: &Screen,
+        current: &Path,
+        exclude: &[PathBuf],
+        top_k: usize,
+    ) -> Option<&Wallpaper> {
+        use rand::Rng;
+
+        let Some(reference) = self
+            .wallpapers
+            .iter()
+            .find(|wp| wp.path == current && !wp.colors.is_empty())
+        else {
+            return self.random_for_screen(screen);
+        };
+
+        let mut candidates: Vec<&Wallpaper> = self.for_screen(screen);
+        if candidates.is_empty() {
+            candidates = self.wallpapers.iter().collect();
+        }
+        let mut scored: Vec<(&Wallpaper, f32)> = candidates
+            .into_iter()
+            .filter(|wp| wp.path != current && !exclude.contains(&wp.path))
+            .filter(|wp| !wp.colors.is_empty())
+            .map(|wp| {
+                let score = crate::utils::image_similarity_weighted(
+                    &reference.colors,
+                    &reference.color_weights,
+                    &wp.colors,
+                    &wp.color_weights,
+                );
+                (wp, score)
+            })
+            .collect();
+        if scored.is_empty() {
+            return self.random_for_screen(screen);
         }
 
-        let idx = rand::thread_rng().gen_range(0..matching.len());
-        Some(matching[idx])
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let top = top_k.clamp(1, scored.len());
+        let idx = crate::utils::with_rng(|rng| rng.gen_range(0..top));
+        Some(scored[idx].0)
     }
 
-    pub fn next_for_screen(&mut self, screen: &Screen) -> Option<&Wallpaper> {
-        let matching: Vec<_> = self
-            .wallpapers
+    /// Random pick for `screen` according to `mode`
+    pub fn random_with_mode(&self, screen: &Screen, mode: RandomMode) -> Option<&Wallpaper> {
+        match mode {
+            RandomMode::Uniform => self.random_for_screen(screen),
+            RandomMode::LeastRecent => self.weighted_random_for_screen(screen),
+            RandomMode::Rated => self.rated_random_for_screen(screen),
+        }
+    }
+
+    /// Record that `path` was just applied
+    pub fn mark_shown(&mut self, path: &Path) {
+        self.last_shown_at.insert(path.to_path_buf(), unix_now());
+    }
+
+    /// Point next/prev for `screen` at `path` and mark it shown.
+    /// Returns false when `path` is not in the library or doesn't match the screen.
+    pub fn set_current_for_screen(&mut self, screen: &Screen, path: &Path) -> bool {
+        let Some(index) = screen_candidates(&self.wallpapers, screen)
             .iter()
-            .enumerate()
-            .filter(|(_, wp)| wp.matches_screen(screen))
-            .collect();
+            .position(|wp| wp.path == path)
+        else {
+            return false;
+        };
+
+        self.screen_indices.insert(screen.name.clone(), index);
+        self.mark_shown(path);
+        true
+    }
+
+    pub fn next_for_screen(&mut self, screen: &Screen) -> Option<&Wallpaper> {
+        let matching = screen_candidates(&self.wallpapers, screen);
 
         if matching.is_empty() {
             return None;
@@ -805,16 +1919,11 @@ impl WallpaperCache {
         let next = (current + 1) % matching.len();
         self.screen_indices.insert(screen.name.clone(), next);
 
-        Some(matching[next].1)
+        Some(matching[next])
     }
 
     pub fn prev_for_screen(&mut self, screen: &Screen) -> Option<&Wallpaper> {
-        let matching: Vec<_> = self
-            .wallpapers
-            .iter()
-            .enumerate()
-            .filter(|(_, wp)| wp.matches_screen(screen))
-            .collect();
+        let matching = screen_candidates(&self.wallpapers, screen);
 
         if matching.is_empty() {
             return None;
@@ -828,7 +1937,7 @@ impl WallpaperCache {
         };
         self.screen_indices.insert(screen.name.clone(), prev);
 
-        Some(matching[prev].1)
+        Some(matching[prev])
     }
 
     pub fn stats(&self) -> CacheStats {
@@ -849,6 +1958,75 @@ impl WallpaperCache {
         stats
     }
 
+    /// Check every entry against the disk. Unlike the sampled validation done on
+    /// load, this reads each image header and lists what is wrong.
+    pub fn check(&self) -> CacheReport {
+        let mut report = CacheReport::default();
+
+        for wp in &self.wallpapers {
+            if !wp.path.exists() {
+                report.missing.push(wp.path.clone());
+                continue;
+            }
+
+            // An empty palette has no weights either; only count it once
+            if wp.colors.is_empty() {
+                report.no_colors.push(wp.path.clone());
+            } else if wp.color_weights.is_empty() {
+                report.no_weights.push(wp.path.clone());
+            }
+
+            if let Ok(actual) = crate::utils::oriented_dimensions(&wp.path) {
+                if actual != (wp.width, wp.height) {
+                    report.wrong_dimensions.push(DimensionMismatch {
+                        path: wp.path.clone(),
+                        cached: (wp.width, wp.height),
+                        actual,
+                    });
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Fix what `check` reports: drop entries for deleted files, correct
+    /// dimensions, and re-extract missing palettes and weights.
+    pub fn repair(&mut self, report: &CacheReport, scan: &ScanConfig) -> RepairSummary {
+        let mut summary = RepairSummary::default();
+
+        let before = self.wallpapers.len();
+        self.wallpapers
+            .retain(|wp| !report.missing.contains(&wp.path));
+        summary.removed = before - self.wallpapers.len();
+
+        for mismatch in &report.wrong_dimensions {
+            if let Some(wp) = self
+                .wallpapers
+                .iter_mut()
+                .find(|wp| wp.path == mismatch.path)
+            {
+                (wp.width, wp.height) = mismatch.actual;
+                wp.aspect_category = Wallpaper::categorize_aspect(wp.width, wp.height);
+                summary.resized += 1;
+            }
+        }
+
+        for path in report.no_colors.iter().chain(&report.no_weights) {
+            let Some(wp) = self.wallpapers.iter_mut().find(|wp| &wp.path == path) else {
+                continue;
+            };
+            wp.colors.clear();
+            wp.color_weights.clear();
+            match wp.extract_colors(scan) {
+                Ok(()) => summary.recolored += 1,
+                Err(_) => summary.failed += 1,
+            }
+        }
+
+        summary
+    }
+
     /// Get all unique tags across all wallpapers
     pub fn all_tags(&self) -> Vec<String> {
         let mut tags: Vec<String> = self
@@ -881,6 +2059,52 @@ impl WallpaperCache {
         }
     }
 
+    /// Merge the `from` tags into `into` on every wallpaper (also used for
+    /// renames). Returns how many wallpapers changed.
+    pub fn merge_tags(&mut self, from: &[String], into: &str) -> usize {
+        self.wallpapers
+            .iter_mut()
+            .map(|wp| wp.merge_tags(from, into))
+            .filter(|&changed| changed)
+            .count()
+    }
+
+    /// Set or clear the per-wallpaper resize override by path
+    pub fn set_resize_override(&mut self, path: &Path, mode: Option<ResizeMode>) -> bool {
+        if let Some(wp) = self.wallpapers.iter_mut().find(|w| w.path == path) {
+            wp.resize_override = mode;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set a wallpaper's rating (0 clears it) by path
+    pub fn set_rating(&mut self, path: &Path, rating: u8) -> bool {
+        if let Some(wp) = self.wallpapers.iter_mut().find(|w| w.path == path) {
+            wp.rating = rating.min(MAX_RATING);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Resize mode for a wallpaper path: its override if cached, else `default`
+    pub fn resize_mode_for(&self, path: &Path, default: ResizeMode) -> ResizeMode {
+        self.wallpapers
+            .iter()
+            .find(|w| w.path == path)
+            .map_or(default, |wp| wp.resize_mode(default))
+    }
+
+    /// Cached palette of a wallpaper path, empty if unknown
+    pub fn colors_for(&self, path: &Path) -> &[String] {
+        self.wallpapers
+            .iter()
+            .find(|w| w.path == path)
+            .map_or(&[], |wp| wp.colors.as_slice())
+    }
+
     /// Get wallpapers with specific tag
     pub fn with_tag(&self, tag: &str) -> Vec<&Wallpaper> {
         self.wallpapers
@@ -915,9 +2139,15 @@ mod tests {
             color_weights: vec![],
             tags: vec![],
             auto_tags: vec![],
+            palette_tags: vec![],
             embedding: None,
             file_size: 0,
             modified_at: 0,
+            resize_override: None,
+            source_url: None,
+            author: None,
+            rating: 0,
+            content_hash: None,
         }
     }
 
@@ -1023,6 +2253,28 @@ mod tests {
         assert!(!wp.matches_screen(&screen));
     }
 
+    // --- meets_resolution ---
+
+    #[test]
+    fn test_meets_resolution_uses_scaled_pixels() {
+        let mut screen = Screen::new("DP-1".into(), 2560, 1440);
+        screen.scale = 1.5;
+        assert!(!test_wallpaper(2560, 1440).meets_resolution(&screen));
+        assert!(test_wallpaper(3840, 2160).meets_resolution(&screen));
+    }
+
+    #[test]
+    fn test_prefer_native_resolution_falls_back_to_all() {
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+        let small = test_wallpaper(1280, 720);
+        let large = test_wallpaper(2560, 1440);
+
+        let preferred = prefer_native_resolution(vec![&small, &large], &screen);
+        assert_eq!(preferred.len(), 1);
+        assert_eq!(preferred[0].width, 2560);
+        assert_eq!(prefer_native_resolution(vec![&small], &screen).len(), 1);
+    }
+
     // --- matches_screen_flexible ---
 
     #[test]
@@ -1172,34 +2424,312 @@ mod tests {
         assert_eq!(all.iter().filter(|t| *t == "nature").count(), 1);
     }
 
-    // --- SortMode / MatchMode cycling ---
+    // --- merge_tags ---
 
     #[test]
-    fn test_sort_mode_cycle() {
-        assert_eq!(SortMode::Name.next(), SortMode::Size);
-        assert_eq!(SortMode::Size.next(), SortMode::Date);
-        assert_eq!(SortMode::Date.next(), SortMode::Name);
+    fn test_merge_tags_renames_and_dedups() {
+        let mut cache = cache_of(&["/w/a.jpg", "/w/b.jpg", "/w/c.jpg"]);
+        cache.wallpapers[0].tags = vec!["Nauture".into(), "sunset".into()];
+        cache.wallpapers[1].tags = vec!["forest".into(), "nature".into(), "nauture".into()];
+        cache.wallpapers[2].tags = vec!["city".into()];
+        cache.wallpapers[2].auto_tags.push(AutoTag {
+            name: "nauture".into(),
+            confidence: 0.9,
+        });
+
+        assert_eq!(cache.merge_tags(&["nauture".into()], "Nature"), 2);
+        assert_eq!(cache.wallpapers[0].tags, vec!["nature", "sunset"]);
+        assert_eq!(cache.wallpapers[1].tags, vec!["forest", "nature"]);
+        assert_eq!(cache.wallpapers[2].tags, vec!["city"]);
+        assert_eq!(
+            cache.wallpapers[2].auto_tags[0].name, "nauture",
+            "auto-tags are model-derived and stay"
+        );
+
+        assert_eq!(
+            cache.merge_tags(&["nature".into()], "nature"),
+            0,
+            "merging a tag into itself changes nothing"
+        );
+        assert_eq!(
+            cache.merge_tags(&["forest".into(), "city".into()], "outdoors"),
+            2
+        );
+        assert_eq!(cache.wallpapers[1].tags, vec!["nature", "outdoors"]);
+    }
+
+    // --- matches_colors ---
+
+    fn teal_gold_wallpaper() -> Wallpaper {
+        let mut wp = test_wallpaper(1920, 1080);
+        wp.colors = vec!["#008080".into(), "#d4af37".into(), "#101010".into()];
+        wp
     }
 
     #[test]
-    fn test_match_mode_cycle() {
-        assert_eq!(MatchMode::Strict.next(), MatchMode::Flexible);
-        assert_eq!(MatchMode::Flexible.next(), MatchMode::All);
-        assert_eq!(MatchMode::All.next(), MatchMode::Strict);
+    fn test_matches_colors_all_requires_every_color() {
+        let wp = teal_gold_wallpaper();
+        let teal_gold = vec!["#008080".to_string(), "#d4af37".to_string()];
+        let teal_pink = vec!["#008080".to_string(), "#ff69b4".to_string()];
+        assert!(wp.matches_colors(&teal_gold, 0.7, true));
+        assert!(
+            !wp.matches_colors(&teal_pink, 0.7, true),
+            "pink is missing from the palette"
+        );
     }
 
-    // --- auto_tags_above ---
+    #[test]
+    fn test_matches_colors_any_needs_one_color() {
+        let wp = teal_gold_wallpaper();
+        let teal_pink = vec!["#008080".to_string(), "#ff69b4".to_string()];
+        let pink = vec!["#ff69b4".to_string()];
+        assert!(wp.matches_colors(&teal_pink, 0.7, false));
+        assert!(!wp.matches_colors(&pink, 0.7, false));
+    }
 
     #[test]
-    fn test_auto_tags_above_threshold() {
-        let mut wp = test_wallpaper(1920, 1080);
-        wp.auto_tags.push(AutoTag {
-            name: "nature".into(),
-            confidence: 0.9,
-        });
-        wp.auto_tags.push(AutoTag {
-            name: "dark".into(),
-            confidence: 0.3,
+    fn test_matches_colors_empty_filter_matches() {
+        let wp = teal_gold_wallpaper();
+        assert!(wp.matches_colors(&[], 0.7, true));
+        assert!(wp.matches_colors(&[], 0.7, false));
+    }
+
+    // --- resize_override ---
+
+    #[test]
+    fn test_resize_mode_prefers_override() {
+        let mut wp = test_wallpaper(5760, 1080);
+        assert_eq!(wp.resize_mode(ResizeMode::Crop), ResizeMode::Crop);
+        wp.resize_override = Some(ResizeMode::Fit);
+        assert_eq!(
+            wp.resize_mode(ResizeMode::Crop),
+            ResizeMode::Fit,
+            "override wins over the global mode"
+        );
+    }
+
+    #[test]
+    fn test_resize_override_defaults_to_none_in_old_caches() {
+        let mut value = serde_json::to_value(test_wallpaper(1920, 1080)).unwrap();
+        value.as_object_mut().unwrap().remove("resize_override");
+        let wp: Wallpaper = serde_json::from_value(value).unwrap();
+        assert_eq!(wp.resize_override, None);
+    }
+
+    // --- attribution sidecar ---
+
+    #[test]
+    fn test_from_path_fast_reads_attribution_sidecar() {
+        let dir = std::env::temp_dir().join("frostwall_test_attribution");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let img_path = dir.join("unsplash_abc.png");
+        image::RgbImage::new(4, 3).save(&img_path).unwrap();
+
+        let plain = Wallpaper::from_path_fast(&img_path).unwrap();
+        assert_eq!(plain.author, None, "no sidecar, no credit");
+
+        std::fs::write(
+            dir.join("unsplash_abc.png.json"),
+            r#"{"source":"Unsplash","id":"abc","author":"Jane Doe","url":"https://example.com/abc"}"#,
+        )
+        .unwrap();
+        let wp = Wallpaper::from_path_fast(&img_path).unwrap();
+        assert_eq!(wp.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(wp.source_url.as_deref(), Some("https://example.com/abc"));
+
+        // A same-named image in another format doesn't pick up this credit
+        let jpg_path = dir.join("unsplash_abc.jpg");
+        image::RgbImage::new(4, 3).save(&jpg_path).unwrap();
+        assert_eq!(Wallpaper::from_path_fast(&jpg_path).unwrap().author, None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- check / repair ---
+
+    #[test]
+    fn test_check_reports_and_repair_fixes_problems() {
+        let dir = std::env::temp_dir().join("frostwall_test_cache_check");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let image_at = |name: &str, width, height| {
+            let path = dir.join(name);
+            image::RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]))
+                .save(&path)
+                .unwrap();
+            let mut wp = test_wallpaper(width, height);
+            wp.path = path;
+            wp.colors = vec!["#c82828".to_string()];
+            wp.color_weights = vec![1.0];
+            wp
+        };
+
+        let good = image_at("good.png", 16, 9);
+        let mut no_colors = image_at("no_colors.png", 16, 9);
+        no_colors.colors.clear();
+        no_colors.color_weights.clear();
+        let mut no_weights = image_at("no_weights.png", 16, 9);
+        no_weights.color_weights.clear();
+        let mut resized = image_at("resized.png", 9, 16);
+        (resized.width, resized.height) = (16, 9);
+        let mut gone = test_wallpaper(16, 9);
+        gone.path = dir.join("deleted.png");
+
+        let mut cache = WallpaperCache {
+            version: CACHE_VERSION,
+            wallpapers: vec![good, no_colors, no_weights, resized, gone],
+            source_dir: dir.clone(),
+            screen_indices: HashMap::new(),
+            recursive: false,
+            last_shown_at: HashMap::new(),
+        };
+
+        let report = cache.check();
+        assert_eq!(report.missing, vec![dir.join("deleted.png")]);
+        assert_eq!(report.no_colors, vec![dir.join("no_colors.png")]);
+        assert_eq!(report.no_weights, vec![dir.join("no_weights.png")]);
+        assert_eq!(report.wrong_dimensions.len(), 1);
+        assert_eq!(report.wrong_dimensions[0].actual, (9, 16));
+
+        let summary = cache.repair(&report, &ScanConfig::default());
+        assert_eq!(
+            (summary.removed, summary.resized, summary.recolored),
+            (1, 1, 2)
+        );
+        assert!(cache.check().is_clean());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- cache migration ---
+
+    #[test]
+    fn test_migrate_v0_cache_keeps_tags_and_fills_weights() {
+        // Written before the cache had a version field
+        let v0 = r##"{
+            "wallpapers": [{
+                "path": "/nonexistent/frostwall/a.jpg",
+                "width": 1920,
+                "height": 1080,
+                "aspect_category": "Landscape",
+                "colors": ["#112233", "#445566"],
+                "tags": ["nature"]
+            }],
+            "source_dir": "/nonexistent/frostwall",
+            "screen_indices": {"DP-1": 0}
+        }"##;
+
+        let mut cache: WallpaperCache = serde_json::from_str(v0).unwrap();
+        assert_eq!(cache.version, 0);
+
+        assert!(cache.migrate());
+        assert_eq!(cache.version, CACHE_VERSION);
+        let wp = &cache.wallpapers[0];
+        assert_eq!(wp.tags, vec!["nature"], "user tags survive the upgrade");
+        assert_eq!(wp.color_weights, vec![0.5, 0.5]);
+        assert!(
+            wp.has_tag("dark"),
+            "palette tags come from the stored palette"
+        );
+        assert_eq!(cache.screen_indices.get("DP-1"), Some(&0));
+    }
+
+    #[test]
+    fn test_migrate_rejects_newer_cache() {
+        let mut cache = WallpaperCache {
+            version: CACHE_VERSION + 1,
+            wallpapers: Vec::new(),
+            source_dir: PathBuf::from("/w"),
+            screen_indices: HashMap::new(),
+            recursive: false,
+            last_shown_at: HashMap::new(),
+        };
+        assert!(!cache.migrate());
+        assert_eq!(cache.version, CACHE_VERSION + 1, "left untouched");
+    }
+
+    // --- EXIF orientation ---
+
+    /// Baseline JPEG with an APP1 segment carrying only the orientation tag
+    fn jpeg_with_orientation(width: u32, height: u32, orientation: u8) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        image::RgbImage::new(width, height)
+            .write_to(
+                &mut std::io::Cursor::new(&mut jpeg),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        #[rustfmt::skip]
+        let app1 = [
+            0xFF, 0xE1, 0x00, 0x22, // APP1, length 34
+            b'E', b'x', b'i', b'f', 0x00, 0x00,
+            b'M', b'M', 0x00, 0x2A, 0x00, 0x00, 0x00, 0x08, // big-endian TIFF, IFD at 8
+            0x00, 0x01, // one entry
+            0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01, // Orientation, SHORT, count 1
+            0x00, orientation, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, // no next IFD
+        ];
+        // Insert right after SOI
+        jpeg.splice(2..2, app1);
+        jpeg
+    }
+
+    #[test]
+    fn test_from_path_fast_applies_exif_rotation() {
+        let dir = std::env::temp_dir().join("frostwall_test_exif");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Stored 32x18 landscape, flagged "rotate 90° CW" (orientation 6)
+        let rotated = dir.join("phone.jpg");
+        std::fs::write(&rotated, jpeg_with_orientation(32, 18, 6)).unwrap();
+        let wp = Wallpaper::from_path_fast(&rotated).unwrap();
+        assert_eq!((wp.width, wp.height), (18, 32));
+        assert_eq!(wp.aspect_category, AspectCategory::Portrait);
+
+        let upright = crate::utils::open_oriented(&rotated).unwrap();
+        assert_eq!((upright.width(), upright.height()), (18, 32));
+
+        // Orientation 1 is "as stored"
+        let plain = dir.join("plain.jpg");
+        std::fs::write(&plain, jpeg_with_orientation(32, 18, 1)).unwrap();
+        let wp = Wallpaper::from_path_fast(&plain).unwrap();
+        assert_eq!((wp.width, wp.height), (32, 18));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- SortMode / MatchMode cycling ---
+
+    #[test]
+    fn test_sort_mode_cycle() {
+        assert_eq!(SortMode::Name.next(), SortMode::Size);
+        assert_eq!(SortMode::Size.next(), SortMode::Date);
+        assert_eq!(SortMode::Date.next(), SortMode::Name);
+    }
+
+    #[test]
+    fn test_match_mode_cycle() {
+        assert_eq!(MatchMode::Strict.next(), MatchMode::Flexible);
+        assert_eq!(MatchMode::Flexible.next(), MatchMode::All);
+        assert_eq!(MatchMode::All.next(), MatchMode::Strict);
+    }
+
+    // --- auto_tags_above ---
+
+    #[test]
+    fn test_auto_tags_above_threshold() {
+        let mut wp = test_wallpaper(1920, 1080);
+        wp.auto_tags.push(AutoTag {
+            name: "nature".into(),
+            confidence: 0.9,
+        });
+        wp.auto_tags.push(AutoTag {
+            name: "dark".into(),
+            confidence: 0.3,
         });
         wp.auto_tags.push(AutoTag {
             name: "forest".into(),
@@ -1220,4 +2750,907 @@ mod tests {
         wp.colors = vec!["#FF0000".into(), "#00FF00".into()];
         assert_eq!(wp.primary_color(), Some("#FF0000"));
     }
+
+    // --- recent ---
+
+    #[test]
+    fn test_recent_filters_and_sorts_newest_first() {
+        let wallpapers = [
+            (100, "/w/old.jpg"),
+            (500, "/w/new.jpg"),
+            (300, "/w/mid.jpg"),
+        ]
+        .into_iter()
+        .map(|(modified_at, path)| {
+            let mut wp = test_wallpaper(1920, 1080);
+            wp.modified_at = modified_at;
+            wp.path = PathBuf::from(path);
+            wp
+        })
+        .collect();
+        let cache = WallpaperCache {
+            version: CACHE_VERSION,
+            wallpapers,
+            source_dir: PathBuf::from("/w"),
+            screen_indices: HashMap::new(),
+            recursive: false,
+            last_shown_at: HashMap::new(),
+        };
+
+        let recent: Vec<_> = cache.recent(300).iter().map(|wp| wp.modified_at).collect();
+        assert_eq!(recent, vec![500, 300], "cutoff is inclusive, newest first");
+        assert!(cache.recent(1000).is_empty());
+    }
+
+    // --- least-recent random ---
+
+    fn cache_of(paths: &[&str]) -> WallpaperCache {
+        WallpaperCache {
+            version: CACHE_VERSION,
+            wallpapers: paths
+                .iter()
+                .map(|path| {
+                    let mut wp = test_wallpaper(1920, 1080);
+                    wp.path = PathBuf::from(path);
+                    wp
+                })
+                .collect(),
+            source_dir: PathBuf::from("/w"),
+            screen_indices: HashMap::new(),
+            recursive: false,
+            last_shown_at: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_least_recent_weight_grows_with_age_and_caps() {
+        let mut cache = cache_of(&["/w/a.jpg"]);
+        let now = 10_000_000;
+        let path = Path::new("/w/a.jpg");
+
+        assert_eq!(
+            cache.least_recent_weight(path, now),
+            LEAST_RECENT_MAX_AGE_SECS,
+            "never shown gets the full weight"
+        );
+        cache.last_shown_at.insert(path.to_path_buf(), now);
+        assert_eq!(cache.least_recent_weight(path, now), 1, "just shown");
+        cache.last_shown_at.insert(path.to_path_buf(), now - 3600);
+        assert_eq!(cache.least_recent_weight(path, now), 3600);
+        cache.last_shown_at.insert(path.to_path_buf(), 0);
+        assert_eq!(
+            cache.least_recent_weight(path, now),
+            LEAST_RECENT_MAX_AGE_SECS
+        );
+    }
+
+    /// Tests that draw random numbers share the process-wide `--seed` RNG
+    static RNG_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    #[test]
+    fn test_weighted_random_favors_unseen_wallpapers() {
+        let _rng = RNG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cache = cache_of(&["/w/seen.jpg", "/w/unseen.jpg"]);
+        cache.mark_shown(Path::new("/w/seen.jpg"));
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+
+        let seen_picks = (0..200)
+            .filter(|_| {
+                cache
+                    .random_with_mode(&screen, RandomMode::LeastRecent)
+                    .unwrap()
+                    .path
+                    == Path::new("/w/seen.jpg")
+            })
+            .count();
+        // Weight 1 (or a few seconds) against 30 days: practically never
+        assert!(
+            seen_picks <= 1,
+            "picked the just-shown one {seen_picks} times"
+        );
+    }
+
+    // --- discover ---
+
+    #[test]
+    fn test_discover_skips_hidden_and_excluded_dirs() {
+        let dir = std::env::temp_dir().join("frostwall_test_discover_exclude");
+        let _ = std::fs::remove_dir_all(&dir);
+        for sub in ["nature", "nature/.cache", ".git", "trash", "raw/2024"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for file in [
+            "a.png",
+            ".hidden.png",
+            "nature/b.png",
+            "nature/.cache/thumb.png",
+            ".git/c.png",
+            "trash/d.png",
+            "raw/2024/e.png",
+        ] {
+            std::fs::write(dir.join(file), b"not decoded").unwrap();
+        }
+        let found = |scan: &ScanConfig| -> Vec<String> {
+            let mut paths: Vec<String> = WallpaperCache::discover(&dir, true, scan)
+                .unwrap()
+                .iter()
+                .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().into_owned())
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let mut scan = ScanConfig {
+            exclude_dirs: vec!["trash".into(), "raw/*".into()],
+            ..ScanConfig::default()
+        };
+        assert_eq!(found(&scan), vec!["a.png", "nature/b.png"]);
+
+        scan.skip_hidden = false;
+        scan.exclude_dirs.clear();
+        assert_eq!(found(&scan).len(), 7, "nothing left out");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_discover_honors_frostwallignore() {
+        let dir = std::env::temp_dir().join("frostwall_test_discover_ignore_file");
+        let _ = std::fs::remove_dir_all(&dir);
+        for sub in ["wip", "nature/drafts"] {
+            std::fs::create_dir_all(dir.join(sub)).unwrap();
+        }
+        for file in [
+            "a.png",
+            "b.jpg",
+            "wip/c.png",
+            "nature/d.png",
+            "nature/draft-e.png",
+            "nature/draft-keep.png",
+            "nature/drafts/f.png",
+        ] {
+            std::fs::write(dir.join(file), b"not decoded").unwrap();
+        }
+        std::fs::write(
+            dir.join(IgnoreFile::NAME),
+            "# work in progress\nwip/*\n*.jpg\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("nature").join(IgnoreFile::NAME),
+            "draft*\n!draft-keep.png\n",
+        )
+        .unwrap();
+
+        let scan = ScanConfig::default();
+        let mut found: Vec<String> = WallpaperCache::discover(&dir, true, &scan)
+            .unwrap()
+            .iter()
+            .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().into_owned())
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec!["a.png", "nature/d.png", "nature/draft-keep.png"]
+        );
+
+        let top: Vec<PathBuf> = WallpaperCache::discover(&dir, false, &scan).unwrap();
+        assert_eq!(
+            top,
+            vec![dir.join("a.png")],
+            "applies without recursion too"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_ignore_file_parse() {
+        let file = IgnoreFile::parse("# comment\n\n/raw/\n*.tmp.png\n!keep.tmp.png\n");
+        assert_eq!(file.matches(Path::new("raw")), Some(true));
+        assert_eq!(file.matches(Path::new("x/y.tmp.png")), Some(true));
+        assert_eq!(file.matches(Path::new("keep.tmp.png")), Some(false));
+        assert_eq!(file.matches(Path::new("fine.png")), None);
+    }
+
+    // --- content_hash ---
+
+    #[test]
+    fn test_content_hash_covers_head_tail_and_size() {
+        let dir = std::env::temp_dir().join("frostwall_test_content_hash");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.png");
+        let big = CONTENT_HASH_SAMPLE as usize * 3;
+
+        let mut data = vec![7u8; big];
+        std::fs::write(&path, &data).unwrap();
+        let original = content_hash(&path).unwrap();
+        assert_eq!(content_hash(&path), Some(original), "deterministic");
+
+        data[big - 1] = 8;
+        std::fs::write(&path, &data).unwrap();
+        assert_ne!(content_hash(&path), Some(original), "tail change");
+
+        data[big - 1] = 7;
+        data[big / 2] = 8;
+        std::fs::write(&path, &data).unwrap();
+        assert_eq!(
+            content_hash(&path),
+            Some(original),
+            "the middle is not sampled"
+        );
+
+        std::fs::write(&path, &data[..big - 1]).unwrap();
+        assert_ne!(content_hash(&path), Some(original), "size change");
+        assert_eq!(content_hash(&dir.join("missing.png")), None);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_validate_catches_edit_with_same_mtime() {
+        let dir = std::env::temp_dir().join("frostwall_test_validate_hash");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.png");
+        std::fs::write(&path, b"first version").unwrap();
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+        let mut cache = WallpaperCache::empty(&dir, false);
+        cache.wallpapers.push(Wallpaper {
+            path: path.clone(),
+            colors: vec!["#112233".into()],
+            modified_at: mtime
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            content_hash: content_hash(&path),
+            ..test_wallpaper(1920, 1080)
+        });
+        let scan = ScanConfig::default();
+        assert!(cache.validate(&scan));
+
+        // Same length, same mtime, different bytes
+        std::fs::write(&path, b"other version").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        assert!(!cache.validate(&scan));
+
+        // Caches from before the hash existed only get the mtime check
+        cache.wallpapers[0].content_hash = None;
+        assert!(cache.validate(&scan));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- scan_with_progress ---
+
+    #[test]
+    fn test_scan_with_progress_reports_and_cancels() {
+        let dir = std::env::temp_dir().join("frostwall_test_scan_progress");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for i in 0..3 {
+            image::RgbImage::from_pixel(16, 9, image::Rgb([40 * i, 80, 120]))
+                .save(dir.join(format!("{i}.png")))
+                .unwrap();
+        }
+        std::fs::write(dir.join("broken.png"), b"not a png").unwrap();
+
+        let updates = std::sync::Mutex::new(Vec::new());
+        let report = |update: ScanUpdate| updates.lock().unwrap().push(update);
+        let scan = ScanConfig::default();
+        let cache = WallpaperCache::scan_with_progress(
+            &dir,
+            false,
+            &scan,
+            &report,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+        .expect("not cancelled");
+        assert_eq!(cache.wallpapers.len(), 3);
+
+        let updates = updates.into_inner().unwrap();
+        let failed = updates
+            .iter()
+            .filter(|u| matches!(u, ScanUpdate::Failed { .. }))
+            .count();
+        assert_eq!(failed, 1, "the broken file is reported, not fatal");
+        for phase in [ScanPhase::Dimensions, ScanPhase::Colors] {
+            let last = updates.iter().rev().find_map(|u| match u {
+                ScanUpdate::Progress {
+                    phase: p,
+                    done,
+                    total,
+                } if *p == phase => Some((*done, *total)),
+                _ => None,
+            });
+            let (done, total) = last.expect("phase reported");
+            assert_eq!(done, total, "{phase:?} ends complete");
+        }
+
+        let cancelled =
+            WallpaperCache::scan_with_progress(&dir, false, &scan, &|_| {}, &AtomicBool::new(true))
+                .unwrap();
+        assert!(cancelled.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- ratings ---
+
+    #[test]
+    fn test_rated_random_favors_higher_ratings() {
+        let _rng = RNG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cache = cache_of(&["/w/plain.jpg", "/w/favorite.jpg"]);
+        assert!(cache.set_rating(Path::new("/w/favorite.jpg"), 9));
+        assert_eq!(cache.wallpapers[1].rating, MAX_RATING, "clamped to 5");
+        assert!(!cache.set_rating(Path::new("/w/missing.jpg"), 3));
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+
+        let favorite_picks = (0..600)
+            .filter(|_| {
+                cache
+                    .random_with_mode(&screen, RandomMode::Rated)
+                    .unwrap()
+                    .path
+                    == Path::new("/w/favorite.jpg")
+            })
+            .count();
+        // Weight 6 against 1: expected ~514 of 600
+        assert!(
+            favorite_picks > 420,
+            "picked the favorite only {favorite_picks} times"
+        );
+    }
+
+    #[test]
+    fn test_parse_rating_filter() {
+        assert_eq!(parse_rating_filter(">=4"), Some(4..=5));
+        assert_eq!(parse_rating_filter(">= 4"), Some(4..=5));
+        assert_eq!(parse_rating_filter("<3"), Some(0..=2));
+        assert_eq!(parse_rating_filter("<=1"), Some(0..=1));
+        assert_eq!(parse_rating_filter(">4"), Some(5..=5));
+        assert_eq!(parse_rating_filter("5"), Some(5..=5));
+        assert_eq!(parse_rating_filter("=0"), Some(0..=0));
+        assert_eq!(parse_rating_filter(">5"), None);
+        assert_eq!(parse_rating_filter("<0"), None);
+        assert_eq!(parse_rating_filter("6"), None);
+        assert_eq!(parse_rating_filter("good"), None);
+    }
+
+    #[test]
+    fn test_rating_stars_and_old_caches() {
+        assert_eq!(rating_stars(0), "unrated");
+        assert_eq!(rating_stars(3), "★★★☆☆");
+
+        let mut value = serde_json::to_value(test_wallpaper(1920, 1080)).unwrap();
+        value.as_object_mut().unwrap().remove("rating");
+        let wp: Wallpaper = serde_json::from_value(value).unwrap();
+        assert_eq!(wp.rating, 0);
+    }
+
+    // --- set_current_for_screen ---
+
+    #[test]
+    fn test_set_current_for_screen_continues_sequence() {
+        let mut cache = cache_of(&["/w/a.jpg", "/w/b.jpg", "/w/c.jpg"]);
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+
+        assert!(cache.set_current_for_screen(&screen, Path::new("/w/b.jpg")));
+        assert!(cache.last_shown_at.contains_key(Path::new("/w/b.jpg")));
+        assert_eq!(
+            cache.next_for_screen(&screen).unwrap().path,
+            Path::new("/w/c.jpg"),
+            "next continues after the applied wallpaper"
+        );
+
+        assert!(!cache.set_current_for_screen(&screen, Path::new("/elsewhere/x.jpg")));
+        assert!(!cache
+            .last_shown_at
+            .contains_key(Path::new("/elsewhere/x.jpg")));
+    }
+
+    #[test]
+    fn test_random_excluding_relaxes_when_all_excluded() {
+        let _rng = RNG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = cache_of(&["/w/a.jpg", "/w/b.jpg", "/w/c.jpg"]);
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+        let cooling = vec![PathBuf::from("/w/a.jpg"), PathBuf::from("/w/b.jpg")];
+
+        for mode in [
+            RandomMode::Uniform,
+            RandomMode::LeastRecent,
+            RandomMode::Rated,
+        ] {
+            for _ in 0..20 {
+                let pick = cache.random_excluding(&screen, mode, &cooling).unwrap();
+                assert_eq!(pick.path, Path::new("/w/c.jpg"), "{mode:?}");
+            }
+        }
+
+        let everything: Vec<PathBuf> = cache.wallpapers.iter().map(|w| w.path.clone()).collect();
+        assert!(
+            cache
+                .random_excluding(&screen, RandomMode::Uniform, &everything)
+                .is_some(),
+            "falls back instead of stalling"
+        );
+    }
+
+    #[test]
+    fn test_seeded_random_is_reproducible() {
+        let _rng = RNG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let paths: Vec<String> = (0..40).map(|i| format!("/w/{i}.jpg")).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        let cache = cache_of(&paths);
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+        let picks = |mode| -> Vec<PathBuf> {
+            (0..10)
+                .map(|_| cache.random_with_mode(&screen, mode).unwrap().path.clone())
+                .collect()
+        };
+
+        for mode in [
+            RandomMode::Uniform,
+            RandomMode::LeastRecent,
+            RandomMode::Rated,
+        ] {
+            crate::utils::set_rng_seed(Some(42));
+            let first = picks(mode);
+            crate::utils::set_rng_seed(Some(42));
+            assert_eq!(picks(mode), first, "{mode:?} repeats with the same seed");
+        }
+        crate::utils::set_rng_seed(None);
+    }
+
+    #[test]
+    fn test_similar_for_screen_follows_colors_and_skips_recent() {
+        let mut cache = cache_of(&["/w/blue.jpg", "/w/navy.jpg", "/w/sky.jpg", "/w/red.jpg"]);
+        for (wp, color) in cache
+            .wallpapers
+            .iter_mut()
+            .zip(["#1040c0", "#102a80", "#60a0f0", "#e01010"])
+        {
+            wp.colors = vec![color.to_string()];
+            wp.color_weights = vec![1.0];
+        }
+        let screen = Screen::new("DP-1".into(), 1920, 1080);
+        let blue = Path::new("/w/blue.jpg");
+
+        let pick = cache.similar_for_screen(&screen, blue, &[], 1).unwrap();
+        assert_ne!(pick.path, PathBuf::from("/w/red.jpg"));
+        assert_ne!(pick.path, blue, "never repeats the current wallpaper");
+
+        let recent = vec![PathBuf::from("/w/navy.jpg"), PathBuf::from("/w/sky.jpg")];
+        let pick = cache.similar_for_screen(&screen, blue, &recent, 1).unwrap();
+        assert_eq!(
+            pick.path,
+            PathBuf::from("/w/red.jpg"),
+            "recent picks are excluded even if closer"
+        );
+
+        assert!(cache
+            .similar_for_screen(&screen, Path::new("/w/unknown.jpg"), &[], 3)
+            .is_some());
+    }
+
+    // --- ScanConfig::includes ---
+
+    #[test]
+    fn test_scan_includes_gif_unless_skipped() {
+        let gif = Path::new("/walls/loop.GIF");
+        let png = Path::new("/walls/still.png");
+
+        let static_mode = ScanConfig::default();
+        assert!(static_mode.includes(gif));
+        assert!(static_mode.includes(png));
+
+        let skip = ScanConfig {
+            gif_mode: GifMode::Skip,
+            ..ScanConfig::default()
+        };
+        assert!(!skip.includes(gif), "skip mode should drop GIFs");
+        assert!(skip.includes(png));
+        assert!(!skip.includes(Path::new("/walls/notes.txt")));
+    }
+
+    // --- animated GIFs ---
+
+    /// Two-frame GIF: solid red, then solid blue
+    fn write_two_frame_gif(path: &Path, size: u32) {
+        use image::codecs::gif::GifEncoder;
+        use image::{Frame, Rgba, RgbaImage};
+
+        let frames = [Rgba([220, 20, 20, 255]), Rgba([20, 20, 220, 255])]
+            .map(|color| Frame::new(RgbaImage::from_pixel(size, size, color)));
+        let file = std::fs::File::create(path).unwrap();
+        GifEncoder::new(file).encode_frames(frames).unwrap();
+    }
+
+    #[test]
+    fn test_extract_colors_uses_first_gif_frame() {
+        let dir = std::env::temp_dir().join("frostwall_test_gif_frames");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let gif = dir.join("loop.gif");
+        write_two_frame_gif(&gif, 32);
+
+        let scan = ScanConfig::default();
+        let mut first = Wallpaper::from_path_fast(&gif).unwrap();
+        first.extract_colors(&scan).unwrap();
+        assert!(
+            red_weight(&first.colors, &first.color_weights) > 0.9,
+            "palette should come from the red first frame: {:?}",
+            first.colors
+        );
+
+        let mut again = Wallpaper::from_path_fast(&gif).unwrap();
+        again.extract_colors(&scan).unwrap();
+        assert_eq!(first.colors, again.colors, "same frame on every run");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scan_excludes_tiny_gifs() {
+        let dir = std::env::temp_dir().join("frostwall_test_tiny_gif");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let emoji = dir.join("emoji.gif");
+        let banner = dir.join("banner.gif");
+        write_two_frame_gif(&emoji, 64);
+        write_two_frame_gif(&banner, 256);
+
+        let scan = ScanConfig::default();
+        assert!(
+            !scan.includes(&emoji),
+            "64px GIF is below the default minimum"
+        );
+        assert!(scan.includes(&banner));
+
+        let keep_all = ScanConfig {
+            min_gif_size: 0,
+            ..ScanConfig::default()
+        };
+        assert!(keep_all.includes(&emoji));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- max_decode_dimension ---
+
+    #[test]
+    fn test_oversize_images_are_skipped_not_decoded() {
+        let dir = std::env::temp_dir().join("frostwall_test_max_decode");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        image::RgbImage::from_pixel(640, 360, image::Rgb([30, 60, 90]))
+            .save(dir.join("huge.png"))
+            .unwrap();
+        image::RgbImage::from_pixel(160, 90, image::Rgb([30, 60, 90]))
+            .save(dir.join("small.png"))
+            .unwrap();
+
+        let scan = ScanConfig {
+            max_decode_dimension: 320,
+            ..ScanConfig::default()
+        };
+        let failed = std::sync::Mutex::new(Vec::new());
+        let report = |update: ScanUpdate| {
+            if let ScanUpdate::Failed { path, error } = update {
+                failed.lock().unwrap().push((path, error));
+            }
+        };
+        let cache = WallpaperCache::scan_with_progress(
+            &dir,
+            false,
+            &scan,
+            &report,
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            cache.wallpapers.len(),
+            2,
+            "oversize files stay in the library"
+        );
+        let huge = cache
+            .wallpapers
+            .iter()
+            .find(|wp| wp.path.ends_with("huge.png"))
+            .unwrap();
+        assert!(huge.colors.is_empty());
+        assert_eq!((huge.width, huge.height), (640, 360));
+        let failed = failed.into_inner().unwrap();
+        assert_eq!(failed.len(), 1);
+        assert!(
+            failed[0].1.contains("max_decode_dimension"),
+            "{}",
+            failed[0].1
+        );
+
+        assert!(crate::utils::check_decode_size(&dir.join("huge.png"), 320).is_err());
+        assert!(crate::utils::check_decode_size(&dir.join("huge.png"), 0).is_ok());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- tag sidecars ---
+
+    #[test]
+    fn test_parse_sidecar_tags() {
+        assert_eq!(
+            parse_sidecar_tags("Nature, sunset\n# from darktable\n\nocean,,\n  Warm  "),
+            vec!["nature", "sunset", "ocean", "warm"]
+        );
+        let paths = tag_sidecar_paths(Path::new("/w/sunset.jpg"));
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("/w/sunset.jpg.tags"),
+                PathBuf::from("/w/sunset.tags"),
+                PathBuf::from("/w/sunset.jpg.txt"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_merges_tag_sidecars() {
+        let dir = std::env::temp_dir().join("frostwall_test_tag_sidecars");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("sunset.png");
+        image::RgbImage::from_pixel(16, 9, image::Rgb([200, 120, 40]))
+            .save(&image)
+            .unwrap();
+        std::fs::write(dir.join("sunset.png.tags"), "Sunset, warm\nsky").unwrap();
+
+        let scan = ScanConfig::default();
+        let mut cache = WallpaperCache::scan_with_progress(
+            &dir,
+            false,
+            &scan,
+            &|_| {},
+            &AtomicBool::new(false),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(cache.wallpapers[0].tags, vec!["sky", "sunset", "warm"]);
+
+        // Manual edits survive and re-reading is idempotent
+        cache.wallpapers[0].add_tag("favorite");
+        assert_eq!(cache.merge_external_tags(&scan), 0);
+        assert_eq!(
+            cache.wallpapers[0].tags,
+            vec!["favorite", "sky", "sunset", "warm"]
+        );
+
+        let off = ScanConfig {
+            read_tag_sidecars: false,
+            ..ScanConfig::default()
+        };
+        let cache =
+            WallpaperCache::scan_with_progress(&dir, false, &off, &|_| {}, &AtomicBool::new(false))
+                .unwrap()
+                .unwrap();
+        assert!(cache.wallpapers[0].tags.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- folder tags ---
+
+    #[test]
+    fn test_dir_tags() {
+        let root = Path::new("/w");
+        let path = Path::new("/w/Nature/forest/Nature/a.jpg");
+        assert_eq!(dir_tags(path, root, false), vec!["nature"]);
+        assert_eq!(dir_tags(path, root, true), vec!["nature", "forest"]);
+        assert!(dir_tags(Path::new("/w/a.jpg"), root, true).is_empty());
+        assert!(dir_tags(Path::new("/elsewhere/x/a.jpg"), root, true).is_empty());
+    }
+
+    #[test]
+    fn test_scan_adds_folder_tags() {
+        let dir = std::env::temp_dir().join("frostwall_test_folder_tags");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("Anime/Night")).unwrap();
+        image::RgbImage::from_pixel(16, 9, image::Rgb([20, 20, 60]))
+            .save(dir.join("Anime/Night/moon.png"))
+            .unwrap();
+
+        let scan = ScanConfig {
+            tags_from_dirs: true,
+            ..ScanConfig::default()
+        };
+        let mut cache =
+            WallpaperCache::scan_with_progress(&dir, true, &scan, &|_| {}, &AtomicBool::new(false))
+                .unwrap()
+                .unwrap();
+        assert_eq!(cache.wallpapers[0].tags, vec!["night"]);
+
+        cache.wallpapers[0].add_tag("favorite");
+        assert_eq!(cache.merge_dir_tags(true), 1);
+        assert_eq!(cache.wallpapers[0].tags, vec!["anime", "favorite", "night"]);
+        assert_eq!(cache.merge_dir_tags(true), 0, "re-running adds nothing");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // --- pixel sampling ---
+
+    /// 64x64 image: red center with black letterbox bars covering half the rows
+    fn letterboxed_red() -> image::RgbImage {
+        image::RgbImage::from_fn(64, 64, |_, y| {
+            if (16..48).contains(&y) {
+                image::Rgb([220, 20, 20])
+            } else {
+                image::Rgb([0, 0, 0])
+            }
+        })
+    }
+
+    /// Combined weight of palette entries that are clearly red
+    fn red_weight(colors: &[String], weights: &[f32]) -> f32 {
+        colors
+            .iter()
+            .zip(weights)
+            .filter(|(hex, _)| {
+                crate::utils::hex_to_rgb(hex).is_some_and(|(r, g, b)| r > 150 && g < 80 && b < 80)
+            })
+            .map(|(_, w)| *w)
+            .sum()
+    }
+
+    #[test]
+    fn test_sample_pixels_counts() {
+        let img = letterboxed_red();
+        assert_eq!(sample_pixels(&img, PixelSampling::All).len(), 64 * 64);
+        assert_eq!(
+            sample_pixels(&img, PixelSampling::Random).len(),
+            32 * 32,
+            "random sampling keeps one pixel per 2x2 cell"
+        );
+        let center = sample_pixels(&img, PixelSampling::CenterWeighted).len();
+        assert!(center < 64 * 64 && center > 64 * 64 / 4);
+    }
+
+    #[test]
+    fn test_sample_pixels_deterministic() {
+        let img = letterboxed_red();
+        assert_eq!(
+            sample_pixels(&img, PixelSampling::Random),
+            sample_pixels(&img, PixelSampling::Random)
+        );
+    }
+
+    #[test]
+    fn test_random_sampling_matches_full_palette() {
+        let img = letterboxed_red();
+        let (all_colors, all_weights) = dominant_colors(
+            &sample_pixels(&img, PixelSampling::All),
+            &ScanConfig::default(),
+        );
+        let (rnd_colors, rnd_weights) = dominant_colors(
+            &sample_pixels(&img, PixelSampling::Random),
+            &ScanConfig::default(),
+        );
+
+        let all_red = red_weight(&all_colors, &all_weights);
+        let rnd_red = red_weight(&rnd_colors, &rnd_weights);
+        assert!((all_red - 0.5).abs() < 0.05, "full palette red={}", all_red);
+        assert!(
+            (rnd_red - all_red).abs() < 0.1,
+            "random red={} vs full red={}",
+            rnd_red,
+            all_red
+        );
+    }
+
+    #[test]
+    fn test_center_weighted_favors_focal_area() {
+        let img = letterboxed_red();
+        let (all_colors, all_weights) = dominant_colors(
+            &sample_pixels(&img, PixelSampling::All),
+            &ScanConfig::default(),
+        );
+        let (cw_colors, cw_weights) = dominant_colors(
+            &sample_pixels(&img, PixelSampling::CenterWeighted),
+            &ScanConfig::default(),
+        );
+
+        let all_red = red_weight(&all_colors, &all_weights);
+        let cw_red = red_weight(&cw_colors, &cw_weights);
+        assert!(
+            cw_red > all_red + 0.1,
+            "center-weighted red={} should exceed full red={}",
+            cw_red,
+            all_red
+        );
+        assert!(crate::utils::hex_to_rgb(&cw_colors[0]).is_some_and(|(r, _, _)| r > 150));
+    }
+
+    #[test]
+    fn test_small_sample_size_keeps_palette_size() {
+        let dir = std::env::temp_dir().join("frostwall_test_sample_size");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bands.png");
+        let bands = [
+            [220, 20, 20],
+            [20, 200, 40],
+            [30, 40, 220],
+            [240, 230, 40],
+            [20, 20, 20],
+            [230, 230, 230],
+        ];
+        image::RgbImage::from_fn(600, 300, |x, _| image::Rgb(bands[(x / 100) as usize]))
+            .save(&path)
+            .unwrap();
+
+        for size in [128, 32] {
+            let scan = ScanConfig {
+                color_sample_size: size,
+                max_iterations: 10,
+                ..ScanConfig::default()
+            };
+            let mut wp = Wallpaper::from_path_fast(&path).unwrap();
+            wp.extract_colors(&scan).unwrap();
+            assert_eq!(wp.colors.len(), 5, "sample size {}", size);
+            assert_eq!(wp.color_weights.len(), 5);
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_monochrome_palette_collapses() {
+        // A soft blue gradient: k-means splits it into five near-identical blues
+        let img = image::RgbImage::from_fn(64, 64, |x, _| {
+            image::Rgb([30, 60 + (x / 8) as u8, 180 + (x / 4) as u8])
+        });
+        let pixels = sample_pixels(&img, PixelSampling::All);
+
+        let (colors, weights) = dominant_colors(&pixels, &ScanConfig::default());
+        assert!(
+            (1..=2).contains(&colors.len()),
+            "expected one or two blues, got {:?}",
+            colors
+        );
+        assert_eq!(weights.len(), colors.len());
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-4);
+
+        let unmerged = ScanConfig {
+            palette_merge_delta_e: 0.0,
+            ..ScanConfig::default()
+        };
+        assert_eq!(dominant_colors(&pixels, &unmerged).0.len(), 5);
+    }
+
+    #[test]
+    fn test_merge_similar_colors_keeps_distinct() {
+        let red = Lab::new(53.0, 80.0, 67.0);
+        let near_red = Lab::new(54.0, 79.0, 66.0);
+        let blue = Lab::new(32.0, 79.0, -108.0);
+        let merged = merge_similar_colors(vec![(near_red, 0.2), (blue, 0.3), (red, 0.5)], 6.0);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].1, 0.7);
+        assert!((merged[0].0.l - (53.0 * 0.5 + 54.0 * 0.2) / 0.7).abs() < 1e-4);
+        assert_eq!(merged[1].1, 0.3);
+    }
+
+    #[test]
+    fn test_dominant_colors_empty() {
+        let (colors, weights) = dominant_colors(&[], &ScanConfig::default());
+        assert!(colors.is_empty() && weights.is_empty());
+    }
 }
@@ -1,36 +1,262 @@
 use crate::app::Config;
+use crate::collections::{self, CollectionStore};
+use crate::idle::{self, IdleSource};
+use crate::schedule::{self, Slot};
 use crate::screen;
 use crate::swww;
-use crate::timeprofile::TimePeriod;
 use crate::wallpaper::WallpaperCache;
 use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
 use notify::{Config as NotifyConfig, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
+/// How the daemon picks the next wallpaper when shuffling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WatchMode {
+    /// Independent random pick every time (`display.random_mode` applies)
+    #[default]
+    Random,
+    /// Drift through color-similar wallpapers, one step at a time
+    Similar,
+}
+
+impl WatchMode {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "random" => Ok(Self::Random),
+            "similar" => Ok(Self::Similar),
+            _ => anyhow::bail!("Unknown watch mode '{}' (expected random or similar)", s),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Random => "random",
+            Self::Similar => "similar",
+        }
+    }
+}
+
+/// How long a wallpaper stays out of the shuffle on a screen after being shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cooldown {
+    /// For this many rotations
+    Rotations(usize),
+    /// For this long
+    Time(Duration),
+}
+
+impl Cooldown {
+    /// A plain number of rotations (`"5"`) or a duration (`"2h"`, see `parse_interval`)
+    pub fn parse(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if let Ok(count) = s.parse::<usize>() {
+            return Ok(Self::Rotations(count));
+        }
+        parse_interval(s).map(Self::Time).with_context(|| {
+            format!(
+                "Invalid cooldown '{}' (expected a rotation count like 5 or a duration like 2h)",
+                s
+            )
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Rotations(count) => format!("{} rotation(s)", count),
+            Self::Time(duration) => format!("{} seconds", duration.as_secs()),
+        }
+    }
+}
+
+/// Wallpapers each screen showed recently, newest last, for `--cooldown`
+struct RecentlyShown {
+    cooldown: Cooldown,
+    shown: HashMap<String, VecDeque<(PathBuf, Instant)>>,
+}
+
+impl RecentlyShown {
+    fn new(cooldown: Cooldown) -> Self {
+        Self {
+            cooldown,
+            shown: HashMap::new(),
+        }
+    }
+
+    /// Paths still cooling down on `screen`; expired entries are dropped
+    fn excluded(&mut self, screen: &str) -> Vec<PathBuf> {
+        let Some(ring) = self.shown.get_mut(screen) else {
+            return Vec::new();
+        };
+        match self.cooldown {
+            Cooldown::Rotations(count) => {
+                while ring.len() > count {
+                    ring.pop_front();
+                }
+            }
+            Cooldown::Time(duration) => {
+                while ring.front().is_some_and(|(_, at)| at.elapsed() >= duration) {
+                    ring.pop_front();
+                }
+            }
+        }
+        ring.iter().map(|(path, _)| path.clone()).collect()
+    }
+
+    fn record(&mut self, screen: &str, path: &Path) {
+        self.shown
+            .entry(screen.to_string())
+            .or_default()
+            .push_back((path.to_path_buf(), Instant::now()));
+    }
+}
+
+/// Similar mode picks among this many closest matches
+const SIMILAR_TOP_K: usize = 5;
+/// Wallpapers shown this recently on a screen are not picked again in similar mode
+const SIMILAR_HISTORY: usize = 20;
+/// Chance of a random pick in similar mode, so it can leave a tight cluster
+const SIMILAR_RANDOM_CHANCE: f64 = 0.15;
+/// How often idle time is queried while a rotation is waiting for idle
+const IDLE_POLL: Duration = Duration::from_secs(5);
+
 /// Watch daemon configuration
 pub struct WatchConfig {
     pub interval: Duration,
+    /// Randomize each wait by up to ± this fraction of `interval` (0.0 = exact)
+    pub jitter: f64,
     pub shuffle: bool,
+    /// Pick strategy when shuffling
+    pub mode: WatchMode,
     pub watch_dir: bool,
+    /// Resolved wallpaper directory (honors `--dir` and the profile)
+    pub wallpaper_dir: PathBuf,
+    /// Profile whose settings are applied, if any
+    pub profile: Option<String>,
+    /// Rescan only after the directory has been quiet this long, so files
+    /// still being written are not picked up half-finished
+    pub debounce_ms: u64,
+    /// File name patterns (`*`, `?`) whose events are ignored, e.g. `*.part`
+    pub ignore_globs: Vec<String>,
+    /// Only rotate once the user has been idle this long (the interval
+    /// still sets the minimum time between changes)
+    pub on_idle: Option<Duration>,
+    /// Command printing idle milliseconds; `None` uses the idle marker file
+    pub idle_command: Option<String>,
+    /// Keep recently shown wallpapers out of shuffled picks per screen
+    pub cooldown: Option<Cooldown>,
 }
 
+/// Temporary files of browsers, download managers and editors
+pub const DEFAULT_IGNORE_GLOBS: &[&str] = &["*.part", "*.crdownload", "*.tmp", ".*"];
+
 impl Default for WatchConfig {
     fn default() -> Self {
         Self {
             interval: Duration::from_secs(30 * 60), // 30 minutes
+            jitter: 0.0,
             shuffle: true,
+            mode: WatchMode::Random,
             watch_dir: true,
+            wallpaper_dir: Config::default().wallpaper_dir(),
+            profile: None,
+            debounce_ms: 2000,
+            ignore_globs: DEFAULT_IGNORE_GLOBS.iter().map(|g| g.to_string()).collect(),
+            on_idle: None,
+            idle_command: None,
+            cooldown: None,
         }
     }
 }
 
-/// Parse interval string like "30m", "1h", "90s"
+impl WatchConfig {
+    /// Wait until the next rotation: `interval ± rand * jitter * interval`
+    pub fn next_interval(&self) -> Duration {
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return self.interval;
+        }
+        use rand::Rng;
+        let offset = crate::utils::with_rng(|rng| rng.gen_range(-jitter..=jitter));
+        self.interval.mul_f64(1.0 + offset)
+    }
+
+    /// Whether events for `path` should be ignored (matched on the file name)
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return false;
+        };
+        self.ignore_globs
+            .iter()
+            .any(|glob| crate::utils::glob_match(glob, name))
+    }
+}
+
+/// Holds a due rotation back until the user has been idle long enough
+struct IdleGate {
+    source: Box<dyn IdleSource + Send>,
+    threshold: Duration,
+    last_check: Option<Instant>,
+    /// A rotation is due and waiting (so the notice prints once)
+    waiting: bool,
+    warned: bool,
+}
+
+impl IdleGate {
+    fn new(threshold: Duration, command: Option<&str>) -> Self {
+        Self {
+            source: idle::source(command),
+            threshold,
+            last_check: None,
+            waiting: false,
+            warned: false,
+        }
+    }
+
+    /// Whether the due rotation may happen now. Queries the source at most
+    /// every `IDLE_POLL`; a failing source counts as active.
+    fn ready(&mut self) -> bool {
+        if self.last_check.is_some_and(|at| at.elapsed() < IDLE_POLL) {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+
+        let idle = match self.source.idle_time() {
+            Ok(idle) => idle,
+            Err(e) => {
+                if !self.warned {
+                    eprintln!("⚠ Could not read idle time: {:#}", e);
+                    self.warned = true;
+                }
+                Duration::ZERO
+            }
+        };
+        if idle >= self.threshold {
+            self.waiting = false;
+            return true;
+        }
+        if !self.waiting {
+            println!("⏸ Interval elapsed, waiting until idle...");
+            self.waiting = true;
+        }
+        false
+    }
+}
+
+/// Parse interval string like "30m", "1h", "90s", "7d"
 pub fn parse_interval(s: &str) -> Option<Duration> {
     let s = s.trim().to_lowercase();
 
+    if let Some(days) = s.strip_suffix('d') {
+        return days
+            .parse::<u64>()
+            .ok()
+            .map(|d| Duration::from_secs(d * 86400));
+    }
     if let Some(mins) = s.strip_suffix('m') {
         return mins
             .parse::<u64>()
@@ -51,16 +277,52 @@ pub fn parse_interval(s: &str) -> Option<Duration> {
     s.parse::<u64>().ok().map(|m| Duration::from_secs(m * 60))
 }
 
-/// Run the watch daemon
-pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
-    let config = Config::load()?;
-    let wallpaper_dir = config.wallpaper_dir();
+/// Run the watch daemon with an already resolved config (profile applied)
+pub async fn run_watch(watch_config: WatchConfig, config: Config) -> Result<()> {
+    crate::swww::require_installed()?;
+    let wallpaper_dir = watch_config.wallpaper_dir.clone();
+    let recursive = config.wallpaper.recursive;
+    let slots = config.schedule.parse()?;
 
     println!("❄️  FrostWall Watch Daemon");
+    if let Some(profile) = &watch_config.profile {
+        println!("   Profile:   {}", profile);
+    }
     println!("   Directory: {}", wallpaper_dir.display());
-    println!("   Interval:  {} seconds", watch_config.interval.as_secs());
+    if watch_config.jitter > 0.0 {
+        println!(
+            "   Interval:  {} seconds (± {:.0}%)",
+            watch_config.interval.as_secs(),
+            watch_config.jitter * 100.0
+        );
+    } else {
+        println!("   Interval:  {} seconds", watch_config.interval.as_secs());
+    }
+    if let Some(on_idle) = watch_config.on_idle {
+        let source = idle::source(watch_config.idle_command.as_deref());
+        println!(
+            "   On idle:   {} seconds ({})",
+            on_idle.as_secs(),
+            source.describe()
+        );
+    }
     println!("   Shuffle:   {}", watch_config.shuffle);
+    if watch_config.shuffle {
+        println!("   Mode:      {}", watch_config.mode.name());
+        if let Some(cooldown) = &watch_config.cooldown {
+            println!("   Cooldown:  {}", cooldown.describe());
+        }
+    }
+    if !slots.is_empty() {
+        println!("   Schedule:  {} slot(s)", slots.len());
+    }
     println!("   Watching:  {}", watch_config.watch_dir);
+    if watch_config.watch_dir {
+        println!("   Debounce:  {} ms", watch_config.debounce_ms);
+        if !watch_config.ignore_globs.is_empty() {
+            println!("   Ignoring:  {}", watch_config.ignore_globs.join(", "));
+        }
+    }
     println!();
 
     // Set up graceful shutdown
@@ -71,7 +333,8 @@ pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
     ctrlc_handler(running_clone);
 
     // Initial scan
-    let mut cache = WallpaperCache::load_or_scan(&wallpaper_dir)?;
+    let mut cache =
+        WallpaperCache::load_or_scan_recursive(&wallpaper_dir, recursive, &config.scan)?;
     println!("✓ Loaded {} wallpapers", cache.wallpapers.len());
 
     // Set up file system watcher
@@ -88,7 +351,12 @@ pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
             NotifyConfig::default(),
         ) {
             Ok(mut w) => {
-                if let Err(e) = w.watch(&wallpaper_dir, RecursiveMode::NonRecursive) {
+                let mode = if recursive {
+                    RecursiveMode::Recursive
+                } else {
+                    RecursiveMode::NonRecursive
+                };
+                if let Err(e) = w.watch(&wallpaper_dir, mode) {
                     eprintln!("⚠ Could not watch directory: {}", e);
                 } else {
                     println!("✓ Watching for file changes");
@@ -102,17 +370,52 @@ pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
     }
 
     // Detect screens
-    let screens = screen::detect_screens().await?;
+    let screens = screen::detect_screens_ordered(&config.display.output_order).await?;
     if screens.is_empty() {
         anyhow::bail!("No screens detected");
     }
     println!("✓ Found {} screen(s)", screens.len());
 
-    // Set initial wallpaper
-    set_wallpapers(&mut cache, &screens, &config, watch_config.shuffle)?;
+    // Shown wallpapers per screen, newest last (similar mode)
+    let mut history: HashMap<String, VecDeque<PathBuf>> = HashMap::new();
+    let similar = watch_config.shuffle && watch_config.mode == WatchMode::Similar;
+    let mut recently_shown = watch_config.cooldown.map(RecentlyShown::new);
+    let mut rotate = |cache: &mut WallpaperCache| {
+        if similar {
+            set_similar_wallpapers(
+                cache,
+                &screens,
+                &config,
+                &mut history,
+                recently_shown.as_mut(),
+            )
+        } else {
+            set_wallpapers(
+                cache,
+                &screens,
+                &config,
+                watch_config.shuffle,
+                recently_shown.as_mut(),
+            )
+        }
+    };
+
+    // Set initial wallpaper; a missed schedule slot takes precedence
+    let mut schedule_minute = wall_minute();
+    let mut last_slot: Option<NaiveDateTime> = None;
+    if !apply_schedule(&slots, &mut last_slot, &screens, &config) {
+        rotate(&mut cache)?;
+    }
 
     let mut last_change = Instant::now();
-    let mut cache_dirty = false;
+    let mut wait = watch_config.next_interval();
+    let mut idle_gate = watch_config
+        .on_idle
+        .map(|threshold| IdleGate::new(threshold, watch_config.idle_command.as_deref()));
+    let debounce = Duration::from_millis(watch_config.debounce_ms);
+    // Changed image paths since the last rescan, and when the latest event arrived
+    let mut changed: HashSet<PathBuf> = HashSet::new();
+    let mut last_event: Option<Instant> = None;
 
     println!("\n🔄 Running... (Ctrl+C to stop)\n");
 
@@ -123,56 +426,64 @@ pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
 
             match event.kind {
                 EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_) => {
-                    // Check if it's an image file
+                    // Only images count; partial downloads and temp files are skipped
                     for path in &event.paths {
-                        if is_image_file(path) {
+                        if !is_image_file(path) || watch_config.is_ignored(path) {
+                            continue;
+                        }
+                        if changed.insert(path.clone()) {
                             println!("📁 File change detected: {}", path.display());
-                            cache_dirty = true;
                         }
+                        last_event = Some(Instant::now());
                     }
                 }
                 _ => {}
             }
         }
 
-        // Reload cache if dirty
-        if cache_dirty {
-            println!("🔄 Rescanning wallpaper directory...");
-            match WallpaperCache::scan(&wallpaper_dir) {
-                Ok(new_cache) => {
-                    let old_count = cache.wallpapers.len();
-                    let new_count = new_cache.wallpapers.len();
-                    cache = new_cache;
-                    cache.save()?;
-
-                    if new_count > old_count {
-                        println!(
-                            "✓ Added {} new wallpaper(s) (total: {})",
-                            new_count - old_count,
-                            new_count
-                        );
-                    } else if new_count < old_count {
-                        println!(
-                            "✓ Removed {} wallpaper(s) (total: {})",
-                            old_count - new_count,
-                            new_count
-                        );
-                    } else {
-                        println!("✓ Cache updated ({} wallpapers)", new_count);
+        // Reload cache once writes have settled
+        if last_event.is_some_and(|at| at.elapsed() >= debounce) {
+            println!(
+                "🔄 Rescanning wallpaper directory ({} changed file(s))...",
+                changed.len()
+            );
+            // Incremental, so tags, ratings, overrides and positions survive
+            match cache.incremental_rescan(recursive, &config.scan) {
+                Ok((added, removed)) => {
+                    let total = cache.wallpapers.len();
+                    if added > 0 {
+                        println!("✓ Added {} new wallpaper(s) (total: {})", added, total);
+                    }
+                    if removed > 0 {
+                        println!("✓ Removed {} wallpaper(s) (total: {})", removed, total);
+                    }
+                    if added == 0 && removed == 0 {
+                        println!("✓ Cache updated ({} wallpapers)", total);
                     }
                 }
                 Err(e) => {
                     eprintln!("⚠ Failed to rescan: {}", e);
                 }
             }
-            cache_dirty = false;
+            changed.clear();
+            last_event = None;
+        }
+
+        // Check the schedule once per wall-clock minute
+        if !slots.is_empty() && wall_minute() != schedule_minute {
+            schedule_minute = wall_minute();
+            if apply_schedule(&slots, &mut last_slot, &screens, &config) {
+                last_change = Instant::now();
+                wait = watch_config.next_interval();
+            }
         }
 
         // Check if it's time to change wallpaper
-        if last_change.elapsed() >= watch_config.interval {
+        if last_change.elapsed() >= wait && idle_gate.as_mut().is_none_or(IdleGate::ready) {
             println!("⏰ Interval elapsed, changing wallpaper...");
-            set_wallpapers(&mut cache, &screens, &config, watch_config.shuffle)?;
+            rotate(&mut cache)?;
             last_change = Instant::now();
+            wait = watch_config.next_interval();
         }
 
         // Sleep a bit before next check without blocking the async runtime
@@ -188,6 +499,53 @@ pub async fn run_watch(watch_config: WatchConfig) -> Result<()> {
     Ok(())
 }
 
+fn wall_minute() -> i64 {
+    chrono::Local::now().naive_local().and_utc().timestamp() / 60
+}
+
+/// Apply the collection of the schedule slot that fired most recently, unless
+/// that firing was already handled. Returns whether a collection was applied.
+fn apply_schedule(
+    slots: &[Slot],
+    last_slot: &mut Option<NaiveDateTime>,
+    screens: &[screen::Screen],
+    config: &Config,
+) -> bool {
+    let Some((slot, fired)) = schedule::active_slot(slots, chrono::Local::now().naive_local())
+    else {
+        return false;
+    };
+    if *last_slot == Some(fired) {
+        return false;
+    }
+    *last_slot = Some(fired);
+
+    let store = match CollectionStore::load() {
+        Ok(store) => store,
+        Err(e) => {
+            eprintln!("⚠ Failed to load collections: {}", e);
+            return false;
+        }
+    };
+    let Some(collection) = store.get(&slot.collection) else {
+        eprintln!(
+            "⚠ Schedule slot {} names unknown collection '{}'",
+            slot.describe(),
+            slot.collection
+        );
+        return false;
+    };
+
+    println!(
+        "🕑 Schedule slot {} ({}), applying collection...",
+        slot.describe(),
+        fired.format("%a %H:%M")
+    );
+    let connected: Vec<String> = screens.iter().map(|s| s.name.clone()).collect();
+    collections::apply(collection, &HashMap::new(), Some(&connected), config);
+    true
+}
+
 /// Set up Ctrl+C handler
 fn ctrlc_handler(running: Arc<AtomicBool>) {
     // Use tokio's signal handling
@@ -203,24 +561,29 @@ fn set_wallpapers(
     screens: &[screen::Screen],
     config: &Config,
     shuffle: bool,
+    mut recently_shown: Option<&mut RecentlyShown>,
 ) -> Result<()> {
     if !shuffle {
         for screen in screens {
             if let Some(wp) = cache.next_for_screen(screen) {
-                swww::set_wallpaper_with_resize(
+                swww::apply_wallpaper(
                     &screen.name,
                     &wp.path,
                     &config.transition(),
-                    config.display.resize_mode,
-                    &config.display.fill_color,
+                    wp.resize_mode(config.display.resize_mode),
+                    &config.display.fill_color_for(&wp.colors),
+                    config.scan.gif_mode,
                 )
                 .with_context(|| format!("Failed to set wallpaper on {}", screen.name))?;
+                crate::current::update_or_warn(&config.display, &screen.name, &wp.path);
 
                 println!(
                     "  {} → {}",
                     screen.name,
                     wp.path.file_name().unwrap_or_default().to_string_lossy()
                 );
+                let shown = wp.path.clone();
+                cache.mark_shown(&shown);
             }
         }
         return Ok(());
@@ -228,16 +591,21 @@ fn set_wallpapers(
 
     // Check if time profiles are enabled
     let use_time_profiles = config.time_profiles.enabled;
-    let period = TimePeriod::current();
+    let period = config.time_profiles.current_period();
 
     if use_time_profiles {
         println!("  {} Time period: {}", period.emoji(), period.name());
     }
 
     for screen in screens {
+        let cooling = recently_shown
+            .as_deref_mut()
+            .map(|recent| recent.excluded(&screen.name))
+            .unwrap_or_default();
         let wp = if use_time_profiles {
-            // Get wallpapers sorted by time profile score
-            let suitable: Vec<_> = cache
+            // Get wallpapers sorted by time profile score; cooling-down ones
+            // only count when nothing else qualifies
+            let scored: Vec<_> = cache
                 .wallpapers
                 .iter()
                 .filter(|wp| !wp.colors.is_empty())
@@ -248,10 +616,16 @@ fn set_wallpapers(
                 })
                 .filter(|(_, score)| *score >= 0.4) // Minimum threshold
                 .collect();
+            let fresh: Vec<_> = scored
+                .iter()
+                .filter(|(wp, _)| !cooling.contains(&wp.path))
+                .copied()
+                .collect();
+            let suitable = if fresh.is_empty() { scored } else { fresh };
 
             if suitable.is_empty() {
                 // Fallback to random if no suitable wallpapers
-                cache.random_for_screen(screen)
+                cache.random_excluding(screen, config.display.random_mode, &cooling)
             } else {
                 // Pick randomly from top 20% of scored wallpapers
                 let top_count = (suitable.len() / 5).max(3).min(suitable.len());
@@ -259,29 +633,93 @@ fn set_wallpapers(
                 sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
 
                 use rand::seq::SliceRandom;
-                sorted[..top_count]
-                    .choose(&mut rand::thread_rng())
-                    .map(|(wp, _)| *wp)
+                crate::utils::with_rng(|rng| sorted[..top_count].choose(rng)).map(|(wp, _)| *wp)
             }
         } else {
-            cache.random_for_screen(screen)
+            cache.random_excluding(screen, config.display.random_mode, &cooling)
         };
 
         if let Some(wp) = wp {
-            swww::set_wallpaper_with_resize(
+            swww::apply_wallpaper(
                 &screen.name,
                 &wp.path,
                 &config.transition(),
-                config.display.resize_mode,
-                &config.display.fill_color,
+                wp.resize_mode(config.display.resize_mode),
+                &config.display.fill_color_for(&wp.colors),
+                config.scan.gif_mode,
             )
             .with_context(|| format!("Failed to set wallpaper on {}", screen.name))?;
+            crate::current::update_or_warn(&config.display, &screen.name, &wp.path);
 
             println!(
                 "  {} → {}",
                 screen.name,
                 wp.path.file_name().unwrap_or_default().to_string_lossy()
             );
+            let shown = wp.path.clone();
+            cache.mark_shown(&shown);
+            if let Some(recent) = recently_shown.as_deref_mut() {
+                recent.record(&screen.name, &shown);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Similar mode: each screen moves to a wallpaper close in color to the one
+/// it shows now, or (occasionally, and for the first pick) a random one.
+fn set_similar_wallpapers(
+    cache: &mut WallpaperCache,
+    screens: &[screen::Screen],
+    config: &Config,
+    history: &mut HashMap<String, VecDeque<PathBuf>>,
+    mut recently_shown: Option<&mut RecentlyShown>,
+) -> Result<()> {
+    use rand::Rng;
+
+    for screen in screens {
+        let cooling = recently_shown
+            .as_deref_mut()
+            .map(|recent| recent.excluded(&screen.name))
+            .unwrap_or_default();
+        let shown = history.entry(screen.name.clone()).or_default();
+        let jump = crate::utils::with_rng(|rng| rng.gen_bool(SIMILAR_RANDOM_CHANCE));
+        let wp = match shown.back() {
+            Some(current) if !jump => {
+                let mut recent: Vec<PathBuf> = shown.iter().cloned().collect();
+                recent.extend(cooling);
+                cache.similar_for_screen(screen, current, &recent, SIMILAR_TOP_K)
+            }
+            _ => cache.random_excluding(screen, config.display.random_mode, &cooling),
+        };
+        let Some(wp) = wp else {
+            continue;
+        };
+
+        swww::apply_wallpaper(
+            &screen.name,
+            &wp.path,
+            &config.transition(),
+            wp.resize_mode(config.display.resize_mode),
+            &config.display.fill_color_for(&wp.colors),
+            config.scan.gif_mode,
+        )
+        .with_context(|| format!("Failed to set wallpaper on {}", screen.name))?;
+        crate::current::update_or_warn(&config.display, &screen.name, &wp.path);
+
+        println!(
+            "  {} → {}",
+            screen.name,
+            wp.path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let path = wp.path.clone();
+        cache.mark_shown(&path);
+        if let Some(recent) = recently_shown.as_deref_mut() {
+            recent.record(&screen.name, &path);
+        }
+        shown.push_back(path);
+        if shown.len() > SIMILAR_HISTORY {
+            shown.pop_front();
         }
     }
     Ok(())
@@ -293,16 +731,16 @@ fn is_image_file(path: &Path) -> bool {
 
 /// Run a single wallpaper change (for cron/timer use)
 #[allow(dead_code)]
-pub async fn run_once(shuffle: bool) -> Result<()> {
-    let config = Config::load()?;
-    let wallpaper_dir = config.wallpaper_dir();
-    let mut cache = WallpaperCache::load_or_scan(&wallpaper_dir)?;
-    let screens = screen::detect_screens().await?;
-
-    set_wallpapers(&mut cache, &screens, &config, shuffle)?;
-    if !shuffle {
-        cache.save()?;
-    }
+pub async fn run_once(config: &Config, wallpaper_dir: &Path, shuffle: bool) -> Result<()> {
+    let mut cache = WallpaperCache::load_or_scan_recursive(
+        wallpaper_dir,
+        config.wallpaper.recursive,
+        &config.scan,
+    )?;
+    let screens = screen::detect_screens_ordered(&config.display.output_order).await?;
+
+    set_wallpapers(&mut cache, &screens, config, shuffle, None)?;
+    cache.save()?;
 
     Ok(())
 }
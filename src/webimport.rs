@@ -1,16 +1,57 @@
 //! Web gallery import for wallpapers
 //!
-//! Download wallpapers from popular galleries like Unsplash and Wallhaven.
+//! Download wallpapers from popular galleries like Unsplash, Wallhaven and Reddit,
+//! or copy them from a local folder kept outside the library.
 
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// HTTP settings for `frostwall import` (`[import]` in config.toml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportConfig {
+    /// Give up on a request after this many seconds (covers the whole download)
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Extra attempts after a network error or 5xx response (0 = no retries)
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+impl Default for ImportConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_timeout_secs(),
+            retries: default_retries(),
+        }
+    }
+}
+
+/// Wait before the first retry; doubled for each further one
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Delay before retry number `retry` (1-based): 1s, 2s, 4s, ...
+fn backoff(retry: u32) -> Duration {
+    RETRY_BASE_DELAY * 2u32.saturating_pow(retry.saturating_sub(1).min(16))
+}
 
 /// Supported web galleries
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Gallery {
     Unsplash,
     Wallhaven,
+    Reddit,
+    /// A folder on disk, e.g. a synced upstream collection; "downloads" are copies
+    LocalDir(PathBuf),
 }
 
 impl Gallery {
@@ -18,6 +59,8 @@ impl Gallery {
         match self {
             Gallery::Unsplash => "Unsplash",
             Gallery::Wallhaven => "Wallhaven",
+            Gallery::Reddit => "Reddit",
+            Gallery::LocalDir(_) => "Local",
         }
     }
 
@@ -26,6 +69,7 @@ impl Gallery {
         match s.to_lowercase().as_str() {
             "unsplash" => Some(Gallery::Unsplash),
             "wallhaven" => Some(Gallery::Wallhaven),
+            "reddit" => Some(Gallery::Reddit),
             _ => None,
         }
     }
@@ -44,6 +88,134 @@ pub struct GalleryImage {
     pub source: Gallery,
 }
 
+/// Relative tolerance when matching an aspect ratio (21:9 vs 64:27 ultrawide panels)
+const RATIO_TOLERANCE: f32 = 0.05;
+
+/// Size and shape limits for search results
+#[derive(Debug, Clone, Default)]
+pub struct ImageFilter {
+    pub min_width: Option<u32>,
+    pub min_height: Option<u32>,
+    /// Aspect ratio as given (`21:9` → (21, 9))
+    pub ratio: Option<(u32, u32)>,
+}
+
+impl ImageFilter {
+    /// Parse a ratio like `21:9` or `16x9`
+    pub fn parse_ratio(s: &str) -> Result<(u32, u32)> {
+        let (w, h) = s
+            .trim()
+            .split_once([':', 'x', 'X'])
+            .with_context(|| format!("Invalid ratio '{}' (expected e.g. 21:9)", s))?;
+        let w: u32 = w
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid ratio '{}'", s))?;
+        let h: u32 = h
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid ratio '{}'", s))?;
+        if w == 0 || h == 0 {
+            anyhow::bail!("Invalid ratio '{}': both sides must be positive", s);
+        }
+        Ok((w, h))
+    }
+
+    /// Ratio as width / height
+    pub fn ratio_value(&self) -> Option<f32> {
+        self.ratio.map(|(w, h)| w as f32 / h as f32)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_width.is_none() && self.min_height.is_none() && self.ratio.is_none()
+    }
+
+    /// Whether an image passes the filter. Unknown (zero) dimensions pass.
+    pub fn matches(&self, image: &GalleryImage) -> bool {
+        if image.width == 0 || image.height == 0 {
+            return true;
+        }
+        if self.min_width.is_some_and(|w| image.width < w)
+            || self.min_height.is_some_and(|h| image.height < h)
+        {
+            return false;
+        }
+        match self.ratio_value() {
+            Some(target) => {
+                let actual = image.width as f32 / image.height as f32;
+                (actual - target).abs() <= target * RATIO_TOLERANCE
+            }
+            None => true,
+        }
+    }
+
+    /// Wallhaven `atleast`/`ratios` query parameters
+    fn wallhaven_params(&self) -> String {
+        let mut params = String::new();
+        if self.min_width.is_some() || self.min_height.is_some() {
+            params.push_str(&format!(
+                "&atleast={}x{}",
+                self.min_width.unwrap_or(1),
+                self.min_height.unwrap_or(1)
+            ));
+        }
+        if let Some((w, h)) = self.ratio {
+            params.push_str(&format!("&ratios={}x{}", w, h));
+        }
+        params
+    }
+
+    /// Unsplash only filters by orientation
+    fn unsplash_orientation(&self) -> &'static str {
+        match self.ratio_value() {
+            Some(r) if r < 1.0 - RATIO_TOLERANCE => "portrait",
+            Some(r) if r <= 1.0 + RATIO_TOLERANCE => "squarish",
+            _ => "landscape",
+        }
+    }
+}
+
+/// Where an imported image came from, stored next to it as `<name>.<ext>.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attribution {
+    pub source: String,
+    pub id: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub url: String,
+}
+
+impl Attribution {
+    fn from_image(image: &GalleryImage) -> Self {
+        Self {
+            source: image.source.name().to_string(),
+            id: image.id.clone(),
+            author: image.author.clone(),
+            url: image.url.clone(),
+        }
+    }
+
+    /// Sidecar file for a wallpaper: the full file name plus `.json`, so
+    /// `foo.jpg` and `foo.png` get separate sidecars
+    pub fn sidecar_path(image_path: &Path) -> PathBuf {
+        let mut name = image_path.as_os_str().to_owned();
+        name.push(".json");
+        PathBuf::from(name)
+    }
+
+    /// Read the sidecar for `image_path`, if one was written on import
+    pub fn load_for(image_path: &Path) -> Option<Self> {
+        let data = std::fs::read_to_string(Self::sidecar_path(image_path)).ok()?;
+        serde_json::from_str(&data).ok()
+    }
+
+    fn save_for(&self, image_path: &Path) -> Result<()> {
+        let path = Self::sidecar_path(image_path);
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
 // Unsplash API response structures
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -87,6 +259,12 @@ struct WallhavenResponse {
     meta: WallhavenMeta,
 }
 
+/// `/api/v1/w/<id>`: a single wallpaper
+#[derive(Debug, Deserialize)]
+struct WallhavenWallpaper {
+    data: WallhavenImage,
+}
+
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 struct WallhavenImage {
@@ -114,24 +292,147 @@ struct WallhavenMeta {
     total: u32,
 }
 
+// Reddit listing structures (only the fields we need)
+#[derive(Debug, Deserialize)]
+struct RedditListing {
+    data: RedditListingData,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditListingData {
+    children: Vec<RedditChild>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditChild {
+    data: RedditPost,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditPost {
+    id: String,
+    author: Option<String>,
+    post_hint: Option<String>,
+    url: Option<String>,
+    #[serde(default)]
+    is_gallery: bool,
+    crosspost_parent: Option<String>,
+    preview: Option<RedditPreview>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditPreview {
+    images: Vec<RedditPreviewImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditPreviewImage {
+    source: RedditImageSource,
+    #[serde(default)]
+    resolutions: Vec<RedditImageSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RedditImageSource {
+    url: String,
+    width: u32,
+    height: u32,
+}
+
+/// Reddit rejects generic user agents; this follows their
+/// `<platform>:<app id>:<version>` convention.
+fn default_reddit_user_agent() -> String {
+    format!(
+        "linux:frostwall:{} (wallpaper importer)",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
 /// Web import client
 pub struct WebImporter {
     client: reqwest::blocking::Client,
     unsplash_key: Option<String>,
     wallhaven_key: Option<String>,
+    reddit_user_agent: String,
+    retries: u32,
 }
 
 impl WebImporter {
     /// Create a new web importer
-    pub fn new() -> Self {
+    pub fn new(config: &ImportConfig) -> Self {
         Self {
             client: reqwest::blocking::Client::builder()
                 .user_agent(format!("FrostWall/{}", env!("CARGO_PKG_VERSION")))
+                .timeout(Duration::from_secs(config.timeout_secs.max(1)))
                 .build()
                 .unwrap_or_else(|_| reqwest::blocking::Client::new()),
             unsplash_key: std::env::var("UNSPLASH_ACCESS_KEY").ok(),
             wallhaven_key: std::env::var("WALLHAVEN_API_KEY").ok(),
+            reddit_user_agent: std::env::var("REDDIT_USER_AGENT")
+                .ok()
+                .filter(|ua| !ua.trim().is_empty())
+                .unwrap_or_else(default_reddit_user_agent),
+            retries: config.retries,
+        }
+    }
+
+    /// Send `request`, retrying network errors and 5xx responses with
+    /// exponential backoff. Other responses (including 404) are returned
+    /// as-is for the caller to check. `what` names the request in errors.
+    fn send(
+        &self,
+        what: &str,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        self.send_with(what, request, Ok)
+    }
+
+    /// `send`, then `read` the response inside the same retry loop, so a
+    /// body that breaks off mid-transfer is retried like a failed request
+    fn send_with<T>(
+        &self,
+        what: &str,
+        request: reqwest::blocking::RequestBuilder,
+        read: impl Fn(reqwest::blocking::Response) -> reqwest::Result<T>,
+    ) -> Result<T> {
+        let attempts = self.retries + 1;
+        for attempt in 1..=attempts {
+            let this_try = request.try_clone().context("Request can't be retried")?;
+            let outcome = this_try.send().and_then(|response| {
+                if response.status().is_server_error() {
+                    Ok(Err(response.status()))
+                } else {
+                    read(response).map(Ok)
+                }
+            });
+            let failure = match outcome {
+                Ok(Ok(value)) => return Ok(value),
+                Ok(Err(status)) if attempt == attempts => anyhow::bail!(
+                    "{} failed: server returned {} (after {} attempt(s))",
+                    what,
+                    status,
+                    attempts
+                ),
+                Ok(Err(status)) => format!("server returned {}", status),
+                // Never echo the URL: it may carry an API key
+                Err(e) if attempt == attempts || e.is_builder() => {
+                    return Err(anyhow::Error::new(e.without_url()))
+                        .with_context(|| format!("{} failed after {} attempt(s)", what, attempt));
+                }
+                Err(e) => format!("{:#}", anyhow::Error::new(e.without_url())),
+            };
+            let delay = backoff(attempt);
+            tracing::warn!(
+                "{}: {}; retrying in {}s ({}/{})",
+                what,
+                failure,
+                delay.as_secs(),
+                attempt,
+                self.retries
+            );
+            std::thread::sleep(delay);
         }
+        unreachable!("the last attempt always returns")
     }
 
     /// Check if a gallery is available (has API key if required)
@@ -139,43 +440,63 @@ impl WebImporter {
         match gallery {
             Gallery::Unsplash => self.unsplash_key.is_some(),
             Gallery::Wallhaven => true, // Public API available without key
+            Gallery::Reddit => true,    // Public JSON listings
+            Gallery::LocalDir(dir) => dir.is_dir(),
         }
     }
 
-    /// Search for images in a gallery
+    /// Search for images in a gallery. Local folders list every image;
+    /// `query` is ignored for them.
     pub fn search(
         &self,
         gallery: Gallery,
         query: &str,
         page: u32,
         per_page: u32,
+        filter: &ImageFilter,
     ) -> Result<Vec<GalleryImage>> {
         match gallery {
-            Gallery::Unsplash => self.search_unsplash(query, page, per_page),
-            Gallery::Wallhaven => self.search_wallhaven(query, page, per_page),
+            Gallery::Unsplash => self.search_unsplash(query, page, per_page, filter),
+            Gallery::Wallhaven => self.search_wallhaven(query, page, per_page, filter),
+            Gallery::Reddit => self.top_reddit(query, per_page, filter),
+            Gallery::LocalDir(dir) => list_local(&dir, page, per_page, filter),
         }
     }
 
     /// Search Unsplash
-    fn search_unsplash(&self, query: &str, page: u32, per_page: u32) -> Result<Vec<GalleryImage>> {
+    fn search_unsplash(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        filter: &ImageFilter,
+    ) -> Result<Vec<GalleryImage>> {
         let api_key = self
             .unsplash_key
             .as_ref()
             .context("Unsplash API key required. Set UNSPLASH_ACCESS_KEY environment variable.")?;
 
+        // Over-fetch when post-filtering, since only orientation is filtered server-side
+        let fetch = if filter.is_empty() {
+            per_page
+        } else {
+            per_page.saturating_mul(2)
+        };
         let url = format!(
-            "https://api.unsplash.com/search/photos?query={}&page={}&per_page={}&orientation=landscape",
+            "https://api.unsplash.com/search/photos?query={}&page={}&per_page={}&orientation={}",
             urlencoding::encode(query),
             page,
-            per_page.min(30) // Unsplash max is 30
+            fetch.min(30), // Unsplash max is 30
+            filter.unsplash_orientation()
         );
 
-        let response: UnsplashSearchResponse = self
+        tracing::debug!("GET {}", url);
+        let request = self
             .client
             .get(&url)
-            .header("Authorization", format!("Client-ID {}", api_key))
-            .send()
-            .context("Failed to connect to Unsplash")?
+            .header("Authorization", format!("Client-ID {}", api_key));
+        let response: UnsplashSearchResponse = self
+            .send("Unsplash search", request)?
             .json()
             .context("Failed to parse Unsplash response")?;
 
@@ -184,22 +505,50 @@ impl WebImporter {
             .into_iter()
             .map(|photo| GalleryImage {
                 id: photo.id,
-                url: format!("{}?w=3840&q=85", photo.urls.raw), // 4K quality
+                // Keep the signed raw URL from the response; it already has a query
+                url: format!(
+                    "{}{}w=3840&q=85", // 4K quality
+                    photo.urls.raw,
+                    if photo.urls.raw.contains('?') {
+                        '&'
+                    } else {
+                        '?'
+                    }
+                ),
                 thumb_url: photo.urls.small,
                 width: photo.width,
                 height: photo.height,
                 author: Some(photo.user.name),
                 source: Gallery::Unsplash,
             })
+            .filter(|img| filter.matches(img))
+            .take(per_page as usize)
             .collect())
     }
 
     /// Search Wallhaven
-    fn search_wallhaven(&self, query: &str, page: u32, per_page: u32) -> Result<Vec<GalleryImage>> {
+    fn search_wallhaven(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        filter: &ImageFilter,
+    ) -> Result<Vec<GalleryImage>> {
         let mut url = format!(
-            "https://wallhaven.cc/api/v1/search?q={}&page={}&categories=111&purity=100&sorting=relevance&order=desc",
+            "https://wallhaven.cc/api/v1/search?q={}&page={}&categories=111&purity=100&sorting=relevance&order=desc{}",
             urlencoding::encode(query),
             page,
+            filter.wallhaven_params(),
+        );
+        // Logged before the key is added so it never ends up in bug reports
+        tracing::debug!(
+            "GET {}{}",
+            url,
+            if self.wallhaven_key.is_some() {
+                " (+apikey)"
+            } else {
+                ""
+            }
         );
 
         // Add API key if available (allows access to NSFW if enabled in account)
@@ -208,17 +557,13 @@ impl WebImporter {
         }
 
         let response: WallhavenResponse = self
-            .client
-            .get(&url)
-            .send()
-            .context("Failed to connect to Wallhaven")?
+            .send("Wallhaven search", self.client.get(&url))?
             .json()
             .context("Failed to parse Wallhaven response")?;
 
         Ok(response
             .data
             .into_iter()
-            .take(per_page as usize)
             .map(|img| GalleryImage {
                 id: img.id,
                 url: img.path,
@@ -228,44 +573,133 @@ impl WebImporter {
                 author: None,
                 source: Gallery::Wallhaven,
             })
+            .filter(|img| filter.matches(img))
+            .take(per_page as usize)
             .collect())
     }
 
-    /// Download an image to the specified directory
-    pub fn download(&self, image: &GalleryImage, dest_dir: &Path) -> Result<PathBuf> {
-        // Create filename from ID and extension
-        let extension = image
-            .url
-            .rsplit('.')
+    /// Look up one Wallhaven wallpaper by ID, for its real file URL
+    pub fn wallhaven_image(&self, id: &str) -> Result<GalleryImage> {
+        let mut url = format!("https://wallhaven.cc/api/v1/w/{}", urlencoding::encode(id));
+        tracing::debug!("GET {}", url);
+        if let Some(key) = &self.wallhaven_key {
+            url.push_str(&format!("?apikey={}", urlencoding::encode(key)));
+        }
+
+        let response = self.send("Wallhaven lookup", self.client.get(&url))?;
+        if !response.status().is_success() {
+            anyhow::bail!("Wallhaven returned {} for {}", response.status(), id);
+        }
+        let img = response
+            .json::<WallhavenWallpaper>()
+            .context("Failed to parse Wallhaven response")?
+            .data;
+
+        Ok(GalleryImage {
+            id: img.id,
+            url: img.path,
+            thumb_url: img.thumbs.small,
+            width: img.dimension_x,
+            height: img.dimension_y,
+            author: None,
+            source: Gallery::Wallhaven,
+        })
+    }
+
+    /// File name of `image` without extension: `<source>_<id>`
+    fn file_stem(image: &GalleryImage) -> String {
+        format!("{}_{}", image.source.name().to_lowercase(), image.id)
+    }
+
+    /// Where `download` expects to save `image`: `<source>_<id>.<ext>` in
+    /// `dest_dir`, with the extension guessed from the URL. The downloaded
+    /// bytes decide the real extension.
+    pub fn target_path(image: &GalleryImage, dest_dir: &Path) -> PathBuf {
+        // Local copies are named after their path below the gallery root
+        // (`nature/a.png` -> `nature_a.png`), so same-named files in
+        // different subfolders don't overwrite each other
+        if let Gallery::LocalDir(_) = image.source {
+            let parts: Vec<_> = Path::new(&image.id)
+                .components()
+                .filter_map(|part| match part {
+                    std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+                    _ => None,
+                })
+                .collect();
+            return dest_dir.join(parts.join("_"));
+        }
+        // Unsplash URLs carry no extension (`/photo-123?ixid=...`), so only
+        // trust one that names an image format
+        let path = image.url.split(['?', '#']).next().unwrap_or(&image.url);
+        let extension = path
+            .rsplit('/')
             .next()
-            .and_then(|ext| ext.split('?').next())
-            .unwrap_or("jpg");
-
-        let filename = format!(
-            "{}_{}.{}",
-            image.source.name().to_lowercase(),
-            image.id,
-            extension
-        );
-        let dest_path = dest_dir.join(&filename);
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_lowercase())
+            .filter(|ext| crate::utils::IMAGE_EXTENSIONS.contains(&ext.as_str()))
+            .unwrap_or_else(|| "jpg".to_string());
+
+        dest_dir.join(format!("{}.{}", Self::file_stem(image), extension))
+    }
+
+    /// An earlier download of `image` in `dest_dir`, whichever extension it
+    /// was saved with
+    pub fn existing_download(image: &GalleryImage, dest_dir: &Path) -> Option<PathBuf> {
+        let target = Self::target_path(image, dest_dir);
+        if target.exists() {
+            return Some(target);
+        }
+        let stem = Self::file_stem(image);
+        crate::utils::IMAGE_EXTENSIONS
+            .iter()
+            .map(|ext| dest_dir.join(format!("{}.{}", stem, ext)))
+            .find(|path| path.exists())
+    }
 
+    /// Download an image to the specified directory. Returns the saved path,
+    /// whose extension follows the downloaded data rather than the URL.
+    pub fn download(&self, image: &GalleryImage, dest_dir: &Path) -> Result<PathBuf> {
         // Skip if already exists
-        if dest_path.exists() {
-            return Ok(dest_path);
+        if let Some(existing) = Self::existing_download(image, dest_dir) {
+            return Ok(existing);
+        }
+        if let Gallery::LocalDir(_) = image.source {
+            return Self::copy_local(image, dest_dir);
         }
 
         // Download the image
-        let response = self
-            .client
-            .get(&image.url)
-            .send()
-            .context("Failed to download image")?;
+        tracing::debug!("Downloading {}", image.url);
+        let mut request = self.client.get(&image.url);
+        if image.source == Gallery::Reddit {
+            request = request.header(reqwest::header::USER_AGENT, &self.reddit_user_agent);
+        }
+        let (status, content_type, bytes) =
+            self.send_with("Image download", request, |response| {
+                let status = response.status();
+                let content_type = response
+                    .headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string);
+                Ok((status, content_type, response.bytes()?))
+            })?;
 
-        if !response.status().is_success() {
-            anyhow::bail!("Download failed with status: {}", response.status());
+        if !status.is_success() {
+            anyhow::bail!("Download failed with status: {}", status);
         }
+        tracing::debug!(
+            "Downloaded {} bytes ({})",
+            bytes.len(),
+            content_type.as_deref().unwrap_or("no content type")
+        );
 
-        let bytes = response.bytes().context("Failed to read image data")?;
+        let extension = detect_image_ext(&bytes, content_type.as_deref()).with_context(|| {
+            format!(
+                "Download is not a supported image (content type: {})",
+                content_type.as_deref().unwrap_or("none")
+            )
+        })?;
+        let dest_path = dest_dir.join(format!("{}.{}", Self::file_stem(image), extension));
 
         // Ensure directory exists
         std::fs::create_dir_all(dest_dir)?;
@@ -273,25 +707,48 @@ impl WebImporter {
         // Write to file
         std::fs::write(&dest_path, &bytes).context("Failed to save image")?;
 
+        // A missing sidecar only loses the credit line; keep the download
+        if let Err(e) = Attribution::from_image(image).save_for(&dest_path) {
+            tracing::warn!("{}", e);
+        }
+
+        Ok(dest_path)
+    }
+
+    /// Copy a local gallery image into `dest_dir`, named as in `target_path`
+    fn copy_local(image: &GalleryImage, dest_dir: &Path) -> Result<PathBuf> {
+        let dest_path = Self::target_path(image, dest_dir);
+        std::fs::create_dir_all(dest_dir)?;
+        std::fs::copy(&image.url, &dest_path)
+            .with_context(|| format!("Failed to copy {}", image.url))?;
+
+        if let Err(e) = Attribution::from_image(image).save_for(&dest_path) {
+            tracing::warn!("{}", e);
+        }
         Ok(dest_path)
     }
 
     /// Get random featured wallpapers from Wallhaven
-    pub fn featured_wallhaven(&self, count: u32) -> Result<Vec<GalleryImage>> {
-        let url = "https://wallhaven.cc/api/v1/search?sorting=toplist&topRange=1M&categories=111&purity=100&atleast=1920x1080";
+    pub fn featured_wallhaven(
+        &self,
+        count: u32,
+        filter: &ImageFilter,
+    ) -> Result<Vec<GalleryImage>> {
+        let mut url = "https://wallhaven.cc/api/v1/search?sorting=toplist&topRange=1M&categories=111&purity=100".to_string();
+        if filter.min_width.is_none() && filter.min_height.is_none() {
+            url.push_str("&atleast=1920x1080");
+        }
+        url.push_str(&filter.wallhaven_params());
 
+        tracing::debug!("GET {}", url);
         let response: WallhavenResponse = self
-            .client
-            .get(url)
-            .send()
-            .context("Failed to connect to Wallhaven")?
+            .send("Wallhaven toplist", self.client.get(&url))?
             .json()
             .context("Failed to parse Wallhaven response")?;
 
         Ok(response
             .data
             .into_iter()
-            .take(count as usize)
             .map(|img| GalleryImage {
                 id: img.id,
                 url: img.path,
@@ -301,13 +758,207 @@ impl WebImporter {
                 author: None,
                 source: Gallery::Wallhaven,
             })
+            .filter(|img| filter.matches(img))
+            .take(count as usize)
             .collect())
     }
+
+    /// Top image posts of the week from a subreddit
+    pub fn top_reddit(
+        &self,
+        subreddit: &str,
+        count: u32,
+        filter: &ImageFilter,
+    ) -> Result<Vec<GalleryImage>> {
+        let subreddit = subreddit.trim().trim_start_matches("r/");
+        let (count, limit) = reddit_limits(count);
+        let url = format!(
+            "https://www.reddit.com/r/{}/top.json?t=week&limit={}&raw_json=1",
+            urlencoding::encode(subreddit),
+            limit
+        );
+
+        tracing::debug!("GET {}", url);
+        let request = self
+            .client
+            .get(&url)
+            .header(reqwest::header::USER_AGENT, &self.reddit_user_agent);
+        let response = self.send("Reddit listing", request)?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Reddit returned {} for r/{}", response.status(), subreddit);
+        }
+
+        let listing: RedditListing = response.json().context("Failed to parse Reddit response")?;
+
+        Ok(listing
+            .data
+            .children
+            .into_iter()
+            .filter_map(|child| reddit_image(child.data))
+            .filter(|img| filter.matches(img))
+            .take(count as usize)
+            .collect())
+    }
+}
+
+/// Posts to keep and the listing `limit` to request for `count`. Reddit caps
+/// listings at 100; below that we over-fetch, since text posts, galleries
+/// and crossposts get filtered out.
+fn reddit_limits(count: u32) -> (u32, u32) {
+    let count = count.clamp(1, 100);
+    (count, (count * 3).min(100))
+}
+
+/// A file in a local gallery rooted at `root`; its ID is the path below `root`
+pub fn local_image(path: &Path, root: &Path) -> Result<GalleryImage> {
+    let (width, height) = image::image_dimensions(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let id = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy();
+    Ok(GalleryImage {
+        id: id.into_owned(),
+        url: path.to_string_lossy().into_owned(),
+        thumb_url: String::new(),
+        width,
+        height,
+        author: None,
+        source: Gallery::LocalDir(root),
+    })
+}
+
+/// Images in `dir` and its subfolders, sorted by path, that pass `filter`.
+/// Hidden entries are skipped; `page` is 1-based.
+fn list_local(
+    dir: &Path,
+    page: u32,
+    per_page: u32,
+    filter: &ImageFilter,
+) -> Result<Vec<GalleryImage>> {
+    if !dir.is_dir() {
+        anyhow::bail!("Not a directory: {}", dir.display());
+    }
+    let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0 || !entry.file_name().to_string_lossy().starts_with('.')
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| crate::utils::is_image_file(path))
+        .collect();
+    paths.sort();
+
+    let skip = page.saturating_sub(1) as usize * per_page as usize;
+    Ok(paths
+        .iter()
+        .filter_map(|path| match local_image(path, dir) {
+            Ok(image) => Some(image),
+            Err(e) => {
+                tracing::warn!("{:#}", e);
+                None
+            }
+        })
+        .filter(|image| filter.matches(image))
+        .skip(skip)
+        .take(per_page as usize)
+        .collect())
+}
+
+/// Turn a Reddit post into a `GalleryImage` if it links straight to an image.
+/// Galleries, crossposts and anything hosted off-site are skipped.
+fn reddit_image(post: RedditPost) -> Option<GalleryImage> {
+    if post.post_hint.as_deref() != Some("image")
+        || post.is_gallery
+        || post.crosspost_parent.is_some()
+    {
+        return None;
+    }
+
+    let url = post.url?;
+    if !is_direct_image_url(&url) {
+        return None;
+    }
+
+    let preview = post.preview.and_then(|p| p.images.into_iter().next());
+    let (width, height, thumb_url) = match preview {
+        Some(image) => {
+            // Pick a mid-sized resolution for the thumbnail
+            let thumb = image
+                .resolutions
+                .iter()
+                .find(|r| r.width >= 320)
+                .or(image.resolutions.last())
+                .map(|r| r.url.clone())
+                .unwrap_or_else(|| image.source.url.clone());
+            (image.source.width, image.source.height, thumb)
+        }
+        None => (0, 0, url.clone()),
+    };
+
+    Some(GalleryImage {
+        id: post.id,
+        url,
+        thumb_url: html_unescape(&thumb_url),
+        width,
+        height,
+        author: post.author,
+        source: Gallery::Reddit,
+    })
+}
+
+/// File extension for downloaded image data. The magic bytes win over the
+/// `Content-Type` header, which CDNs sometimes get wrong; the header is only
+/// used when the bytes are not recognized. `None` if neither names an image
+/// format frostwall reads.
+pub fn detect_image_ext(bytes: &[u8], content_type: Option<&str>) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\xFF\xD8\xFF", "jpg"),
+        (b"\x89PNG\r\n\x1A\n", "png"),
+        (b"GIF87a", "gif"),
+        (b"GIF89a", "gif"),
+        (b"BM", "bmp"),
+    ];
+    if let Some((_, ext)) = MAGIC.iter().find(|(magic, _)| bytes.starts_with(magic)) {
+        return Some(ext);
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("webp");
+    }
+
+    let mime = content_type?.split(';').next()?.trim().to_lowercase();
+    match mime.as_str() {
+        "image/jpeg" | "image/jpg" | "image/pjpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        "image/gif" => Some("gif"),
+        "image/bmp" | "image/x-ms-bmp" => Some("bmp"),
+        _ => None,
+    }
+}
+
+/// Whether `url` points at an image file rather than a page
+pub fn is_direct_image_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.rsplit_once('.')
+        .map(|(_, ext)| {
+            let ext = ext.to_lowercase();
+            !ext.contains('/') && crate::utils::IMAGE_EXTENSIONS.contains(&ext.as_str())
+        })
+        .unwrap_or(false)
+}
+
+/// Reddit escapes `&` in preview URLs unless `raw_json=1` is honored
+fn html_unescape(s: &str) -> String {
+    s.replace("&amp;", "&")
 }
 
 impl Default for WebImporter {
     fn default() -> Self {
-        Self::new()
+        Self::new(&ImportConfig::default())
     }
 }
 
@@ -331,3 +982,153 @@ mod urlencoding {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // --- backoff ---
+
+    #[test]
+    fn test_backoff_doubles() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert!(backoff(u32::MAX) >= backoff(3), "no overflow");
+    }
+
+    // --- reddit_limits ---
+
+    #[test]
+    fn test_reddit_limits_cap_at_listing_size() {
+        assert_eq!(reddit_limits(10), (10, 30));
+        assert_eq!(reddit_limits(0), (1, 3));
+        assert_eq!(reddit_limits(50), (50, 100));
+        assert_eq!(reddit_limits(150), (100, 100), "no panic above 100");
+    }
+
+    // --- detect_image_ext ---
+
+    #[test]
+    fn test_detect_image_ext_from_magic_bytes() {
+        assert_eq!(
+            detect_image_ext(b"\xFF\xD8\xFF\xE0\0\x10JFIF", None),
+            Some("jpg")
+        );
+        assert_eq!(
+            detect_image_ext(b"\x89PNG\r\n\x1A\n\0\0\0\rIHDR", None),
+            Some("png")
+        );
+        assert_eq!(
+            detect_image_ext(b"RIFF\x24\0\0\0WEBPVP8 ", None),
+            Some("webp")
+        );
+        assert_eq!(detect_image_ext(b"GIF89a\x01\0", None), Some("gif"));
+        assert_eq!(detect_image_ext(b"BM\x36\0\0\0", None), Some("bmp"));
+    }
+
+    #[test]
+    fn test_detect_image_ext_bytes_beat_content_type() {
+        // Served as jpeg, actually webp
+        assert_eq!(
+            detect_image_ext(b"RIFF\x24\0\0\0WEBPVP8 ", Some("image/jpeg")),
+            Some("webp")
+        );
+        assert_eq!(
+            detect_image_ext(b"\x89PNG\r\n\x1A\n", Some("application/octet-stream")),
+            Some("png")
+        );
+    }
+
+    #[test]
+    fn test_detect_image_ext_falls_back_to_content_type() {
+        assert_eq!(
+            detect_image_ext(b"????", Some("image/JPEG; charset=binary")),
+            Some("jpg")
+        );
+        assert_eq!(detect_image_ext(b"????", Some("image/webp")), Some("webp"));
+        assert_eq!(
+            detect_image_ext(b"<!DOCTYPE html>", Some("text/html")),
+            None,
+            "error pages are not images"
+        );
+        assert_eq!(detect_image_ext(b"", None), None);
+        assert_eq!(detect_image_ext(b"RIFF\0\0\0\0WAVE", None), None);
+    }
+
+    // --- local gallery ---
+
+    #[test]
+    fn test_local_gallery_lists_and_copies() {
+        let dir = std::env::temp_dir().join("frostwall_test_local_gallery");
+        let _ = std::fs::remove_dir_all(&dir);
+        let upstream = dir.join("upstream");
+        std::fs::create_dir_all(upstream.join("nature")).unwrap();
+        std::fs::create_dir_all(upstream.join(".trash")).unwrap();
+        image::RgbImage::new(32, 18)
+            .save(upstream.join("nature/wide.png"))
+            .unwrap();
+        image::RgbImage::new(18, 32)
+            .save(upstream.join("tall.png"))
+            .unwrap();
+        image::RgbImage::new(32, 18)
+            .save(upstream.join(".trash/old.png"))
+            .unwrap();
+        std::fs::write(upstream.join("notes.txt"), "not an image").unwrap();
+
+        let all = list_local(&upstream, 1, 10, &ImageFilter::default()).unwrap();
+        let ids: Vec<&str> = all.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, ["nature/wide.png", "tall.png"]);
+        assert_eq!((all[0].width, all[0].height), (32, 18));
+
+        let wide = ImageFilter {
+            ratio: Some((16, 9)),
+            ..ImageFilter::default()
+        };
+        assert_eq!(list_local(&upstream, 1, 10, &wide).unwrap().len(), 1);
+        assert_eq!(
+            list_local(&upstream, 2, 1, &ImageFilter::default()).unwrap()[0].id,
+            "tall.png"
+        );
+
+        let library = dir.join("library");
+        let copied = WebImporter::copy_local(&all[0], &library).unwrap();
+        assert_eq!(copied, library.join("nature_wide.png"));
+        assert!(upstream.join("nature/wide.png").exists(), "source is kept");
+        assert_eq!(
+            WebImporter::existing_download(&all[0], &library),
+            Some(copied.clone())
+        );
+        let attribution = Attribution::load_for(&copied).unwrap();
+        assert_eq!(attribution.source, "Local");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_local_copies_from_subfolders_do_not_collide() {
+        let dir = std::env::temp_dir().join("frostwall_test_local_collide");
+        let _ = std::fs::remove_dir_all(&dir);
+        let upstream = dir.join("upstream");
+        for folder in ["nature", "city"] {
+            std::fs::create_dir_all(upstream.join(folder)).unwrap();
+            image::RgbImage::new(8, 8)
+                .save(upstream.join(folder).join("a.png"))
+                .unwrap();
+        }
+
+        let library = dir.join("library");
+        let copies: Vec<PathBuf> = list_local(&upstream, 1, 10, &ImageFilter::default())
+            .unwrap()
+            .iter()
+            .map(|image| WebImporter::copy_local(image, &library).unwrap())
+            .collect();
+        assert_eq!(
+            copies,
+            [library.join("city_a.png"), library.join("nature_a.png")]
+        );
+        assert!(copies.iter().all(|path| path.exists()));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -89,3 +89,252 @@ fn test_random_with_images() {
 
     let _ = std::fs::remove_dir_all(&tmp);
 }
+
+#[test]
+fn test_list_recent_counts_new_images() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_recent");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+
+    for i in 0..2 {
+        let img = image::RgbImage::from_fn(10, 10, |_, _| image::Rgb([0, 0, 255]));
+        img.save(tmp.join(format!("new_{}.png", i))).unwrap();
+    }
+
+    let output = frostwall()
+        .args(["-d", tmp.to_str().unwrap(), "list", "--recent", "1d"])
+        .output()
+        .expect("failed to run");
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "list --recent should exit 0");
+    assert!(
+        stdout.contains("2 of 2 wallpaper(s) added in the last 1d"),
+        "freshly written images should count as recent, got: {}",
+        stdout
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_no_save_leaves_config_and_cache_untouched() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_no_save");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let walls = tmp.join("walls");
+    std::fs::create_dir_all(&walls).unwrap();
+    let img = image::RgbImage::from_fn(10, 10, |_, _| image::Rgb([0, 0, 255]));
+    img.save(walls.join("blue.png")).unwrap();
+
+    let output = frostwall()
+        .env("HOME", &tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .args(["--no-save", "-d", walls.to_str().unwrap(), "list"])
+        .output()
+        .expect("failed to run");
+
+    assert!(
+        output.status.success(),
+        "list --no-save should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        !tmp.join("config").exists(),
+        "--no-save should not create config.toml"
+    );
+    assert!(
+        !tmp.join("cache").join("frostwall").exists(),
+        "--no-save should not write the wallpaper cache"
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_json_stats_is_machine_readable() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_json_stats");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let walls = tmp.join("walls");
+    std::fs::create_dir_all(&walls).unwrap();
+    image::RgbImage::new(32, 10)
+        .save(walls.join("wide.png"))
+        .unwrap();
+    image::RgbImage::new(16, 9)
+        .save(walls.join("normal.png"))
+        .unwrap();
+
+    let output = frostwall()
+        .env("HOME", &tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .args(["--json", "-d", walls.to_str().unwrap(), "scan"])
+        .output()
+        .expect("failed to run");
+
+    assert!(
+        output.status.success(),
+        "scan --json should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stats: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be pure JSON");
+    assert_eq!(stats["total"], 2);
+    assert_eq!(stats["ultrawide"], 1);
+    assert_eq!(stats["landscape"], 1);
+
+    // --json is global, so it also works after the subcommand
+    let output = frostwall()
+        .env("HOME", &tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .args(["-d", walls.to_str().unwrap(), "stats", "--json"])
+        .output()
+        .expect("failed to run");
+    assert!(
+        output.status.success(),
+        "stats --json should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stats: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout should be pure JSON");
+    assert_eq!(stats["total"], 2);
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_init_defaults_writes_config_and_refuses_overwrite() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_init_defaults");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let walls = tmp.join("walls");
+    let config_path = tmp.join("config").join("frostwall").join("config.toml");
+
+    let init = |extra: &[&str]| {
+        frostwall()
+            .env("HOME", &tmp)
+            .env("XDG_CONFIG_HOME", tmp.join("config"))
+            .env("XDG_CACHE_HOME", tmp.join("cache"))
+            .args(["init", "--defaults", "--dir", walls.to_str().unwrap()])
+            .args(extra)
+            .output()
+            .expect("failed to run")
+    };
+
+    let output = init(&["--recursive"]);
+    assert!(
+        output.status.success(),
+        "init --defaults should succeed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(walls.is_dir(), "wallpaper directory should be created");
+    let config = std::fs::read_to_string(&config_path).expect("config.toml written");
+    assert!(config.contains("recursive = true"));
+
+    let again = init(&[]);
+    assert!(!again.status.success(), "existing config needs --force");
+    assert_eq!(std::fs::read_to_string(&config_path).unwrap(), config);
+
+    let forced = init(&["--force"]);
+    assert!(forced.status.success());
+    assert!(std::fs::read_to_string(&config_path)
+        .unwrap()
+        .contains("recursive = false"));
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_apply_rejects_unsupported_file() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_apply");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+    let notes = tmp.join("notes.txt");
+    std::fs::write(&notes, "not an image").unwrap();
+
+    let output = frostwall()
+        .env("HOME", &tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .args(["--no-save", "apply", notes.to_str().unwrap()])
+        .output()
+        .expect("failed to run");
+
+    assert!(!output.status.success(), "apply should refuse a text file");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("not a supported image"),
+        "unexpected error: {stderr}"
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_random_without_swww_explains() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_no_swww");
+    let _ = std::fs::remove_dir_all(&tmp);
+    std::fs::create_dir_all(&tmp).unwrap();
+
+    let output = frostwall()
+        .env("PATH", &tmp)
+        .env("HOME", &tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .args(["--no-save", "-d", tmp.to_str().unwrap(), "random"])
+        .output()
+        .expect("failed to run");
+
+    assert!(!output.status.success(), "random needs swww");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("swww not found on PATH"),
+        "unexpected error: {stderr}"
+    );
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}
+
+#[test]
+fn test_config_path_edit_and_show() {
+    let tmp = std::env::temp_dir().join("frostwall_integration_config_cmds");
+    let _ = std::fs::remove_dir_all(&tmp);
+    let config_path = tmp.join("config").join("frostwall").join("config.toml");
+
+    let run = |args: &[&str]| {
+        frostwall()
+            .env("HOME", &tmp)
+            .env("XDG_CONFIG_HOME", tmp.join("config"))
+            .env("XDG_CACHE_HOME", tmp.join("cache"))
+            .env("VISUAL", "true")
+            .args(args)
+            .output()
+            .expect("failed to run")
+    };
+
+    let path = run(&["config", "path"]);
+    assert!(path.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&path.stdout).trim(),
+        config_path.to_str().unwrap()
+    );
+    assert!(!config_path.exists(), "path alone creates nothing");
+
+    let edit = run(&["config", "edit"]);
+    assert!(
+        edit.status.success(),
+        "config edit should succeed: {}",
+        String::from_utf8_lossy(&edit.stderr)
+    );
+    assert!(config_path.exists(), "edit creates the default config");
+
+    // Keys missing from the file still show up with their defaults
+    std::fs::write(&config_path, "[display]\nmirror_screens = true\n").unwrap();
+    let show = run(&["config", "show"]);
+    assert!(show.status.success());
+    let stdout = String::from_utf8_lossy(&show.stdout);
+    assert!(stdout.contains("mirror_screens = true"), "{stdout}");
+    assert!(stdout.contains("[wallpaper]"), "{stdout}");
+
+    let _ = std::fs::remove_dir_all(&tmp);
+}